@@ -0,0 +1,200 @@
+//! Analysis of the period and the symmetry of a found pattern.
+use crate::{
+    cells::State,
+    config::{Symmetry, Transform},
+    rules::Rule,
+    world::World,
+};
+
+#[cfg(feature = "serialize")]
+use serde::{Deserialize, Serialize};
+
+/// The actual period, transformation, translation and symmetry
+/// of a found pattern.
+///
+/// They may be different from the configuration. For example,
+/// a search for period-4 oscillators may find a period-2 oscillator,
+/// and a search for spaceships may find a spaceship which becomes
+/// its own reflection after half of its period.
+///
+/// Only the transformations of the square grid are considered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct Analysis {
+    /// The smallest period, which divides the period of the configuration.
+    pub period: isize,
+    /// The transformation after the period, as in `Config::transform`.
+    pub transform: Transform,
+    /// The horizontal translation after the period, as in `Config::dx`.
+    pub dx: isize,
+    /// The vertical translation after the period, as in `Config::dy`.
+    pub dy: isize,
+    /// The largest symmetry such that every generation is unchanged
+    /// by its transformations, up to translation.
+    ///
+    /// It is one of the first 10 values of `Symmetry`,
+    /// and its center may be different from the center of the world.
+    pub symmetry: Symmetry,
+}
+
+/// The cells of a generation whose states are different from
+/// the background, sorted by their coordinates.
+type Pattern = Vec<(isize, isize, State)>;
+
+impl<'a, R: Rule> World<'a, R> {
+    /// The cells of a generation whose states are different from
+    /// the background.
+    ///
+    /// Unknown cells are considered to be in the background state.
+    pub(crate) fn pattern(&self, t: isize) -> Pattern {
+        let mut pattern = Vec::new();
+        for x in 0..self.config.width {
+            for y in 0..self.config.height {
+                let cell = self.find_cell((x, y, t)).unwrap();
+                if let Some(state) = cell.state.get() {
+                    if state != cell.background {
+                        pattern.push((x, y, state));
+                    }
+                }
+            }
+        }
+        pattern
+    }
+
+    /// If `to` is the image of `from` under the transformation around
+    /// the center of the world, followed by some translation,
+    /// returns the translation.
+    fn match_pattern(
+        &self,
+        from: &[(isize, isize, State)],
+        to: &[(isize, isize, State)],
+        transform: Transform,
+    ) -> Option<(isize, isize)> {
+        if from.len() != to.len() {
+            return None;
+        }
+        let mut image = from
+            .iter()
+            .map(|&(x, y, state)| {
+                let (x, y) = self.config.apply_transform(transform, x, y);
+                (x, y, state)
+            })
+            .collect::<Pattern>();
+        image.sort();
+        let (ex, ey) = match (image.first(), to.first()) {
+            (Some(&(x0, y0, _)), Some(&(x1, y1, _))) => (x1 - x0, y1 - y0),
+            _ => return Some((0, 0)),
+        };
+        if image
+            .iter()
+            .zip(to)
+            .all(|(&(x0, y0, s0), &(x1, y1, s1))| (x0 + ex, y0 + ey, s0) == (x1, y1, s1))
+        {
+            Some((ex, ey))
+        } else {
+            None
+        }
+    }
+
+    /// A canonical form of the pattern, which is the same for
+    /// all its phases, translations, rotations and reflections.
+    ///
+    /// It is the smallest of the images of all generations under all
+    /// transformations, translated so that the smallest coordinates are `0`.
+    /// Unknown cells are considered to be in the background state.
+    pub(crate) fn canonical_form(&self) -> Vec<(isize, isize, State)> {
+        let mut canonical = None;
+        for t in 0..self.config.period {
+            let pattern = self.pattern(t);
+            for &transform in Transform::ALL.iter() {
+                let mut image = pattern
+                    .iter()
+                    .map(|&(x, y, state)| {
+                        let (x, y) = self.config.apply_transform(transform, x, y);
+                        (x, y, state)
+                    })
+                    .collect::<Pattern>();
+                let min_x = image.iter().map(|&(x, _, _)| x).min().unwrap_or(0);
+                let min_y = image.iter().map(|&(_, y, _)| y).min().unwrap_or(0);
+                for (x, y, _) in image.iter_mut() {
+                    *x -= min_x;
+                    *y -= min_y;
+                }
+                image.sort();
+                if canonical.as_ref().is_none_or(|c| image < *c) {
+                    canonical = Some(image);
+                }
+            }
+        }
+        canonical.unwrap_or_default()
+    }
+
+    /// Analyzes the actual period, transformation, translation
+    /// and symmetry of the pattern.
+    ///
+    /// This should be called after a result is found.
+    /// Unknown cells are considered to be in the background state.
+    pub(crate) fn analyze(&self) -> Analysis {
+        let period = self.config.period;
+        let patterns = (0..period).map(|t| self.pattern(t)).collect::<Vec<_>>();
+        let background = |t| self.find_cell((0, 0, t)).unwrap().background;
+
+        let mut analysis = Analysis {
+            period,
+            transform: self.config.transform,
+            dx: self.config.dx,
+            dy: self.config.dy,
+            symmetry: Symmetry::C1,
+        };
+
+        // Generation `t + p` at `q` is generation `t` at `T(q + d)`,
+        // so generation `t` is the image of generation `t + p` under `T`,
+        // translated by `L(d)`, where `L` is the linear part of `T`.
+        'period: for p in 1..period {
+            if period % p != 0 || background(0) != background(p) {
+                continue;
+            }
+            'transform: for &transform in Transform::ALL.iter() {
+                let mut shift = None;
+                for t in 0..period - p {
+                    let from = &patterns[(t + p) as usize];
+                    let to = &patterns[t as usize];
+                    match self.match_pattern(from, to, transform) {
+                        Some(e) if shift.is_none_or(|s| s == e) => shift = Some(e),
+                        _ => continue 'transform,
+                    }
+                }
+                let (ex, ey) = shift.unwrap();
+                let m = transform.inverse().matrix();
+                analysis.period = p;
+                analysis.transform = transform;
+                analysis.dx = m[0][0] * ex + m[0][1] * ey;
+                analysis.dy = m[1][0] * ex + m[1][1] * ey;
+                break 'period;
+            }
+        }
+
+        let transforms = Transform::ALL
+            .iter()
+            .copied()
+            .filter(|&transform| {
+                patterns
+                    .iter()
+                    .all(|pattern| self.match_pattern(pattern, pattern, transform).is_some())
+            })
+            .collect::<Vec<_>>();
+        analysis.symmetry = Symmetry::ALL
+            .iter()
+            .copied()
+            .find(|&symmetry| {
+                let elements = symmetry.transforms();
+                !symmetry.is_glide()
+                    && !symmetry.is_hex()
+                    && elements.len() == transforms.len()
+                    && elements.iter().all(|element| transforms.contains(element))
+            })
+            .unwrap_or_default();
+
+        analysis
+    }
+}
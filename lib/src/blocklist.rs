@@ -0,0 +1,157 @@
+//! Known patterns that must not be found again.
+
+use crate::{
+    cells::{State, ALIVE, DEAD},
+    config::Transform,
+    error::Error,
+    rules::Rule,
+    world::World,
+};
+
+/// The cells of a pattern which are not in the background state,
+/// sorted by their coordinates.
+pub(crate) type Pattern = Vec<(isize, isize, State)>;
+
+/// Parses a pattern in RLE.
+///
+/// Comment lines starting with `#` and the header line starting with `x`
+/// are skipped. Dead cells are `b` or `.`, living cells are `o`,
+/// and the states of Generations rules are uppercase letters, where
+/// `A` is `1`, `B` is `2`, and so on, as in `Search::rle_gen`.
+///
+/// Returns an error if there is an unknown cell or any other character.
+pub(crate) fn parse_rle(rle: &str) -> Result<Pattern, Error> {
+    let mut pattern = Vec::new();
+    let (mut x, mut y) = (0, 0);
+    let mut count: Option<isize> = None;
+    'lines: for line in rle.lines() {
+        let line = line.trim();
+        if line.starts_with('#') || line.starts_with('x') {
+            continue;
+        }
+        for c in line.chars() {
+            let state = match c {
+                '0'..='9' => {
+                    let digit = c.to_digit(10).unwrap() as isize;
+                    count = Some(count.unwrap_or(0) * 10 + digit);
+                    continue;
+                }
+                'b' | '.' => DEAD,
+                'o' => ALIVE,
+                'A'..='Z' => State((c as u8 - b'A') as usize + 1),
+                '$' => {
+                    y += count.take().unwrap_or(1);
+                    x = 0;
+                    continue;
+                }
+                '!' => break 'lines,
+                c if c.is_whitespace() => continue,
+                _ => return Err(Error::BlocklistError(rle.to_string())),
+            };
+            let n = count.take().unwrap_or(1);
+            if state != DEAD {
+                pattern.extend((x..x + n).map(|x| (x, y, state)));
+            }
+            x += n;
+        }
+    }
+    pattern.sort();
+    Ok(pattern)
+}
+
+/// Translates the pattern so that the smallest coordinates are `0`,
+/// and sorts it.
+pub(crate) fn normalize(mut pattern: Pattern) -> Pattern {
+    let min_x = pattern.iter().map(|&(x, _, _)| x).min().unwrap_or(0);
+    let min_y = pattern.iter().map(|&(_, y, _)| y).min().unwrap_or(0);
+    for (x, y, _) in pattern.iter_mut() {
+        *x -= min_x;
+        *y -= min_y;
+    }
+    pattern.sort();
+    pattern
+}
+
+impl<'a, R: Rule> World<'a, R> {
+    /// Parses the patterns in the blocklist, and records their images
+    /// under all transformations.
+    ///
+    /// Invalid patterns are skipped. They are rejected by
+    /// `Config::check_blocklist` before the world is created.
+    pub(crate) fn init_blocklist(mut self) -> Self {
+        self.blocklist = self
+            .config
+            .blocklist
+            .iter()
+            .filter_map(|rle| parse_rle(rle).ok())
+            .filter(|pattern| !pattern.is_empty())
+            .map(|pattern| {
+                let mut images = Transform::ALL
+                    .iter()
+                    .map(|&transform| {
+                        normalize(
+                            pattern
+                                .iter()
+                                .map(|&(x, y, state)| {
+                                    let (x, y) = self.config.apply_transform(transform, x, y);
+                                    (x, y, state)
+                                })
+                                .collect(),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                images.sort();
+                images.dedup();
+                images
+            })
+            .collect();
+        self
+    }
+
+    /// Whether the result is in the blocklist.
+    ///
+    /// That is, some generation of the result is the same as a pattern
+    /// in the blocklist, up to translation, rotation and reflection.
+    /// If `blocklist_subpattern` is enabled, it is also blocked when
+    /// generation 0 contains such a pattern.
+    pub(crate) fn is_blocked(&self) -> bool {
+        if self.blocklist.is_empty() {
+            return false;
+        }
+        let phases = (0..self.config.period)
+            .map(|t| normalize(self.pattern(t)))
+            .collect::<Vec<_>>();
+        self.blocklist.iter().flatten().any(|image| {
+            phases.contains(image) || (self.config.blocklist_subpattern && self.contains(image))
+        })
+    }
+
+    /// Whether generation 0 contains the pattern somewhere,
+    /// i.e., all the cells in the bounding box of the pattern
+    /// have the same states as in the pattern.
+    ///
+    /// Cells outside the world and unknown cells are considered to be
+    /// in their background states.
+    fn contains(&self, image: &[(isize, isize, State)]) -> bool {
+        let width = image.iter().map(|&(x, _, _)| x + 1).max().unwrap_or(0);
+        let height = image.iter().map(|&(_, y, _)| y + 1).max().unwrap_or(0);
+        let background = self.find_cell((0, 0, 0)).unwrap().background;
+        let state = |x, y| match self.find_cell((x, y, 0)) {
+            Some(cell) => (cell.state.get().unwrap_or(cell.background), cell.background),
+            None => (background, background),
+        };
+        (1 - width..self.config.width).any(|dx| {
+            (1 - height..self.config.height).any(|dy| {
+                (0..width).all(|x| {
+                    (0..height).all(|y| {
+                        let (state, background) = state(x + dx, y + dy);
+                        let expected = image
+                            .binary_search_by_key(&(x, y), |&(x, y, _)| (x, y))
+                            .map_or(background, |i| image[i].2);
+                        state == expected
+                    })
+                })
+            })
+        })
+    }
+}
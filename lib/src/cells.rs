@@ -1,6 +1,6 @@
 //! Cells in the cellular automaton.
 
-use crate::rules::Rule;
+use crate::rules::{Rule, MAX_NBHD_SIZE};
 use derivative::Derivative;
 use std::{
     cell::Cell,
@@ -18,7 +18,7 @@ pub const ALIVE: State = State(1);
 ///
 /// During the search, the state of a cell is represented by `Option<State>`,
 /// where `None` means that the state of the cell is unknown.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct State(pub usize);
 
@@ -53,6 +53,8 @@ pub struct LifeCell<'a, R: Rule> {
     /// For rules without `B0`, it is always dead.
     /// For rules with `B0`, it is dead on even generations,
     /// alive on odd generations.
+    ///
+    /// With an agar, it is the state of the agar. See `Config::agar`.
     pub(crate) background: State,
 
     /// The state of the cell.
@@ -74,23 +76,65 @@ pub struct LifeCell<'a, R: Rule> {
     ///
     /// The cell in the next generation at the same position.
     pub(crate) succ: Option<CellRef<'a, R>>,
-    /// The eight cells in the neighborhood.
-    pub(crate) nbhd: [Option<CellRef<'a, R>>; 8],
-    /// The cells in the same generation that must has the same state
-    /// with this cell because of the symmetry.
+    /// The cells in the neighborhood.
+    ///
+    /// See `Rule::nbhd` for the order of the neighbors.
+    pub(crate) nbhd: [Option<CellRef<'a, R>>; MAX_NBHD_SIZE],
+    /// The cells that must has the same state with this cell
+    /// because of the symmetry, or because it is a stator cell.
+    ///
+    /// They are in the same generation, except for glide symmetries,
+    /// the time-reversal symmetry and the `stator_cells`.
+    ///
+    /// The states are compared relative to the backgrounds: if the
+    /// background of a symmetric cell is different, its state is inverted.
     pub(crate) sym: Vec<CellRef<'a, R>>,
 
     /// Whether the cell is on the first row or column.
     ///
     /// Here the choice of row or column depends on the search order.
     pub(crate) is_front: bool,
+
+    /// Whether the state of the cell is irrelevant.
+    ///
+    /// Such cells are not counted in the cell counts,
+    /// and are ignored when checking whether the pattern is trivial.
+    pub(crate) dont_care: bool,
+
+    /// Whether the cell is outside a bounded grid.
+    ///
+    /// Such cells are always dead, and are never consistified.
+    /// On a bounded plane, cells that would be born there are ignored.
+    /// On a torus, they are not neighbors of any cell in the world.
+    pub(crate) outside: bool,
+
+    /// Whether the cell is outside the fundamental domain of the symmetry.
+    ///
+    /// Such cells are never consistified, because their symmetric cells
+    /// in the domain give the same deductions.
+    /// See `Config::fundamental_domain`.
+    pub(crate) redundant: bool,
+
+    /// How often the cell is involved in conflicts, with older conflicts
+    /// weighted less.
+    ///
+    /// Only used when `Config::activity_order` or
+    /// `Config::order_switch_interval` is enabled.
+    pub(crate) activity: Cell<f64>,
+
+    /// Whether the states of the cell, its neighbors or its successor
+    /// have changed since the cell was last consistified.
+    ///
+    /// Cells that are not dirty would not give any new deduction,
+    /// so they are skipped in `World::consistify10`.
+    pub(crate) dirty: Cell<bool>,
 }
 
 impl<'a, R: Rule> LifeCell<'a, R> {
     /// Generates a new cell with state `state`, such that its neighborhood
     /// descriptor says that all neighboring cells also have the same state.
     ///
-    /// `first_gen` and `first_col` are set to `false`.
+    /// `is_front`, `dont_care`, `outside` and `redundant` are set to `false`.
     pub(crate) fn new(coord: Coord, background: State, b0: bool) -> Self {
         let succ_state = if b0 { !background } else { background };
         LifeCell {
@@ -103,9 +147,61 @@ impl<'a, R: Rule> LifeCell<'a, R> {
             nbhd: Default::default(),
             sym: Default::default(),
             is_front: false,
+            dont_care: false,
+            outside: false,
+            redundant: false,
+            activity: Cell::new(0.0),
+            dirty: Cell::new(true),
         }
     }
 
+    /// The state of the cell, or `None` if it is unknown.
+    pub fn state(&self) -> Option<State> {
+        self.state.get()
+    }
+
+    /// The background state of the cell.
+    ///
+    /// For rules without `B0`, it is always dead.
+    /// For rules with `B0`, it is dead on even generations,
+    /// alive on odd generations.
+    ///
+    /// With an agar, it is the state of the agar. See `Config::agar`.
+    pub fn background(&self) -> State {
+        self.background
+    }
+
+    /// The neighborhood descriptor of the cell.
+    pub fn desc(&self) -> R::Desc {
+        self.desc.get()
+    }
+
+    /// Replaces the neighborhood descriptor of the cell.
+    ///
+    /// This should only be called in `Rule::update_desc`.
+    pub fn set_desc(&self, desc: R::Desc) {
+        self.desc.set(desc)
+    }
+
+    /// The cell in the last generation at the same position,
+    /// or `None` if it is out of the world.
+    pub fn pred(&self) -> Option<CellRef<'a, R>> {
+        self.pred
+    }
+
+    /// The cell in the next generation at the same position,
+    /// or `None` if it is out of the world.
+    pub fn succ(&self) -> Option<CellRef<'a, R>> {
+        self.succ
+    }
+
+    /// The neighbors of the cell, in the order of `Rule::nbhd`.
+    ///
+    /// Neighbors that are out of the world are `None`.
+    pub fn nbhd(&self) -> &[Option<CellRef<'a, R>>] {
+        &self.nbhd
+    }
+
     /// Returns a `CellRef` from a `LifeCell`.
     pub(crate) fn borrow(&self) -> CellRef<'a, R> {
         let cell = unsafe { (self as *const LifeCell<'a, R>).as_ref().unwrap() };
@@ -125,6 +221,10 @@ impl<'a, R: Rule<Desc = D>, D: Copy + Debug> Debug for LifeCell<'a, R> {
     }
 }
 
+/// A reference to a `LifeCell`.
+///
+/// It dereferences to the cell, and two references are equal
+/// if they refer to the same cell.
 #[derive(Derivative)]
 #[derivative(Clone(bound = ""), Copy(bound = ""))]
 pub struct CellRef<'a, R: Rule> {
@@ -137,8 +237,18 @@ impl<'a, R: Rule> CellRef<'a, R> {
     ///
     /// The `state` is the new state of the cell when `new` is true,
     /// the old state when `new` is false.
+    ///
+    /// Also marks the cells whose consistification depends on this cell
+    /// as dirty: the cell itself, its neighbors and its predecessor.
     pub(crate) fn update_desc(self, state: Option<State>, new: bool) {
         R::update_desc(self, state, new);
+        self.dirty.set(true);
+        if let Some(pred) = self.pred {
+            pred.dirty.set(true);
+        }
+        for neigh in self.nbhd.iter().flatten() {
+            neigh.dirty.set(true);
+        }
     }
 }
 
@@ -0,0 +1,152 @@
+#![cfg(feature = "serialize")]
+//! Writing checkpoints of the search periodically.
+
+use crate::{
+    header::{FileKind, Header},
+    save::WorldSer,
+    traits::Search,
+};
+use std::{
+    ffi::OsString,
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+/// Writes the state of the search as a checkpoint.
+///
+/// The file starts with a `Header`, followed by the `WorldSer`
+/// of the search in JSON.
+pub fn write_checkpoint<W: Write>(search: &dyn Search, writer: &mut W) -> io::Result<()> {
+    Header::new(FileKind::Checkpoint, search.config()).write_to(writer)?;
+    serde_json::to_writer(&mut *writer, &search.ser())?;
+    writeln!(writer)
+}
+
+/// Reads the header and the state of the search in a checkpoint.
+///
+/// The search can be resumed by `WorldSer::world`.
+///
+/// Returns an error if the header is invalid. See `Header::read_from`.
+pub fn read_checkpoint<R: Read>(reader: &mut R) -> io::Result<(Header, WorldSer)> {
+    let header = Header::read_from(reader, FileKind::Checkpoint)?;
+    let world_ser = serde_json::from_reader(reader)?;
+    Ok((header, world_ser))
+}
+
+/// Writes checkpoints of a search to a file periodically,
+/// so that a long search can be resumed after a crash.
+///
+/// A checkpoint is written to a temporary file first, and then renamed,
+/// so the file is never half-written. The previous checkpoint is kept
+/// in a file with the suffix `.prev`, in case the latest one is lost.
+#[derive(Clone, Debug)]
+pub struct Checkpoint {
+    /// The path of the latest checkpoint.
+    path: PathBuf,
+
+    /// The maximal time between two checkpoints.
+    interval: Option<Duration>,
+
+    /// The maximal number of conflicts between two checkpoints.
+    conflicts: Option<u64>,
+
+    /// When the last checkpoint was written.
+    last_time: Instant,
+
+    /// Number of conflicts in the search when the last checkpoint
+    /// was written.
+    last_conflicts: u64,
+}
+
+impl Checkpoint {
+    /// Creates a new checkpoint writer.
+    ///
+    /// By default a checkpoint is written every 10 minutes.
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Checkpoint {
+            path: path.into(),
+            interval: Some(Duration::from_secs(600)),
+            conflicts: None,
+            last_time: Instant::now(),
+            last_conflicts: 0,
+        }
+    }
+
+    /// Sets the maximal time between two checkpoints.
+    ///
+    /// `None` means that checkpoints are not written by time.
+    pub fn set_interval(mut self, interval: Option<Duration>) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Sets the maximal number of conflicts between two checkpoints.
+    ///
+    /// `None` means that checkpoints are not written by conflicts.
+    pub fn set_conflicts(mut self, conflicts: Option<u64>) -> Self {
+        self.conflicts = conflicts;
+        self
+    }
+
+    /// The path of the latest checkpoint.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The path of the previous checkpoint.
+    pub fn prev_path(&self) -> PathBuf {
+        with_suffix(&self.path, ".prev")
+    }
+
+    /// Whether it is time to write a checkpoint.
+    pub fn is_due(&self, search: &dyn Search) -> bool {
+        self.interval
+            .is_some_and(|interval| self.last_time.elapsed() >= interval)
+            || self.conflicts.is_some_and(|conflicts| {
+                search.conflicts().saturating_sub(self.last_conflicts) >= conflicts
+            })
+    }
+
+    /// Writes a checkpoint if it is due.
+    ///
+    /// Returns whether a checkpoint is written.
+    pub fn update(&mut self, search: &dyn Search) -> io::Result<bool> {
+        if self.is_due(search) {
+            self.write(search)?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Writes a checkpoint now.
+    pub fn write(&mut self, search: &dyn Search) -> io::Result<()> {
+        let tmp_path = with_suffix(&self.path, ".tmp");
+        let mut writer = BufWriter::new(File::create(&tmp_path)?);
+        write_checkpoint(search, &mut writer)?;
+        writer.into_inner()?.sync_all()?;
+        if self.path.exists() {
+            fs::rename(&self.path, self.prev_path())?;
+        }
+        fs::rename(&tmp_path, &self.path)?;
+        self.last_time = Instant::now();
+        self.last_conflicts = search.conflicts();
+        Ok(())
+    }
+
+    /// Reads the latest checkpoint, or the previous one
+    /// if the latest one can not be read.
+    pub fn read(&self) -> io::Result<(Header, WorldSer)> {
+        let read = |path: &Path| read_checkpoint(&mut BufReader::new(File::open(path)?));
+        read(&self.path).or_else(|e| read(&self.prev_path()).map_err(|_| e))
+    }
+}
+
+/// Appends a suffix to a path.
+pub(crate) fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = OsString::from(path);
+    path.push(suffix);
+    PathBuf::from(path)
+}
@@ -0,0 +1,30 @@
+//! Choosing the next unknown cell to decide.
+
+use crate::{cells::Coord, config::SearchOrder, traits::Search};
+
+/// A branching heuristic that chooses the next unknown cell to decide.
+///
+/// It can be set by `Search::set_chooser`. The built-in search orders
+/// also implement this trait, but setting the `search_order` in the
+/// configuration is much faster.
+pub trait ChooseCell {
+    /// Chooses an unknown cell in the world to decide.
+    ///
+    /// `search` gives the current states of the cells and the configuration.
+    ///
+    /// Returns `None` to fall back to the `search_order` in the configuration.
+    /// Known cells and cells outside the world are also ignored.
+    fn choose(&mut self, search: &dyn Search) -> Option<Coord>;
+}
+
+impl ChooseCell for SearchOrder {
+    /// Chooses the first unknown cell in this order.
+    fn choose(&mut self, search: &dyn Search) -> Option<Coord> {
+        let config = search.config();
+        config
+            .ordered_coords(*self)
+            .into_iter()
+            .flat_map(|(x, y)| (0..config.period).map(move |t| (x, y, t)))
+            .find(|&coord| matches!(search.get_cell_state(coord), Ok(None)))
+    }
+}
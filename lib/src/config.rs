@@ -1,17 +1,19 @@
 //! World configuration.
 
 use crate::{
-    cells::Coord,
+    blocklist::{parse_rle, Pattern},
+    cells::{Coord, State, ALIVE, DEAD},
     error::Error,
-    rules::{Life, LifeGen, NtLife, NtLifeGen, Rule},
+    rules::{AnyRule, Rule},
     traits::Search,
-    world::World,
+    world::new_world,
 };
 use derivative::Derivative;
 use std::{
     cmp::Ordering,
-    fmt::{self, Debug, Formatter},
+    fmt::{self, Debug, Display, Formatter},
     str::FromStr,
+    time::Duration,
 };
 
 #[cfg(feature = "serialize")]
@@ -34,6 +36,10 @@ use serde::{Deserialize, Serialize};
 /// `F` means reflections (flips).
 /// The symbol after it is the axis of reflection.
 ///
+/// For example, `R90` with period 1 finds oscillators which rotate
+/// by 90° in each generation, e.g., the blinker, and `F|` with a vertical
+/// translation finds glide-reflective ships in half of their periods.
+///
 /// Some of the transformations are only valid when the world is square.
 #[derive(Clone, Copy, Derivative, PartialEq, Eq)]
 #[derivative(Default)]
@@ -110,29 +116,110 @@ impl Debug for Transform {
 }
 
 impl Transform {
+    /// All 8 transformations.
+    pub const ALL: [Transform; 8] = [
+        Transform::Id,
+        Transform::Rotate90,
+        Transform::Rotate180,
+        Transform::Rotate270,
+        Transform::FlipRow,
+        Transform::FlipCol,
+        Transform::FlipDiag,
+        Transform::FlipAntidiag,
+    ];
+
+    /// The matrix of the transformation, acting on the coordinates
+    /// relative to the center of the world.
+    pub(crate) fn matrix(self) -> [[isize; 2]; 2] {
+        match self {
+            Transform::Id => [[1, 0], [0, 1]],
+            Transform::Rotate90 => [[0, 1], [-1, 0]],
+            Transform::Rotate180 => [[-1, 0], [0, -1]],
+            Transform::Rotate270 => [[0, -1], [1, 0]],
+            Transform::FlipRow => [[1, 0], [0, -1]],
+            Transform::FlipCol => [[-1, 0], [0, 1]],
+            Transform::FlipDiag => [[0, 1], [1, 0]],
+            Transform::FlipAntidiag => [[0, -1], [-1, 0]],
+        }
+    }
+
+    /// The transformation obtained by applying `other` first, and then `self`.
+    pub fn compose(self, other: Transform) -> Transform {
+        let m = mul(self.matrix(), other.matrix());
+        *Transform::ALL
+            .iter()
+            .find(|transform| transform.matrix() == m)
+            .unwrap()
+    }
+
+    /// The inverse transformation.
+    pub fn inverse(self) -> Transform {
+        match self {
+            Transform::Rotate90 => Transform::Rotate270,
+            Transform::Rotate270 => Transform::Rotate90,
+            _ => self,
+        }
+    }
+
+    /// Whether the transformation leaves a translation `(dx, dy)` unchanged.
+    pub fn fixes(self, dx: isize, dy: isize) -> bool {
+        let m = self.matrix();
+        (m[0][0] * dx + m[0][1] * dy, m[1][0] * dx + m[1][1] * dy) == (dx, dy)
+    }
+
     /// Whether the transformation requires the world to be square.
     ///
     /// Returns `true` for `R90`, `R270`, `F\` and `F/`.
     pub fn square_world(self) -> bool {
-        match self {
+        matches!(
+            self,
             Transform::Rotate90
-            | Transform::Rotate270
-            | Transform::FlipDiag
-            | Transform::FlipAntidiag => true,
-            _ => false,
-        }
+                | Transform::Rotate270
+                | Transform::FlipDiag
+                | Transform::FlipAntidiag
+        )
     }
 }
 
 /// Symmetries of the pattern.
 ///
-/// 10 different values correspond to 10 subgroups of the dihedral group
+/// The first 10 values correspond to 10 subgroups of the dihedral group
 /// _D_<sub>8</sub>.
 ///
 /// The notations are stolen from Oscar Cunningham's
 /// [Logic Life Search](https://github.com/OscarCunningham/logic-life-search).
 /// Please see the [Life Wiki](https://conwaylife.com/wiki/Symmetry) for details.
 ///
+/// The next 4 values are glide symmetries: after half a period,
+/// the pattern becomes its own reflection, translated by half of
+/// `(dx, dy)`. They require an even period, an even translation
+/// parallel to the axis of reflection, and no transformation.
+/// See `Config::check_symmetry` for details.
+///
+/// The last 2 values are gutter symmetries: reflections whose axis
+/// is a row or a column of cells which is always in the background state,
+/// as in [gutter](https://conwaylife.com/wiki/Gutter) ship searches.
+/// The axis must go through the cells, e.g., the world must have
+/// an odd height for `D2-g` if the axis is not moved by `axis_offset`.
+///
+/// The last 4 values are symmetries of the hexagonal grid,
+/// and are only valid for rules with the hexagonal neighborhood
+/// `(-1, -1), (-1, 0), (0, -1), (0, 1), (1, 0), (1, 1)`,
+/// or rules on the triangular grid.
+///
+/// On the hexagonal grid, they rotate around the cell at the center of
+/// the world, so the width and the height must be odd. `C2`, `D2\`, `D2/`,
+/// `D4X`, `G\` and `G/` are also valid, and the first 4 of them
+/// correspond to the hexagonal _C_<sub>2</sub>, _D_<sub>2</sub>
+/// and _D_<sub>4</sub>. See `Symmetry::preserves`.
+///
+/// On the triangular grid, they rotate around the vertex at the center of
+/// the world, so the height must be even, and `width + height` must be
+/// `1` modulo `4`. The reflections in `D6` and `D12` include the reflection
+/// across the middle column. `C2`, `D2|`, `D2-`, `D4+`, `G|` and `G-`
+/// are also valid, when their centers and axes agree with the triangles.
+/// See `Config::preserves_parity_nbhd`.
+///
 /// Some of the symmetries are only valid when the world is square.
 #[derive(Clone, Copy, Derivative, PartialEq, Eq)]
 #[derivative(Default)]
@@ -181,6 +268,49 @@ pub enum Symmetry {
     ///
     /// Symmetry under all 8 transformations.
     D8,
+    /// `G-`.
+    ///
+    /// Glide symmetry with reflection across the middle row.
+    GlideRow,
+    /// `G|`.
+    ///
+    /// Glide symmetry with reflection across the middle column.
+    GlideCol,
+    /// `G\`.
+    ///
+    /// Glide symmetry with reflection across the diagonal.
+    GlideDiag,
+    /// `G/`.
+    ///
+    /// Glide symmetry with reflection across the antidiagonal.
+    GlideAntidiag,
+    /// `D2-g`.
+    ///
+    /// Symmetry under reflection across the middle row,
+    /// which is always dead.
+    GutterRow,
+    /// `D2|g`.
+    ///
+    /// Symmetry under reflection across the middle column,
+    /// which is always dead.
+    GutterCol,
+    /// `C3`.
+    ///
+    /// Symmetry under 120° rotation on the hexagonal grid.
+    C3,
+    /// `C6`.
+    ///
+    /// Symmetry under 60° rotation on the hexagonal grid.
+    C6,
+    /// `D6`.
+    ///
+    /// Symmetry under 120° rotation and reflection across the diagonal
+    /// on the hexagonal grid.
+    D6,
+    /// `D12`.
+    ///
+    /// Symmetry under all 12 transformations of the hexagonal grid.
+    D12,
 }
 
 impl FromStr for Symmetry {
@@ -198,6 +328,16 @@ impl FromStr for Symmetry {
             "D4+" => Ok(Symmetry::D4Ortho),
             "D4X" => Ok(Symmetry::D4Diag),
             "D8" => Ok(Symmetry::D8),
+            "G-" => Ok(Symmetry::GlideRow),
+            "G|" => Ok(Symmetry::GlideCol),
+            "G\\" => Ok(Symmetry::GlideDiag),
+            "G/" => Ok(Symmetry::GlideAntidiag),
+            "D2-g" => Ok(Symmetry::GutterRow),
+            "D2|g" => Ok(Symmetry::GutterCol),
+            "C3" => Ok(Symmetry::C3),
+            "C6" => Ok(Symmetry::C6),
+            "D6" => Ok(Symmetry::D6),
+            "D12" => Ok(Symmetry::D12),
             _ => Err(String::from("invalid symmetry")),
         }
     }
@@ -216,6 +356,16 @@ impl Debug for Symmetry {
             Symmetry::D4Ortho => "D4+",
             Symmetry::D4Diag => "D4X",
             Symmetry::D8 => "D8",
+            Symmetry::GlideRow => "G-",
+            Symmetry::GlideCol => "G|",
+            Symmetry::GlideDiag => "G\\",
+            Symmetry::GlideAntidiag => "G/",
+            Symmetry::GutterRow => "D2-g",
+            Symmetry::GutterCol => "D2|g",
+            Symmetry::C3 => "C3",
+            Symmetry::C6 => "C6",
+            Symmetry::D6 => "D6",
+            Symmetry::D12 => "D12",
         };
         write!(f, "{}", s)?;
         Ok(())
@@ -225,17 +375,235 @@ impl Debug for Symmetry {
 impl Symmetry {
     /// Whether the transformation requires the world to be square.
     ///
-    /// Returns `true` for `C4`, `D2\`, `D2/`, `D4X` and `D8`.
+    /// Returns `true` for `C4`, `D2\`, `D2/`, `D4X`, `D8`, `G\` and `G/`.
     pub fn square_world(self) -> bool {
-        match self {
+        matches!(
+            self,
             Symmetry::C4
-            | Symmetry::D2Diag
-            | Symmetry::D2Antidiag
-            | Symmetry::D4Diag
-            | Symmetry::D8 => true,
-            _ => false,
+                | Symmetry::D2Diag
+                | Symmetry::D2Antidiag
+                | Symmetry::D4Diag
+                | Symmetry::D8
+                | Symmetry::GlideDiag
+                | Symmetry::GlideAntidiag
+        )
+    }
+
+    /// All 20 symmetries.
+    pub const ALL: [Symmetry; 20] = [
+        Symmetry::C1,
+        Symmetry::C2,
+        Symmetry::C4,
+        Symmetry::D2Row,
+        Symmetry::D2Col,
+        Symmetry::D2Diag,
+        Symmetry::D2Antidiag,
+        Symmetry::D4Ortho,
+        Symmetry::D4Diag,
+        Symmetry::D8,
+        Symmetry::GlideRow,
+        Symmetry::GlideCol,
+        Symmetry::GlideDiag,
+        Symmetry::GlideAntidiag,
+        Symmetry::GutterRow,
+        Symmetry::GutterCol,
+        Symmetry::C3,
+        Symmetry::C6,
+        Symmetry::D6,
+        Symmetry::D12,
+    ];
+
+    /// The transformations that leave each generation of the pattern
+    /// unchanged, i.e., the elements of the subgroup.
+    ///
+    /// Glide symmetries do not leave a generation unchanged,
+    /// so they only give `Id`. Hexagonal symmetries only give
+    /// the elements that are also transformations of the square grid.
+    pub fn transforms(self) -> &'static [Transform] {
+        match self {
+            Symmetry::C2 | Symmetry::C6 => &[Transform::Id, Transform::Rotate180],
+            Symmetry::C4 => &[
+                Transform::Id,
+                Transform::Rotate90,
+                Transform::Rotate180,
+                Transform::Rotate270,
+            ],
+            Symmetry::D2Row | Symmetry::GutterRow => &[Transform::Id, Transform::FlipRow],
+            Symmetry::D2Col | Symmetry::GutterCol => &[Transform::Id, Transform::FlipCol],
+            Symmetry::D2Diag | Symmetry::D6 => &[Transform::Id, Transform::FlipDiag],
+            Symmetry::D2Antidiag => &[Transform::Id, Transform::FlipAntidiag],
+            Symmetry::D4Ortho => &[
+                Transform::Id,
+                Transform::FlipRow,
+                Transform::FlipCol,
+                Transform::Rotate180,
+            ],
+            Symmetry::D4Diag | Symmetry::D12 => &[
+                Transform::Id,
+                Transform::FlipDiag,
+                Transform::FlipAntidiag,
+                Transform::Rotate180,
+            ],
+            Symmetry::D8 => &Transform::ALL,
+            _ => &[Transform::Id],
+        }
+    }
+
+    /// Whether the symmetry is a glide symmetry,
+    /// which maps a generation to another generation.
+    ///
+    /// Returns `true` for `G-`, `G|`, `G\` and `G/`.
+    pub fn is_glide(self) -> bool {
+        matches!(
+            self,
+            Symmetry::GlideRow | Symmetry::GlideCol | Symmetry::GlideDiag | Symmetry::GlideAntidiag
+        )
+    }
+
+    /// Whether the symmetry is a symmetry of the hexagonal or
+    /// the triangular grid, which is not a symmetry of the square grid.
+    ///
+    /// Returns `true` for `C3`, `C6`, `D6` and `D12`.
+    pub fn is_hex(self) -> bool {
+        matches!(
+            self,
+            Symmetry::C3 | Symmetry::C6 | Symmetry::D6 | Symmetry::D12
+        )
+    }
+
+    /// The matrices of the rotations and reflections in the symmetry,
+    /// acting on the coordinates relative to the center of the world.
+    ///
+    /// For glide symmetries, this is the matrix of the reflection.
+    ///
+    /// For hexagonal symmetries, these are the matrices on the hexagonal grid.
+    pub(crate) fn matrices(self) -> Vec<[[isize; 2]; 2]> {
+        // The 60° rotation and the reflection across the diagonal
+        // of the hexagonal grid.
+        const HEX_ROTATE: [[isize; 2]; 2] = [[1, -1], [1, 0]];
+        const HEX_FLIP: [[isize; 2]; 2] = [[0, 1], [1, 0]];
+        match self {
+            Symmetry::GlideRow => vec![Transform::FlipRow.matrix()],
+            Symmetry::GlideCol => vec![Transform::FlipCol.matrix()],
+            Symmetry::GlideDiag => vec![Transform::FlipDiag.matrix()],
+            Symmetry::GlideAntidiag => vec![Transform::FlipAntidiag.matrix()],
+            sym if sym.is_hex() => sym.hex_matrices(HEX_ROTATE, HEX_FLIP),
+            _ => self
+                .transforms()
+                .iter()
+                .map(|transform| transform.matrix())
+                .collect(),
+        }
+    }
+
+    /// The matrices of a hexagonal symmetry, generated by the matrices
+    /// of a 60° rotation and a reflection, starting from the identity.
+    ///
+    /// Returns only the identity for other symmetries.
+    pub(crate) fn hex_matrices(
+        self,
+        rotate: [[isize; 2]; 2],
+        reflect: [[isize; 2]; 2],
+    ) -> Vec<[[isize; 2]; 2]> {
+        let (step, flip) = match self {
+            Symmetry::C3 => (2, false),
+            Symmetry::C6 => (1, false),
+            Symmetry::D6 => (2, true),
+            Symmetry::D12 => (1, true),
+            _ => (6, false),
+        };
+        let rotate = (1..step).fold(rotate, |m, _| mul(rotate, m));
+        let mut matrices = vec![[[1, 0], [0, 1]]];
+        for _ in 1..6 / step {
+            let last = matrices[matrices.len() - 1];
+            matrices.push(mul(rotate, last));
+        }
+        if flip {
+            let rotations = matrices.clone();
+            matrices.extend(rotations.into_iter().map(|m| mul(reflect, m)));
+        }
+        matrices
+    }
+
+    /// Whether every rotation and reflection in the symmetry maps
+    /// the neighborhood `nbhd` to itself.
+    ///
+    /// A symmetry is only valid for a rule whose neighborhood does not
+    /// depend on the parity of the coordinates if this is true.
+    pub fn preserves(self, nbhd: &[(isize, isize)]) -> bool {
+        self.matrices().into_iter().all(|m| {
+            nbhd.iter().all(|&(x, y)| {
+                nbhd.contains(&(m[0][0] * x + m[0][1] * y, m[1][0] * x + m[1][1] * y))
+            })
+        })
+    }
+}
+
+/// Multiplies two 2×2 matrices.
+fn mul(a: [[isize; 2]; 2], b: [[isize; 2]; 2]) -> [[isize; 2]; 2] {
+    let mut m = [[0; 2]; 2];
+    for (i, row) in m.iter_mut().enumerate() {
+        for (j, entry) in row.iter_mut().enumerate() {
+            *entry = a[i][0] * b[0][j] + a[i][1] * b[1][j];
+        }
+    }
+    m
+}
+
+/// A user-defined symmetry, given by an affine mapping of the coordinates
+/// `(x, y) ↦ (xx * x + xy * y + x0, yx * x + yy * y + y0)`.
+///
+/// Each cell must have the same state as its image in the same generation.
+/// Images outside the search range are in the background state.
+///
+/// The mapping should be a permutation of the cells in the world,
+/// e.g., a rotation or a reflection, so that repeatedly applying it
+/// returns to the original cell. Several mappings can be combined
+/// to describe symmetry groups that are not in `Symmetry`.
+///
+/// For example, the reflection across the middle column of a world
+/// of width `w` is `xx = -1, xy = 0, x0 = w - 1, yx = 0, yy = 1, y0 = 0`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct SymmetryMap {
+    /// The coefficient of `x` in the new x-coordinate.
+    pub xx: isize,
+    /// The coefficient of `y` in the new x-coordinate.
+    pub xy: isize,
+    /// The constant term of the new x-coordinate.
+    pub x0: isize,
+    /// The coefficient of `x` in the new y-coordinate.
+    pub yx: isize,
+    /// The coefficient of `y` in the new y-coordinate.
+    pub yy: isize,
+    /// The constant term of the new y-coordinate.
+    pub y0: isize,
+}
+
+impl SymmetryMap {
+    /// Creates a mapping from the rows of its matrix.
+    pub fn new(x_row: (isize, isize, isize), y_row: (isize, isize, isize)) -> Self {
+        let (xx, xy, x0) = x_row;
+        let (yx, yy, y0) = y_row;
+        SymmetryMap {
+            xx,
+            xy,
+            x0,
+            yx,
+            yy,
+            y0,
         }
     }
+
+    /// Applies the mapping to a coord. The generation is unchanged.
+    pub fn apply(&self, coord: Coord) -> Coord {
+        let (x, y, t) = coord;
+        (
+            self.xx * x + self.xy * y + self.x0,
+            self.yx * x + self.yy * y + self.y0,
+            t,
+        )
+    }
 }
 
 /// The order to find a new unknown cell.
@@ -264,10 +632,32 @@ pub enum SearchOrder {
     /// 369
     /// ```
     ColumnFirst,
+
+    /// Searches all cells of a diagonal first,
+    /// and the go to the next diagonal.
+    ///
+    /// ```plaintext
+    /// 136
+    /// 258
+    /// 479
+    /// ```
+    ///
+    /// This is useful for diagonal spaceships.
+    Diagonal,
+
+    /// Searches the cells in the center first,
+    /// and then the rings around it, from the inside out.
+    ///
+    /// ```plaintext
+    /// 234
+    /// 516
+    /// 789
+    /// ```
+    CenterOut,
 }
 
 /// How to choose a state for an unknown cell.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 #[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub enum NewState {
     /// Choosing the background state.
@@ -283,16 +673,425 @@ pub enum NewState {
     /// `Dead` in odd generations.
     ///
     /// For other rules, it always chooese `Alive`.
+    #[default]
     ChooseAlive,
     /// Random.
     ///
     /// For life-like rules, the probability of either state is 1/2.
     Random,
+    /// Random, but biased.
+    ///
+    /// Chooses the oposite of the background state with the given
+    /// probability in percent, and the background state otherwise.
+    /// For example, `Biased(75)` is often better than `ChooseAlive`
+    /// for dense oscillators, because it still explores different
+    /// parts of the search tree.
+    Biased(u8),
+}
+
+impl NewState {
+    /// Whether the states are chosen by the random number generator.
+    pub fn is_random(self) -> bool {
+        matches!(self, NewState::Random | NewState::Biased(_))
+    }
+}
+
+/// The search engine.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum Engine {
+    /// Decides one cell at a time, and deduces the consequences.
+    ///
+    /// This is the default.
+    #[default]
+    Cells,
+    /// Enumerates all the possible states of a whole line at once,
+    /// in the style of gfind, and searches them line by line.
+    ///
+    /// A line is a row, a column, a diagonal or a ring, depending on
+    /// the search order. The possible states of a line are found by the
+    /// same deductions as `Cells`, and only those which leave some
+    /// possible states for the next line are kept. This is much faster
+    /// for some narrow spaceship searches, where many branches die
+    /// within a few lines.
+    ///
+    /// Options about choosing and backtracking single cells, e.g.,
+    /// `backjump`, `nogood_limit`, `restart_interval`, `activity_order`,
+    /// `probe_depth`, `deepening`, `dead_end_cache`, `reduce_max` and
+    /// `minimize`, do not apply. `Search::search_partial` still uses
+    /// `Cells`.
+    Rows,
+}
+
+/// The topology of a bounded grid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum Topology {
+    /// `T`.
+    ///
+    /// A torus. Cells on the opposite edges of the world are neighbors.
+    ///
+    /// If one of the dimensions is infinite, it is a cylinder, or a tube:
+    /// e.g., `T40,0` only wraps the x dimension, with a period of 40,
+    /// and the pattern is bounded by the height of the world in the
+    /// configuration. This is useful for wicks and fuses.
+    Torus,
+    /// `P`.
+    ///
+    /// A bounded plane. Cells outside the world are always dead,
+    /// and cells that would be born there are ignored.
+    ///
+    /// If one of the dimensions is infinite, it is an infinite strip.
+    Plane,
+    /// `K`.
+    ///
+    /// A Klein bottle. Cells on the opposite edges of the world are
+    /// neighbors, as on a torus, but one pair of edges is joined with
+    /// a twist, i.e., reversed.
+    ///
+    /// The twisted edges are marked by an asterisk after the corresponding
+    /// dimension. If `twisted_width` is `true`, as in `K40*,20`, the top and
+    /// bottom edges are twisted: going across them, `x` becomes
+    /// `width - 1 - x`. Otherwise, as in `K40,20*`, the left and right
+    /// edges are twisted, and `y` becomes `height - 1 - y`.
+    ///
+    /// Both dimensions must be finite. The neighborhood of the rule must be
+    /// symmetric under the reflection.
+    KleinBottle { twisted_width: bool },
+    /// `C`.
+    ///
+    /// A cross-surface, also known as a real projective plane.
+    /// Both pairs of opposite edges are joined with a twist.
+    ///
+    /// Both dimensions must be finite. The neighborhood of the rule must be
+    /// symmetric under the reflections across both axes.
+    CrossSurface,
+    /// `S`.
+    ///
+    /// A sphere. The top edge is joined to the left edge, and the bottom
+    /// edge to the right edge: the cell above `(x, 0)` is `(0, x)`, and the
+    /// cell below `(x, size - 1)` is `(size - 1, x)`.
+    ///
+    /// The grid must be a square of a finite size, given by a single number,
+    /// e.g., `S40`. The neighborhood of the rule must be symmetric under
+    /// rotations by 90 degrees.
+    Sphere,
+}
+
+/// A bounded grid, given by a suffix of the rule string in the
+/// [notation of Golly](https://golly.sourceforge.net/Help/bounded.html),
+/// e.g., `B3/S23:T40,20`.
+///
+/// All the topologies in Golly are supported: tori (`T`), bounded planes
+/// (`P`), Klein bottles (`K`), cross-surfaces (`C`) and spheres (`S`).
+/// Shifted edges are not supported.
+///
+/// On a cross-surface or a sphere, there is no cell beyond the corners of
+/// the grid: a neighbor that is outside the grid in both directions is
+/// always in the background state. Rules that depend on
+/// the parity of the coordinates are only supported on tori and bounded
+/// planes.
+///
+/// The size of the grid overrides the size of the world in the
+/// configuration. A dimension of `0` is infinite. Then the size of the world
+/// in this direction is unchanged, and the pattern is not allowed to grow
+/// out of the world, as in a world without a bounded grid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct BoundedGrid {
+    /// The topology of the grid.
+    pub topology: Topology,
+
+    /// The width of the grid, or `0` if it is infinite.
+    pub width: isize,
+
+    /// The height of the grid, or `0` if it is infinite.
+    ///
+    /// If it is omitted in the suffix, it is the same as the width.
+    pub height: isize,
+}
+
+impl FromStr for BoundedGrid {
+    type Err = Error;
+
+    /// Parses the suffix of a bounded grid, without the colon, e.g., `T40,20`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::GridError(s.to_string());
+        let mut chars = s.chars();
+        let kind = match chars.next() {
+            Some(c) if "TPKCS".contains(c.to_ascii_uppercase()) => c.to_ascii_uppercase(),
+            _ => return Err(invalid()),
+        };
+        let klein = kind == 'K';
+        let mut size = chars.as_str().splitn(2, ',');
+        let parse_size = |size: &str| -> Result<(isize, bool), Error> {
+            let (size, twisted) = match size.strip_suffix('*') {
+                Some(size) if klein => (size, true),
+                _ => (size, false),
+            };
+            match size.parse::<isize>() {
+                Ok(size) if size >= 0 => Ok((size, twisted)),
+                _ => Err(invalid()),
+            }
+        };
+        let (width, twisted_width) = parse_size(size.next().unwrap())?;
+        let (height, twisted_height) = match size.next() {
+            Some(height) => parse_size(height)?,
+            None => (width, false),
+        };
+        if width == 0 && height == 0 {
+            return Err(invalid());
+        }
+        let topology = match kind {
+            'T' => Topology::Torus,
+            'P' => Topology::Plane,
+            _ if width == 0 || height == 0 => return Err(invalid()),
+            'K' if twisted_width != twisted_height => Topology::KleinBottle { twisted_width },
+            'C' => Topology::CrossSurface,
+            'S' if width == height => Topology::Sphere,
+            _ => return Err(invalid()),
+        };
+        Ok(BoundedGrid {
+            topology,
+            width,
+            height,
+        })
+    }
+}
+
+impl BoundedGrid {
+    /// The symmetry that the neighborhood of the rule must have,
+    /// so that the joined edges preserve the neighborhood.
+    pub(crate) fn nbhd_symmetry(self) -> Symmetry {
+        match self.topology {
+            Topology::Torus | Topology::Plane => Symmetry::C1,
+            Topology::KleinBottle {
+                twisted_width: true,
+            } => Symmetry::D2Col,
+            Topology::KleinBottle {
+                twisted_width: false,
+            } => Symmetry::D2Row,
+            Topology::CrossSurface => Symmetry::D4Ortho,
+            Topology::Sphere => Symmetry::C4,
+        }
+    }
+}
+
+impl Display for BoundedGrid {
+    /// Writes the suffix of the bounded grid, without the colon.
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        let (width, height) = (self.width, self.height);
+        match self.topology {
+            Topology::Torus => write!(f, "T{},{}", width, height),
+            Topology::Plane => write!(f, "P{},{}", width, height),
+            Topology::KleinBottle {
+                twisted_width: true,
+            } => write!(f, "K{}*,{}", width, height),
+            Topology::KleinBottle {
+                twisted_width: false,
+            } => write!(f, "K{},{}*", width, height),
+            Topology::CrossSurface => write!(f, "C{},{}", width, height),
+            Topology::Sphere => write!(f, "S{}", width),
+        }
+    }
+}
+
+/// A built-in neighborhood, which replaces the Moore neighborhood
+/// of a totalistic Life-like rule.
+///
+/// The rule string then gives the numbers of living neighbors in the new
+/// neighborhood, e.g., `B3/S23` with `Knight` means that a cell is born
+/// with 3 living knight-move neighbors.
+///
+/// Requires the `custom` feature. The implication table is generated
+/// from the rule string as a `Custom` rule.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum Neighborhood {
+    /// `far-corners`.
+    ///
+    /// The Moore neighborhood whose corners are moved to distance 2:
+    /// the four orthogonal neighbors and the four cells at `(±2, ±2)`.
+    FarCorners,
+    /// `far-edges`.
+    ///
+    /// The Moore neighborhood whose edges are moved to distance 2:
+    /// the four diagonal neighbors and the four cells at `(0, ±2)`
+    /// and `(±2, 0)`.
+    FarEdges,
+    /// `cross`.
+    ///
+    /// The eight cells at distance 1 or 2 on the same row or column.
+    Cross,
+    /// `hash`.
+    ///
+    /// The twelve cells at the crossings and the ends of the strokes
+    /// of a `#`: the four diagonal neighbors and the eight knight-move
+    /// neighbors.
+    ///
+    /// Numbers of living neighbors greater than 8 cannot be written
+    /// in the rule string, so they never cause births or survivals.
+    Hash,
+    /// `knight`.
+    ///
+    /// The eight cells a knight's move away.
+    Knight,
+}
+
+impl Neighborhood {
+    /// The offsets of the neighbors.
+    pub fn offsets(self) -> &'static [(isize, isize)] {
+        match self {
+            Neighborhood::FarCorners => &[
+                (0, -1),
+                (0, 1),
+                (-1, 0),
+                (1, 0),
+                (-2, -2),
+                (2, 2),
+                (-2, 2),
+                (2, -2),
+            ],
+            Neighborhood::FarEdges => &[
+                (-1, -1),
+                (1, 1),
+                (-1, 1),
+                (1, -1),
+                (0, -2),
+                (0, 2),
+                (-2, 0),
+                (2, 0),
+            ],
+            Neighborhood::Cross => &[
+                (0, -1),
+                (0, 1),
+                (-1, 0),
+                (1, 0),
+                (0, -2),
+                (0, 2),
+                (-2, 0),
+                (2, 0),
+            ],
+            Neighborhood::Hash => &[
+                (-1, -1),
+                (1, 1),
+                (-1, 1),
+                (1, -1),
+                (1, 2),
+                (-1, -2),
+                (2, 1),
+                (-2, -1),
+                (2, -1),
+                (-2, 1),
+                (1, -2),
+                (-1, 2),
+            ],
+            Neighborhood::Knight => &[
+                (1, 2),
+                (-1, -2),
+                (2, 1),
+                (-2, -1),
+                (2, -1),
+                (-2, 1),
+                (1, -2),
+                (-1, 2),
+            ],
+        }
+    }
+}
+
+impl FromStr for Neighborhood {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "far-corners" => Ok(Neighborhood::FarCorners),
+            "far-edges" => Ok(Neighborhood::FarEdges),
+            "cross" => Ok(Neighborhood::Cross),
+            "hash" => Ok(Neighborhood::Hash),
+            "knight" => Ok(Neighborhood::Knight),
+            _ => Err(String::from("invalid neighborhood")),
+        }
+    }
+}
+
+/// A cell whose state is known before the search.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct KnownCell {
+    /// The coordinates of the known cell.
+    pub coord: Coord,
+
+    /// The state of the known cell.
+    pub state: State,
+}
+
+/// A periodic agar, which fills the background instead of the vacuum.
+///
+/// The agar repeats a tile of `width` × `height` cells in space,
+/// and the generations of the tile in time. The cell `(x, y, t)` has
+/// the state of the cell `(x mod width, y mod height)` in the generation
+/// `t mod gens.len()` of the tile.
+///
+/// The agar must evolve by the rule. Otherwise, there would be conflicts
+/// around the pattern, and no result would be found. Only rules with
+/// 2 states are supported.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct Agar {
+    /// The width of the tile.
+    pub width: isize,
+
+    /// The height of the tile.
+    pub height: isize,
+
+    /// The generations of the tile in RLE, in the same format as
+    /// the patterns in the `blocklist`.
+    pub gens: Vec<String>,
 }
 
-impl Default for NewState {
-    fn default() -> Self {
-        NewState::ChooseAlive
+impl Agar {
+    /// Parses the generations of the tile.
+    ///
+    /// Returns an error if the size of the tile is not positive,
+    /// if there is no generation, or if a generation is invalid RLE,
+    /// does not fit in the tile, or contains states other than
+    /// `DEAD` and `ALIVE`.
+    pub(crate) fn parse(&self) -> Result<Vec<Pattern>, Error> {
+        if self.width <= 0 || self.height <= 0 || self.gens.is_empty() {
+            return Err(Error::AgarError(format!(
+                "{} × {} tile with {} generations",
+                self.width,
+                self.height,
+                self.gens.len()
+            )));
+        }
+        self.gens
+            .iter()
+            .map(|rle| {
+                let pattern = parse_rle(rle).map_err(|_| Error::AgarError(rle.clone()))?;
+                if pattern
+                    .iter()
+                    .all(|&(x, y, state)| x < self.width && y < self.height && state == ALIVE)
+                {
+                    Ok(pattern)
+                } else {
+                    Err(Error::AgarError(rle.clone()))
+                }
+            })
+            .collect()
+    }
+
+    /// The state of the agar at the coordinates, given the parsed tile.
+    pub(crate) fn state(&self, tile: &[Pattern], coord: Coord) -> State {
+        let (x, y, t) = coord;
+        let pattern = &tile[t.rem_euclid(tile.len() as isize) as usize];
+        let cell = (x.rem_euclid(self.width), y.rem_euclid(self.height), ALIVE);
+        if pattern.binary_search(&cell).is_ok() {
+            ALIVE
+        } else {
+            DEAD
+        }
     }
 }
 
@@ -321,6 +1120,22 @@ pub struct Config {
     /// Vertical translation.
     pub dy: isize,
 
+    /// Translation in every generation, i.e., the horizontal and vertical
+    /// displacement of the reference frame in each step.
+    ///
+    /// The successor of the cell at `(x, y)` in generation `t` is the cell
+    /// at `(x + shift.0, y + shift.1)` in generation `t + 1`. After the last
+    /// generation, the transformation and the translation defined by `dx`
+    /// and `dy` are also applied. So a spaceship of period 1 with
+    /// the translation `(1, 0)` is a still life in the frame with
+    /// shift `(1, 0)`, and the cells are linked in the same way.
+    ///
+    /// Every element of the symmetry must leave the shift unchanged,
+    /// as for `(dx, dy)`. A shift is not supported with glide symmetries,
+    /// the time-reversal symmetry or an agar.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub shift: (isize, isize),
+
     /// Transformations (rotations and reflections) after the last generation.
     ///
     /// After the last generation, the pattern will return to
@@ -337,22 +1152,107 @@ pub struct Config {
     /// and then go to another cell.
     ///
     /// `None` means that it will automatically choose a search order
-    /// according to the width and height of the world.
+    /// according to the width and height of the world,
+    /// and the `diagonal_width`.
     pub search_order: Option<SearchOrder>,
 
     /// How to choose a state for an unknown cell.
     pub new_state: NewState,
 
+    /// The search engine.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub engine: Engine,
+
     /// The number of minimum living cells in all generations must not
     /// exceed this number.
     ///
     /// `None` means that there is no limit for the cell count.
     pub max_cell_count: Option<usize>,
 
+    /// The number of living cells in the first generation must not
+    /// exceed this number.
+    ///
+    /// Unlike `max_cell_count`, this also deduces the states of
+    /// the unknown cells: once the bound is reached, all the other
+    /// cells in the first generation are set to the background.
+    ///
+    /// `None` means that there is no limit for the cell count.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub max_gen0_cell_count: Option<usize>,
+
+    /// The number of living cells in every generation must not
+    /// exceed this number, i.e., it bounds the maximal population
+    /// over all generations, while `max_cell_count` bounds the minimal one.
+    ///
+    /// Like `max_gen0_cell_count`, once the bound is reached in
+    /// a generation, all the other cells in that generation are set
+    /// to the background.
+    ///
+    /// `None` means that there is no limit for the cell count.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub max_gen_cell_count: Option<usize>,
+
+    /// The number of living cells in each row of each generation
+    /// must not exceed this number.
+    ///
+    /// Once the bound is reached in a row, all the other cells in that row
+    /// are set to the background. This is useful for searching for
+    /// thin patterns.
+    ///
+    /// `None` means that there is no limit for the cell count.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub max_row_cell_count: Option<usize>,
+
+    /// The number of living cells in each column of each generation
+    /// must not exceed this number.
+    ///
+    /// Once the bound is reached in a column, all the other cells
+    /// in that column are set to the background.
+    ///
+    /// `None` means that there is no limit for the cell count.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub max_column_cell_count: Option<usize>,
+
+    /// The number of rotor cells must not exceed this number.
+    ///
+    /// A rotor cell is a cell whose state changes during the period,
+    /// i.e., which is in its background state in some generations,
+    /// and not in others. This is useful for searching for oscillators
+    /// with small rotors.
+    ///
+    /// `None` means that there is no limit for the number of rotor cells.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub max_rotor_cell_count: Option<usize>,
+
+    /// Restricts the search range to a diagonal strip.
+    ///
+    /// If it is `Some(d)`, the cells whose x and y coordinates differ
+    /// by `d` or more are in the background state, so that only
+    /// the cells within a band of width `2 * d - 1` around the diagonal
+    /// are searched. This is the natural search range for diagonal
+    /// spaceships, and is much smaller than the whole square.
+    ///
+    /// `None` means that the whole world is searched.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub diagonal_width: Option<isize>,
+
+    /// Restricts the search range to a shape, given by the `(x, y)`
+    /// coordinates of the cells in it.
+    ///
+    /// Cells in the world but outside the shape are in the background
+    /// state in all generations, as the cells outside the world.
+    /// This is useful for search ranges that are not rectangles,
+    /// e.g., an L-shape or a cross.
+    ///
+    /// `None` means that the whole world is searched.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub shape: Option<Vec<(isize, isize)>>,
+
     /// Whether to force the first row/column to be nonempty.
     ///
     /// Here 'front' means the first row or column to be searched,
-    /// according to the search order.
+    /// according to the search order. For diagonal and center-out
+    /// search orders, it means the first row and the first column together.
     #[derivative(Default(value = "true"))]
     pub non_empty_front: bool,
 
@@ -363,39 +1263,430 @@ pub struct Config {
     /// the current result minus one.
     pub reduce_max: bool,
 
+    /// Whether to search for the result with the smallest cell count,
+    /// instead of the first result.
+    ///
+    /// Whenever a result is found, the `max_cell_count` is set to
+    /// its cell count minus one, and the search continues, until
+    /// no more results can be found. Then the last result,
+    /// which has the smallest cell count, is returned.
+    ///
+    /// Here the cell count is the minimum number of living cells
+    /// in all generations, as in `max_cell_count`.
+    /// It does not apply to `Search::search_partial`.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub minimize: bool,
+
     /// The rule string of the cellular automaton.
     #[derivative(Default(value = "String::from(\"B3/S23\")"))]
     pub rule_string: String,
-}
 
-impl Config {
-    /// Sets up a new configuration with given size.
-    pub fn new(width: isize, height: isize, period: isize) -> Self {
-        Config {
-            width,
-            height,
-            period,
-            ..Config::default()
-        }
-    }
+    /// Cells whose states are known before the search.
+    ///
+    /// Cells outside the search range, and cells whose states are
+    /// already determined by the boundary or the symmetry, are ignored.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub known_cells: Vec<KnownCell>,
 
-    /// Sets the translations `(dx, dy)`.
-    pub fn set_translate(mut self, dx: isize, dy: isize) -> Self {
-        self.dx = dx;
-        self.dy = dy;
-        self
-    }
+    /// Known patterns in RLE, which are not reported as results.
+    ///
+    /// A result is skipped if some generation of it is the same as one of
+    /// these patterns, up to translation, rotation and reflection,
+    /// so that re-running a search does not find the known results again.
+    /// Cells in the background state are not part of a pattern.
+    ///
+    /// The patterns are in the same format as `Search::rle_gen`,
+    /// without unknown cells.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub blocklist: Vec<String>,
 
-    /// Sets the transformation.
-    pub fn set_transform(mut self, transform: Transform) -> Self {
-        self.transform = transform;
-        self
-    }
+    /// Whether to also skip a result if generation 0 contains a pattern
+    /// in the `blocklist` somewhere.
+    ///
+    /// Then all the cells in the bounding box of the pattern must have
+    /// the same states as in the pattern.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub blocklist_subpattern: bool,
 
-    /// Sets the symmetry.
-    pub fn set_symmetry(mut self, symmetry: Symmetry) -> Self {
-        self.symmetry = symmetry;
-        self
+    /// Cells whose states are irrelevant.
+    ///
+    /// They are still searched, but they are not counted in the cell counts,
+    /// and are ignored when checking whether the pattern is empty,
+    /// whether the front is empty, and whether the period is too small.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub dont_care_cells: Vec<Coord>,
+
+    /// Cells that are dead in all generations, given by their
+    /// `(x, y)` coordinates.
+    ///
+    /// They are known from the start of the search, e.g., to carve
+    /// channels or to keep lanes clear. Cells outside the world are ignored.
+    ///
+    /// Note that they are dead even if the background is not,
+    /// e.g., for rules with `B0` or with an agar.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub dead_cells: Vec<(isize, isize)>,
+
+    /// Cells that have the same state in all generations, given by their
+    /// `(x, y)` coordinates.
+    ///
+    /// This is useful to fix the stator of an oscillator, or a casing
+    /// around it. The states are compared relative to the backgrounds,
+    /// as in the symmetries. Cells outside the world are ignored.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub stator_cells: Vec<(isize, isize)>,
+
+    /// A periodic agar in the background, instead of the vacuum.
+    ///
+    /// Cells outside the search range are then in the state of the agar,
+    /// and the background state of each cell, e.g., in the cell counts,
+    /// in `NewState::ChooseDead` and in the symmetries, is the state
+    /// of the agar there. For rules with `B0`, the agar replaces the
+    /// alternating background, and the period need not be even.
+    ///
+    /// After the period, the agar must return to itself, with the
+    /// transformation and the translation applied.
+    /// See `Agar` for details.
+    ///
+    /// `None` means that the background is the vacuum.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub agar: Option<Agar>,
+
+    /// The seed of the random number generator,
+    /// which is used when `new_state` is `NewState::Random`
+    /// or `NewState::Biased`.
+    ///
+    /// With the same seed and the same configuration, the results
+    /// are always found in the same order, on every platform.
+    ///
+    /// `None` means that the seed is chosen randomly. The chosen seed
+    /// is recorded in the configuration of the world, and is written in
+    /// the output of `Search::rle_gen` when the states are chosen randomly.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub seed: Option<u64>,
+
+    /// A built-in neighborhood that replaces the Moore neighborhood
+    /// of the rule.
+    ///
+    /// `None` means that the neighborhood is given by the rule string.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub neighborhood: Option<Neighborhood>,
+
+    /// Moves the axes of the reflections across the middle row
+    /// and the middle column, in `D2-`, `D2|`, `D4+`, `G-`, `G|`,
+    /// `D2-g` and `D2|g`,
+    /// measured in half cells.
+    ///
+    /// The first number moves the vertical axis to the right,
+    /// the second moves the horizontal axis downwards.
+    /// An odd number switches between an axis through the cells
+    /// and an axis between the cells, e.g., for a pattern of even width
+    /// in a world of odd width.
+    ///
+    /// Cells whose reflections are out of the world are in the background state.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub axis_offset: (isize, isize),
+
+    /// Moves the axes of the reflections across the diagonal
+    /// and the antidiagonal, in `D2\`, `D2/`, `D4X`, `G\` and `G/`.
+    ///
+    /// The first number `k` moves the diagonal to the line `y = x + k`,
+    /// so that a cell `(x, y)` is reflected to `(y - k, x + k)`.
+    /// The second number `k` moves the antidiagonal to the line
+    /// `x + y = width - 1 + k`.
+    ///
+    /// This is useful for diagonal ships whose axis of symmetry
+    /// is not the diagonal of the world.
+    ///
+    /// Cells whose reflections are out of the world are in the background state.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub diagonal_offset: (isize, isize),
+
+    /// Time-reversal symmetry.
+    ///
+    /// If it is `Some(transform)`, generation `period - t` must be
+    /// generation `t` with the transformation applied, so that the pattern
+    /// runs backwards in the second half of the period. `Some(Transform::Id)`
+    /// means that generation `period - t` equals generation `t`.
+    ///
+    /// Generation `period` is generation 0 with the transformation
+    /// and the translation of the configuration applied,
+    /// so this is mainly useful for oscillators.
+    ///
+    /// `None` means that there is no time-reversal symmetry.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub time_reversal: Option<Transform>,
+
+    /// Whether the symmetry is only required in generation 0.
+    ///
+    /// Later generations are then free to break the symmetry.
+    /// This is useful for finding predecessors of symmetric patterns,
+    /// whose evolutions need not stay symmetric in the world.
+    ///
+    /// This applies to `symmetry` and `symmetry_maps`, but not to
+    /// glide symmetries and the time-reversal symmetry,
+    /// which relate different generations.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub symmetry_gen0_only: bool,
+
+    /// User-defined symmetries, in addition to `symmetry`.
+    ///
+    /// See `SymmetryMap` for details.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub symmetry_maps: Vec<SymmetryMap>,
+
+    /// Whether to only consistify the cells in a fundamental domain
+    /// of the symmetry.
+    ///
+    /// The other cells are still stored, and their states are copied
+    /// from their symmetric cells in the domain. Consistifying them
+    /// would give the same deductions as their symmetric cells,
+    /// so they are skipped, which reduces the propagation work
+    /// by up to the order of the symmetry, e.g., 8 for `D8`.
+    /// It does not reduce the memory used by the world.
+    ///
+    /// This only takes effect when the rule is isotropic (see
+    /// `Rule::is_isotropic`) and does not depend on the parity of the
+    /// coordinates, every element of the symmetry maps the neighborhood
+    /// of the rule to itself, the world has no bounded grid or agar,
+    /// and the symmetry is centered in the world, i.e., it is not
+    /// a glide symmetry or a hexagonal symmetry, `axis_offset` and
+    /// `diagonal_offset` are zero, and `symmetry_gen0_only` is not set.
+    /// `symmetry_maps` are not considered.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub fundamental_domain: bool,
+
+    /// Whether to search only one orientation of each pattern
+    /// when there is no symmetry.
+    ///
+    /// For each rotation and reflection that maps the search space
+    /// to itself, the states of the cells in the search order must be
+    /// lexicographically no larger than the states of their images.
+    /// So the rotations and reflections of a pattern are not searched
+    /// separately, which can cut the time of an asymmetric search by up to
+    /// the number of such transformations, i.e., 8 for a still life
+    /// in a square world.
+    ///
+    /// This only takes effect when the `symmetry` is `C1`, the `transform`
+    /// is `Id`, the rule is isotropic (see `Rule::is_isotropic`) and does not
+    /// depend on the parity of the coordinates, and there is no bounded grid,
+    /// known cell, cell whose state is irrelevant, user-defined symmetry,
+    /// time-reversal symmetry or agar, and `non_empty_front` is not set.
+    /// Only the transformations that fix the translation, and keep the shape
+    /// of the world and the population bounds of the rows and columns,
+    /// are used.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub symmetry_breaking: bool,
+
+    /// Whether to jump back to the last decision that is responsible
+    /// for a conflict, instead of the last decision.
+    ///
+    /// The decisions are found by following the causes of the deduced
+    /// cells back from the cells involved in the conflict.
+    /// It skips the branches that would fail for the same reason,
+    /// so the results are found in the same order, with fewer conflicts,
+    /// but each conflict takes longer to analyze.
+    ///
+    /// This assumes that consistifying a cell only depends on the cell,
+    /// its neighbors, and their successors. It falls back to the usual
+    /// backtracking for Generations rules, rule ranges, and conflicts
+    /// with `max_cell_count`, `max_gen0_cell_count`, `max_gen_cell_count`
+    /// or `non_empty_front`.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub backjump: bool,
+
+    /// The maximal number of nogoods learnt from the conflicts.
+    ///
+    /// A nogood is a combination of states of the decided cells
+    /// that leads to a conflict. They are learnt when the conflicts
+    /// are analyzed for backjumping, so a positive limit also enables
+    /// `backjump`. During the search, a cell is deduced when all the
+    /// other cells in a nogood have the states in it.
+    ///
+    /// When the limit is reached, the less active half of the nogoods
+    /// are forgotten, where the activity of a nogood counts the
+    /// conflicts and deductions it causes.
+    ///
+    /// `0` means that no nogoods are learnt.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub nogood_limit: usize,
+
+    /// The number of conflicts between restarts, scaled by
+    /// the Luby sequence `1, 1, 2, 1, 1, 2, 4, 1, 1, 2, ...`.
+    ///
+    /// The `i`-th restart happens after `restart_interval` times
+    /// the `i`-th term of the sequence conflicts since the previous one.
+    /// A restart undoes all the decisions, but keeps the learnt nogoods.
+    /// It is most useful together with a random `new_state` or
+    /// `nogood_limit`, so that the next attempt makes different decisions.
+    ///
+    /// Restarts are disabled after a result is found, so that the same
+    /// result is not found again.
+    ///
+    /// `None` means that there are no restarts.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub restart_interval: Option<u64>,
+
+    /// Whether to choose the unknown cell that is most often involved
+    /// in recent conflicts, instead of the first one in the `search_order`.
+    ///
+    /// Each cell has an activity, which is increased whenever the cell is
+    /// involved in a conflict. The increment grows after each conflict,
+    /// so that the activities of older conflicts decay, as in the VSIDS
+    /// heuristic of SAT solvers. Ties are broken by the `search_order`,
+    /// which is also used before any conflict.
+    ///
+    /// The cells involved in a conflict are only known for the rules
+    /// supported by `backjump`. A custom chooser set by
+    /// `Search::set_chooser` takes precedence over this.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub activity_order: bool,
+
+    /// The number of conflicts before the search order is re-evaluated.
+    /// It doubles after each re-evaluation.
+    ///
+    /// A static search order is often badly wrong, e.g., for asymmetric
+    /// translations. At each re-evaluation, the order that puts the cells
+    /// most often involved in recent conflicts first is chosen among
+    /// row-first, column-first and diagonal, using the same activities
+    /// as `activity_order`. If it is not the current order, all the
+    /// decisions are undone, as in a restart, and the search continues
+    /// in the new order.
+    ///
+    /// Center-out is never chosen. The cells in the middle are usually
+    /// the most active ones, but it leaves the front to the end.
+    ///
+    /// Like restarts, re-evaluations are disabled after a result is found.
+    /// They are also not done in `Search::search_partial`, and only
+    /// work for the rules supported by `backjump`.
+    ///
+    /// `None` means that the search order is never changed.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub order_switch_interval: Option<u64>,
+
+    /// The step of iterative deepening on the number of decisions.
+    ///
+    /// The number of decisions on the search path is capped, starting
+    /// from this value. Branches that need more decisions are cut.
+    /// When the search under the cap is exhausted, and some branches
+    /// were cut, the cap is raised by this value, and the search starts
+    /// again from the beginning. This finds shallow results, e.g., of
+    /// some spaceship searches, much faster than a full depth-first search.
+    ///
+    /// Results that are found within the previous cap are skipped in the
+    /// later rounds, since they have been found before. This assumes that
+    /// the search makes the same decisions in each round, so it should not
+    /// be combined with random states, restarts, `activity_order` or
+    /// `order_switch_interval` if all the results are needed.
+    /// The learnt nogoods are forgotten when the cap is raised.
+    ///
+    /// `None` means that the number of decisions is not capped.
+    /// `0` is treated as `1`.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub deepening: Option<usize>,
+
+    /// The maximal number of dead ends to remember.
+    ///
+    /// When the search goes by rows or columns, the lines that can
+    /// interact with the unknown lines are remembered when every branch
+    /// below them leads to a conflict. When the search reaches the same
+    /// states at the same line again, it backtracks at once.
+    /// When the limit is reached, the least recently used dead end
+    /// is forgotten.
+    ///
+    /// This only applies when the search order is `RowFirst` or
+    /// `ColumnFirst`, and the constraints are local: no symmetry,
+    /// bounded grid, limit on the numbers of living cells,
+    /// `deepening`, `symmetry_breaking`, or rule to be deduced.
+    /// Otherwise it is ignored.
+    ///
+    /// `0` means that no dead ends are remembered.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub dead_end_cache: usize,
+
+    /// The number of unknown cells below which the search enumerates
+    /// their states directly.
+    ///
+    /// When there are at most this many unknown cells, all the
+    /// combinations of their states are tried in turn. The cells in
+    /// a combination are set at once and checked together, without the
+    /// bookkeeping of decisions and backtracking, which is slow near
+    /// the leaves of the search tree. Since nothing is deduced, this
+    /// only pays off for small thresholds, e.g., `8`.
+    ///
+    /// It does not apply to `Search::search_partial` or `Engine::Rows`.
+    ///
+    /// `0` means that the search never enumerates the cells directly.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub endgame_threshold: usize,
+
+    /// The maximal number of cells to examine when probing a state.
+    ///
+    /// Before deciding the state of a cell, each state is tried in turn,
+    /// and its consequences are deduced for at most `probe_depth` cells.
+    /// If one of the states leads to a conflict, the cell is set to the
+    /// other state, without a decision. This prunes the search tree,
+    /// but each decision takes longer.
+    ///
+    /// Probing is not supported for Generations rules.
+    ///
+    /// `0` means that there is no probing.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub probe_depth: usize,
+
+    /// Probes only once every `probe_interval` decisions.
+    ///
+    /// `0` is the same as `1`, which probes before every decision.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub probe_interval: u64,
+
+    /// The maximal wall-clock time of the search,
+    /// counted from the first call to `Search::search`.
+    ///
+    /// When the time is up, the search returns `Status::TimedOut`,
+    /// and the world is set to the deepest partial result reached
+    /// so far, i.e., the one with the most known cells.
+    /// Later searches time out immediately.
+    ///
+    /// `None` means that there is no time limit.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub time_limit: Option<Duration>,
+}
+
+impl Config {
+    /// Sets up a new configuration with given size.
+    pub fn new(width: isize, height: isize, period: isize) -> Self {
+        Config {
+            width,
+            height,
+            period,
+            ..Config::default()
+        }
+    }
+
+    /// Sets the translations `(dx, dy)`.
+    pub fn set_translate(mut self, dx: isize, dy: isize) -> Self {
+        self.dx = dx;
+        self.dy = dy;
+        self
+    }
+
+    /// Sets the translation in every generation.
+    pub fn set_shift(mut self, x: isize, y: isize) -> Self {
+        self.shift = (x, y);
+        self
+    }
+
+    /// Sets the transformation.
+    pub fn set_transform(mut self, transform: Transform) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    /// Sets the symmetry.
+    pub fn set_symmetry(mut self, symmetry: Symmetry) -> Self {
+        self.symmetry = symmetry;
+        self
     }
 
     /// Sets the search order.
@@ -410,12 +1701,96 @@ impl Config {
         self
     }
 
+    /// Sets the search engine.
+    pub fn set_engine(mut self, engine: Engine) -> Self {
+        self.engine = engine;
+        self
+    }
+
     /// Sets the maximal number of living cells.
     pub fn set_max_cell_count(mut self, max_cell_count: Option<usize>) -> Self {
         self.max_cell_count = max_cell_count;
         self
     }
 
+    /// Sets the maximal number of living cells in the first generation.
+    pub fn set_max_gen0_cell_count(mut self, max_gen0_cell_count: Option<usize>) -> Self {
+        self.max_gen0_cell_count = max_gen0_cell_count;
+        self
+    }
+
+    /// Sets the maximal number of living cells in every generation.
+    pub fn set_max_gen_cell_count(mut self, max_gen_cell_count: Option<usize>) -> Self {
+        self.max_gen_cell_count = max_gen_cell_count;
+        self
+    }
+
+    /// Sets the maximal number of living cells in each row.
+    pub fn set_max_row_cell_count(mut self, max_row_cell_count: Option<usize>) -> Self {
+        self.max_row_cell_count = max_row_cell_count;
+        self
+    }
+
+    /// Sets the maximal number of living cells in each column.
+    pub fn set_max_column_cell_count(mut self, max_column_cell_count: Option<usize>) -> Self {
+        self.max_column_cell_count = max_column_cell_count;
+        self
+    }
+
+    /// Sets the maximal number of rotor cells.
+    pub fn set_max_rotor_cell_count(mut self, max_rotor_cell_count: Option<usize>) -> Self {
+        self.max_rotor_cell_count = max_rotor_cell_count;
+        self
+    }
+
+    /// Sets the width of the diagonal strip that restricts the search range.
+    pub fn set_diagonal_width(mut self, diagonal_width: Option<isize>) -> Self {
+        self.diagonal_width = diagonal_width;
+        self
+    }
+
+    /// Sets the shape that restricts the search range.
+    pub fn set_shape(mut self, shape: Option<Vec<(isize, isize)>>) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    /// Adds the cells in a rectangle to the shape that restricts
+    /// the search range. If there is no shape yet, the shape
+    /// is this rectangle.
+    ///
+    /// The rectangle has its top left corner at `(x, y)`.
+    pub fn add_shape_rect(mut self, x: isize, y: isize, width: isize, height: isize) -> Self {
+        let shape = self.shape.get_or_insert_with(Vec::new);
+        for j in y..y + height {
+            for i in x..x + width {
+                shape.push((i, j));
+            }
+        }
+        self
+    }
+
+    /// Sets the shape that restricts the search range from a pattern in
+    /// [Plaintext](https://conwaylife.com/wiki/Plaintext) format.
+    ///
+    /// The living cells, i.e., `O`, `o` and `*`, are in the shape.
+    /// Lines starting with `!` are comments.
+    pub fn set_shape_plaintext(mut self, plaintext: &str) -> Self {
+        let shape = plaintext
+            .lines()
+            .filter(|line| !line.starts_with('!'))
+            .enumerate()
+            .flat_map(|(y, line)| {
+                line.chars()
+                    .enumerate()
+                    .filter(|&(_, c)| c == 'O' || c == 'o' || c == '*')
+                    .map(move |(x, _)| (x as isize, y as isize))
+            })
+            .collect();
+        self.shape = Some(shape);
+        self
+    }
+
     /// Sets whether to force the first row/column to be nonempty.
     pub fn set_non_empty_front(mut self, non_empty_front: bool) -> Self {
         self.non_empty_front = non_empty_front;
@@ -429,18 +1804,579 @@ impl Config {
         self
     }
 
+    /// Sets whether to search for the result with the smallest cell count.
+    pub fn set_minimize(mut self, minimize: bool) -> Self {
+        self.minimize = minimize;
+        self
+    }
+
     /// Sets the rule string.
     pub fn set_rule_string<S: ToString>(mut self, rule_string: S) -> Self {
         self.rule_string = rule_string.to_string();
         self
     }
 
+    /// Sets the cells whose states are known before the search.
+    pub fn set_known_cells(mut self, known_cells: Vec<KnownCell>) -> Self {
+        self.known_cells = known_cells;
+        self
+    }
+
+    /// Sets the known patterns which are not reported as results.
+    pub fn set_blocklist(mut self, blocklist: Vec<String>) -> Self {
+        self.blocklist = blocklist;
+        self
+    }
+
+    /// Sets whether to also skip a result if generation 0 contains
+    /// a pattern in the `blocklist`.
+    pub fn set_blocklist_subpattern(mut self, blocklist_subpattern: bool) -> Self {
+        self.blocklist_subpattern = blocklist_subpattern;
+        self
+    }
+
+    /// Sets the cells whose states are irrelevant.
+    pub fn set_dont_care_cells(mut self, dont_care_cells: Vec<Coord>) -> Self {
+        self.dont_care_cells = dont_care_cells;
+        self
+    }
+
+    /// Sets the cells that are dead in all generations.
+    pub fn set_dead_cells(mut self, dead_cells: Vec<(isize, isize)>) -> Self {
+        self.dead_cells = dead_cells;
+        self
+    }
+
+    /// Adds the cells in a rectangle to the cells that are dead
+    /// in all generations.
+    ///
+    /// The rectangle has its top left corner at `(x, y)`.
+    pub fn add_dead_rect(mut self, x: isize, y: isize, width: isize, height: isize) -> Self {
+        for j in y..y + height {
+            for i in x..x + width {
+                self.dead_cells.push((i, j));
+            }
+        }
+        self
+    }
+
+    /// Sets the cells that have the same state in all generations.
+    pub fn set_stator_cells(mut self, stator_cells: Vec<(isize, isize)>) -> Self {
+        self.stator_cells = stator_cells;
+        self
+    }
+
+    /// Adds the cells in a rectangle to the cells that have the same state
+    /// in all generations.
+    ///
+    /// The rectangle has its top left corner at `(x, y)`.
+    pub fn add_stator_rect(mut self, x: isize, y: isize, width: isize, height: isize) -> Self {
+        for j in y..y + height {
+            for i in x..x + width {
+                self.stator_cells.push((i, j));
+            }
+        }
+        self
+    }
+
+    /// Sets the periodic agar in the background.
+    pub fn set_agar(mut self, agar: Option<Agar>) -> Self {
+        self.agar = agar;
+        self
+    }
+
+    /// Sets the seed of the random number generator.
+    pub fn set_seed(mut self, seed: Option<u64>) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Sets the built-in neighborhood that replaces the Moore neighborhood
+    /// of the rule.
+    pub fn set_neighborhood(mut self, neighborhood: Option<Neighborhood>) -> Self {
+        self.neighborhood = neighborhood;
+        self
+    }
+
+    /// Sets the offset of the axes of the reflections.
+    pub fn set_axis_offset(mut self, x: isize, y: isize) -> Self {
+        self.axis_offset = (x, y);
+        self
+    }
+
+    /// Sets the offset of the axes of the diagonal reflections.
+    pub fn set_diagonal_offset(mut self, diag: isize, antidiag: isize) -> Self {
+        self.diagonal_offset = (diag, antidiag);
+        self
+    }
+
+    /// Sets the time-reversal symmetry.
+    pub fn set_time_reversal(mut self, time_reversal: Option<Transform>) -> Self {
+        self.time_reversal = time_reversal;
+        self
+    }
+
+    /// Sets whether the symmetry is only required in generation 0.
+    pub fn set_symmetry_gen0_only(mut self, symmetry_gen0_only: bool) -> Self {
+        self.symmetry_gen0_only = symmetry_gen0_only;
+        self
+    }
+
+    /// Sets the user-defined symmetries.
+    pub fn set_symmetry_maps(mut self, symmetry_maps: Vec<SymmetryMap>) -> Self {
+        self.symmetry_maps = symmetry_maps;
+        self
+    }
+
+    /// Sets whether to only consistify the cells in a fundamental domain
+    /// of the symmetry.
+    pub fn set_fundamental_domain(mut self, fundamental_domain: bool) -> Self {
+        self.fundamental_domain = fundamental_domain;
+        self
+    }
+
+    /// Sets whether to search only one orientation of each pattern
+    /// when there is no symmetry.
+    pub fn set_symmetry_breaking(mut self, symmetry_breaking: bool) -> Self {
+        self.symmetry_breaking = symmetry_breaking;
+        self
+    }
+
+    /// Sets whether to jump back to the last decision that is responsible
+    /// for a conflict.
+    pub fn set_backjump(mut self, backjump: bool) -> Self {
+        self.backjump = backjump;
+        self
+    }
+
+    /// Sets the maximal number of nogoods learnt from the conflicts.
+    pub fn set_nogood_limit(mut self, nogood_limit: usize) -> Self {
+        self.nogood_limit = nogood_limit;
+        self
+    }
+
+    /// Sets the number of conflicts between restarts.
+    pub fn set_restart_interval(mut self, restart_interval: Option<u64>) -> Self {
+        self.restart_interval = restart_interval;
+        self
+    }
+
+    /// Sets whether to choose the unknown cell that is most often involved
+    /// in recent conflicts.
+    pub fn set_activity_order(mut self, activity_order: bool) -> Self {
+        self.activity_order = activity_order;
+        self
+    }
+
+    /// Sets the number of conflicts before the search order is re-evaluated.
+    pub fn set_order_switch_interval(mut self, order_switch_interval: Option<u64>) -> Self {
+        self.order_switch_interval = order_switch_interval;
+        self
+    }
+
+    /// Sets the step of iterative deepening on the number of decisions.
+    pub fn set_deepening(mut self, deepening: Option<usize>) -> Self {
+        self.deepening = deepening;
+        self
+    }
+
+    /// Sets the maximal number of dead ends to remember.
+    pub fn set_dead_end_cache(mut self, dead_end_cache: usize) -> Self {
+        self.dead_end_cache = dead_end_cache;
+        self
+    }
+
+    /// Sets the number of unknown cells below which the search
+    /// enumerates their states directly.
+    pub fn set_endgame_threshold(mut self, endgame_threshold: usize) -> Self {
+        self.endgame_threshold = endgame_threshold;
+        self
+    }
+
+    /// Sets the maximal number of cells to examine when probing a state.
+    pub fn set_probe_depth(mut self, probe_depth: usize) -> Self {
+        self.probe_depth = probe_depth;
+        self
+    }
+
+    /// Sets how often to probe before a decision.
+    pub fn set_probe_interval(mut self, probe_interval: u64) -> Self {
+        self.probe_interval = probe_interval;
+        self
+    }
+
+    /// Sets the maximal wall-clock time of the search.
+    pub fn set_time_limit(mut self, time_limit: Option<Duration>) -> Self {
+        self.time_limit = time_limit;
+        self
+    }
+
+    /// Checks whether the width, the height and the period are positive.
+    ///
+    /// Infinite dimensions of the bounded grid should be already applied,
+    /// so that they are not taken as zero.
+    pub fn check_dimensions(&self) -> Result<(), Error> {
+        if self.width <= 0 || self.height <= 0 || self.period <= 0 {
+            return Err(Error::SizeError(self.width, self.height, self.period));
+        }
+        Ok(())
+    }
+
+    /// Checks whether the size of the world is valid for the symmetry
+    /// and the transformation.
+    ///
+    /// Some symmetries and transformations are only valid when the world
+    /// is square. Otherwise the symmetric cells would be linked wrongly.
+    ///
+    /// This includes the transformation of the time-reversal symmetry.
+    pub fn check_size(&self) -> Result<(), Error> {
+        if self.width != self.height {
+            if self.symmetry.square_world() {
+                return Err(Error::SymmetrySizeError(
+                    self.symmetry,
+                    self.width,
+                    self.height,
+                ));
+            }
+            if self.transform.square_world() {
+                return Err(Error::TransformSizeError(
+                    self.transform,
+                    self.width,
+                    self.height,
+                ));
+            }
+            if let Some(transform) = self.time_reversal.filter(|t| t.square_world()) {
+                return Err(Error::TransformSizeError(
+                    transform,
+                    self.width,
+                    self.height,
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks whether the `shift` is supported by the other options.
+    ///
+    /// A shift is not supported with the time-reversal symmetry or an agar.
+    /// The symmetry is checked in `check_symmetry`.
+    pub fn check_shift(&self) -> Result<(), Error> {
+        if self.shift == (0, 0) {
+            return Ok(());
+        }
+        if self.time_reversal.is_some() {
+            return Err(Error::ShiftError(String::from("time-reversal symmetries")));
+        }
+        if self.agar.is_some() {
+            return Err(Error::ShiftError(String::from("agars")));
+        }
+        Ok(())
+    }
+
+    /// Checks whether the patterns in the `blocklist` are valid RLE.
+    pub fn check_blocklist(&self) -> Result<(), Error> {
+        for rle in self.blocklist.iter() {
+            parse_rle(rle)?;
+        }
+        Ok(())
+    }
+
+    /// Checks whether the `agar` is valid. See `Agar::parse`.
+    pub fn check_agar(&self) -> Result<(), Error> {
+        if let Some(agar) = self.agar.as_ref() {
+            agar.parse()?;
+        }
+        Ok(())
+    }
+
+    /// Checks whether the period, the transformation and the translation
+    /// are valid for the symmetry.
+    ///
+    /// A glide symmetry maps generation `t` to generation `t + period / 2`,
+    /// reflecting the pattern and translating it by `(dx / 2, dy / 2)`.
+    /// Applying it twice must give the translation `(dx, dy)` over a period,
+    /// so the period, `dx` and `dy` must be even, the transformation must be
+    /// `Id`, and the translation must be parallel to the axis of reflection.
+    /// There must be no `shift`.
+    ///
+    /// A gutter symmetry requires its axis to go through a row or a column
+    /// of cells, i.e., `height + axis_offset.1` must be odd for `D2-g`,
+    /// and `width + axis_offset.0` must be odd for `D2|g`.
+    ///
+    /// Other symmetries must agree with the transformation and
+    /// the translation, unless `symmetry_gen0_only` is set:
+    /// every element of the symmetry must leave `(dx, dy)` and the `shift`
+    /// unchanged, and conjugating an element by the transformation must give
+    /// an element of the symmetry. For example, `D4+` does not allow
+    /// any translation. Otherwise the error lists the symmetries that
+    /// are compatible with the configuration. See `is_compatible`.
+    ///
+    /// The size of the world for hexagonal symmetries depends on
+    /// the grid of the rule, and is checked when the world is created.
+    pub fn check_symmetry(&self) -> Result<(), Error> {
+        if self.symmetry.is_glide() && !self.glide_compatible(self.symmetry) {
+            return Err(Error::GlideSymmetryError(self.symmetry));
+        }
+        let gutter = match self.symmetry {
+            Symmetry::GutterRow => self.height + self.axis_offset.1,
+            Symmetry::GutterCol => self.width + self.axis_offset.0,
+            _ => 1,
+        };
+        if gutter % 2 == 0 {
+            return Err(Error::GutterSymmetryError(self.symmetry));
+        }
+        if !self.symmetry.is_glide()
+            && !self.symmetry_gen0_only
+            && !self.translation_compatible(self.symmetry)
+        {
+            return Err(Error::SymmetryTranslationError(
+                self.symmetry,
+                self.compatible_symmetries(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Whether the center of the world is a center of the hexagonal
+    /// symmetries.
+    ///
+    /// On the hexagonal grid, it must be a cell, i.e., the width and
+    /// the height must be odd. On the triangular grid, it must be
+    /// a vertex, i.e., the height must be even, and `width + height`
+    /// must be `1` modulo `4`.
+    pub(crate) fn hex_centered(&self, triangle: bool) -> bool {
+        if triangle {
+            self.height % 2 == 0 && (self.width + self.height) % 4 == 1
+        } else {
+            self.width % 2 != 0 && self.height % 2 != 0
+        }
+    }
+
+    /// Whether the symmetry preserves the neighborhoods of a rule
+    /// whose neighborhood depends on the parity of `x + y`,
+    /// e.g., a rule on the triangular grid.
+    ///
+    /// `nbhd` and `nbhd_odd` are the neighborhoods of cells with even
+    /// and odd `x + y`. A rotation or a reflection maps a cell to a cell
+    /// of the same parity or of the other parity, depending on its center
+    /// or axis. The neighborhood of a cell must be mapped to the
+    /// neighborhood of its image.
+    ///
+    /// Hexagonal symmetries are assumed to be valid, as the neighborhood
+    /// on the triangular grid is invariant under the rotations and
+    /// reflections around a vertex.
+    pub fn preserves_parity_nbhd(
+        &self,
+        nbhd: &[(isize, isize)],
+        nbhd_odd: &[(isize, isize)],
+    ) -> bool {
+        let (mirror_x, mirror_y) = (
+            self.width - 1 + self.axis_offset.0,
+            self.height - 1 + self.axis_offset.1,
+        );
+        let (hx, hy) = (self.dx / 2, self.dy / 2);
+        // The transformations in the symmetry, with the sums of the
+        // constant terms in their mappings of coordinates.
+        let elements: Vec<(Transform, isize)> = match self.symmetry {
+            sym if sym.is_hex() => return true,
+            Symmetry::GlideRow => vec![(Transform::FlipRow, mirror_y + hx + hy)],
+            Symmetry::GlideCol => vec![(Transform::FlipCol, mirror_x + hx + hy)],
+            Symmetry::GlideDiag => vec![(Transform::FlipDiag, 0)],
+            Symmetry::GlideAntidiag => vec![(Transform::FlipAntidiag, 0)],
+            sym => sym
+                .transforms()
+                .iter()
+                .map(|&transform| {
+                    let shift = match transform {
+                        Transform::FlipRow => mirror_y,
+                        Transform::FlipCol => mirror_x,
+                        Transform::Rotate180 if sym == Symmetry::D4Ortho => mirror_x + mirror_y,
+                        Transform::Rotate180 => self.width + self.height,
+                        _ => 0,
+                    };
+                    (transform, shift)
+                })
+                .collect(),
+        };
+        elements.into_iter().all(|(transform, shift)| {
+            let m = transform.matrix();
+            let target = if shift % 2 == 0 { nbhd } else { nbhd_odd };
+            nbhd.iter().all(|&(x, y)| {
+                target.contains(&(m[0][0] * x + m[0][1] * y, m[1][0] * x + m[1][1] * y))
+            })
+        })
+    }
+
+    /// Whether a glide symmetry agrees with the period, the transformation
+    /// and the translation. See `check_symmetry`.
+    fn glide_compatible(&self, symmetry: Symmetry) -> bool {
+        let parallel = match symmetry {
+            Symmetry::GlideRow => self.dy == 0,
+            Symmetry::GlideCol => self.dx == 0,
+            Symmetry::GlideDiag => self.dx == self.dy,
+            Symmetry::GlideAntidiag => self.dx == -self.dy,
+            _ => return false,
+        };
+        parallel
+            && self.shift == (0, 0)
+            && self.period % 2 == 0
+            && self.dx % 2 == 0
+            && self.dy % 2 == 0
+            && self.transform == Transform::Id
+    }
+
+    /// Whether a symmetry is compatible with the size of the world,
+    /// the period, the transformation and the translation.
+    ///
+    /// Since every generation must have the symmetry, the transformation
+    /// and the translation after the last generation must map a symmetric
+    /// pattern to a symmetric pattern. So every element of the symmetry
+    /// must leave `(dx, dy)` unchanged, and conjugating an element by the
+    /// transformation must give an element of the symmetry.
+    ///
+    /// Glide symmetries are checked as in `check_symmetry`.
+    /// Gutter symmetries need an odd height or width.
+    /// Hexagonal symmetries need a world whose center is a cell of
+    /// the hexagonal grid or a vertex of the triangular grid, no translation,
+    /// and a transformation other than `R90` and `R270`.
+    /// If `symmetry_gen0_only` is set, other symmetries only need
+    /// a square world when they require it.
+    ///
+    /// The rule is not checked. See `Symmetry::preserves`.
+    ///
+    /// The axes are assumed to be at the center of the world, i.e.,
+    /// `axis_offset`, `diagonal_offset` and `symmetry_maps` are ignored.
+    pub fn is_compatible(&self, symmetry: Symmetry) -> bool {
+        if symmetry.square_world() && self.width != self.height {
+            return false;
+        }
+        if symmetry.is_glide() {
+            return self.glide_compatible(symmetry);
+        }
+        let odd = match symmetry {
+            Symmetry::GutterRow => self.height % 2 != 0,
+            Symmetry::GutterCol => self.width % 2 != 0,
+            sym if sym.is_hex() => self.hex_centered(false) || self.hex_centered(true),
+            _ => true,
+        };
+        if !odd {
+            return false;
+        }
+        self.symmetry_gen0_only || self.translation_compatible(symmetry)
+    }
+
+    /// Whether a symmetry which is not a glide symmetry agrees with
+    /// the transformation and the translation. See `is_compatible`.
+    fn translation_compatible(&self, symmetry: Symmetry) -> bool {
+        if symmetry.is_hex() {
+            return (self.dx, self.dy) == (0, 0)
+                && self.shift == (0, 0)
+                && self.transform != Transform::Rotate90
+                && self.transform != Transform::Rotate270;
+        }
+        let elements = symmetry.transforms();
+        elements.iter().all(|&element| {
+            let conjugate = self
+                .transform
+                .compose(element)
+                .compose(self.transform.inverse());
+            element.fixes(self.dx, self.dy)
+                && element.fixes(self.shift.0, self.shift.1)
+                && elements.contains(&conjugate)
+        })
+    }
+
+    /// All symmetries that are compatible with the configuration,
+    /// in the order of `Symmetry::ALL`. See `is_compatible`.
+    ///
+    /// Frontends can use this to disable invalid choices.
+    pub fn compatible_symmetries(&self) -> Vec<Symmetry> {
+        Symmetry::ALL
+            .iter()
+            .copied()
+            .filter(|&symmetry| self.is_compatible(symmetry))
+            .collect()
+    }
+
+    /// The largest compatible symmetry that is neither a glide symmetry,
+    /// a gutter symmetry, nor a hexagonal symmetry.
+    ///
+    /// The compatible subgroups of _D_<sub>8</sub> are closed under joins,
+    /// so every other such compatible symmetry is contained in it.
+    pub fn max_symmetry(&self) -> Symmetry {
+        self.compatible_symmetries()
+            .into_iter()
+            .filter(|&symmetry| {
+                !symmetry.is_glide()
+                    && !symmetry.is_hex()
+                    && symmetry != Symmetry::GutterRow
+                    && symmetry != Symmetry::GutterCol
+            })
+            .max_by_key(|symmetry| symmetry.transforms().len())
+            .unwrap_or_default()
+    }
+
+    /// Doubles the period, and adjusts the transformation and the translation
+    /// so that they describe two periods of the original configuration.
+    ///
+    /// This is mainly for rules that contain `B0`, where the background
+    /// alternates between `Dead` and `Alive`, so that the period must be even.
+    /// A pattern with an odd period `p` in such a rule returns to itself
+    /// with the states inverted after `p` generations. It returns to itself
+    /// after `2 * p` generations, so it can be found by searching with
+    /// the doubled period. Note that such a search may also find patterns
+    /// whose period is exactly `2 * p`.
+    ///
+    /// Known cells and cells whose states are irrelevant are unchanged.
+    pub fn double_period(mut self) -> Self {
+        let (x, y, _) = self.translate((0, 0, 2 * self.period));
+        let transform = match self.transform {
+            Transform::Rotate90 | Transform::Rotate270 => Transform::Rotate180,
+            _ => Transform::Id,
+        };
+        let (dx, dy) = match transform {
+            Transform::Rotate180 => (self.width - 1 - x, self.height - 1 - y),
+            _ => (x, y),
+        };
+        self.period *= 2;
+        self.transform = transform;
+        self.dx = dx;
+        self.dy = dy;
+        self
+    }
+
+    /// Expands the world to the smallest valid size for the symmetry
+    /// and the transformation.
+    ///
+    /// If the symmetry or the transformation requires the world to be square,
+    /// both the width and the height are set to the larger one.
+    /// Otherwise the size is unchanged.
+    ///
+    /// Use `check_size` first if you want to tell whether the size is changed.
+    pub fn expand_size(mut self) -> Self {
+        if self.symmetry.square_world()
+            || self.transform.square_world()
+            || self.time_reversal.is_some_and(Transform::square_world)
+        {
+            let size = self.width.max(self.height);
+            self.width = size;
+            self.height = size;
+        }
+        self
+    }
+
     /// Automatically determines the search order if `search_order` is `None`.
+    ///
+    /// The search order is diagonal if the search range is restricted to
+    /// a diagonal strip in a square world.
     pub(crate) fn auto_search_order(&self) -> SearchOrder {
         self.search_order.unwrap_or_else(|| {
+            if self.diagonal_width.is_some() && self.width == self.height {
+                return SearchOrder::Diagonal;
+            }
             let (width, height) = match self.symmetry {
-                Symmetry::D2Row => (self.width, (self.height + 1) / 2),
-                Symmetry::D2Col => ((self.width + 1) / 2, self.height),
+                Symmetry::D2Row | Symmetry::GutterRow => (self.width, (self.height + 1) / 2),
+                Symmetry::D2Col | Symmetry::GutterCol => ((self.width + 1) / 2, self.height),
                 _ => (self.width, self.height),
             };
             match width.cmp(&height) {
@@ -457,38 +2393,91 @@ impl Config {
         })
     }
 
+    /// The number of lines in the world for the search order.
+    ///
+    /// A line is a row, a column, a diagonal, or a ring around the center,
+    /// depending on the search order. See `line_index`.
+    pub(crate) fn line_count(&self) -> isize {
+        match self.auto_search_order() {
+            SearchOrder::RowFirst => self.height,
+            SearchOrder::ColumnFirst => self.width,
+            SearchOrder::Diagonal => self.width + self.height - 1,
+            SearchOrder::CenterOut => (self.width.max(self.height) + 1) / 2,
+        }
+    }
+
+    /// The index of the line that contains the cell for the search order.
+    ///
+    /// Lines with smaller indices are searched first.
+    pub(crate) fn line_index(&self, x: isize, y: isize) -> isize {
+        match self.auto_search_order() {
+            SearchOrder::RowFirst => y,
+            SearchOrder::ColumnFirst => x,
+            SearchOrder::Diagonal => x + y,
+            SearchOrder::CenterOut => self.ring(x, y),
+        }
+    }
+
+    /// The index of the ring around the center that contains the cell.
+    fn ring(&self, x: isize, y: isize) -> isize {
+        (2 * x - self.width + 1)
+            .abs()
+            .max((2 * y - self.height + 1).abs())
+            / 2
+    }
+
+    /// The coordinates of all cells in a generation,
+    /// sorted by the search order.
+    ///
+    /// Cells are sorted by lines, and then by rows or columns within a line.
+    pub(crate) fn ordered_coords(&self, search_order: SearchOrder) -> Vec<(isize, isize)> {
+        let mut coords = Vec::with_capacity((self.width * self.height) as usize);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                coords.push((x, y));
+            }
+        }
+        coords.sort_by_key(|&(x, y)| match search_order {
+            SearchOrder::RowFirst => (y, x, 0),
+            SearchOrder::ColumnFirst => (x, y, 0),
+            SearchOrder::Diagonal => (x + y, x, y),
+            SearchOrder::CenterOut => (self.ring(x, y), y, x),
+        });
+        coords
+    }
+
+    /// Applies a transformation around the center of the world
+    /// to the coordinates of a cell.
+    pub(crate) fn apply_transform(
+        &self,
+        transform: Transform,
+        x: isize,
+        y: isize,
+    ) -> (isize, isize) {
+        match transform {
+            Transform::Id => (x, y),
+            Transform::Rotate90 => (y, self.width - 1 - x),
+            Transform::Rotate180 => (self.width - 1 - x, self.height - 1 - y),
+            Transform::Rotate270 => (self.height - 1 - y, x),
+            Transform::FlipRow => (x, self.height - 1 - y),
+            Transform::FlipCol => (self.width - 1 - x, y),
+            Transform::FlipDiag => (y, x),
+            Transform::FlipAntidiag => (self.height - 1 - y, self.width - 1 - x),
+        }
+    }
+
     /// Applies the transformation and translation to a coord.
     pub(crate) fn translate(&self, coord: Coord) -> Coord {
         let (mut x, mut y, mut t) = coord;
         while t < 0 {
             t += self.period;
-            let (new_x, new_y) = match self.transform {
-                Transform::Id => (x, y),
-                Transform::Rotate90 => (self.height - 1 - y, x),
-                Transform::Rotate180 => (self.width - 1 - x, self.height - 1 - y),
-                Transform::Rotate270 => (y, self.width - 1 - x),
-                Transform::FlipRow => (x, self.height - 1 - y),
-                Transform::FlipCol => (self.width - 1 - x, y),
-                Transform::FlipDiag => (y, x),
-                Transform::FlipAntidiag => (self.height - 1 - y, self.width - 1 - x),
-            };
+            let (new_x, new_y) = self.apply_transform(self.transform.inverse(), x, y);
             x = new_x - self.dx;
             y = new_y - self.dy;
         }
         while t >= self.period {
             t -= self.period;
-            x += self.dx;
-            y += self.dy;
-            let (new_x, new_y) = match self.transform {
-                Transform::Id => (x, y),
-                Transform::Rotate90 => (y, self.width - 1 - x),
-                Transform::Rotate180 => (self.width - 1 - x, self.height - 1 - y),
-                Transform::Rotate270 => (self.height - 1 - y, x),
-                Transform::FlipRow => (x, self.height - 1 - y),
-                Transform::FlipCol => (self.width - 1 - x, y),
-                Transform::FlipDiag => (y, x),
-                Transform::FlipAntidiag => (self.height - 1 - y, self.width - 1 - x),
-            };
+            let (new_x, new_y) = self.apply_transform(self.transform, x + self.dx, y + self.dy);
             x = new_x;
             y = new_y;
         }
@@ -496,38 +2485,124 @@ impl Config {
     }
 
     /// Creates a new world from the configuration.
-    /// Returns an error if the rule string is invalid.
+    /// Returns an error if the rule string is invalid,
+    /// or if the size of the world is invalid for the symmetry
+    /// or the transformation.
     ///
     /// In rules that contain `B0`, cells outside the search range are
     /// considered `Dead` in even generations, `Alive` in odd generations.
     /// In other rules, all cells outside the search range are `Dead`.
+    /// Thus rules that contain `B0` require an even period, and an error is
+    /// returned if the period is odd. See `double_period` for a workaround.
     ///
     /// After the last generation, the pattern will return to
     /// the first generation, applying the transformation first,
     /// and then the translation defined by `dx` and `dy`.
+    ///
+    /// If the rule string has the suffix of a bounded grid, e.g.,
+    /// `B3/S23:T40,20`, its size overrides the size of the world.
+    /// See `BoundedGrid` for details.
+    ///
+    /// If `neighborhood` is set, the rule string must be a totalistic
+    /// Life-like rule. See `Neighborhood` for details.
+    ///
+    /// Returns an error if the symmetry is a glide symmetry that does not
+    /// agree with the period or the translation. See `check_symmetry`.
+    /// Also returns an error if the width, the height or the period
+    /// is not positive, if a pattern in the `blocklist` is invalid,
+    /// or if the `agar` is invalid or does not return to itself.
+    /// See `check` for checking the configuration without creating the world.
     pub fn world(&self) -> Result<Box<dyn Search>, Error> {
-        if let Ok(rule) = self.rule_string.parse::<Life>() {
-            Ok(Box::new(World::new(&self, rule)))
-        } else if let Ok(rule) = self.rule_string.parse::<NtLife>() {
-            Ok(Box::new(World::new(&self, rule)))
-        } else if let Ok(rule) = self.rule_string.parse::<LifeGen>() {
-            if rule.gen() > 2 {
-                Ok(Box::new(World::new(&self, rule)))
-            } else {
-                let rule = rule.non_gen();
-                Ok(Box::new(World::new(&self, rule)))
+        let config = self.checked()?;
+        AnyRule::from_config(&config)?.world(&config)
+    }
+
+    /// Creates a new world from the configuration and a given rule,
+    /// instead of parsing the rule string.
+    ///
+    /// This is for rules that have no rule string, e.g., `Custom`.
+    /// The rule string in the configuration is then only used in the output.
+    ///
+    /// Returns an error if the size of the world is invalid for the symmetry
+    /// or the transformation, or if the rule contains `B0` and the period
+    /// is odd, or if a pattern in the `blocklist` or the `agar` is invalid.
+    pub fn world_with_rule<R: Rule + 'static>(&self, rule: R) -> Result<Box<dyn Search>, Error> {
+        let config = self.checked()?;
+        new_world(&config, rule)
+    }
+
+    /// Checks the whole configuration, without creating the world.
+    ///
+    /// This returns the same errors as `world`, e.g., when the rule string
+    /// is invalid, the size of the world is invalid for the symmetry,
+    /// or the rule contains `B0` and the period is odd. The only
+    /// exception is an agar that does not return to itself, which is
+    /// only found when the world is created.
+    pub fn check(&self) -> Result<(), Error> {
+        let config = self.checked()?;
+        AnyRule::from_config(&config)?.check(&config)
+    }
+
+    /// Checks the whole configuration as in `check`, and returns it
+    /// if it is valid.
+    ///
+    /// This ends a chain of `set_*` methods, e.g.,
+    /// `Config::new(20, 16, 7).set_translate(0, 3).build()?`.
+    pub fn build(self) -> Result<Self, Error> {
+        self.check()?;
+        Ok(self)
+    }
+
+    /// Applies the bounded grid to the configuration, and checks
+    /// the parts of it that do not depend on the rule.
+    pub(crate) fn checked(&self) -> Result<Config, Error> {
+        let config = self.apply_bounded_grid()?;
+        config.check_dimensions()?;
+        config.check_size()?;
+        config.check_symmetry()?;
+        config.check_shift()?;
+        config.check_blocklist()?;
+        config.check_agar()?;
+        Ok(config)
+    }
+
+    /// The bounded grid given by the suffix of the rule string,
+    /// e.g., `B3/S23:T40,20`, or `None` if there is no such suffix.
+    ///
+    /// Returns an error if the suffix is invalid or unsupported.
+    pub fn bounded_grid(&self) -> Result<Option<BoundedGrid>, Error> {
+        match self.rule_string.find(':') {
+            Some(i) => Ok(Some(self.rule_string[i + 1..].parse()?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Overrides the size of the world with the size of the bounded grid.
+    ///
+    /// Infinite dimensions of the grid are unchanged.
+    pub(crate) fn apply_bounded_grid(&self) -> Result<Config, Error> {
+        let mut config = self.clone();
+        if let Some(grid) = self.bounded_grid()? {
+            if grid.width > 0 {
+                config.width = grid.width;
             }
-        } else {
-            let rule = self
-                .rule_string
-                .parse::<NtLifeGen>()
-                .map_err(Error::ParseRuleError)?;
-            if rule.gen() > 2 {
-                Ok(Box::new(World::new(&self, rule)))
-            } else {
-                let rule = rule.non_gen();
-                Ok(Box::new(World::new(&self, rule)))
+            if grid.height > 0 {
+                config.height = grid.height;
             }
         }
+        Ok(config)
+    }
+
+    /// Creates a new world from the configuration, and deduces the states
+    /// of the cells that are forced by the configuration, without
+    /// deciding any unknown cell.
+    ///
+    /// This is a quick way to see what is already determined before
+    /// the real search starts.
+    ///
+    /// Returns `Ok(None)` if the configuration is contradictory.
+    pub fn preview(&self) -> Result<Option<Box<dyn Search>>, Error> {
+        let mut world = self.world()?;
+        Ok(if world.preview() { Some(world) } else { None })
     }
 }
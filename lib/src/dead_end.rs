@@ -0,0 +1,280 @@
+//! A cache of dead ends found by the search.
+//!
+//! When the search goes line by line, and all the constraints are local,
+//! whether the known lines can be extended to a result only depends on
+//! the last few lines, which are close enough to interact with the
+//! unknown lines. When every branch below such a state leads to a conflict,
+//! the states of these lines are remembered as a dead end, and the search
+//! backtracks as soon as it reaches them again, e.g., after a different
+//! choice in an earlier line.
+
+use crate::{
+    cells::{CellRef, State},
+    config::{SearchOrder, Symmetry, Transform},
+    rules::Rule,
+    search::Reason,
+    world::World,
+};
+use derivative::Derivative;
+use std::collections::{BTreeMap, HashMap};
+
+/// The index of the last known line, and the states of the cells
+/// in the lines of the window that ends with it.
+type Key = (isize, Vec<State>);
+
+/// A state on the search path where all the lines up to some line
+/// are known.
+struct Frontier {
+    /// Length of the `set_stack` when the state is reached.
+    len: usize,
+
+    /// The last known line and the states in the window.
+    key: Key,
+
+    /// Whether every branch below it has led to a conflict so far.
+    dead: bool,
+}
+
+/// The dead ends and the frontiers on the search path.
+#[derive(Derivative)]
+#[derivative(Default(bound = ""))]
+pub(crate) struct DeadEnds<'a, R: Rule> {
+    /// The cells in the search range, grouped by lines.
+    ///
+    /// Empty if the cache does not apply to the configuration.
+    lines: Vec<Vec<CellRef<'a, R>>>,
+
+    /// Number of known lines that the unknown lines can depend on.
+    window: usize,
+
+    /// The first line that can end a frontier.
+    ///
+    /// If `non_empty_front` is enabled, the front must be known.
+    first: isize,
+
+    /// The dead ends, and when they were last used.
+    cache: HashMap<Key, u64>,
+
+    /// The dead ends, sorted by when they were last used.
+    by_time: BTreeMap<u64, Key>,
+
+    /// Number of times that the cache is used.
+    time: u64,
+
+    /// The frontiers on the search path, from the earliest.
+    frontiers: Vec<Frontier>,
+}
+
+impl<'a, R: Rule> DeadEnds<'a, R> {
+    /// Whether the state is a known dead end.
+    ///
+    /// If it is, marks it as the most recently used.
+    fn get(&mut self, key: &Key) -> bool {
+        self.time += 1;
+        match self.cache.get_mut(key) {
+            Some(time) => {
+                self.by_time.remove(time);
+                *time = self.time;
+                self.by_time.insert(self.time, key.clone());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Adds a dead end.
+    ///
+    /// If there are already `limit` dead ends, forgets the least
+    /// recently used one first.
+    fn insert(&mut self, key: Key, limit: usize) {
+        if self.cache.contains_key(&key) {
+            return;
+        }
+        if self.cache.len() >= limit {
+            if let Some(&time) = self.by_time.keys().next() {
+                let oldest = self.by_time.remove(&time).unwrap();
+                self.cache.remove(&oldest);
+            }
+        }
+        self.time += 1;
+        self.by_time.insert(self.time, key.clone());
+        self.cache.insert(key, self.time);
+    }
+}
+
+impl<'a, R: Rule> World<'a, R> {
+    /// Whether the `dead_end_cache` applies to the configuration.
+    ///
+    /// The search order must be by rows or columns, and the constraints
+    /// must not relate cells that are far away from each other,
+    /// so there is no symmetry, bounded grid, count limit, etc.
+    fn dead_ends_apply(&self) -> bool {
+        let config = &self.config;
+        config.dead_end_cache > 0
+            && config.symmetry == Symmetry::C1
+            && config.symmetry_maps.is_empty()
+            && config.time_reversal.is_none()
+            && config.shift == (0, 0)
+            && matches!(config.bounded_grid(), Ok(None))
+            && config.max_cell_count.is_none()
+            && config.max_gen0_cell_count.is_none()
+            && config.max_gen_cell_count.is_none()
+            && config.max_row_cell_count.is_none()
+            && config.max_column_cell_count.is_none()
+            && config.max_rotor_cell_count.is_none()
+            && !config.reduce_max
+            && !config.minimize
+            && config.deepening.is_none()
+            && self.lex_pairs.is_empty()
+            && self.rule.deduced_rules().is_none()
+            && match config.auto_search_order() {
+                SearchOrder::RowFirst => {
+                    config.transform == Transform::Id || config.transform == Transform::FlipCol
+                }
+                SearchOrder::ColumnFirst => {
+                    config.transform == Transform::Id || config.transform == Transform::FlipRow
+                }
+                SearchOrder::Diagonal | SearchOrder::CenterOut => false,
+            }
+    }
+
+    /// Groups the cells by lines for the `dead_end_cache`, and computes
+    /// the size of the window.
+    pub(crate) fn init_dead_ends(mut self) -> Self {
+        if !self.dead_ends_apply() {
+            return self;
+        }
+        let config = &self.config;
+        let (axis, shift) = match config.auto_search_order() {
+            SearchOrder::RowFirst => (1, config.dy),
+            _ => (0, config.dx),
+        };
+        let radius = self
+            .rule
+            .nbhd()
+            .iter()
+            .chain(self.rule.nbhd_odd())
+            .map(|&(x, y)| if axis == 0 { x.abs() } else { y.abs() })
+            .fold(1, isize::max);
+        let mut lines = vec![Vec::new(); config.line_count() as usize];
+        let mut first = 0;
+        for &cell in self.search_list.iter() {
+            let line = config.line_index(cell.coord.0, cell.coord.1);
+            if config.non_empty_front && cell.is_front {
+                first = first.max(line);
+            }
+            lines[line as usize].push(cell);
+        }
+        // A cell within `radius` lines of the unknown lines still has
+        // to be consistified, which involves the `radius` lines before it.
+        self.dead_ends.window = (2 * radius + shift.abs()) as usize;
+        self.dead_ends.first = first;
+        self.dead_ends.lines = lines;
+        self
+    }
+
+    /// Checks whether the known lines end with a dead end.
+    ///
+    /// Otherwise, if more lines are known than at the last frontier,
+    /// and no cell after them is decided, records a new frontier.
+    ///
+    /// Returns `false` if it is a dead end.
+    pub(crate) fn check_dead_ends(&mut self) -> bool {
+        if self.dead_ends.lines.is_empty() {
+            return true;
+        }
+        let start = self
+            .dead_ends
+            .frontiers
+            .last()
+            .map_or(0, |frontier| frontier.key.0 + 1);
+        let known = self.dead_ends.lines[start as usize..]
+            .iter()
+            .take_while(|cells| cells.iter().all(|cell| cell.state.get().is_some()))
+            .count() as isize;
+        let line = start + known - 1;
+        if known == 0
+            || line < self.dead_ends.first
+            || line as usize + 1 == self.dead_ends.lines.len()
+        {
+            return true;
+        }
+        let config = &self.config;
+        let decided_after = self.set_stack.iter().any(|set_cell| {
+            let decided = match set_cell.reason {
+                Reason::Decide(_) | Reason::TryAnother(_, _) => true,
+                _ => set_cell.flipped,
+            };
+            let (x, y, _) = set_cell.cell.coord;
+            decided && config.line_index(x, y) > line
+        });
+        if decided_after {
+            return true;
+        }
+        let from = (line + 1 - self.dead_ends.window as isize).max(0) as usize;
+        let window = self.dead_ends.lines[from..=line as usize]
+            .iter()
+            .flatten()
+            .copied()
+            .collect::<Vec<_>>();
+        let key = (
+            line,
+            window
+                .iter()
+                .map(|cell| cell.state.get().unwrap())
+                .collect(),
+        );
+        if self.dead_ends.get(&key) {
+            self.conflict = Some(window);
+            return false;
+        }
+        self.dead_ends.frontiers.push(Frontier {
+            len: self.set_stack.len(),
+            key,
+            dead: true,
+        });
+        true
+    }
+
+    /// Called when the search backtracks to the first `len` cells
+    /// in the `set_stack`, because every branch after them leads to
+    /// a conflict.
+    ///
+    /// Remembers the frontiers after them as dead ends, unless some
+    /// branch below them did not lead to a conflict.
+    pub(crate) fn exhaust_dead_ends(&mut self, len: usize) {
+        while let Some(frontier) = self.dead_ends.frontiers.pop() {
+            if frontier.len <= len {
+                self.dead_ends.frontiers.push(frontier);
+                break;
+            }
+            if frontier.dead {
+                self.dead_ends
+                    .insert(frontier.key, self.config.dead_end_cache);
+            }
+        }
+    }
+
+    /// Forgets the frontiers after the first `len` cells in the `set_stack`,
+    /// without remembering them as dead ends.
+    pub(crate) fn leave_dead_ends(&mut self, len: usize) {
+        while self
+            .dead_ends
+            .frontiers
+            .last()
+            .is_some_and(|frontier| frontier.len > len)
+        {
+            self.dead_ends.frontiers.pop();
+        }
+    }
+
+    /// Called when the search reaches a state without a conflict,
+    /// i.e., a result, or a branch that is skipped by hand.
+    ///
+    /// Then none of the frontiers on the search path is a dead end.
+    pub(crate) fn revive_dead_ends(&mut self) {
+        for frontier in self.dead_ends.frontiers.iter_mut() {
+            frontier.dead = false;
+        }
+    }
+}
@@ -0,0 +1,100 @@
+//! Enumerating the last few unknown cells directly.
+//!
+//! See `Config::endgame_threshold`.
+
+use crate::{
+    cells::{CellRef, State},
+    rules::Rule,
+    search::Reason,
+    world::World,
+};
+
+/// The state of the enumeration.
+pub(crate) struct Endgame<'a, R: Rule> {
+    /// The cells that were unknown when the endgame started.
+    pub(crate) cells: Vec<CellRef<'a, R>>,
+
+    /// Length of the `set_stack` when the endgame started.
+    pub(crate) len: usize,
+
+    /// Index of the next assignment to try.
+    ///
+    /// The `i`-th cell has the `i`-th digit of the index in base `gen`,
+    /// counted from the background state.
+    pub(crate) next: u64,
+}
+
+impl<'a, R: Rule> World<'a, R> {
+    /// Whether the search is in the endgame, or should start it,
+    /// i.e., there are at most `endgame_threshold` unknown cells.
+    pub(crate) fn endgame_due(&self) -> bool {
+        if self.endgame.is_some() {
+            return true;
+        }
+        let threshold = self.config.endgame_threshold;
+        if threshold == 0 {
+            return false;
+        }
+        let unknown = self
+            .search_list
+            .iter()
+            .filter(|cell| cell.state.get().is_none())
+            .take(threshold + 1)
+            .count();
+        unknown <= threshold
+            && (self.rule.gen() as u64)
+                .checked_pow(unknown as u32)
+                .is_some()
+    }
+
+    /// Tries the assignments of the unknown cells in turn, starting the
+    /// endgame if it has not started, until one of them is consistent.
+    ///
+    /// All the cells of an assignment are set at once, and then checked
+    /// together, without deducing any cell.
+    ///
+    /// Returns `false` when there are no more assignments. Then the cells
+    /// are undone, and the endgame is over.
+    pub(crate) fn next_endgame(&mut self) -> bool {
+        let Endgame {
+            cells,
+            len,
+            mut next,
+        } = match self.endgame.take() {
+            Some(endgame) => endgame,
+            None => Endgame {
+                cells: self
+                    .search_list
+                    .iter()
+                    .copied()
+                    .filter(|cell| cell.state.get().is_none())
+                    .collect(),
+                len: self.set_stack.len(),
+                next: 0,
+            },
+        };
+        let gen = self.rule.gen();
+        let total = (gen as u64).pow(cells.len() as u32);
+        while next < total {
+            self.undo_to(len);
+            let mut index = next;
+            next += 1;
+            let mut consistent = true;
+            for &cell in cells.iter() {
+                let state = State((cell.background.0 + (index % gen as u64) as usize) % gen);
+                index /= gen as u64;
+                if !self.set_cell(cell, state, Reason::Deduce) {
+                    consistent = false;
+                    break;
+                }
+            }
+            if consistent && self.proceed() {
+                self.endgame = Some(Endgame { cells, len, next });
+                return true;
+            }
+            self.conflicts += 1;
+        }
+        self.undo_to(len);
+        false
+    }
+}
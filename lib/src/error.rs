@@ -1,4 +1,7 @@
-use crate::cells::Coord;
+use crate::{
+    cells::Coord,
+    config::{Symmetry, Transform},
+};
 use ca_rules::ParseRuleError;
 use thiserror::Error;
 
@@ -11,4 +14,68 @@ pub enum Error {
     SetCellError(Coord),
     #[error("Invalid rule: {0:?}")]
     ParseRuleError(#[from] ParseRuleError),
+    #[cfg(feature = "table")]
+    #[error("Invalid rule table: {0}")]
+    ParseTableError(String),
+    #[error("The width, height and period must be positive, but they are {0}, {1} and {2}")]
+    SizeError(isize, isize, isize),
+    #[error("Symmetry {0:?} requires a square world, but the world is {1} x {2}")]
+    SymmetrySizeError(Symmetry, isize, isize),
+    #[error("Transformation {0:?} requires a square world, but the world is {1} x {2}")]
+    TransformSizeError(Transform, isize, isize),
+    #[error(
+        "Glide symmetry {0:?} requires an even period (divisible by 4 for Generations rules with B0), \
+         no transformation, no shift, and an even translation along its axis"
+    )]
+    GlideSymmetryError(Symmetry),
+    #[error("Gutter symmetry {0:?} requires an axis through a row or column of cells")]
+    GutterSymmetryError(Symmetry),
+    #[error(
+        "Symmetry {0:?} does not agree with the translation and the transformation; \
+         the compatible symmetries are {1:?}"
+    )]
+    SymmetryTranslationError(Symmetry, Vec<Symmetry>),
+    #[error(
+        "Hexagonal symmetry {0:?} requires the center of the world to be a cell \
+         of the hexagonal grid, or a vertex of the triangular grid"
+    )]
+    HexSymmetryError(Symmetry),
+    #[error("Symmetry {0:?} does not preserve the neighborhood of the rule")]
+    NbhdSymmetryError(Symmetry),
+    #[error("Rules with B0 require an even period, but the period is {0}")]
+    B0OddPeriodError(isize),
+    #[error("Unsupported format version {0}")]
+    FormatVersionError(u32),
+    #[error("Rule {0} is not contained in rule {1}")]
+    RuleRangeError(String, String),
+    #[cfg(feature = "rule-range")]
+    #[error("Rule ranges with B0 are not supported: {0}")]
+    RangeB0Error(String),
+    #[cfg(feature = "rule-range")]
+    #[error("Unable to restore a search in rule range {0}")]
+    RestoreRangeError(String),
+    #[error("Invalid or unsupported bounded grid: {0}")]
+    GridError(String),
+    #[error("Bounded planes are not supported for rules with B0")]
+    GridB0Error,
+    #[error("Invalid neighborhood: {0}")]
+    NbhdError(String),
+    #[error("Invalid pattern in the blocklist: {0}")]
+    BlocklistError(String),
+    #[error("Invalid or unsupported agar: {0}")]
+    AgarError(String),
+    #[error("A shift in every generation is not supported with {0}")]
+    ShiftError(String),
+    #[cfg(all(feature = "pipeline", feature = "serialize"))]
+    #[error("Unable to write the checkpoint of the pipeline: {0}")]
+    PipelineCheckpointError(String),
+    #[cfg(feature = "split")]
+    #[error("The time limit is reached before subtree {0} is exhausted")]
+    SplitTimeLimitError(usize),
+    #[cfg(feature = "sat")]
+    #[error("Unsupported by the SAT backend: {0}")]
+    SatUnsupportedError(String),
+    #[cfg(feature = "sat")]
+    #[error("SAT solver error: {0}")]
+    SatSolverError(String),
 }
@@ -0,0 +1,120 @@
+//! Searching in larger and larger worlds.
+
+use crate::{config::Config, error::Error, search::Status, session::Session, traits::Search};
+
+/// How to enlarge the world when a search is exhausted. See `Session::grow`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Growth {
+    /// Number of columns added at each step.
+    pub width: isize,
+
+    /// Number of rows added at each step.
+    pub height: isize,
+
+    /// Whether to add columns and rows in turn, starting with columns,
+    /// instead of adding both at each step.
+    pub alternate: bool,
+
+    /// The width is never enlarged beyond this value.
+    ///
+    /// `None` means that there is no limit.
+    pub max_width: Option<isize>,
+
+    /// The height is never enlarged beyond this value.
+    ///
+    /// `None` means that there is no limit.
+    pub max_height: Option<isize>,
+}
+
+impl Growth {
+    /// Adds `width` columns and `height` rows at each step,
+    /// without limits on the size.
+    pub fn new(width: isize, height: isize) -> Self {
+        Growth {
+            width,
+            height,
+            alternate: false,
+            max_width: None,
+            max_height: None,
+        }
+    }
+
+    /// Sets whether to add columns and rows in turn.
+    pub fn set_alternate(mut self, alternate: bool) -> Self {
+        self.alternate = alternate;
+        self
+    }
+
+    /// Sets the maximal width and height.
+    pub fn set_max_size(mut self, max_width: Option<isize>, max_height: Option<isize>) -> Self {
+        self.max_width = max_width;
+        self.max_height = max_height;
+        self
+    }
+
+    /// The size of the world after the `step`-th enlargement of a world
+    /// of the given size, or `None` if it cannot be enlarged.
+    fn next_size(&self, width: isize, height: isize, step: usize) -> Option<(isize, isize)> {
+        let grow = |size: isize, add: isize, max: Option<isize>| {
+            max.map_or(size + add, |max| (size + add).min(max).max(size))
+        };
+        let new_width = grow(width, self.width, self.max_width);
+        let new_height = grow(height, self.height, self.max_height);
+        let size = if !self.alternate {
+            (new_width, new_height)
+        } else if step.is_multiple_of(2) && new_width > width || new_height == height {
+            (new_width, height)
+        } else {
+            (width, new_height)
+        };
+        if size == (width, height) {
+            None
+        } else {
+            Some(size)
+        }
+    }
+}
+
+impl Session {
+    /// Searches with the configuration, and whenever the search is
+    /// exhausted without a result, enlarges the world as in `growth`
+    /// and searches again.
+    ///
+    /// Rules are parsed in this session, so that their tables are reused
+    /// in the larger worlds. If the symmetry or the transformation
+    /// requires a square world, the size is expanded as in `expand_size`.
+    ///
+    /// Returns the last world, and the status of its search: `Found`
+    /// if there is a result, `None` if there are no results in the largest
+    /// world, `Searching` if the number of steps in some world exceeds
+    /// `max_step`, and `TimedOut` if the `time_limit` is reached.
+    ///
+    /// Note that the size of a bounded grid in the rule string overrides
+    /// the size of the world, so it is never enlarged. Then only the first
+    /// world is searched.
+    pub fn grow(
+        &mut self,
+        config: &Config,
+        growth: Growth,
+        max_step: Option<u64>,
+    ) -> Result<(Box<dyn Search>, Status), Error> {
+        let mut config = config.clone();
+        let mut step = 0;
+        loop {
+            let mut search = self.world(&config)?;
+            let status = search.search(max_step);
+            if status != Status::None || config.bounded_grid()?.is_some() {
+                return Ok((search, status));
+            }
+            match growth.next_size(config.width, config.height, step) {
+                Some((width, height)) => {
+                    config.width = width;
+                    config.height = height;
+                    config = config.expand_size();
+                    step += 1;
+                }
+                None => return Ok((search, status)),
+            }
+        }
+    }
+}
@@ -0,0 +1,28 @@
+//! Guiding the search by an external agent.
+
+use crate::{
+    cells::{Coord, State},
+    traits::Search,
+};
+
+/// An external agent that makes the decisions in the search,
+/// e.g., a human, a script, or a learned model.
+///
+/// It can be set by `Search::set_guide`. At each branch point, the guide
+/// is asked which unknown cell to decide next, and which state to try
+/// first. The built-in heuristics are used for whatever it leaves open.
+///
+/// It is more general than `ChooseCell` and `Policy`, which only choose
+/// the cell or the state, but also slower, since it is given the whole
+/// world instead of the states around a cell.
+pub trait Guide {
+    /// Chooses an unknown cell to decide, and optionally its state.
+    ///
+    /// `search` gives the current states of the cells and the configuration.
+    ///
+    /// Returns `None` to fall back to the `chooser` or the `search_order`.
+    /// If the state is `None`, it is chosen by the `policy` or the
+    /// `new_state` in the configuration. Known cells, cells outside
+    /// the world, and invalid states are also ignored.
+    fn decide(&mut self, search: &dyn Search) -> Option<(Coord, Option<State>)>;
+}
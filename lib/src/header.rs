@@ -0,0 +1,218 @@
+//! A self-describing header for the files written by rlifesrc.
+//!
+//! A header is a single line of the form
+//!
+//! ``` plaintext
+//! #C rlifesrc <kind> <version> <digest> <rule string>
+//! ```
+//!
+//! where `<digest>` is a 16-digit hexadecimal digest of the configuration.
+//! It starts with `#C`, so that it is read as a comment in RLE files.
+//! In HTML files, the line is wrapped in `<!--` and `-->`.
+
+use crate::config::Config;
+use std::{
+    fmt::{self, Display, Formatter},
+    io::{self, Read, Write},
+    str::FromStr,
+};
+
+/// The version of the file formats.
+///
+/// It is increased whenever the format of any file changes.
+pub const FORMAT_VERSION: u32 = 2;
+
+/// The prefix of a header line.
+const MAGIC: &str = "#C rlifesrc";
+
+/// The maximal length of a header line, in bytes.
+const MAX_HEADER_LEN: usize = 1 << 16;
+
+/// Kinds of files with a header.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileKind {
+    /// A trace of decisions, written by `write_trace`.
+    Trace,
+    /// An HTML gallery of results.
+    Gallery,
+    /// A census report of small objects.
+    Census,
+    /// A checkpoint of the search state, written by `write_checkpoint`.
+    Checkpoint,
+    /// A log of decisions, written by `write_decisions`.
+    Decisions,
+    /// A checkpoint of a pipeline, written by `Pipeline::write_checkpoint`.
+    Pipeline,
+}
+
+impl Display for FileKind {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let s = match self {
+            FileKind::Trace => "trace",
+            FileKind::Gallery => "gallery",
+            FileKind::Census => "census",
+            FileKind::Checkpoint => "checkpoint",
+            FileKind::Decisions => "decisions",
+            FileKind::Pipeline => "pipeline",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl FromStr for FileKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "trace" => Ok(FileKind::Trace),
+            "gallery" => Ok(FileKind::Gallery),
+            "census" => Ok(FileKind::Census),
+            "checkpoint" => Ok(FileKind::Checkpoint),
+            "decisions" => Ok(FileKind::Decisions),
+            "pipeline" => Ok(FileKind::Pipeline),
+            _ => Err(String::from("invalid file kind")),
+        }
+    }
+}
+
+/// Computes a digest of the configuration.
+///
+/// It is the 64-bit FNV-1a hash of the debug representation
+/// of the configuration.
+pub fn config_digest(config: &Config) -> u64 {
+    format!("{:?}", config)
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+/// The header of a file written by rlifesrc.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Header {
+    /// The kind of the file.
+    pub kind: FileKind,
+
+    /// The version of the file format.
+    pub version: u32,
+
+    /// The digest of the configuration. See `config_digest`.
+    pub digest: u64,
+
+    /// The rule string in the configuration.
+    pub rule_string: String,
+}
+
+impl Header {
+    /// Creates a header of the current format version
+    /// for a file produced with the configuration.
+    pub fn new(kind: FileKind, config: &Config) -> Self {
+        Header {
+            kind,
+            version: FORMAT_VERSION,
+            digest: config_digest(config),
+            rule_string: config.rule_string.clone(),
+        }
+    }
+
+    /// Whether the file was produced with the configuration.
+    pub fn matches(&self, config: &Config) -> bool {
+        self.digest == config_digest(config) && self.rule_string == config.rule_string
+    }
+
+    /// Writes the header line, followed by a newline.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writeln!(writer, "{}", self)
+    }
+
+    /// Reads and validates a header line of the given kind.
+    ///
+    /// Only the header line is read, so that the rest of the file can be
+    /// read from the same reader.
+    ///
+    /// Returns an error if the header is malformed, if the file is of
+    /// another kind, or if the format version is newer than
+    /// `FORMAT_VERSION`.
+    pub fn read_from<R: Read>(reader: &mut R, kind: FileKind) -> io::Result<Self> {
+        let mut line = Vec::new();
+        let mut byte = [0];
+        loop {
+            reader.read_exact(&mut byte)?;
+            if byte[0] == b'\n' {
+                break;
+            }
+            if line.len() >= MAX_HEADER_LEN {
+                return Err(invalid_data("header too long"));
+            }
+            line.push(byte[0]);
+        }
+        let line = String::from_utf8(line).map_err(|_| invalid_data("header is not UTF-8"))?;
+        let header = line.parse::<Header>().map_err(invalid_data)?;
+        if header.kind != kind {
+            return Err(invalid_data(format!(
+                "expected a {} file, found a {} file",
+                kind, header.kind
+            )));
+        }
+        if header.version > FORMAT_VERSION {
+            return Err(invalid_data(format!(
+                "unsupported format version {}",
+                header.version
+            )));
+        }
+        Ok(header)
+    }
+}
+
+impl Display for Header {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {:016x} {}",
+            MAGIC, self.kind, self.version, self.digest, self.rule_string
+        )
+    }
+}
+
+impl FromStr for Header {
+    type Err = String;
+
+    /// Parses a header line, without the newline.
+    ///
+    /// A line wrapped in `<!--` and `-->` is also accepted.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim_end_matches('\r');
+        let s = s
+            .strip_prefix("<!-- ")
+            .and_then(|s| s.strip_suffix(" -->"))
+            .unwrap_or(s);
+        let s = s
+            .strip_prefix(MAGIC)
+            .and_then(|s| s.strip_prefix(' '))
+            .ok_or_else(|| String::from("not an rlifesrc file"))?;
+        let mut fields = s.splitn(4, ' ');
+        let mut next = || {
+            fields
+                .next()
+                .ok_or_else(|| String::from("incomplete header"))
+        };
+        let kind = next()?.parse()?;
+        let version = next()?
+            .parse()
+            .map_err(|_| String::from("invalid format version"))?;
+        let digest =
+            u64::from_str_radix(next()?, 16).map_err(|_| String::from("invalid digest"))?;
+        let rule_string = next()?.to_string();
+        Ok(Header {
+            kind,
+            version,
+            digest,
+            rule_string,
+        })
+    }
+}
+
+/// Creates an `InvalidData` error.
+fn invalid_data<E: Into<Box<dyn std::error::Error + Send + Sync>>>(error: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
@@ -0,0 +1,40 @@
+//! Callbacks on the events in the search.
+
+use crate::{
+    cells::{Coord, State},
+    traits::Search,
+};
+
+/// Callbacks that are called on the events in the search,
+/// e.g., for visualizers and loggers.
+///
+/// They can be set by `Search::set_hooks`. All the methods do nothing
+/// by default.
+///
+/// The hooks can not change the search directly. To stop the search
+/// on some custom criterion, keep a clone of a `Stop` handle that is
+/// set by `Search::set_stop`, and stop it in a hook.
+pub trait Hooks {
+    /// Called when the state of a cell is set.
+    ///
+    /// `decision` is `true` if the state is chosen by a decision,
+    /// or is another state of a decision that leads to a conflict,
+    /// and `false` if it is deduced from other cells.
+    fn on_set_cell(&mut self, _coord: Coord, _state: State, _decision: bool) {}
+
+    /// Called when a decision is backtracked.
+    ///
+    /// `depth` is the number of cells that are still known,
+    /// including the cells known before the search.
+    fn on_backtrack(&mut self, _depth: usize) {}
+
+    /// Called when a conflict is found.
+    ///
+    /// `conflicts` is the number of conflicts so far, including this one.
+    fn on_conflict(&mut self, _conflicts: u64) {}
+
+    /// Called when a result is found.
+    ///
+    /// `search` is the world of the result.
+    fn on_solution(&mut self, _search: &dyn Search) {}
+}
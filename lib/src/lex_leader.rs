@@ -0,0 +1,122 @@
+//! Symmetry-breaking constraints for searches without symmetry.
+//!
+//! For each rotation and reflection that maps the search space to itself,
+//! the states of the cells, read in a fixed order, must be
+//! lexicographically no larger than the states of their images.
+//! Each orbit of patterns has exactly one smallest element,
+//! so only one orientation of each pattern is searched.
+
+use crate::{
+    cells::CellRef,
+    config::{Symmetry, Transform},
+    rules::Rule,
+    world::World,
+};
+
+impl<'a, R: Rule> World<'a, R> {
+    /// The rotations and reflections that map the search space to itself,
+    /// for `symmetry_breaking`.
+    ///
+    /// Returns an empty list if `symmetry_breaking` is disabled,
+    /// or does not apply to the configuration.
+    fn breakable_transforms(&self) -> Vec<Transform> {
+        let config = &self.config;
+        let nbhd = self.rule.nbhd();
+        if !config.symmetry_breaking
+            || config.symmetry != Symmetry::C1
+            || config.transform != Transform::Id
+            || !self.rule.is_isotropic()
+            || self.rule.has_parity()
+            || nbhd != self.rule.nbhd_odd()
+            || !matches!(config.bounded_grid(), Ok(None))
+            || config.time_reversal.is_some()
+            || !config.symmetry_maps.is_empty()
+            || !config.known_cells.is_empty()
+            || !config.dont_care_cells.is_empty()
+            || !config.dead_cells.is_empty()
+            || !config.stator_cells.is_empty()
+            || config.shape.is_some()
+            || config.non_empty_front
+            || config.agar.is_some()
+        {
+            return Vec::new();
+        }
+        let apply = |m: [[isize; 2]; 2], (x, y): (isize, isize)| {
+            (m[0][0] * x + m[0][1] * y, m[1][0] * x + m[1][1] * y)
+        };
+        Transform::ALL
+            .iter()
+            .copied()
+            .filter(|&transform| {
+                let m = transform.matrix();
+                transform != Transform::Id
+                    && (!transform.square_world()
+                        || config.width == config.height
+                            && config.max_row_cell_count == config.max_column_cell_count)
+                    && apply(m, (config.dx, config.dy)) == (config.dx, config.dy)
+                    && apply(m, config.shift) == config.shift
+                    && (config.diagonal_width.is_none()
+                        || config.width == config.height
+                            && (transform == Transform::Rotate180
+                                || transform == Transform::FlipDiag
+                                || transform == Transform::FlipAntidiag))
+                    && nbhd.iter().all(|&offset| nbhd.contains(&apply(m, offset)))
+            })
+            .collect()
+    }
+
+    /// Pairs each cell with its image under each transformation
+    /// for `symmetry_breaking`, in the order of the `search_list`.
+    ///
+    /// The order is fixed here, so that it is not changed by
+    /// `order_switch_interval`. Cells that are their own images are skipped.
+    pub(crate) fn init_lex_leader(mut self) -> Self {
+        self.lex_pairs = self
+            .breakable_transforms()
+            .into_iter()
+            .map(|transform| {
+                self.search_list
+                    .iter()
+                    .filter_map(|&cell| {
+                        let (x, y, t) = cell.coord;
+                        let (x, y) = self.config.apply_transform(transform, x, y);
+                        let image = self.find_cell((x, y, t)).unwrap();
+                        if image == cell {
+                            None
+                        } else {
+                            Some((cell, image))
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        self
+    }
+
+    /// Checks the symmetry-breaking constraints.
+    ///
+    /// For each transformation, compares the states of the cells and
+    /// their images in order, until they differ or one of them is unknown.
+    ///
+    /// Returns `false` if the states of the cells are larger than those of
+    /// their images for some transformation, `true` otherwise, including
+    /// when the comparison is not decided yet.
+    pub(crate) fn check_lex_leader(&mut self) -> bool {
+        let not_larger = |pairs: &[(CellRef<'a, R>, CellRef<'a, R>)]| {
+            for (cell, image) in pairs.iter() {
+                match (cell.state.get(), image.state.get()) {
+                    (Some(a), Some(b)) if a == b => (),
+                    (Some(a), Some(b)) => return a.0 < b.0,
+                    _ => return true,
+                }
+            }
+            true
+        };
+        if self.lex_pairs.iter().all(|pairs| not_larger(pairs)) {
+            true
+        } else {
+            self.conflict = None;
+            false
+        }
+    }
+}
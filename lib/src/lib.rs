@@ -45,23 +45,74 @@
 //! ............o..o!
 //! ```
 
+mod analysis;
+mod blocklist;
 mod cells;
+mod choose;
 mod config;
+mod dead_end;
+mod endgame;
 mod error;
+mod grow;
+mod guide;
+mod header;
+mod hooks;
+mod lex_leader;
+mod nogood;
+mod presolve;
+mod replay;
+mod rows;
 pub mod rules;
 mod search;
+mod session;
+mod sweep;
+mod trace;
 mod traits;
 mod world;
 
+#[cfg(feature = "serialize")]
+mod checkpoint;
+#[cfg(feature = "pipeline")]
+mod pipeline;
+#[cfg(feature = "portfolio")]
+mod portfolio;
+#[cfg(feature = "sat")]
+mod sat;
 #[cfg(feature = "serialize")]
 mod save;
+#[cfg(feature = "split")]
+mod split;
 
-pub use cells::{State, ALIVE, DEAD};
-pub use config::{Config, NewState, SearchOrder, Symmetry, Transform};
+pub use analysis::Analysis;
+pub use cells::{CellRef, Coord, LifeCell, State, ALIVE, DEAD};
+pub use choose::ChooseCell;
+pub use config::{
+    Agar, BoundedGrid, Config, Engine, KnownCell, Neighborhood, NewState, SearchOrder, Symmetry,
+    SymmetryMap, Topology, Transform,
+};
 pub use error::Error;
-pub use search::Status;
-pub use traits::Search;
+pub use grow::Growth;
+pub use guide::Guide;
+pub use header::{config_digest, FileKind, Header, FORMAT_VERSION};
+pub use hooks::Hooks;
+pub use presolve::Presolve;
+pub use replay::{read_decisions, write_decisions};
+pub use search::{Budget, Stats, Status, Stop};
+pub use session::Session;
+pub use sweep::rule_range;
+pub use trace::{read_trace, write_trace, Features, Outcome, Policy, TraceEntry};
+pub use traits::{Search, Solutions};
 pub use world::World;
 
+#[cfg(feature = "serialize")]
+pub use checkpoint::{read_checkpoint, write_checkpoint, Checkpoint};
+#[cfg(feature = "pipeline")]
+pub use pipeline::{Pipeline, Prefix};
+#[cfg(feature = "portfolio")]
+pub use portfolio::{Portfolio, Winner};
+#[cfg(feature = "sat")]
+pub use sat::solve_sat;
 #[cfg(feature = "serialize")]
 pub use save::WorldSer;
+#[cfg(feature = "split")]
+pub use split::Split;
@@ -0,0 +1,141 @@
+//! Nogoods learnt from conflicts.
+//!
+//! A nogood is a combination of states that is known to lead to a conflict,
+//! i.e., the cells in it can not have these states at the same time.
+//! They are learnt in backjumping, and checked during the search
+//! to deduce the states of other cells.
+
+use crate::{
+    cells::{CellRef, Coord, State},
+    rules::Rule,
+    search::{Cause, Reason},
+    world::World,
+};
+use derivative::Derivative;
+use std::{cmp::Reverse, collections::HashMap};
+
+/// A learnt nogood.
+struct Nogood<'a, R: Rule> {
+    /// The cells and their states.
+    cells: Vec<(CellRef<'a, R>, State)>,
+
+    /// The number of conflicts and deductions caused by this nogood.
+    ///
+    /// It is halved whenever some nogoods are forgotten.
+    activity: u64,
+}
+
+/// The learnt nogoods.
+#[derive(Derivative)]
+#[derivative(Default(bound = ""))]
+pub(crate) struct Nogoods<'a, R: Rule> {
+    /// A list of the nogoods.
+    list: Vec<Nogood<'a, R>>,
+
+    /// The positions in the `list` of the nogoods that contain each cell.
+    index: HashMap<Coord, Vec<usize>>,
+}
+
+impl<'a, R: Rule> Nogoods<'a, R> {
+    /// Adds a nogood.
+    ///
+    /// If there are already `limit` nogoods, forgets the less active half
+    /// of them first.
+    pub(crate) fn learn(&mut self, cells: Vec<(CellRef<'a, R>, State)>, limit: usize) {
+        if self.list.len() >= limit {
+            self.forget(limit / 2);
+        }
+        let i = self.list.len();
+        for (cell, _) in cells.iter() {
+            self.index.entry(cell.coord).or_default().push(i);
+        }
+        self.list.push(Nogood { cells, activity: 0 });
+    }
+
+    /// The nogoods and their activities.
+    #[cfg(feature = "serialize")]
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&[(CellRef<'a, R>, State)], u64)> {
+        self.list
+            .iter()
+            .map(|nogood| (nogood.cells.as_slice(), nogood.activity))
+    }
+
+    /// Adds a nogood with the given activity, without forgetting
+    /// any other nogood.
+    #[cfg(feature = "serialize")]
+    pub(crate) fn restore(&mut self, cells: Vec<(CellRef<'a, R>, State)>, activity: u64) {
+        self.learn(cells, usize::MAX);
+        self.list.last_mut().unwrap().activity = activity;
+    }
+
+    /// Keeps only the `keep` most active nogoods, and halves their activities.
+    fn forget(&mut self, keep: usize) {
+        self.list.sort_by_key(|nogood| Reverse(nogood.activity));
+        self.list.truncate(keep);
+        self.index.clear();
+        for (i, nogood) in self.list.iter_mut().enumerate() {
+            nogood.activity /= 2;
+            for (cell, _) in nogood.cells.iter() {
+                self.index.entry(cell.coord).or_default().push(i);
+            }
+        }
+    }
+}
+
+impl<'a, R: Rule> World<'a, R> {
+    /// Checks the learnt nogoods that contain the cell.
+    ///
+    /// If all but one of the cells in a nogood have the states in it,
+    /// and the remaining cell is unknown, deduces that the remaining cell
+    /// has the other state.
+    ///
+    /// Returns `false` if all the cells in a nogood have the states in it,
+    /// or the deduction leads to a conflict.
+    pub(crate) fn check_nogoods(&mut self, cell: CellRef<'a, R>) -> bool {
+        let indices = match self.nogoods.index.get(&cell.coord) {
+            Some(indices) => indices.clone(),
+            None => return true,
+        };
+        for i in indices {
+            let nogood = &self.nogoods.list[i];
+            let mut unknown = None;
+            let mut active = true;
+            for &(c, state) in nogood.cells.iter() {
+                match c.state.get() {
+                    Some(s) if s == state => (),
+                    None if unknown.is_none() => unknown = Some((c, state)),
+                    _ => {
+                        active = false;
+                        break;
+                    }
+                }
+            }
+            if !active {
+                continue;
+            }
+            let others = nogood
+                .cells
+                .iter()
+                .map(|&(c, _)| c)
+                .filter(|&c| unknown.is_none_or(|(u, _)| c != u))
+                .collect::<Vec<_>>();
+            self.nogoods.list[i].activity += 1;
+            match unknown {
+                None => {
+                    self.conflict = Some(others);
+                    return false;
+                }
+                Some((c, state)) => {
+                    self.cause = Cause::Nogood(others);
+                    let result = self.set_cell(c, !state, Reason::Deduce);
+                    self.cause = Cause::Unknown;
+                    if !result {
+                        self.conflict = self.count_conflict();
+                        return false;
+                    }
+                }
+            }
+        }
+        true
+    }
+}
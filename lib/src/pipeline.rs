@@ -0,0 +1,288 @@
+//! Searching the pattern band by band.
+
+use crate::{
+    config::{Config, KnownCell},
+    error::Error,
+    search::Status,
+    traits::Search,
+};
+use std::{collections::VecDeque, thread};
+
+#[cfg(feature = "serialize")]
+use crate::{
+    checkpoint::with_suffix,
+    header::{FileKind, Header},
+};
+#[cfg(feature = "serialize")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "serialize")]
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Read, Write},
+    path::PathBuf,
+};
+
+/// A partial result, where the first few lines are known.
+///
+/// Here 'lines' means rows or columns, depending on the search order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct Prefix {
+    /// Number of known lines.
+    pub lines: isize,
+
+    /// The states of the cells in the known lines, in all generations.
+    ///
+    /// The known cells in the configuration are not included.
+    pub known_cells: Vec<KnownCell>,
+}
+
+/// A search which completes the pattern band by band,
+/// like [qfind](https://conwaylife.com/wiki/Qfind).
+///
+/// Each band consists of a few lines. Once a band is completed,
+/// the known lines are committed as a `Prefix`. A prefix can have
+/// several continuations, which are searched independently,
+/// and can be searched in parallel.
+///
+/// The prefixes are kept in a stack, so the longest prefixes are
+/// extended first.
+///
+/// Since only a limited number of continuations are kept for each prefix,
+/// the search is not exhaustive. A `Status::None` from a normal search
+/// does not follow from a failed pipeline.
+///
+/// With the `serialize` feature, the prefixes can be written to
+/// a checkpoint after each band, so that the search can be resumed.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct Pipeline {
+    /// World configuration.
+    config: Config,
+
+    /// Number of lines in a band.
+    band: isize,
+
+    /// The maximal number of continuations of a prefix.
+    branches: usize,
+
+    /// Prefixes waiting to be extended.
+    stack: Vec<Prefix>,
+
+    /// Complete results that are found but not yet returned by `run`.
+    results: VecDeque<Prefix>,
+
+    /// The path of the checkpoint, if any.
+    #[cfg(feature = "serialize")]
+    #[serde(skip)]
+    checkpoint: Option<PathBuf>,
+}
+
+impl Pipeline {
+    /// Creates a new pipeline from the configuration.
+    ///
+    /// By default each band has only one line,
+    /// and each prefix has at most 64 continuations.
+    pub fn new(config: Config) -> Self {
+        let empty = Prefix {
+            lines: 0,
+            known_cells: Vec::new(),
+        };
+        Pipeline {
+            config,
+            band: 1,
+            branches: 64,
+            stack: vec![empty],
+            results: VecDeque::new(),
+            #[cfg(feature = "serialize")]
+            checkpoint: None,
+        }
+    }
+
+    /// Sets the number of lines in a band.
+    pub fn set_band(mut self, band: isize) -> Self {
+        self.band = band.max(1);
+        self
+    }
+
+    /// Sets the maximal number of continuations of a prefix.
+    pub fn set_branches(mut self, branches: usize) -> Self {
+        self.branches = branches.max(1);
+        self
+    }
+
+    /// Number of lines in the world.
+    pub fn lines(&self) -> isize {
+        self.config.line_count()
+    }
+
+    /// Prefixes waiting to be extended.
+    pub fn prefixes(&self) -> &[Prefix] {
+        &self.stack
+    }
+
+    /// Sets the path of the checkpoint.
+    ///
+    /// The checkpoint is written by `run` after each band is completed,
+    /// and before a result is returned. It is written to a temporary
+    /// file first, and then renamed, so the file is never half-written.
+    ///
+    /// `None` means that no checkpoint is written.
+    #[cfg(feature = "serialize")]
+    pub fn set_checkpoint<P: Into<PathBuf>>(mut self, path: Option<P>) -> Self {
+        self.checkpoint = path.map(Into::into);
+        self
+    }
+
+    /// Writes the state of the pipeline as a checkpoint.
+    ///
+    /// The file starts with a `Header`, followed by the pipeline in JSON.
+    /// The path of the checkpoint itself is not written.
+    #[cfg(feature = "serialize")]
+    pub fn write_checkpoint<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        Header::new(FileKind::Pipeline, &self.config).write_to(writer)?;
+        serde_json::to_writer(&mut *writer, self)?;
+        writeln!(writer)
+    }
+
+    /// Reads the header and the pipeline in a checkpoint.
+    ///
+    /// Returns an error if the header is invalid. See `Header::read_from`.
+    #[cfg(feature = "serialize")]
+    pub fn read_checkpoint<R: Read>(reader: &mut R) -> io::Result<(Header, Self)> {
+        let header = Header::read_from(reader, FileKind::Pipeline)?;
+        let pipeline = serde_json::from_reader(reader)?;
+        Ok((header, pipeline))
+    }
+
+    /// Writes the checkpoint, if its path is set.
+    #[cfg(feature = "serialize")]
+    fn update_checkpoint(&self) -> Result<(), Error> {
+        let write = |path: &PathBuf| -> io::Result<()> {
+            let tmp_path = with_suffix(path, ".tmp");
+            let mut writer = BufWriter::new(File::create(&tmp_path)?);
+            self.write_checkpoint(&mut writer)?;
+            writer.into_inner()?.sync_all()?;
+            fs::rename(&tmp_path, path)
+        };
+        match &self.checkpoint {
+            Some(path) => write(path).map_err(|e| Error::PipelineCheckpointError(e.to_string())),
+            None => Ok(()),
+        }
+    }
+
+    /// Finds at most `branches` continuations of the prefix,
+    /// each with one more band.
+    ///
+    /// A continuation which contains all lines is a complete result.
+    pub fn extend(&self, prefix: &Prefix) -> Result<Vec<Prefix>, Error> {
+        extend(&self.config, self.band, self.branches, prefix)
+    }
+
+    /// Extends at most `threads` prefixes in parallel,
+    /// until a complete result is found, or all prefixes are exhausted.
+    ///
+    /// Returns a world of the result, where the states of all
+    /// cells are known. Calling `search` on this world would
+    /// return `Status::None`.
+    ///
+    /// Calling this function again would continue the search,
+    /// and find another result. When several results are found
+    /// in the same band, the others are kept and returned first.
+    ///
+    /// If a prefix fails to be extended, the prefixes of the current
+    /// batch are put back, and the error is returned.
+    pub fn run(&mut self, threads: usize) -> Result<Option<Box<dyn Search>>, Error> {
+        let lines = self.lines();
+        loop {
+            if let Some(prefix) = self.results.pop_front() {
+                #[cfg(feature = "serialize")]
+                self.update_checkpoint()?;
+                let mut known_cells = self.config.known_cells.clone();
+                known_cells.extend(prefix.known_cells);
+                let config = self.config.clone().set_known_cells(known_cells);
+                return Ok(Some(config.world()?));
+            }
+            if self.stack.is_empty() {
+                return Ok(None);
+            }
+            let count = threads.max(1).min(self.stack.len());
+            let batch = self.stack.split_off(self.stack.len() - count);
+            let handles: Vec<_> = batch
+                .iter()
+                .cloned()
+                .map(|prefix| {
+                    let config = self.config.clone();
+                    let band = self.band;
+                    let branches = self.branches;
+                    thread::spawn(move || extend(&config, band, branches, &prefix))
+                })
+                .collect();
+            let continuations = match handles
+                .into_iter()
+                .map(|handle| handle.join().expect("a search thread panicked"))
+                .collect::<Result<Vec<_>, _>>()
+            {
+                Ok(continuations) => continuations,
+                Err(e) => {
+                    self.stack.extend(batch);
+                    return Err(e);
+                }
+            };
+            for prefix in continuations.into_iter().flat_map(|c| c.into_iter().rev()) {
+                if prefix.lines >= lines {
+                    self.results.push_back(prefix);
+                } else {
+                    self.stack.push(prefix);
+                }
+            }
+            #[cfg(feature = "serialize")]
+            self.update_checkpoint()?;
+        }
+    }
+}
+
+/// Finds at most `branches` continuations of the prefix.
+fn extend(
+    config: &Config,
+    band: isize,
+    branches: usize,
+    prefix: &Prefix,
+) -> Result<Vec<Prefix>, Error> {
+    let mut known_cells = config.known_cells.clone();
+    known_cells.extend_from_slice(&prefix.known_cells);
+    let config = config.clone().set_known_cells(known_cells);
+    let mut search = config.world()?;
+    let lines = config.line_count();
+    let new_lines = (prefix.lines + band).min(lines);
+    let mut continuations = Vec::new();
+    while continuations.len() < branches {
+        let status = if new_lines == lines {
+            search.search(None)
+        } else {
+            search.search_partial(new_lines, None)
+        };
+        if status != Status::Found {
+            break;
+        }
+        let mut known_cells = Vec::new();
+        for t in 0..config.period {
+            for x in 0..config.width {
+                for y in 0..config.height {
+                    if config.line_index(x, y) >= new_lines {
+                        continue;
+                    }
+                    let coord = (x, y, t);
+                    if let Some(state) = search.get_cell_state(coord)? {
+                        known_cells.push(KnownCell { coord, state });
+                    }
+                }
+            }
+        }
+        continuations.push(Prefix {
+            lines: new_lines,
+            known_cells,
+        });
+    }
+    Ok(continuations)
+}
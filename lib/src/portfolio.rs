@@ -0,0 +1,204 @@
+//! Running several configurations of the same search in parallel.
+
+use crate::{
+    config::{Config, KnownCell},
+    error::Error,
+    search::Status,
+    traits::Search,
+};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+};
+
+/// The first search in a portfolio to finish.
+pub struct Winner {
+    /// The position of the winning configuration in the portfolio.
+    pub index: usize,
+
+    /// The configuration of the winning search.
+    ///
+    /// If the seed of the random number generator was not given,
+    /// this is the seed that was actually used, so the search
+    /// can be reproduced.
+    pub config: Config,
+
+    /// The status of the winning search.
+    ///
+    /// It is `Found` or `None`, unless every search stopped without
+    /// finishing, e.g., because its `time_limit` is reached. Then it is
+    /// the status of the search with the deepest partial result,
+    /// e.g., `TimedOut`.
+    pub status: Status,
+
+    /// The world of the result, where all the cells found by the
+    /// winning search are known, or `None` if the winning search
+    /// proved that there is no such pattern.
+    ///
+    /// If the status is `TimedOut` or `Paused`, this is the world of
+    /// the partial result of the search.
+    pub search: Option<Box<dyn Search>>,
+}
+
+/// A portfolio of searches, which run the same problem with different
+/// configurations, e.g., different seeds, search orders or heuristics,
+/// in parallel.
+///
+/// Each configuration is searched in its own thread. The first search
+/// that finds a result, or proves that there is none, wins,
+/// and the others are cancelled.
+///
+/// A search that stops without finishing, e.g., because its `time_limit`
+/// is reached, does not cancel the others. It only wins if all the
+/// searches stop in this way, and it has the deepest partial result.
+///
+/// The configurations should describe the same problem. Otherwise a
+/// `Status::None` from one of them says nothing about the others.
+#[derive(Clone, Debug)]
+pub struct Portfolio {
+    /// The configurations to search.
+    configs: Vec<Config>,
+
+    /// Number of steps between checks for cancellation.
+    step: u64,
+}
+
+impl Portfolio {
+    /// Creates a new portfolio from the configurations.
+    ///
+    /// By default each search checks for cancellation every 1000 steps.
+    pub fn new(configs: Vec<Config>) -> Self {
+        Portfolio {
+            configs,
+            step: 1000,
+        }
+    }
+
+    /// Creates a portfolio of `count` copies of the configuration,
+    /// with the seeds `0..count`.
+    ///
+    /// This is only useful if the states are chosen randomly,
+    /// or the search restarts.
+    pub fn with_seeds(config: &Config, count: u64) -> Self {
+        let configs = (0..count)
+            .map(|seed| config.clone().set_seed(Some(seed)))
+            .collect();
+        Portfolio::new(configs)
+    }
+
+    /// Sets the number of steps between checks for cancellation.
+    pub fn set_step(mut self, step: u64) -> Self {
+        self.step = step.max(1);
+        self
+    }
+
+    /// Searches all the configurations in parallel,
+    /// and returns the first one to finish.
+    ///
+    /// Returns `Ok(None)` if the portfolio is empty.
+    /// If the world of some configuration can not be created,
+    /// the other searches are cancelled, and the error is returned.
+    pub fn run(&self) -> Result<Option<Winner>, Error> {
+        if self.configs.is_empty() {
+            return Ok(None);
+        }
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let (sender, receiver) = mpsc::channel();
+        let handles = self
+            .configs
+            .iter()
+            .cloned()
+            .enumerate()
+            .map(|(index, config)| {
+                let cancelled = Arc::clone(&cancelled);
+                let sender = sender.clone();
+                let step = self.step;
+                thread::spawn(move || {
+                    if let Some(result) = work(config, step, &cancelled) {
+                        let _ = sender.send((index, result));
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        drop(sender);
+        let mut received: Option<(usize, WorkResult)> = None;
+        for (index, result) in receiver.iter() {
+            let finished = matches!(
+                result,
+                Ok((Status::Found, _, _)) | Ok((Status::None, _, _)) | Err(_)
+            );
+            let deeper = match (&received, &result) {
+                (Some((_, Ok((_, _, Some(old))))), Ok((_, _, Some(new)))) => new.len() > old.len(),
+                _ => true,
+            };
+            if finished || deeper {
+                received = Some((index, result));
+            }
+            if finished {
+                break;
+            }
+        }
+        cancelled.store(true, Ordering::Relaxed);
+        for handle in handles {
+            let _ = handle.join();
+        }
+        let (index, result) = received.expect("a search thread panicked");
+        let (status, seed, known_cells) = result?;
+        let config = self.configs[index].clone().set_seed(seed);
+        let search = match known_cells {
+            Some(known_cells) => Some(config.clone().set_known_cells(known_cells).world()?),
+            None => None,
+        };
+        Ok(Some(Winner {
+            index,
+            config,
+            status,
+            search,
+        }))
+    }
+}
+
+/// The result of a search in a portfolio: its status, the seed it actually
+/// used, and the states of the known cells unless the status is `None`.
+type WorkResult = Result<(Status, Option<u64>, Option<Vec<KnownCell>>), Error>;
+
+/// Searches a configuration until it finishes, stops, or is cancelled.
+///
+/// Returns `None` if it is cancelled, or another search finished first.
+/// A search that stops without finishing, e.g., `TimedOut`, does not
+/// cancel the others.
+fn work(config: Config, step: u64, cancelled: &AtomicBool) -> Option<WorkResult> {
+    let mut search = match config.world() {
+        Ok(search) => search,
+        Err(e) => return finish(cancelled, Err(e)),
+    };
+    loop {
+        if cancelled.load(Ordering::Relaxed) {
+            return None;
+        }
+        let seed = search.config().seed;
+        match search.search(Some(step)) {
+            Status::Found => {
+                return finish(
+                    cancelled,
+                    Ok((Status::Found, seed, Some(search.known_cells()))),
+                )
+            }
+            Status::None => return finish(cancelled, Ok((Status::None, seed, None))),
+            Status::Initial | Status::Searching => (),
+            status => return Some(Ok((status, seed, Some(search.known_cells())))),
+        }
+    }
+}
+
+/// Claims the victory, unless another search has already finished.
+fn finish(cancelled: &AtomicBool, result: WorkResult) -> Option<WorkResult> {
+    if cancelled.swap(true, Ordering::Relaxed) {
+        None
+    } else {
+        Some(result)
+    }
+}
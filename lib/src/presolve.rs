@@ -0,0 +1,73 @@
+//! Reporting the cells that are forced before the search.
+
+use crate::{
+    cells::Coord,
+    config::{Config, KnownCell},
+    error::Error,
+};
+use std::collections::HashSet;
+
+/// The cells whose states are forced by the constraints alone,
+/// before any decision. See `Config::presolve`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Presolve {
+    /// Whether the constraints are consistent.
+    ///
+    /// If not, the other fields describe the world when the conflict
+    /// was found.
+    pub consistent: bool,
+
+    /// Number of cells in the search range, in all generations.
+    pub total: usize,
+
+    /// Number of cells whose states are given by `known_cells`
+    /// or `dead_cells` in the configuration.
+    pub known: usize,
+
+    /// The cells whose states are deduced from the constraints,
+    /// i.e., the rule, the symmetry, the boundary, the background
+    /// and the known cells, sorted by generation, then by coordinates.
+    ///
+    /// The cells in `known_cells` and `dead_cells` are not included.
+    pub forced: Vec<KnownCell>,
+
+    /// Number of cells which are still unknown.
+    pub unknown: usize,
+}
+
+impl Config {
+    /// Deduces the states of the cells that are forced by the
+    /// configuration, as in `Config::preview`, and reports
+    /// how many and which cells are forced.
+    ///
+    /// This is a quick sanity check of a configuration: a contradictory
+    /// configuration, or one where most cells are forced, is usually
+    /// not what was intended.
+    pub fn presolve(&self) -> Result<Presolve, Error> {
+        let mut search = self.world()?;
+        let consistent = search.preview();
+        let config = search.config();
+        let total = (config.width * config.height * config.period) as usize;
+        let given = self
+            .known_cells
+            .iter()
+            .map(|known_cell| known_cell.coord)
+            .chain(
+                self.dead_cells
+                    .iter()
+                    .flat_map(|&(x, y)| (0..config.period).map(move |t| (x, y, t))),
+            )
+            .collect::<HashSet<Coord>>();
+        let (known, forced): (Vec<_>, Vec<_>) = search
+            .known_cells()
+            .into_iter()
+            .partition(|known_cell| given.contains(&known_cell.coord));
+        Ok(Presolve {
+            consistent,
+            total,
+            known: known.len(),
+            unknown: total - known.len() - forced.len(),
+            forced,
+        })
+    }
+}
@@ -0,0 +1,158 @@
+//! Recording the decisions on the current search path, and replaying them.
+//!
+//! A log of the decisions is much smaller than a checkpoint, and does not
+//! depend on the internal state of the search, so it is convenient for
+//! sharing interesting search positions, and for debugging changes in
+//! the search engine.
+
+use crate::{
+    cells::State,
+    config::{Config, KnownCell},
+    error::Error,
+    header::{FileKind, Header},
+    rules::Rule,
+    search::Reason,
+    trace::Features,
+    world::World,
+};
+use std::io::{self, Read, Write};
+
+/// Size of a decision in the binary format, in bytes.
+const DECISION_SIZE: usize = 16;
+
+/// Writes a log of decisions in a compact binary format.
+///
+/// The file starts with a `Header`, followed by the decisions.
+/// A decision consists of the 3 coordinates as `i32`s and the state
+/// as a `u32`. All numbers are little-endian.
+pub fn write_decisions<W: Write>(
+    config: &Config,
+    decisions: &[KnownCell],
+    writer: &mut W,
+) -> io::Result<()> {
+    Header::new(FileKind::Decisions, config).write_to(writer)?;
+    for decision in decisions {
+        let mut bytes = [0; DECISION_SIZE];
+        let (x, y, t) = decision.coord;
+        bytes[0..4].copy_from_slice(&(x as i32).to_le_bytes());
+        bytes[4..8].copy_from_slice(&(y as i32).to_le_bytes());
+        bytes[8..12].copy_from_slice(&(t as i32).to_le_bytes());
+        bytes[12..16].copy_from_slice(&(decision.state.0 as u32).to_le_bytes());
+        writer.write_all(&bytes)?;
+    }
+    Ok(())
+}
+
+/// Reads the header and the decisions in a log written by `write_decisions`.
+///
+/// Returns an error if the header is invalid. See `Header::read_from`.
+pub fn read_decisions<R: Read>(reader: &mut R) -> io::Result<(Header, Vec<KnownCell>)> {
+    let header = Header::read_from(reader, FileKind::Decisions)?;
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    if bytes.len() % DECISION_SIZE != 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "incomplete decision",
+        ));
+    }
+    let decisions = bytes
+        .chunks(DECISION_SIZE)
+        .map(|chunk| {
+            let int = |i: usize| {
+                let mut int = [0; 4];
+                int.copy_from_slice(&chunk[i..i + 4]);
+                int
+            };
+            KnownCell {
+                coord: (
+                    i32::from_le_bytes(int(0)) as isize,
+                    i32::from_le_bytes(int(4)) as isize,
+                    i32::from_le_bytes(int(8)) as isize,
+                ),
+                state: State(u32::from_le_bytes(int(12)) as usize),
+            }
+        })
+        .collect();
+    Ok((header, decisions))
+}
+
+impl<'a, R: Rule> World<'a, R> {
+    /// The cells whose states are chosen on the current search path,
+    /// in order.
+    ///
+    /// These are the decisions, and the cells whose other states
+    /// are tried after a decision leads to a conflict.
+    pub fn decision_path(&self) -> Vec<KnownCell> {
+        self.set_stack
+            .iter()
+            .filter(|set_cell| match set_cell.reason {
+                Reason::Decide(_) | Reason::TryAnother(_, _) => true,
+                Reason::Deduce => set_cell.flipped,
+                Reason::Known => false,
+            })
+            .map(|set_cell| KnownCell {
+                coord: set_cell.cell.coord,
+                state: set_cell.cell.state.get().unwrap(),
+            })
+            .collect()
+    }
+
+    /// Replays the decisions, e.g., from `decision_path`, to reach
+    /// the same search position.
+    ///
+    /// Each cell is set as a decision, and its consequences are deduced.
+    /// Cells that are already known with the same state are skipped.
+    ///
+    /// The deductions from learnt nogoods and from probing are not
+    /// recorded in the path, so the position is only reproduced exactly
+    /// if the search that recorded it learnt no nogood and did no probing.
+    ///
+    /// Returns `Ok(false)` if the decisions lead to a conflict.
+    /// Then the next `search` backtracks from the conflict.
+    /// Returns an error if a cell is not in the world, or its state
+    /// can not be decided, e.g., it is outside the fundamental domain
+    /// of the symmetry. When the cells are chosen by the search order,
+    /// it is also an error to decide a cell while an earlier cell
+    /// in the order is still unknown, which the search would never do.
+    pub fn replay(&mut self, decisions: &[KnownCell]) -> Result<bool, Error> {
+        if !self.proceed() {
+            return Ok(false);
+        }
+        for &KnownCell { coord, state } in decisions {
+            let cell = self.find_cell(coord).ok_or(Error::SetCellError(coord))?;
+            match cell.state.get() {
+                Some(old_state) if old_state == state => continue,
+                Some(_) => return Ok(false),
+                None => (),
+            }
+            let i = self
+                .search_list
+                .iter()
+                .position(|&c| c == cell)
+                .ok_or(Error::SetCellError(coord))?;
+            let by_order =
+                self.chooser.is_none() && self.guide.is_none() && !self.config.activity_order;
+            if state.0 >= self.rule.gen()
+                || by_order && self.get_unknown(0).is_some_and(|(j, _)| j < i)
+            {
+                return Err(Error::SetCellError(coord));
+            }
+            if by_order {
+                self.search_index = i + 1;
+            }
+            self.decisions += 1;
+            if let Some(trace) = self.trace.as_mut() {
+                trace.decide(Features::new(cell), state);
+            }
+            if !self.set_cell(cell, state, Reason::Decide(i)) {
+                self.backup();
+                return Ok(false);
+            }
+            if !self.proceed() {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
@@ -0,0 +1,214 @@
+//! A search engine that enumerates whole lines, in the style of gfind.
+//!
+//! See `Engine::Rows`.
+
+use crate::{
+    cells::{CellRef, State},
+    config::Engine,
+    rules::Rule,
+    search::{Budget, Reason, Status},
+    world::World,
+};
+use derivative::Derivative;
+
+#[cfg(feature = "serialize")]
+use serde::{Deserialize, Serialize};
+
+/// A line on the search path, with the possible states of its cells.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub(crate) struct RowLevel {
+    /// Index of the line.
+    pub(crate) line: usize,
+
+    /// Length of the `set_stack` before the cells in the line are set.
+    pub(crate) len: usize,
+
+    /// The possible states of the cells in the line.
+    pub(crate) rows: Vec<Vec<State>>,
+
+    /// The position in `rows` of the next states to try.
+    pub(crate) next: usize,
+}
+
+/// The state of the row engine.
+#[derive(Derivative)]
+#[derivative(Default(bound = ""))]
+pub(crate) struct Rows<'a, R: Rule> {
+    /// The cells in the search range, grouped by lines.
+    ///
+    /// Empty unless the engine is `Rows`.
+    lines: Vec<Vec<CellRef<'a, R>>>,
+
+    /// The lines on the search path, from the first.
+    pub(crate) levels: Vec<RowLevel>,
+
+    /// Whether the search has started.
+    pub(crate) started: bool,
+}
+
+impl<'a, R: Rule> World<'a, R> {
+    /// Groups the cells by lines for `Engine::Rows`.
+    pub(crate) fn init_rows(mut self) -> Self {
+        if self.config.engine != Engine::Rows {
+            return self;
+        }
+        let mut lines = vec![Vec::new(); self.config.line_count() as usize];
+        for &cell in self.search_list.iter() {
+            let line = self.config.line_index(cell.coord.0, cell.coord.1);
+            lines[line as usize].push(cell);
+        }
+        self.rows.lines = lines;
+        self
+    }
+
+    /// The first line that contains an unknown cell.
+    fn next_line(&self) -> Option<usize> {
+        self.rows
+            .lines
+            .iter()
+            .position(|cells| cells.iter().any(|cell| cell.state.get().is_none()))
+    }
+
+    /// Sets the unknown cells in the line to the given states,
+    /// and deduces the consequences.
+    ///
+    /// Returns `false` if there is a conflict.
+    fn set_row(&mut self, line: usize, row: &[State]) -> bool {
+        for (i, &state) in row.iter().enumerate() {
+            let cell = self.rows.lines[line][i];
+            if cell.state.get().is_none() && !self.set_cell(cell, state, Reason::Deduce) {
+                return false;
+            }
+        }
+        self.proceed()
+    }
+
+    /// Finds the possible states of the line, starting from its `i`-th
+    /// cell, until there are `max` of them.
+    ///
+    /// Each unknown cell is set to each state in turn, and the
+    /// consequences are deduced. The cells are undone afterwards.
+    fn extend_row(&mut self, line: usize, i: usize, max: usize, rows: &mut Vec<Vec<State>>) {
+        let unknown = self.rows.lines[line][i..]
+            .iter()
+            .position(|cell| cell.state.get().is_none());
+        let j = match unknown {
+            Some(j) => i + j,
+            None => {
+                rows.push(
+                    self.rows.lines[line]
+                        .iter()
+                        .map(|cell| cell.state.get().unwrap())
+                        .collect(),
+                );
+                return;
+            }
+        };
+        let cell = self.rows.lines[line][j];
+        for state in 0..self.rule.gen() {
+            let len = self.set_stack.len();
+            if self.set_cell(cell, State(state), Reason::Deduce) && self.proceed() {
+                self.extend_row(line, j + 1, max, rows);
+            }
+            self.undo_to(len);
+            if rows.len() >= max {
+                return;
+            }
+        }
+    }
+
+    /// The possible states of the line, such that the next unknown line
+    /// still has some possible states.
+    fn candidate_rows(&mut self, line: usize) -> Vec<Vec<State>> {
+        let mut rows = Vec::new();
+        self.extend_row(line, 0, usize::MAX, &mut rows);
+        let len = self.set_stack.len();
+        rows.retain(|row| {
+            let mut next = Vec::new();
+            if self.set_row(line, row) {
+                match self.next_line() {
+                    Some(next_line) => self.extend_row(next_line, 0, 1, &mut next),
+                    None => next.push(Vec::new()),
+                }
+            }
+            self.undo_to(len);
+            !next.is_empty()
+        });
+        rows
+    }
+
+    /// Adds the line to the search path, with its possible states.
+    ///
+    /// Returns `false` if there are no possible states.
+    fn push_row_level(&mut self, line: usize) -> bool {
+        let rows = self.candidate_rows(line);
+        if rows.is_empty() {
+            return false;
+        }
+        self.rows.levels.push(RowLevel {
+            line,
+            len: self.set_stack.len(),
+            rows,
+            next: 0,
+        });
+        true
+    }
+
+    /// The search function of `Engine::Rows`.
+    ///
+    /// Tries the possible states of each line on the search path in turn,
+    /// and goes to the next line that contains an unknown cell.
+    ///
+    /// The return values are the same as `Search::search_with_budget`.
+    /// A step sets the states of a line.
+    pub(crate) fn search_rows(&mut self, budget: Budget) -> Status {
+        let mut step_count = 0;
+        let (conflicts, decisions) = (self.conflicts, self.decisions);
+        if let Some(status) = self.start_deadline() {
+            return status;
+        }
+        if !self.rows.started {
+            self.rows.started = true;
+            if !self.proceed_checked() {
+                return self.exhausted();
+            }
+            if let Some(line) = self.next_line() {
+                if !self.push_row_level(line) {
+                    self.conflicts += 1;
+                }
+            } else if self.nontrivial() && !self.is_blocked() {
+                return self.result_found();
+            }
+        }
+        while let Some(level) = self.rows.levels.last_mut() {
+            let (line, len) = (level.line, level.len);
+            let row = match level.rows.get(level.next) {
+                Some(row) => row.clone(),
+                None => {
+                    self.rows.levels.pop();
+                    continue;
+                }
+            };
+            level.next += 1;
+            step_count += 1;
+            self.decisions += 1;
+            self.undo_to(len);
+            let consistent = self.set_row(line, &row);
+            self.max_depth = self.max_depth.max(self.set_stack.len());
+            if !consistent {
+                self.conflicts += 1;
+            } else if let Some(next_line) = self.next_line() {
+                if !self.push_row_level(next_line) {
+                    self.conflicts += 1;
+                }
+            } else if self.nontrivial() && !self.is_blocked() {
+                return self.result_found();
+            }
+            if let Some(status) = self.check_limits(budget, step_count, conflicts, decisions) {
+                return status;
+            }
+        }
+        self.exhausted()
+    }
+}
@@ -0,0 +1,79 @@
+//! Names of well-known rules.
+
+use crate::{error::Error, rules::AnyRule};
+use lazy_static::lazy_static;
+use std::{collections::HashMap, sync::RwLock};
+
+/// Well-known rules and their rule strings.
+///
+/// Most of the names are taken from
+/// [LifeWiki](https://conwaylife.com/wiki/List_of_Life-like_cellular_automata).
+const DEFAULT_ALIASES: &[(&str, &str)] = &[
+    ("Life", "B3/S23"),
+    ("HighLife", "B36/S23"),
+    ("Day&Night", "B3678/S34678"),
+    ("tlife", "B3/S2-i34q"),
+    ("Seeds", "B2/S"),
+    ("Life without Death", "B3/S012345678"),
+    ("2x2", "B36/S125"),
+    ("Move", "B368/S245"),
+    ("DryLife", "B37/S23"),
+    ("Pedestrian Life", "B38/S23"),
+    ("HoneyLife", "B38/S238"),
+    ("Maze", "B3/S12345"),
+    ("Mazectric", "B3/S1234"),
+    ("Diamoeba", "B35678/S5678"),
+    ("Replicator", "B1357/S1357"),
+    ("Fredkin", "B1357/S02468"),
+    ("Coral", "B3/S45678"),
+    ("Long Life", "B345/S5"),
+    ("Anneal", "B4678/S35678"),
+    ("Amoeba", "B357/S1358"),
+    ("Gnarl", "B1/S1"),
+    ("Serviettes", "B234/S"),
+    ("Flock", "B3/S12"),
+    ("Stains", "B3678/S235678"),
+    ("Walled Cities", "B45678/S2345"),
+    ("Assimilation", "B345/S4567"),
+    ("Coagulations", "B378/S235678"),
+    ("34 Life", "B34/S34"),
+    ("Vote", "B5678/S45678"),
+    ("Vote 4/5", "B4678/S35678"),
+    ("Brian's Brain", "B2/S/C3"),
+    ("Star Wars", "B2/S345/C4"),
+];
+
+lazy_static! {
+    /// Registered aliases, indexed by their names in lowercase.
+    static ref ALIASES: RwLock<HashMap<String, String>> = RwLock::new(
+        DEFAULT_ALIASES
+            .iter()
+            .map(|(name, rule_string)| (name.to_lowercase(), rule_string.to_string()))
+            .collect()
+    );
+}
+
+/// Registers a name for a rule string, e.g., `HighLife` for `B36/S23`.
+///
+/// Then the name can be used as the rule string of a `Config`.
+/// Names are case-insensitive. An existing alias with the same name
+/// is replaced.
+///
+/// The rule string itself cannot be an alias.
+/// Returns an error if it is invalid.
+pub fn register_alias(name: &str, rule_string: &str) -> Result<(), Error> {
+    AnyRule::parse_rule_string(rule_string)?;
+    ALIASES
+        .write()
+        .unwrap()
+        .insert(name.to_lowercase(), rule_string.to_string());
+    Ok(())
+}
+
+/// Finds the rule string of a name, or returns `None` if the name is not
+/// registered.
+///
+/// Names are case-insensitive.
+pub fn resolve_alias(name: &str) -> Option<String> {
+    ALIASES.read().unwrap().get(&name.to_lowercase()).cloned()
+}
@@ -0,0 +1,378 @@
+//! BSFKL rules.
+
+use crate::{
+    cells::{CellRef, State, ALIVE, DEAD},
+    rules::Rule,
+    search::Reason,
+    world::World,
+};
+use bitflags::bitflags;
+use ca_rules::ParseRuleError;
+use std::{str::FromStr, sync::Arc};
+
+/// The third state, which is called 'dying' in Generations rules.
+const DYING: State = State(2);
+
+bitflags! {
+    /// Flags to imply the state of a cell and its neighbors.
+    #[derive(Default)]
+    struct ImplFlags: u16 {
+        /// A conflict is detected.
+        const CONFLICT = 0b_0000_0000_0001;
+
+        /// The successor must be dead.
+        const SUCC_DEAD = 0b_0000_0000_0010;
+
+        /// The successor must be alive.
+        const SUCC_ALIVE = 0b_0000_0000_0100;
+
+        /// The successor must be dying.
+        const SUCC_DYING = 0b_0000_0000_1000;
+
+        /// The state of the successor is implied.
+        const SUCC = Self::SUCC_DEAD.bits | Self::SUCC_ALIVE.bits | Self::SUCC_DYING.bits;
+
+        /// The cell itself must be dead.
+        const SELF_DEAD = 0b_0000_0001_0000;
+
+        /// The cell itself must be alive.
+        const SELF_ALIVE = 0b_0000_0010_0000;
+
+        /// The cell itself must be dying.
+        const SELF_DYING = 0b_0000_0100_0000;
+
+        /// The state of the cell itself is implied.
+        const SELF = Self::SELF_DEAD.bits | Self::SELF_ALIVE.bits | Self::SELF_DYING.bits;
+
+        /// All unknown neighbors must be dead.
+        const NBHD_DEAD = 0b_0001_0000_0000;
+
+        /// All unknown neighbors must be alive.
+        const NBHD_ALIVE = 0b_0010_0000_0000;
+
+        /// All unknown neighbors must be dying.
+        const NBHD_DYING = 0b_0100_0000_0000;
+
+        /// The states of all unknown neighbors are implied.
+        const NBHD = Self::NBHD_DEAD.bits | Self::NBHD_ALIVE.bits | Self::NBHD_DYING.bits;
+    }
+}
+
+impl ImplFlags {
+    /// The flag that implies the successor to be in some state.
+    fn succ(state: State) -> Self {
+        match state {
+            DEAD => ImplFlags::SUCC_DEAD,
+            ALIVE => ImplFlags::SUCC_ALIVE,
+            _ => ImplFlags::SUCC_DYING,
+        }
+    }
+
+    /// The flag that implies the cell itself to be in some state.
+    fn self_state(state: State) -> Self {
+        match state {
+            DEAD => ImplFlags::SELF_DEAD,
+            ALIVE => ImplFlags::SELF_ALIVE,
+            _ => ImplFlags::SELF_DYING,
+        }
+    }
+
+    /// The state implied by the flags, among the three flags starting
+    /// from `first`.
+    fn state(self, first: ImplFlags) -> State {
+        if self.contains(first) {
+            DEAD
+        } else if self.contains(ImplFlags::from_bits_truncate(first.bits << 1)) {
+            ALIVE
+        } else {
+            DYING
+        }
+    }
+}
+
+/// The neighborhood descriptor.
+///
+/// It is a 16-bit integer of the form `0b_abcd_efgh_ijkl_mn_op`,
+/// where:
+///
+/// * `0b_abcd` is the number of dying cells in the neighborhood.
+/// * `0b_efgh` is the number of living cells in the neighborhood.
+/// * `0b_ijkl` is the number of dead cells in the neighborhood.
+/// * `0b_mn` is the state of the successor.
+/// * `0b_op` is the state of the cell itself.
+///
+/// For `0b_mn` and `0b_op`:
+/// * `0b_00` means unknown,
+/// * `0b_01` means dead,
+/// * `0b_10` means alive,
+/// * `0b_11` means dying.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NbhdDesc(u16);
+
+impl NbhdDesc {
+    /// Encodes a state as 2 bits.
+    fn encode(state: Option<State>) -> u16 {
+        state.map_or(0, |State(i)| i as u16 + 1)
+    }
+
+    /// Increment of the descriptor for a neighbor in this state.
+    fn count(state: State) -> u16 {
+        1 << (4 + 4 * state.0)
+    }
+}
+
+/// BSFKL rules, e.g., `B3/S23/F0/K/L`.
+///
+/// A BSFKL rule has 3 states: dead, alive and dying, which are represented
+/// by `State(0)`, `State(1)` and `State(2)`. The rule string consists of
+/// five lists of numbers of neighbors:
+///
+/// * A dead cell becomes alive if the number of living neighbors is in `B`
+///   (birth), and the number of dying neighbors is in `F` (forcing).
+///   Otherwise it stays dead.
+/// * A living cell becomes dying if the number of dying neighbors is in `K`
+///   (killing). Otherwise it stays alive if the number of living neighbors
+///   is in `S` (survival), and becomes dying if not.
+/// * A dying cell stays dying if the number of living neighbors is in `L`
+///   (living). Otherwise it becomes dead.
+///
+/// The neighborhood is the Moore neighborhood.
+#[derive(Clone)]
+pub struct Bsfkl {
+    /// Whether the rule contains `B0`.
+    ///
+    /// In other words, whether `0` is in both `B` and `F`.
+    b0: bool,
+    /// An array of actions for all neighborhood descriptors.
+    impl_table: Arc<Vec<ImplFlags>>,
+}
+
+impl Bsfkl {
+    /// Constructs a new rule from the `b`, `s`, `f`, `k` and `l` data.
+    pub fn new(b: Vec<u8>, s: Vec<u8>, f: Vec<u8>, k: Vec<u8>, l: Vec<u8>) -> Self {
+        let b0 = b.contains(&0) && f.contains(&0);
+
+        // The next state of a cell, given the numbers of living
+        // and dying neighbors.
+        let next = |state: State, alives: u8, dyings: u8| match state {
+            DEAD => {
+                if b.contains(&alives) && f.contains(&dyings) {
+                    ALIVE
+                } else {
+                    DEAD
+                }
+            }
+            ALIVE => {
+                if !k.contains(&dyings) && s.contains(&alives) {
+                    ALIVE
+                } else {
+                    DYING
+                }
+            }
+            _ => {
+                if l.contains(&alives) {
+                    DYING
+                } else {
+                    DEAD
+                }
+            }
+        };
+
+        let mut impl_table = vec![ImplFlags::empty(); 1 << 16];
+        for deads in 0..=8 {
+            for alives in 0..=8 - deads {
+                for dyings in 0..=8 - deads - alives {
+                    let unknowns = 8 - deads - alives - dyings;
+                    let nbhd_desc = dyings << 12 | alives << 8 | deads << 4;
+                    for succ_state in 0..4 {
+                        for state in 0..4 {
+                            let desc = nbhd_desc | succ_state << 2 | state;
+                            impl_table[desc] = Self::init_impl(
+                                &next,
+                                (alives as u8, dyings as u8, unknowns as u8),
+                                succ_state,
+                                state,
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
+        Bsfkl {
+            b0,
+            impl_table: Arc::new(impl_table),
+        }
+    }
+
+    /// Deduces the implication for a neighborhood descriptor,
+    /// by trying all possible states of the unknown cells.
+    ///
+    /// The states of the successor and the cell itself are encoded as in
+    /// `NbhdDesc`.
+    fn init_impl<F>(next: &F, counts: (u8, u8, u8), succ_state: usize, state: usize) -> ImplFlags
+    where
+        F: Fn(State, u8, u8) -> State,
+    {
+        let (alives, dyings, unknowns) = counts;
+        let states = if state == 0 {
+            vec![DEAD, ALIVE, DYING]
+        } else {
+            vec![State(state - 1)]
+        };
+
+        let mut possible = Vec::new();
+        for &state in states.iter() {
+            for new_alives in 0..=unknowns {
+                for new_dyings in 0..=unknowns - new_alives {
+                    let succ = next(state, alives + new_alives, dyings + new_dyings);
+                    if succ_state == 0 || succ == State(succ_state - 1) {
+                        possible.push((state, new_alives, new_dyings, succ));
+                    }
+                }
+            }
+        }
+
+        let first = match possible.first() {
+            Some(&first) => first,
+            None => return ImplFlags::CONFLICT,
+        };
+        let mut flags = ImplFlags::empty();
+        if succ_state == 0 && possible.iter().all(|p| p.3 == first.3) {
+            flags |= ImplFlags::succ(first.3);
+        }
+        if state == 0 && possible.iter().all(|p| p.0 == first.0) {
+            flags |= ImplFlags::self_state(first.0);
+        }
+        if unknowns > 0 {
+            if possible.iter().all(|p| p.1 == 0 && p.2 == 0) {
+                flags |= ImplFlags::NBHD_DEAD;
+            } else if possible.iter().all(|p| p.1 == unknowns) {
+                flags |= ImplFlags::NBHD_ALIVE;
+            } else if possible.iter().all(|p| p.2 == unknowns) {
+                flags |= ImplFlags::NBHD_DYING;
+            }
+        }
+        flags
+    }
+}
+
+impl FromStr for Bsfkl {
+    type Err = ParseRuleError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut chars = input.chars().peekable();
+        let mut lists = Vec::new();
+        for (i, letter) in "BSFKL".chars().enumerate() {
+            if i > 0 {
+                match chars.next() {
+                    Some('/') => (),
+                    _ => return Err(ParseRuleError::Missing('/')),
+                }
+            }
+            match chars.next() {
+                Some(c) if c.to_ascii_uppercase() == letter => (),
+                _ => return Err(ParseRuleError::Missing(letter)),
+            }
+            let mut list = Vec::new();
+            while let Some(n) = chars.peek().and_then(|c| c.to_digit(9)) {
+                chars.next();
+                list.push(n as u8);
+            }
+            lists.push(list);
+        }
+        if chars.next().is_some() {
+            return Err(ParseRuleError::ExtraJunk);
+        }
+        let l = lists.pop().unwrap();
+        let k = lists.pop().unwrap();
+        let f = lists.pop().unwrap();
+        let s = lists.pop().unwrap();
+        let b = lists.pop().unwrap();
+        Ok(Bsfkl::new(b, s, f, k, l))
+    }
+}
+
+/// NOTE: The `background` of the world assumes that a rule with `B0`
+/// goes through the 3 states in order, like a Generations rule.
+impl Rule for Bsfkl {
+    type Desc = NbhdDesc;
+
+    const IS_GEN: bool = true;
+
+    fn has_b0(&self) -> bool {
+        self.b0
+    }
+
+    fn gen(&self) -> usize {
+        3
+    }
+
+    fn is_isotropic(&self) -> bool {
+        true
+    }
+
+    fn new_desc(state: State, succ_state: State) -> Self::Desc {
+        let nbhd_state = NbhdDesc::count(state) * 8;
+        let succ_state = NbhdDesc::encode(Some(succ_state));
+        let state = NbhdDesc::encode(Some(state));
+        NbhdDesc(nbhd_state | succ_state << 2 | state)
+    }
+
+    fn update_desc(cell: CellRef<Self>, state: Option<State>, new: bool) {
+        if let Some(state) = state {
+            for &neigh in cell.nbhd.iter().take(8) {
+                let neigh = neigh.unwrap();
+                let mut desc = neigh.desc.get();
+                if new {
+                    desc.0 += NbhdDesc::count(state);
+                } else {
+                    desc.0 -= NbhdDesc::count(state);
+                }
+                neigh.desc.set(desc);
+            }
+        }
+        let code = if new { NbhdDesc::encode(state) } else { 0 };
+        if let Some(pred) = cell.pred {
+            let mut desc = pred.desc.get();
+            desc.0 = (desc.0 & !0b1100) | code << 2;
+            pred.desc.set(desc);
+        }
+        let mut desc = cell.desc.get();
+        desc.0 = (desc.0 & !0b11) | code;
+        cell.desc.set(desc);
+    }
+
+    fn consistify<'a>(world: &mut World<'a, Self>, cell: CellRef<'a, Self>) -> bool {
+        let flags = world.rule.impl_table[cell.desc.get().0 as usize];
+        if flags.is_empty() {
+            return true;
+        }
+        if flags.contains(ImplFlags::CONFLICT) {
+            return false;
+        }
+        if flags.intersects(ImplFlags::SUCC) {
+            let state = flags.state(ImplFlags::SUCC_DEAD);
+            let succ = cell.succ.unwrap();
+            return world.set_cell(succ, state, Reason::Deduce);
+        }
+        if flags.intersects(ImplFlags::SELF) {
+            let state = flags.state(ImplFlags::SELF_DEAD);
+            if !world.set_cell(cell, state, Reason::Deduce) {
+                return false;
+            }
+        }
+        if flags.intersects(ImplFlags::NBHD) {
+            let state = flags.state(ImplFlags::NBHD_DEAD);
+            for &neigh in cell.nbhd.iter() {
+                if let Some(neigh) = neigh {
+                    if neigh.state.get().is_none() && !world.set_cell(neigh, state, Reason::Deduce)
+                    {
+                        return false;
+                    }
+                }
+            }
+        }
+        true
+    }
+}
@@ -0,0 +1,197 @@
+//! Canonical rule strings of non-totalistic Life-like rules.
+
+use ca_rules::{
+    ParseNtHex, ParseNtHexGen, ParseNtLife, ParseNtLifeGen, ParseNtNeumann, ParseNtNeumannGen,
+    ParseRuleError,
+};
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+/// The letters of the isotropic non-totalistic notation for each number of
+/// living neighbors, in the order used by Golly.
+const LETTERS: [&str; 9] = [
+    "",
+    "ce",
+    "ceaikn",
+    "ceaiknjqry",
+    "ceaiknjqrytwz",
+    "ceaiknjqry",
+    "ceaikn",
+    "ce",
+    "",
+];
+
+/// Characters used in the Base64 encoding of MAP strings.
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// A parsed non-totalistic Life-like rule, possibly a Generations rule,
+/// which displays as its canonical rule string.
+///
+/// Any rule string with the Moore neighborhood is accepted: totalistic
+/// rules, isotropic non-totalistic rules in the Hensel notation, MAP strings,
+/// and the corresponding Generations rules, e.g., `23/3`, `B3/S23/C3`,
+/// `MAPARYXfhZofugWaH7oaIDogBZofuhogOiAaIDogIAAgAAWaH7oaIDogGiA6ICAAIAAaIDogIAAgACAAIAAAAAAAA`.
+///
+/// Isotropic rules are displayed in the Hensel notation, in the same form
+/// as Golly, e.g., `B3/S23` or `B2-a/S12`.
+/// Non-isotropic rules are displayed as MAP strings.
+/// Generations rules end with the number of states, e.g., `B3/S23/C3`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CanonicalRule {
+    /// Neighborhoods that cause a cell to be born, as in `ParseNtLife`.
+    b: Vec<u8>,
+    /// Neighborhoods that cause a cell to survive, as in `ParseNtLife`.
+    s: Vec<u8>,
+    /// Number of states.
+    gen: usize,
+}
+
+impl ParseNtLife for CanonicalRule {
+    fn from_bs(b: Vec<u8>, s: Vec<u8>) -> Self {
+        CanonicalRule { b, s, gen: 2 }
+    }
+}
+
+impl ParseNtLifeGen for CanonicalRule {
+    fn from_bsg(b: Vec<u8>, s: Vec<u8>, gen: usize) -> Self {
+        CanonicalRule { b, s, gen }
+    }
+}
+
+/// Hexagonal rules and rules with von Neumann neighborhoods.
+///
+/// The parsers of non-totalistic Life-like rules also accept them,
+/// but they have no canonical form with the Moore neighborhood.
+struct OtherNbhd;
+
+impl ParseNtHex for OtherNbhd {
+    fn from_bs(_b: Vec<u8>, _s: Vec<u8>) -> Self {
+        OtherNbhd
+    }
+}
+
+impl ParseNtHexGen for OtherNbhd {
+    fn from_bsg(_b: Vec<u8>, _s: Vec<u8>, _gen: usize) -> Self {
+        OtherNbhd
+    }
+}
+
+impl ParseNtNeumann for OtherNbhd {
+    fn from_bs(_b: Vec<u8>, _s: Vec<u8>) -> Self {
+        OtherNbhd
+    }
+}
+
+impl ParseNtNeumannGen for OtherNbhd {
+    fn from_bsg(_b: Vec<u8>, _s: Vec<u8>, _gen: usize) -> Self {
+        OtherNbhd
+    }
+}
+
+impl FromStr for CanonicalRule {
+    type Err = ParseRuleError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let other: Result<OtherNbhd, _> = ParseNtHex::parse_rule(input)
+            .or_else(|_| ParseNtHexGen::parse_rule(input))
+            .or_else(|_| ParseNtNeumann::parse_rule(input))
+            .or_else(|_| ParseNtNeumannGen::parse_rule(input));
+        if other.is_ok() {
+            return Err(ParseRuleError::ExtraJunk);
+        }
+        ParseNtLife::parse_rule(input).or_else(|_| ParseNtLifeGen::parse_rule(input))
+    }
+}
+
+impl CanonicalRule {
+    /// The neighborhoods of each letter of each number of living
+    /// neighbors.
+    fn letter_groups() -> Vec<(usize, char, Vec<u8>)> {
+        let mut groups = Vec::new();
+        for (n, letters) in LETTERS.iter().enumerate() {
+            for letter in letters.chars() {
+                let rule: CanonicalRule =
+                    ParseNtLife::parse_rule(&format!("B{}{}/S", n, letter)).unwrap();
+                groups.push((n, letter, rule.b));
+            }
+        }
+        groups
+    }
+
+    /// Writes the transitions in the Hensel notation,
+    /// or returns `None` if they are not isotropic.
+    fn hensel(bs: &[u8], groups: &[(usize, char, Vec<u8>)]) -> Option<String> {
+        let mut string = String::new();
+        for (n, letters) in LETTERS.iter().enumerate() {
+            if letters.is_empty() {
+                if bs.iter().any(|&i| i.count_ones() as usize == n) {
+                    string.push((b'0' + n as u8) as char);
+                }
+                continue;
+            }
+            let mut present = String::new();
+            let mut absent = String::new();
+            for (_, letter, group) in groups.iter().filter(|g| g.0 == n) {
+                let count = group.iter().filter(|i| bs.contains(i)).count();
+                if count == group.len() {
+                    present.push(*letter);
+                } else if count == 0 {
+                    absent.push(*letter);
+                } else {
+                    return None;
+                }
+            }
+            if present.is_empty() {
+                continue;
+            }
+            string.push((b'0' + n as u8) as char);
+            if absent.is_empty() {
+                continue;
+            }
+            if present.len() > absent.len() {
+                string.push('-');
+                string.push_str(&absent);
+            } else {
+                string.push_str(&present);
+            }
+        }
+        Some(string)
+    }
+
+    /// Writes the rule as a MAP string, without the number of states.
+    fn map(&self) -> String {
+        let mut bits = [false; 512];
+        for &(bs, center) in [(&self.b, 0), (&self.s, 1)].iter() {
+            for &i in bs.iter() {
+                let i = i as usize;
+                bits[(i & 0xf0) << 1 | center << 4 | (i & 0x0f)] = true;
+            }
+        }
+        let mut string = String::from("MAP");
+        for chunk in bits.chunks(6) {
+            let index = chunk
+                .iter()
+                .enumerate()
+                .fold(0, |acc, (j, &bit)| acc | (bit as usize) << (5 - j));
+            string.push(BASE64[index] as char);
+        }
+        string
+    }
+}
+
+impl Display for CanonicalRule {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let groups = Self::letter_groups();
+        match (
+            Self::hensel(&self.b, &groups),
+            Self::hensel(&self.s, &groups),
+        ) {
+            (Some(b), Some(s)) if self.gen > 2 => write!(f, "B{}/S{}/C{}", b, s, self.gen),
+            (Some(b), Some(s)) => write!(f, "B{}/S{}", b, s),
+            _ if self.gen > 2 => write!(f, "{}/{}", self.map(), self.gen),
+            _ => write!(f, "{}", self.map()),
+        }
+    }
+}
@@ -0,0 +1,420 @@
+//! Rules with custom neighborhoods and transitions.
+
+use crate::{
+    cells::{CellRef, State, ALIVE, DEAD},
+    config::Neighborhood,
+    error::Error,
+    rules::{Rule, MAX_NBHD_SIZE},
+    search::Reason,
+    world::World,
+};
+use bitflags::bitflags;
+use ca_rules::ParseLife;
+use std::sync::Arc;
+
+bitflags! {
+    /// Flags to imply the state of a cell and its neighbors.
+    #[derive(Default)]
+    struct ImplFlags: u32 {
+        /// A conflict is detected.
+        const CONFLICT = 0b_0000_0001;
+
+        /// The successor must be alive.
+        const SUCC_ALIVE = 0b_0000_0100;
+
+        /// The successor must be dead.
+        const SUCC_DEAD = 0b_0000_1000;
+
+        /// The state of the successor is implied.
+        const SUCC = Self::SUCC_ALIVE.bits | Self::SUCC_DEAD.bits;
+
+        /// The cell itself must be alive.
+        const SELF_ALIVE = 0b_0001_0000;
+
+        /// The cell itself must be dead.
+        const SELF_DEAD = 0b_0010_0000;
+
+        /// The state of the cell itself is implied.
+        const SELF = Self::SELF_ALIVE.bits | Self::SELF_DEAD.bits;
+
+        /// The state of at least one unknown neighbor is implied.
+        ///
+        /// The `i`-th neighbor must be alive if bit `2 * i + 6` is set,
+        /// and must be dead if bit `2 * i + 7` is set.
+        const NBHD = 0xff_ffff << 6;
+    }
+}
+
+/// The neighborhood descriptor.
+///
+/// It is a 28-bit integer of the form `0b_a_b_ij_kl`, where:
+///
+/// * `0b_a` is a 12-bit mask of the dead cells in the neighborhood.
+/// * `0b_b` is a 12-bit mask of the living cells in the neighborhood.
+/// * `0b_ij` is the state of the successor.
+/// * `0b_kl` is the state of the cell itself.
+///
+/// For `0b_ij` and `0b_kl`:
+/// * `0b_10` means dead,
+/// * `0b_01` means alive,
+/// * `0b_00` means unknown.
+///
+/// The `i`-th bit of each mask is the `i`-th neighbor in `Rule::nbhd`.
+/// Bits beyond the size of the neighborhood are ignored.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NbhdDesc(u32);
+
+/// A rule with a custom neighborhood and custom transitions,
+/// e.g., a rule with the knight-move neighborhood.
+///
+/// The neighborhood is a list of at most 12 offsets. It must not contain
+/// `(0, 0)` or duplicates, and must be symmetric: if `(x, y)` is in the
+/// neighborhood, so is `(-x, -y)`.
+///
+/// The transitions are given by a function, which takes whether a cell is
+/// alive, and a mask of its living neighbors, and returns whether the cell
+/// is alive in the next generation. The `i`-th bit of the mask is the
+/// `i`-th offset in the neighborhood. The implication table is built from
+/// this function.
+///
+/// Such a rule has no rule string. The world is created with
+/// `Config::world_with_rule`, and the rule string in the configuration is
+/// only used in the output. Totalistic rules with a built-in neighborhood
+/// are the exception; see `Neighborhood`.
+#[derive(Clone)]
+pub struct Custom {
+    /// Whether the rule contains `B0`.
+    b0: bool,
+    /// Whether the states are inverted.
+    ///
+    /// See `Rule::is_inverted` for details.
+    inverted: bool,
+    /// The neighborhood, reordered so that the `i`-th neighbor and
+    /// the `i ^ 1`-th neighbor are opposite to each other.
+    nbhd: Vec<(isize, isize)>,
+    /// The mask of all neighbors.
+    full: u32,
+    /// Converts a mask of neighbors to a number in base 3,
+    /// where each neighbor is a digit.
+    base3: Vec<usize>,
+    /// An array of actions for all neighborhood descriptors,
+    /// indexed in base 3.
+    ///
+    /// A neighbor is `0` if unknown, `1` if alive, `2` if dead.
+    impl_table: Arc<Vec<ImplFlags>>,
+}
+
+impl Custom {
+    /// Constructs a new rule from the neighborhood and the transitions.
+    ///
+    /// If the rule contains both `B0` and its analogue of `S8`, the states
+    /// are inverted, so that the background is always dead.
+    /// See `Rule::is_inverted` for details.
+    ///
+    /// Returns an error if the neighborhood is invalid.
+    pub fn new<F>(nbhd: &[(isize, isize)], trans: F) -> Result<Self, Error>
+    where
+        F: Fn(bool, u32) -> bool,
+    {
+        let invalid = |reason: &str| Err(Error::NbhdError(reason.to_string()));
+        if nbhd.len() > MAX_NBHD_SIZE {
+            return invalid("more than 12 neighbors");
+        }
+        if nbhd.contains(&(0, 0)) {
+            return invalid("contains the cell itself");
+        }
+        if (0..nbhd.len()).any(|i| nbhd[i + 1..].contains(&nbhd[i])) {
+            return invalid("contains duplicated neighbors");
+        }
+        if nbhd.iter().any(|&(x, y)| !nbhd.contains(&(-x, -y))) {
+            return invalid("not symmetric");
+        }
+
+        // Reorders the neighborhood into pairs of opposite neighbors.
+        // `order[i]` is the index of the `i`-th neighbor in the original
+        // neighborhood.
+        let mut order = Vec::with_capacity(nbhd.len());
+        for (i, &(x, y)) in nbhd.iter().enumerate() {
+            if !order.contains(&i) {
+                let j = nbhd.iter().position(|&o| o == (-x, -y)).unwrap();
+                order.push(i);
+                order.push(j);
+            }
+        }
+        let to_original = |mask: u32| -> u32 {
+            order
+                .iter()
+                .enumerate()
+                .filter(|&(i, _)| mask >> i & 1 == 1)
+                .fold(0, |acc, (_, &j)| acc | 1 << j)
+        };
+
+        let full = (1 << nbhd.len()) - 1;
+        let b0 = trans(false, 0);
+        let inverted = b0 && trans(true, full);
+        let next = |state: bool, mask: u32| -> bool {
+            let mask = to_original(mask);
+            if inverted {
+                !trans(!state, !mask & full)
+            } else {
+                trans(state, mask)
+            }
+        };
+
+        let base3 = (0..=full)
+            .map(|mask| {
+                (0..nbhd.len())
+                    .rev()
+                    .fold(0, |acc, i| acc * 3 + (mask >> i & 1) as usize)
+            })
+            .collect();
+
+        Ok(Custom {
+            b0: b0 && !inverted,
+            inverted,
+            nbhd: order.iter().map(|&i| nbhd[i]).collect(),
+            full,
+            base3,
+            impl_table: Arc::new(Self::init_impl(nbhd.len(), next)),
+        })
+    }
+
+    /// Builds the implication table.
+    fn init_impl<F>(size: usize, next: F) -> Vec<ImplFlags>
+    where
+        F: Fn(bool, u32) -> bool,
+    {
+        let pow3: Vec<usize> = (0..size).map(|i| 3_usize.pow(i as u32)).collect();
+        let count = 3_usize.pow(size as u32);
+        let digit = |index: usize, i: usize| index / pow3[i] % 3;
+
+        // The possible states of the successor, for each neighborhood.
+        // Bits 0 and 1 say whether the successor of a dead cell can be
+        // dead or alive. Bits 2 and 3 are for a living cell.
+        let mut reach = vec![0_u8; count];
+        for index in (0..count).rev() {
+            reach[index] = match (0..size).find(|&i| digit(index, i) == 0) {
+                Some(i) => reach[index + pow3[i]] | reach[index + 2 * pow3[i]],
+                None => {
+                    let mask = (0..size)
+                        .filter(|&i| digit(index, i) == 1)
+                        .fold(0, |acc, i| acc | 1 << i);
+                    let succ = |state| if next(state, mask) { 0b10 } else { 0b01 };
+                    succ(false) | succ(true) << 2
+                }
+            };
+        }
+
+        let mut impl_table = vec![ImplFlags::empty(); count << 4];
+        for index in 0..count {
+            for state in 0..=2 {
+                // The possible states of the cell itself: `0` for dead,
+                // `1` for alive.
+                let states: &[usize] = match state {
+                    0b01 => &[1],
+                    0b10 => &[0],
+                    _ => &[0, 1],
+                };
+                for succ_state in 0..=2 {
+                    let flags = &mut impl_table[index << 4 | succ_state << 2 | state];
+                    if succ_state == 0 {
+                        let succ = states
+                            .iter()
+                            .fold(0, |acc, &s| acc | reach[index] >> (2 * s) & 0b11);
+                        *flags = match succ {
+                            0b01 => ImplFlags::SUCC_DEAD,
+                            0b10 => ImplFlags::SUCC_ALIVE,
+                            _ => ImplFlags::empty(),
+                        };
+                        continue;
+                    }
+
+                    let target = if succ_state == 0b01 { 0b10 } else { 0b01 };
+                    let possible = |index: usize, s: usize| reach[index] >> (2 * s) & target != 0;
+                    let states: Vec<usize> = states
+                        .iter()
+                        .copied()
+                        .filter(|&s| possible(index, s))
+                        .collect();
+                    match states[..] {
+                        [] => {
+                            *flags = ImplFlags::CONFLICT;
+                            continue;
+                        }
+                        [s] if state == 0 => {
+                            *flags |= if s == 1 {
+                                ImplFlags::SELF_ALIVE
+                            } else {
+                                ImplFlags::SELF_DEAD
+                            };
+                        }
+                        _ => (),
+                    }
+                    for i in (0..size).filter(|&i| digit(index, i) == 0) {
+                        let possibly_alive = states.iter().any(|&s| possible(index + pow3[i], s));
+                        let possibly_dead =
+                            states.iter().any(|&s| possible(index + 2 * pow3[i], s));
+                        if possibly_alive && !possibly_dead {
+                            *flags |= ImplFlags::from_bits(1 << (2 * i + 6)).unwrap();
+                        } else if !possibly_alive && possibly_dead {
+                            *flags |= ImplFlags::from_bits(1 << (2 * i + 7)).unwrap();
+                        }
+                    }
+                }
+            }
+        }
+        impl_table
+    }
+
+    /// Constructs a totalistic rule with a built-in neighborhood.
+    ///
+    /// The rule string must be a totalistic Life-like rule, e.g., `B3/S23`,
+    /// whose numbers are the numbers of living cells in the neighborhood.
+    pub fn with_neighborhood(neighborhood: Neighborhood, rule_string: &str) -> Result<Self, Error> {
+        let Totalistic { b, s } = ParseLife::parse_rule(rule_string)?;
+        Self::new(neighborhood.offsets(), |alive, mask| {
+            let count = mask.count_ones() as u8;
+            if alive {
+                s.contains(&count)
+            } else {
+                b.contains(&count)
+            }
+        })
+    }
+}
+
+/// The `b` and `s` data of a totalistic Life-like rule.
+struct Totalistic {
+    b: Vec<u8>,
+    s: Vec<u8>,
+}
+
+impl ParseLife for Totalistic {
+    fn from_bs(b: Vec<u8>, s: Vec<u8>) -> Self {
+        Totalistic { b, s }
+    }
+}
+
+impl Rule for Custom {
+    type Desc = NbhdDesc;
+
+    const IS_GEN: bool = false;
+
+    fn nbhd(&self) -> &[(isize, isize)] {
+        &self.nbhd
+    }
+
+    fn nbhd_odd(&self) -> &[(isize, isize)] {
+        &self.nbhd
+    }
+
+    fn has_b0(&self) -> bool {
+        self.b0
+    }
+
+    fn is_inverted(&self) -> bool {
+        self.inverted
+    }
+
+    fn gen(&self) -> usize {
+        2
+    }
+
+    fn new_desc(state: State, succ_state: State) -> Self::Desc {
+        let nbhd_state = match state {
+            ALIVE => 0x00_0fff,
+            _ => 0xff_f000,
+        };
+        let succ_state = match succ_state {
+            ALIVE => 0b01,
+            _ => 0b10,
+        };
+        let state = match state {
+            ALIVE => 0b01,
+            _ => 0b10,
+        };
+        NbhdDesc(nbhd_state << 4 | succ_state << 2 | state)
+    }
+
+    /// The `i`-th neighbor of a cell has the cell as its `i ^ 1`-th
+    /// neighbor.
+    fn update_desc(cell: CellRef<Self>, state: Option<State>, _new: bool) {
+        let nbhd_change_num = match state {
+            Some(ALIVE) => 0x0001,
+            Some(_) => 0x1000,
+            _ => 0x0000,
+        };
+        for (i, &neigh) in cell.nbhd.iter().enumerate() {
+            if let Some(neigh) = neigh {
+                let mut desc = neigh.desc.get();
+                desc.0 ^= nbhd_change_num << (i ^ 1) << 4;
+                neigh.desc.set(desc);
+            }
+        }
+        let change_num = match state {
+            Some(ALIVE) => 0b01,
+            Some(_) => 0b10,
+            _ => 0,
+        };
+        if let Some(pred) = cell.pred {
+            let mut desc = pred.desc.get();
+            desc.0 ^= change_num << 2;
+            pred.desc.set(desc);
+        }
+        let mut desc = cell.desc.get();
+        desc.0 ^= change_num;
+        cell.desc.set(desc);
+    }
+
+    fn consistify<'a>(world: &mut World<'a, Self>, cell: CellRef<'a, Self>) -> bool {
+        let desc = cell.desc.get().0;
+        let rule = &world.rule;
+        let alives = (desc >> 4 & rule.full) as usize;
+        let deads = (desc >> 16 & rule.full) as usize;
+        let index = (rule.base3[alives] + 2 * rule.base3[deads]) << 4 | (desc & 0xf) as usize;
+        let flags = rule.impl_table[index];
+        if flags.is_empty() {
+            return true;
+        }
+        if flags.contains(ImplFlags::CONFLICT) {
+            return false;
+        }
+        if flags.intersects(ImplFlags::SUCC) {
+            let state = if flags.contains(ImplFlags::SUCC_DEAD) {
+                DEAD
+            } else {
+                ALIVE
+            };
+            let succ = cell.succ.unwrap();
+            return world.set_cell(succ, state, Reason::Deduce);
+        }
+        if flags.intersects(ImplFlags::SELF) {
+            let state = if flags.contains(ImplFlags::SELF_DEAD) {
+                DEAD
+            } else {
+                ALIVE
+            };
+            if !world.set_cell(cell, state, Reason::Deduce) {
+                return false;
+            }
+        }
+        if flags.intersects(ImplFlags::NBHD) {
+            for (i, &neigh) in cell.nbhd.iter().enumerate() {
+                if flags.intersects(ImplFlags::from_bits_truncate(3 << (2 * i + 6))) {
+                    if let Some(neigh) = neigh {
+                        let state =
+                            if flags.contains(ImplFlags::from_bits_truncate(1 << (2 * i + 7))) {
+                                DEAD
+                            } else {
+                                ALIVE
+                            };
+                        if !world.set_cell(neigh, state, Reason::Deduce) {
+                            return false;
+                        }
+                    }
+                }
+            }
+        }
+        true
+    }
+}
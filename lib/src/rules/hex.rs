@@ -0,0 +1,32 @@
+//! Totalistic hexagonal rules.
+
+use crate::{
+    cells::{CellRef, State, ALIVE, DEAD},
+    rules::Rule,
+    search::Reason,
+    world::World,
+};
+use bitflags::bitflags;
+use ca_rules::{ParseHex, ParseHexGen, ParseRuleError};
+use std::{str::FromStr, sync::Arc};
+
+/// Offsets of the neighbors in the hexagonal neighborhood.
+pub(super) const HEX: [(isize, isize); 6] = [(-1, -1), (-1, 0), (0, -1), (0, 1), (1, 0), (1, 1)];
+
+impl_totalistic! {
+    /// Totalistic hexagonal rules.
+    ///
+    /// Each cell has 6 neighbors. The hexagonal grid is represented
+    /// by a square grid, where the neighbors `(1, -1)` and `(-1, 1)`
+    /// are removed from the Moore neighborhood.
+    pub struct Hex {
+        Parser: ParseHex,
+        Nbhd: &HEX,
+        Size: 6,
+    }
+
+    /// Totalistic hexagonal Generations rules.
+    pub struct HexGen {
+        Parser: ParseHexGen,
+    }
+}
@@ -0,0 +1,98 @@
+//! Parsing totalistic rules in the HROT notation.
+
+use ca_rules::ParseRuleError;
+
+/// Parses a list of numbers of living neighbors in the `b` or `s` data,
+/// e.g., `2-3,5`.
+///
+/// Each item of the list is a number or a range of numbers. Numbers must
+/// be at most `max`.
+fn parse_bs(input: &str, max: u8) -> Result<Vec<u8>, ParseRuleError> {
+    let mut bs = Vec::new();
+    if input.is_empty() {
+        return Ok(bs);
+    }
+    let parse_num = |s: &str| -> Result<u8, ParseRuleError> {
+        match s.parse::<u8>() {
+            Ok(n) if n <= max => Ok(n),
+            Ok(_) => Err(ParseRuleError::ExtraJunk),
+            Err(_) => Err(ParseRuleError::MissingNumber),
+        }
+    };
+    for item in input.split(',') {
+        let mut range = item.splitn(2, '-');
+        let start = parse_num(range.next().unwrap())?;
+        let end = match range.next() {
+            Some(end) => parse_num(end)?,
+            None => start,
+        };
+        bs.extend(start..=end);
+    }
+    bs.sort_unstable();
+    bs.dedup();
+    Ok(bs)
+}
+
+/// Parses a rule string in the HROT notation of LifeViewer,
+/// e.g., `R2,C2,S2-3,B3,NN`.
+///
+/// The range must be `range`, and the neighborhood must be `nbhd`,
+/// e.g., `"NN"` for the von Neumann neighborhood. For the Moore neighborhood
+/// `"NM"`, the neighborhood can be omitted. `size` is the number of
+/// neighbors.
+///
+/// The number of states `C` may be `0` or `2` for a rule that is not
+/// a Generations rule.
+///
+/// After the number of states there may be `M0` or `M1`. `M1` means that
+/// a living cell counts itself as a neighbor, as in the Vote rules, e.g.,
+/// `R1,C2,M1,S5-9,B5-9,NM`. Such a rule is converted to a rule with `M0`,
+/// where a living cell survives with `n` living neighbors if `n + 1` is in
+/// the `s` data. A dead cell never has `size + 1` living cells in its
+/// neighborhood, so this number is ignored in the `b` data.
+///
+/// Returns the `b` data, the `s` data, and the number of states.
+pub(crate) fn parse_hrot(
+    input: &str,
+    range: usize,
+    nbhd: &str,
+    size: u8,
+) -> Result<(Vec<u8>, Vec<u8>, usize), ParseRuleError> {
+    let input = input.to_ascii_uppercase();
+    let rest = input
+        .strip_prefix(&format!("R{},C", range))
+        .ok_or(ParseRuleError::Missing('R'))?;
+    let rest = match rest.strip_suffix(&format!(",{}", nbhd)) {
+        Some(rest) => rest,
+        None if nbhd == "NM" && !rest.contains(",N") => rest,
+        None => return Err(ParseRuleError::Missing('N')),
+    };
+
+    let comma = rest.find(',').ok_or(ParseRuleError::Missing(','))?;
+    let gen = match rest[..comma].parse::<usize>() {
+        Ok(0) => 2,
+        Ok(gen) => gen,
+        Err(_) => return Err(ParseRuleError::MissingNumber),
+    };
+    let rest = &rest[comma + 1..];
+    let (center, rest) = if let Some(rest) = rest.strip_prefix("M1,") {
+        (true, rest)
+    } else {
+        (false, rest.strip_prefix("M0,").unwrap_or(rest))
+    };
+
+    let rest = rest.strip_prefix('S').ok_or(ParseRuleError::Missing('S'))?;
+    let split = rest.find(",B").ok_or(ParseRuleError::Missing('B'))?;
+    let mut s = parse_bs(&rest[..split], size + center as u8)?;
+    let mut b = parse_bs(&rest[split + 2..], size + center as u8)?;
+    if center {
+        s = s.into_iter().filter(|&n| n > 0).map(|n| n - 1).collect();
+        b.retain(|&n| n <= size);
+    }
+
+    if gen < 2 {
+        Err(ParseRuleError::GenLessThan2)
+    } else {
+        Ok((b, s, gen))
+    }
+}
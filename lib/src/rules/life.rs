@@ -8,7 +8,7 @@ use crate::{
 };
 use bitflags::bitflags;
 use ca_rules::{ParseLife, ParseLifeGen, ParseRuleError};
-use std::str::FromStr;
+use std::{str::FromStr, sync::Arc};
 
 bitflags! {
     /// Flags to imply the state of a cell and its neighbors.
@@ -85,7 +85,7 @@ impl_rule! {
             Some(_) => 0x10,
             None => 0,
         };
-        for &neigh in cell.nbhd.iter() {
+        for &neigh in cell.nbhd.iter().take(8) {
             let neigh = neigh.unwrap();
             let mut desc = neigh.desc.get();
             if new {
@@ -128,37 +128,57 @@ impl_rule! {
 }
 
 impl Life {
+    /// The state of the successor of a cell, given the state of the cell
+    /// and the number of living cells in its neighborhood.
+    ///
+    /// If the rule is inverted, the states are also inverted.
+    #[cfg(feature = "sat")]
+    pub(crate) fn successor(&self, state: State, alives: usize) -> State {
+        let desc = (8 - alives) << 8 | alives << 4 | if state == ALIVE { 0b01 } else { 0b10 };
+        if self.impl_table[desc].contains(ImplFlags::SUCC_ALIVE) {
+            ALIVE
+        } else {
+            DEAD
+        }
+    }
+
     /// Constructs a new rule from the `b` and `s` data.
     pub fn new(b: Vec<u8>, s: Vec<u8>) -> Self {
         let b0 = b.contains(&0);
 
         let impl_table = [ImplFlags::empty(); 1 << 12];
 
-        Life { b0, impl_table }
-            .init_trans(b, s)
-            .init_conflict()
-            .init_impl()
-            .init_impl_nbhd()
+        Life {
+            b0,
+            inverted: false,
+            isotropic: true,
+            impl_table: Arc::new(impl_table),
+        }
+        .init_trans(b, s)
+        .init_conflict()
+        .init_impl()
+        .init_impl_nbhd()
     }
 
     /// Deduces the implication for the successor.
     fn init_trans(mut self, b: Vec<u8>, s: Vec<u8>) -> Self {
+        let impl_table = Arc::make_mut(&mut self.impl_table);
         // Fills in the positions of the neighborhood descriptors
         // that have no unknown neighbors.
         for alives in 0..=8 {
             let desc = ((8 - alives) << 8) | alives << 4;
             let alives = alives as u8;
-            self.impl_table[desc | 0b10] |= if b.contains(&alives) {
+            impl_table[desc | 0b10] |= if b.contains(&alives) {
                 ImplFlags::SUCC_ALIVE
             } else {
                 ImplFlags::SUCC_DEAD
             };
-            self.impl_table[desc | 0b01] |= if s.contains(&alives) {
+            impl_table[desc | 0b01] |= if s.contains(&alives) {
                 ImplFlags::SUCC_ALIVE
             } else {
                 ImplFlags::SUCC_DEAD
             };
-            self.impl_table[desc] |= if b.contains(&alives) && s.contains(&alives) {
+            impl_table[desc] |= if b.contains(&alives) && s.contains(&alives) {
                 ImplFlags::SUCC_ALIVE
             } else if !b.contains(&alives) && !s.contains(&alives) {
                 ImplFlags::SUCC_DEAD
@@ -175,10 +195,10 @@ impl Life {
                 let desc1 = (8 - alives - unknowns) << 8 | (alives + 1) << 4;
 
                 for state in 0..=2 {
-                    let trans0 = self.impl_table[desc0 | state];
+                    let trans0 = impl_table[desc0 | state];
 
-                    if trans0 == self.impl_table[desc1 | state] {
-                        self.impl_table[desc | state] |= trans0;
+                    if trans0 == impl_table[desc1 | state] {
+                        impl_table[desc | state] |= trans0;
                     }
                 }
             }
@@ -189,14 +209,15 @@ impl Life {
 
     /// Deduces the conflicts.
     fn init_conflict(mut self) -> Self {
+        let impl_table = Arc::make_mut(&mut self.impl_table);
         for nbhd_state in 0..0xff {
             for state in 0..=2 {
                 let desc = nbhd_state << 4 | state;
 
-                if self.impl_table[desc].contains(ImplFlags::SUCC_ALIVE) {
-                    self.impl_table[desc | 0b10 << 2] = ImplFlags::CONFLICT;
-                } else if self.impl_table[desc].contains(ImplFlags::SUCC_DEAD) {
-                    self.impl_table[desc | 0b01 << 2] = ImplFlags::CONFLICT;
+                if impl_table[desc].contains(ImplFlags::SUCC_ALIVE) {
+                    impl_table[desc | 0b10 << 2] = ImplFlags::CONFLICT;
+                } else if impl_table[desc].contains(ImplFlags::SUCC_DEAD) {
+                    impl_table[desc | 0b01 << 2] = ImplFlags::CONFLICT;
                 }
             }
         }
@@ -205,6 +226,7 @@ impl Life {
 
     /// Deduces the implication for the cell itself.
     fn init_impl(mut self) -> Self {
+        let impl_table = Arc::make_mut(&mut self.impl_table);
         for unknowns in 0..=8 {
             for alives in 0..=8 - unknowns {
                 let desc = (8 - alives - unknowns) << 8 | alives << 4;
@@ -216,16 +238,16 @@ impl Life {
                         ImplFlags::SUCC_DEAD | ImplFlags::CONFLICT
                     };
 
-                    let possibly_dead = !self.impl_table[desc | 0b10].intersects(flag);
-                    let possibly_alive = !self.impl_table[desc | 0b01].intersects(flag);
+                    let possibly_dead = !impl_table[desc | 0b10].intersects(flag);
+                    let possibly_alive = !impl_table[desc | 0b01].intersects(flag);
 
                     let index = desc | succ_state << 2;
                     if possibly_dead && !possibly_alive {
-                        self.impl_table[index] |= ImplFlags::SELF_DEAD;
+                        impl_table[index] |= ImplFlags::SELF_DEAD;
                     } else if !possibly_dead && possibly_alive {
-                        self.impl_table[index] |= ImplFlags::SELF_ALIVE;
+                        impl_table[index] |= ImplFlags::SELF_ALIVE;
                     } else if !possibly_dead && !possibly_alive {
-                        self.impl_table[index] = ImplFlags::CONFLICT;
+                        impl_table[index] = ImplFlags::CONFLICT;
                     }
                 }
             }
@@ -236,6 +258,7 @@ impl Life {
 
     ///  Deduces the implication for the neighbors.
     fn init_impl_nbhd(mut self) -> Self {
+        let impl_table = Arc::make_mut(&mut self.impl_table);
         for unknowns in 1..=8 {
             for alives in 0..=8 - unknowns {
                 let desc = (8 - alives - unknowns) << 8 | alives << 4;
@@ -252,15 +275,15 @@ impl Life {
                     let index = desc | succ_state << 2;
 
                     for state in 0..=2 {
-                        let possibly_dead = !self.impl_table[desc0 | state].intersects(flag);
-                        let possibly_alive = !self.impl_table[desc1 | state].intersects(flag);
+                        let possibly_dead = !impl_table[desc0 | state].intersects(flag);
+                        let possibly_alive = !impl_table[desc1 | state].intersects(flag);
 
                         if possibly_dead && !possibly_alive {
-                            self.impl_table[index | state] |= ImplFlags::NBHD_DEAD;
+                            impl_table[index | state] |= ImplFlags::NBHD_DEAD;
                         } else if !possibly_dead && possibly_alive {
-                            self.impl_table[index | state] |= ImplFlags::NBHD_ALIVE;
+                            impl_table[index | state] |= ImplFlags::NBHD_ALIVE;
                         } else if !possibly_dead && !possibly_alive {
-                            self.impl_table[index | state] = ImplFlags::CONFLICT;
+                            impl_table[index | state] = ImplFlags::CONFLICT;
                         }
                     }
                 }
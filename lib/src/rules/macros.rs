@@ -1,4 +1,5 @@
-//! A macro to generate the corresponding Generations rule of a rule.
+//! Macros to generate the corresponding Generations rule of a rule,
+//! and totalistic rules with small neighborhoods.
 #![macro_use]
 
 /// Implements `Rule` trait for a rule and the corresponding Generations rule.
@@ -10,7 +11,10 @@ macro_rules! impl_rule {
         $(#[$doc:meta])*
         pub struct $rule:ident {
             Parser: $parser:ident,
-            impl_table: $impl_table:ty $(,)?
+            impl_table: $impl_table:ty
+            $(, Nbhd: $nbhd:expr)?
+            $(, NbhdOdd: $nbhd_odd:expr)?
+            $(, Full: $full:expr)? $(,)?
         }
 
         $(#[$doc_gen:meta])*
@@ -47,17 +51,55 @@ macro_rules! impl_rule {
         pub struct NbhdDesc($desc_type);
 
         $(#[$doc])*
+        #[derive(Clone)]
         pub struct $rule {
             /// Whether the rule contains `B0`.
             b0: bool,
+            /// Whether the states are inverted.
+            ///
+            /// See `Rule::is_inverted` for details.
+            inverted: bool,
+            /// Whether the rule is isotropic.
+            ///
+            /// See `Rule::is_isotropic` for details.
+            isotropic: bool,
             /// An array of actions for all neighborhood descriptors.
-            impl_table: $impl_table,
+            impl_table: Arc<$impl_table>,
         }
 
         /// A parser for the rule.
         impl $parser for $rule {
             fn from_bs(b: Vec<u8>, s: Vec<u8>) -> Self {
-                Self::new(b, s)
+                Self::new_inverted(b, s)
+            }
+        }
+
+        impl $rule {
+            /// Constructs a new rule from the `b` and `s` data.
+            ///
+            /// If the rule contains both `B0` and `S8` (or its analogue for
+            /// other neighborhoods), the states are inverted, so that the
+            /// background is always dead. See `Rule::is_inverted` for details.
+            pub fn new_inverted(b: Vec<u8>, s: Vec<u8>) -> Self {
+                #[allow(unused_variables)]
+                let full = Self::NBHD.len() as u8;
+                $(let full = $full;)?
+                if b.contains(&0) && s.contains(&full) {
+                    let b_inv = (0..=full)
+                        .filter(|n| !s.contains(n))
+                        .map(|n| full - n)
+                        .collect();
+                    let s_inv = (0..=full)
+                        .filter(|n| !b.contains(n))
+                        .map(|n| full - n)
+                        .collect();
+                    $rule {
+                        inverted: true,
+                        ..Self::new(b_inv, s_inv)
+                    }
+                } else {
+                    Self::new(b, s)
+                }
             }
         }
 
@@ -74,14 +116,25 @@ macro_rules! impl_rule {
 
             const IS_GEN: bool = false;
 
+            $(const NBHD: &'static [(isize, isize)] = $nbhd;)?
+            $(const NBHD_ODD: &'static [(isize, isize)] = $nbhd_odd;)?
+
             fn has_b0(&self) -> bool {
                 self.b0
             }
 
+            fn is_inverted(&self) -> bool {
+                self.inverted
+            }
+
             fn gen(&self) -> usize {
                 2
             }
 
+            fn is_isotropic(&self) -> bool {
+                self.isotropic
+            }
+
             fn new_desc(state: State, succ_state: State) -> Self::Desc {
                 let nbhd_state = match state {
                     ALIVE => $alive_desc,
@@ -161,26 +214,40 @@ macro_rules! impl_rule {
         pub struct NbhdDescGen ($desc_type, Option<State>);
 
         $(#[$doc_gen])*
+        #[derive(Clone)]
         pub struct $rule_gen {
             /// Whether the rule contains `B0`.
             b0: bool,
+            /// Whether the states are inverted.
+            ///
+            /// This is only possible when the number of states is 2.
+            inverted: bool,
+            /// Whether the rule is isotropic.
+            isotropic: bool,
             /// Number of states.
             gen: usize,
             /// An array of actions for all neighborhood descriptors.
-            impl_table: $impl_table,
+            impl_table: Arc<$impl_table>,
         }
 
         impl $rule_gen {
             /// Constructs a new rule from the `b` and `s` data
             /// and the number of states.
+            ///
+            /// When the number of states is 2, rules with both `B0` and `S8`
+            /// are inverted as in `new_inverted` of the non-Generations rule.
             pub fn new(b: Vec<u8>, s: Vec<u8>, gen: usize) -> Self {
-                let b0 = b.contains(&0);
-                let life = $rule::new(b, s);
-                let impl_table = life.impl_table;
+                let life = if gen == 2 {
+                    $rule::new_inverted(b, s)
+                } else {
+                    $rule::new(b, s)
+                };
                 Self {
-                    b0,
+                    b0: life.b0,
+                    inverted: life.inverted,
+                    isotropic: life.isotropic,
                     gen,
-                    impl_table,
+                    impl_table: life.impl_table,
                 }
             }
 
@@ -188,6 +255,8 @@ macro_rules! impl_rule {
             pub fn non_gen(self) -> $rule {
                 $rule {
                     b0: self.b0,
+                    inverted: self.inverted,
+                    isotropic: self.isotropic,
                     impl_table: self.impl_table,
                 }
             }
@@ -214,14 +283,25 @@ macro_rules! impl_rule {
 
             const IS_GEN: bool = true;
 
+            $(const NBHD: &'static [(isize, isize)] = $nbhd;)?
+            $(const NBHD_ODD: &'static [(isize, isize)] = $nbhd_odd;)?
+
             fn has_b0(&self) -> bool {
                 self.b0
             }
 
+            fn is_inverted(&self) -> bool {
+                self.inverted
+            }
+
             fn gen(&self) -> usize {
                 self.gen
             }
 
+            fn is_isotropic(&self) -> bool {
+                self.isotropic
+            }
+
             fn new_desc(state: State, succ_state: State) -> Self::Desc {
                 let desc = $rule::new_desc(state, succ_state);
                 NbhdDescGen(desc.0, Some(succ_state))
@@ -299,17 +379,14 @@ macro_rules! impl_rule {
                         }
                     }
                     None => match desc.1 {
-                        Some(DEAD) => {
-                            if $flags_gen.contains(ImplFlags::SELF_ALIVE) {
-                                return $world_gen.set_cell(
-                                    $cell_cons_gen,
-                                    State(gen - 1),
-                                    Reason::Deduce
-                                );
-                            } else {
-                                return true;
-                            }
+                        Some(DEAD) if $flags_gen.contains(ImplFlags::SELF_ALIVE) => {
+                            return $world_gen.set_cell(
+                                $cell_cons_gen,
+                                State(gen - 1),
+                                Reason::Deduce
+                            );
                         }
+                        Some(DEAD) => return true,
                         Some(ALIVE) => {
                             if $flags_gen.intersects(ImplFlags::SELF) {
                                 let state = if $flags_gen.contains(ImplFlags::SELF_DEAD) {
@@ -348,3 +425,296 @@ macro_rules! impl_rule {
         }
     };
 }
+
+/// Implements a totalistic rule whose neighborhood has at most 7 cells,
+/// and the corresponding Generations rule.
+///
+/// The numbers of living and dead neighbors are stored in 3 bits each
+/// in the neighborhood descriptor. `Size` is the number of neighbors.
+#[cfg(any(feature = "hex", feature = "von-neumann"))]
+macro_rules! impl_totalistic {
+    {
+        $(#[$doc:meta])*
+        pub struct $rule:ident {
+            Parser: $parser:ident,
+            Nbhd: $nbhd:expr,
+            Size: $size:expr $(,)?
+        }
+
+        $(#[$doc_gen:meta])*
+        pub struct $rule_gen:ident {
+            Parser: $parser_gen:ident $(,)?
+        }
+    } => {
+        bitflags! {
+            /// Flags to imply the state of a cell and its neighbors.
+            #[derive(Default)]
+            struct ImplFlags: u8 {
+                /// A conflict is detected.
+                const CONFLICT = 0b_0000_0001;
+
+                /// The successor must be alive.
+                const SUCC_ALIVE = 0b_0000_0100;
+
+                /// The successor must be dead.
+                const SUCC_DEAD = 0b_0000_1000;
+
+                /// The state of the successor is implied.
+                const SUCC = Self::SUCC_ALIVE.bits | Self::SUCC_DEAD.bits;
+
+                /// The cell itself must be alive.
+                const SELF_ALIVE = 0b_0001_0000;
+
+                /// The cell itself must be dead.
+                const SELF_DEAD = 0b_0010_0000;
+
+                /// The state of the cell itself is implied.
+                const SELF = Self::SELF_ALIVE.bits | Self::SELF_DEAD.bits;
+
+                /// All unknown neighbors must be alive.
+                const NBHD_ALIVE = 0b_0100_0000;
+
+                /// All unknown neighbors must be dead.
+                const NBHD_DEAD = 0b_1000_0000;
+
+                /// The states of all unknown neighbors are implied.
+                const NBHD = Self::NBHD_ALIVE.bits | Self::NBHD_DEAD.bits;
+            }
+        }
+
+        impl_rule! {
+            /// The neighborhood descriptor.
+            ///
+            /// It is a 10-bit integer of the form `0b_abc_def_ij_kl`,
+            /// where:
+            ///
+            /// * `0b_abc` is the number of dead cells in the neighborhood.
+            /// * `0b_def` is the number of living cells in the neighborhood.
+            /// * `0b_ij` is the state of the successor.
+            /// * `0b_kl` is the state of the cell itself.
+            ///
+            /// For `0b_ij` and `0b_kl`:
+            /// * `0b_10` means dead,
+            /// * `0b_01` means alive,
+            /// * `0b_00` means unknown.
+            pub struct NbhdDesc(u16);
+
+            $(#[$doc])*
+            pub struct $rule {
+                Parser: $parser,
+                impl_table: [ImplFlags; 1 << 10],
+                Nbhd: $nbhd,
+            }
+
+            $(#[$doc_gen])*
+            pub struct $rule_gen {
+                Parser: $parser_gen,
+            }
+
+            fn new_desc {
+                ALIVE => $size,
+                DEAD => $size << 3,
+            }
+
+            fn update_desc(cell, state, new, change_num) {
+                let state_num = match state {
+                    Some(ALIVE) => 0x01,
+                    Some(_) => 0x08,
+                    None => 0,
+                };
+                for &neigh in cell.nbhd.iter().take($size) {
+                    let neigh = neigh.unwrap();
+                    let mut desc = neigh.desc.get();
+                    if new {
+                        desc.0 += state_num << 4;
+                    } else {
+                        desc.0 -= state_num << 4;
+                    }
+                    neigh.desc.set(desc);
+                }
+            }
+
+            fn consistify<'a>(world, cell, flags) {
+                let state = if flags.contains(ImplFlags::NBHD_DEAD) {
+                    DEAD
+                } else {
+                    ALIVE
+                };
+                for &neigh in cell.nbhd.iter() {
+                    if let Some(neigh) = neigh {
+                        if neigh.state.get().is_none() && !world.set_cell(neigh, state, Reason::Deduce)
+                        {
+                            return false;
+                        }
+                    }
+                }
+            }
+
+            fn consistify_gen<'a>(world, cell, flags) {
+                if flags.intersects(ImplFlags::NBHD_ALIVE) {
+                    for &neigh in cell.nbhd.iter() {
+                        if let Some(neigh) = neigh {
+                            if neigh.state.get().is_none() && !world.set_cell(neigh, ALIVE, Reason::Deduce)
+                            {
+                                return false;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        impl $rule {
+            /// Constructs a new rule from the `b` and `s` data.
+            pub fn new(b: Vec<u8>, s: Vec<u8>) -> Self {
+                let b0 = b.contains(&0);
+
+                let impl_table = [ImplFlags::empty(); 1 << 10];
+
+                $rule {
+                    b0,
+                    inverted: false,
+                    isotropic: true,
+                    impl_table: Arc::new(impl_table),
+                }
+                .init_trans(b, s)
+                .init_conflict()
+                .init_impl()
+                .init_impl_nbhd()
+            }
+
+            /// Deduces the implication for the successor.
+            fn init_trans(mut self, b: Vec<u8>, s: Vec<u8>) -> Self {
+                let impl_table = Arc::make_mut(&mut self.impl_table);
+                // Fills in the positions of the neighborhood descriptors
+                // that have no unknown neighbors.
+                for alives in 0..=$size {
+                    let desc = (($size - alives) << 7) | alives << 4;
+                    let alives = alives as u8;
+                    impl_table[desc | 0b10] |= if b.contains(&alives) {
+                        ImplFlags::SUCC_ALIVE
+                    } else {
+                        ImplFlags::SUCC_DEAD
+                    };
+                    impl_table[desc | 0b01] |= if s.contains(&alives) {
+                        ImplFlags::SUCC_ALIVE
+                    } else {
+                        ImplFlags::SUCC_DEAD
+                    };
+                    impl_table[desc] |= if b.contains(&alives) && s.contains(&alives) {
+                        ImplFlags::SUCC_ALIVE
+                    } else if !b.contains(&alives) && !s.contains(&alives) {
+                        ImplFlags::SUCC_DEAD
+                    } else {
+                        ImplFlags::empty()
+                    };
+                }
+
+                // Fills in other positions.
+                for unknowns in 1..=$size {
+                    for alives in 0..=$size - unknowns {
+                        let desc = ($size - alives - unknowns) << 7 | alives << 4;
+                        let desc0 = ($size - alives - unknowns + 1) << 7 | alives << 4;
+                        let desc1 = ($size - alives - unknowns) << 7 | (alives + 1) << 4;
+
+                        for state in 0..=2 {
+                            let trans0 = impl_table[desc0 | state];
+
+                            if trans0 == impl_table[desc1 | state] {
+                                impl_table[desc | state] |= trans0;
+                            }
+                        }
+                    }
+                }
+
+                self
+            }
+
+            /// Deduces the conflicts.
+            fn init_conflict(mut self) -> Self {
+                let impl_table = Arc::make_mut(&mut self.impl_table);
+                for nbhd_state in 0..0x3f {
+                    for state in 0..=2 {
+                        let desc = nbhd_state << 4 | state;
+
+                        if impl_table[desc].contains(ImplFlags::SUCC_ALIVE) {
+                            impl_table[desc | 0b10 << 2] = ImplFlags::CONFLICT;
+                        } else if impl_table[desc].contains(ImplFlags::SUCC_DEAD) {
+                            impl_table[desc | 0b01 << 2] = ImplFlags::CONFLICT;
+                        }
+                    }
+                }
+                self
+            }
+
+            /// Deduces the implication for the cell itself.
+            fn init_impl(mut self) -> Self {
+                let impl_table = Arc::make_mut(&mut self.impl_table);
+                for unknowns in 0..=$size {
+                    for alives in 0..=$size - unknowns {
+                        let desc = ($size - alives - unknowns) << 7 | alives << 4;
+
+                        for succ_state in 1..=2 {
+                            let flag = if succ_state == 0b10 {
+                                ImplFlags::SUCC_ALIVE | ImplFlags::CONFLICT
+                            } else {
+                                ImplFlags::SUCC_DEAD | ImplFlags::CONFLICT
+                            };
+
+                            let possibly_dead = !impl_table[desc | 0b10].intersects(flag);
+                            let possibly_alive = !impl_table[desc | 0b01].intersects(flag);
+
+                            let index = desc | succ_state << 2;
+                            if possibly_dead && !possibly_alive {
+                                impl_table[index] |= ImplFlags::SELF_DEAD;
+                            } else if !possibly_dead && possibly_alive {
+                                impl_table[index] |= ImplFlags::SELF_ALIVE;
+                            } else if !possibly_dead && !possibly_alive {
+                                impl_table[index] = ImplFlags::CONFLICT;
+                            }
+                        }
+                    }
+                }
+
+                self
+            }
+
+            ///  Deduces the implication for the neighbors.
+            fn init_impl_nbhd(mut self) -> Self {
+                let impl_table = Arc::make_mut(&mut self.impl_table);
+                for unknowns in 1..=$size {
+                    for alives in 0..=$size - unknowns {
+                        let desc = ($size - alives - unknowns) << 7 | alives << 4;
+                        let desc0 = ($size - alives - unknowns + 1) << 7 | alives << 4;
+                        let desc1 = ($size - alives - unknowns) << 7 | (alives + 1) << 4;
+
+                        for succ_state in 1..=2 {
+                            let flag = if succ_state == 0b10 {
+                                ImplFlags::SUCC_ALIVE | ImplFlags::CONFLICT
+                            } else {
+                                ImplFlags::SUCC_DEAD | ImplFlags::CONFLICT
+                            };
+
+                            let index = desc | succ_state << 2;
+
+                            for state in 0..=2 {
+                                let possibly_dead = !impl_table[desc0 | state].intersects(flag);
+                                let possibly_alive = !impl_table[desc1 | state].intersects(flag);
+
+                                if possibly_dead && !possibly_alive {
+                                    impl_table[index | state] |= ImplFlags::NBHD_DEAD;
+                                } else if !possibly_dead && possibly_alive {
+                                    impl_table[index | state] |= ImplFlags::NBHD_ALIVE;
+                                } else if !possibly_dead && !possibly_alive {
+                                    impl_table[index | state] = ImplFlags::CONFLICT;
+                                }
+                            }
+                        }
+                    }
+                }
+
+                self
+            }
+        }
+    };
+}
@@ -0,0 +1,207 @@
+//! Rules with the Margolus neighborhood, e.g., Critters.
+
+use crate::{
+    cells::{CellRef, State, ALIVE, DEAD},
+    rules::{Rule, MOORE},
+    search::Reason,
+    world::World,
+};
+use ca_rules::ParseRuleError;
+use std::str::FromStr;
+
+/// Rules with the Margolus neighborhood, e.g., Critters.
+///
+/// The grid is partitioned into 2×2 blocks. In even generations, the
+/// top-left cells of the blocks have even coordinates; in odd generations,
+/// they have odd coordinates. The rule maps the states of the 4 cells
+/// in a block to the states of the same 4 cells in the next generation.
+///
+/// The rule string is in the [MCell](http://psoup.math.wisc.edu/mcell/)
+/// notation, e.g., `MS,D15;14;13;3;11;5;6;1;7;9;10;2;12;4;8;0` for Critters.
+/// It lists the new state of a block for each of the 16 states of the block,
+/// where the top-left, top-right, bottom-left and bottom-right cells are
+/// counted as 1, 2, 4 and 8 respectively.
+///
+/// Since the partition alternates between generations, the period should be
+/// even, unless the transformation and the translation map the blocks of
+/// an odd generation to the blocks of an even generation.
+/// The same is true for symmetries.
+#[derive(Clone)]
+pub struct Margolus {
+    /// Whether the rule maps an empty block to a full block.
+    b0: bool,
+    /// The new state of a block for each state of the block.
+    map: [u8; 16],
+}
+
+impl Margolus {
+    /// Constructs a new rule from the new states of a block.
+    ///
+    /// Only the lowest 4 bits of each entry are used.
+    pub fn new(map: [u8; 16]) -> Self {
+        let mut map = map;
+        for new in map.iter_mut() {
+            *new &= 0xf;
+        }
+        let b0 = map[0] != 0;
+        Margolus { b0, map }
+    }
+
+    /// Finds the cells in the block of a cell, in the same generation.
+    ///
+    /// The cells are ordered as top-left, top-right, bottom-left and
+    /// bottom-right. `None` means that the cell is outside the world,
+    /// and is in the background state.
+    fn block<'a>(cell: CellRef<'a, Self>) -> [Option<CellRef<'a, Self>>; 4] {
+        let (x, y, t) = cell.coord;
+        let x0 = -(x - t).rem_euclid(2);
+        let y0 = -(y - t).rem_euclid(2);
+        let mut block = [None; 4];
+        for (i, entry) in block.iter_mut().enumerate() {
+            let offset = (x0 + (i & 1) as isize, y0 + (i >> 1) as isize);
+            *entry = if offset == (0, 0) {
+                Some(cell)
+            } else {
+                let j = MOORE.iter().position(|&o| o == offset).unwrap();
+                cell.nbhd[j]
+            };
+        }
+        block
+    }
+}
+
+impl FromStr for Margolus {
+    type Err = ParseRuleError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut chars = input.chars();
+        for expected in "MS,D".chars() {
+            match chars.next() {
+                Some(c) if c.to_ascii_uppercase() == expected => (),
+                _ => return Err(ParseRuleError::Missing(expected)),
+            }
+        }
+        let mut map = [0; 16];
+        let mut len = 0;
+        for entry in chars.as_str().split(';') {
+            let first = entry.chars().next().ok_or(ParseRuleError::MissingNumber)?;
+            let new = entry
+                .parse::<u8>()
+                .ok()
+                .filter(|&n| n < 16)
+                .ok_or(ParseRuleError::Unexpected(first))?;
+            if len == 16 {
+                return Err(ParseRuleError::ExtraJunk);
+            }
+            map[len] = new;
+            len += 1;
+        }
+        if len < 16 {
+            return Err(ParseRuleError::MissingNumber);
+        }
+        // An empty block must stay empty, or become a full block.
+        if map[0] != 0 && map[0] != 0xf {
+            return Err(ParseRuleError::Unexpected(';'));
+        }
+        Ok(Margolus::new(map))
+    }
+}
+
+impl Rule for Margolus {
+    /// The rule does not use neighborhood descriptors.
+    ///
+    /// The states of the block are read directly in `consistify`.
+    type Desc = ();
+
+    const IS_GEN: bool = false;
+
+    fn has_b0(&self) -> bool {
+        self.b0
+    }
+
+    fn gen(&self) -> usize {
+        2
+    }
+
+    /// The partition into blocks depends on the parity of the coordinates.
+    fn has_parity(&self) -> bool {
+        true
+    }
+
+    fn new_desc(_state: State, _succ_state: State) -> Self::Desc {}
+
+    fn update_desc(_cell: CellRef<Self>, _state: Option<State>, _new: bool) {}
+
+    fn consistify<'a>(world: &mut World<'a, Self>, cell: CellRef<'a, Self>) -> bool {
+        let block = Margolus::block(cell);
+        let background = cell.background;
+        let succ_background = if world.rule.b0 {
+            !background
+        } else {
+            background
+        };
+
+        // Known bits of the block in this generation and the next generation.
+        let (mut mask, mut bits) = (0, 0);
+        let (mut succ_mask, mut succ_bits) = (0, 0);
+        for (i, entry) in block.iter().enumerate() {
+            let state = entry.map_or(Some(background), |c| c.state.get());
+            if let Some(state) = state {
+                mask |= 1 << i;
+                if state == ALIVE {
+                    bits |= 1 << i;
+                }
+            }
+            let succ_state = entry
+                .and_then(|c| c.succ)
+                .map_or(Some(succ_background), |c| c.state.get());
+            if let Some(state) = succ_state {
+                succ_mask |= 1 << i;
+                if state == ALIVE {
+                    succ_bits |= 1 << i;
+                }
+            }
+        }
+
+        // Bits that are alive / dead in all possible transitions.
+        let (mut alive, mut dead) = (0xf, 0xf);
+        let (mut succ_alive, mut succ_dead) = (0xf, 0xf);
+        let mut possible = false;
+        for (state, &new) in world.rule.map.iter().enumerate() {
+            if state & mask == bits && new as usize & succ_mask == succ_bits {
+                possible = true;
+                alive &= state;
+                dead &= !state;
+                succ_alive &= new as usize;
+                succ_dead &= !new as usize;
+            }
+        }
+        if !possible {
+            return false;
+        }
+
+        for (i, entry) in block.iter().enumerate() {
+            if let Some(c) = *entry {
+                if c.state.get().is_none() && (alive | dead) >> i & 1 == 1 {
+                    let state = if alive >> i & 1 == 1 { ALIVE } else { DEAD };
+                    if !world.set_cell(c, state, Reason::Deduce) {
+                        return false;
+                    }
+                }
+                if let Some(succ) = c.succ {
+                    if succ.state.get().is_none() && (succ_alive | succ_dead) >> i & 1 == 1 {
+                        let state = if succ_alive >> i & 1 == 1 {
+                            ALIVE
+                        } else {
+                            DEAD
+                        };
+                        if !world.set_cell(succ, state, Reason::Deduce) {
+                            return false;
+                        }
+                    }
+                }
+            }
+        }
+        true
+    }
+}
@@ -5,17 +5,137 @@
 
 mod macros;
 
+mod alias;
+
+#[cfg(feature = "bsfkl")]
+mod bsfkl;
+mod canonical;
+#[cfg(feature = "custom")]
+mod custom;
+#[cfg(feature = "hex")]
+mod hex;
+mod hrot;
 mod life;
+#[cfg(feature = "margolus")]
+mod margolus;
+#[cfg(feature = "von-neumann")]
+mod neumann;
+#[cfg(feature = "von-neumann")]
+mod neumann2;
+#[cfg(feature = "hex")]
+mod nthex;
 mod ntlife;
+#[cfg(feature = "rule-range")]
+mod range;
+#[cfg(feature = "table")]
+mod table;
+#[cfg(feature = "triangle")]
+mod triangle;
 
 use crate::{
     cells::{CellRef, State},
-    world::World,
+    config::{Config, Transform},
+    error::Error,
+    traits::Search,
+    world::{check_rule, new_world, World},
 };
+use hrot::parse_hrot;
+
+pub use alias::{register_alias, resolve_alias};
+#[cfg(feature = "bsfkl")]
+pub use bsfkl::Bsfkl;
+pub use canonical::CanonicalRule;
+#[cfg(feature = "custom")]
+pub use custom::Custom;
+#[cfg(feature = "hex")]
+pub use hex::{Hex, HexGen};
 pub use life::{Life, LifeGen};
+#[cfg(feature = "margolus")]
+pub use margolus::Margolus;
+#[cfg(feature = "von-neumann")]
+pub use neumann::{VonNeumann, VonNeumannGen};
+#[cfg(feature = "von-neumann")]
+pub use neumann2::{VonNeumann2, VonNeumann2Gen};
+#[cfg(feature = "hex")]
+pub use nthex::{NtHex, NtHexGen};
 pub use ntlife::{NtLife, NtLifeGen};
+#[cfg(feature = "rule-range")]
+pub use range::LifeRange;
+#[cfg(feature = "table")]
+pub use table::{table_to_rule_string, MAX_TABLE_STATES};
+#[cfg(feature = "triangle")]
+pub use triangle::{Triangle, TriangleGen};
+
+/// The maximal number of neighbors of a cell.
+pub const MAX_NBHD_SIZE: usize = 12;
+
+/// Offsets of the neighbors in the Moore neighborhood.
+pub(crate) const MOORE: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// Whether a non-totalistic rule, given by its `b` and `s` data,
+/// is unchanged by the rotations and reflections that map the
+/// neighborhood `nbhd` to itself.
+///
+/// Each entry of the data is a set of living neighbors, where the
+/// highest of the `nbhd.len()` bits is the first neighbor in `nbhd`,
+/// as in the neighborhood descriptors.
+pub(crate) fn is_isotropic_nt(nbhd: &[(isize, isize)], b: &[u8], s: &[u8]) -> bool {
+    let len = nbhd.len();
+    Transform::ALL.iter().all(|transform| {
+        let m = transform.matrix();
+        let perm = nbhd
+            .iter()
+            .map(|&(x, y)| {
+                let image = (m[0][0] * x + m[0][1] * y, m[1][0] * x + m[1][1] * y);
+                nbhd.iter().position(|&offset| offset == image)
+            })
+            .collect::<Option<Vec<_>>>();
+        let perm = match perm {
+            Some(perm) => perm,
+            None => return true,
+        };
+        let apply = |alives: u8| {
+            (0..len)
+                .filter(|&i| alives >> (len - 1 - i) & 1 != 0)
+                .fold(0, |image, i| image | 1 << (len - 1 - perm[i]))
+        };
+        [b, s]
+            .iter()
+            .all(|data| data.iter().all(|&alives| data.contains(&apply(alives))))
+    })
+}
 
 /// A cellular automaton rule.
+///
+/// The rules in this crate implement this trait, and it can also be
+/// implemented outside the crate. A world with such a rule is created
+/// with `Config::world_with_rule`.
+///
+/// Each cell of the world keeps a neighborhood descriptor `Desc`, which
+/// summarizes the states of the cell itself, its neighbors, and its
+/// successor. A rule implements the following contract:
+///
+/// * `new_desc` gives the descriptor of a cell in the initial background.
+/// * Whenever the state of a cell changes, `update_desc` is called, and
+///   must update the descriptors of the cell itself, of its neighbors,
+///   and of its predecessor, with `LifeCell::desc` and `LifeCell::set_desc`.
+/// * Whenever a cell, its successor, or one of its neighbors is set,
+///   `consistify` is called on the cell. It reads the descriptor,
+///   returns `false` if the cell cannot produce its successor, and may
+///   set unknown cells with `World::deduce`.
+///
+/// `consistify` may do as few deductions as it likes, as long as it
+/// detects a conflict when the cell, its neighbors and its successor are
+/// all known. More deductions make the search faster.
 pub trait Rule: Sized {
     /// The type of neighborhood descriptor of the rule.
     ///
@@ -26,15 +146,111 @@ pub trait Rule: Sized {
     /// Whether the rule is a Generations rule.
     const IS_GEN: bool;
 
+    /// Offsets of the neighbors of a cell.
+    ///
+    /// There are at most 12 neighbors. The `i`-th neighbor is stored in
+    /// the `i`-th entry of `nbhd` of the cell, and the other entries are
+    /// `None`.
+    ///
+    /// The default is the Moore neighborhood.
+    const NBHD: &'static [(isize, isize)] = &MOORE;
+
+    /// Offsets of the neighbors of a cell whose `x + y` is odd.
+    ///
+    /// This is for grids where the neighborhood of a cell depends on
+    /// its parity, e.g., the triangular grid.
+    /// It must have the same length as `NBHD`.
+    ///
+    /// The default is the same as `NBHD`.
+    const NBHD_ODD: &'static [(isize, isize)] = Self::NBHD;
+
+    /// Offsets of the neighbors of a cell whose `x + y` is even.
+    ///
+    /// This is for rules whose neighborhoods are only known at runtime,
+    /// e.g., `Custom`. The world is built from this method rather than
+    /// from the constant.
+    ///
+    /// The default is `NBHD`.
+    fn nbhd(&self) -> &[(isize, isize)] {
+        Self::NBHD
+    }
+
+    /// Offsets of the neighbors of a cell whose `x + y` is odd.
+    ///
+    /// The default is `NBHD_ODD`.
+    fn nbhd_odd(&self) -> &[(isize, isize)] {
+        Self::NBHD_ODD
+    }
+
+    /// Whether the rule depends on the parity of the coordinates,
+    /// e.g., rules on the triangular grid.
+    ///
+    /// On a torus, the joined dimensions must be even for such rules.
+    ///
+    /// The default is whether `nbhd` and `nbhd_odd` are different.
+    fn has_parity(&self) -> bool {
+        self.nbhd() != self.nbhd_odd()
+    }
+
     /// Whether the rule contains `B0`.
     ///
     /// In other words, whether a cell would become `Alive` in the next
     /// generation, if all its neighbors in this generation are dead.
     fn has_b0(&self) -> bool;
 
+    /// Whether the states are inverted.
+    ///
+    /// A rule that contains both `B0` and `S8` (or its analogue for other
+    /// neighborhoods) turns an empty background into a full background,
+    /// which then stays full. Such a rule is searched as its black/white
+    /// reversal, which does not contain `B0`, so that the background is
+    /// always dead.
+    ///
+    /// The states of known cells in the configuration are inverted when
+    /// the world is created, and the states of the cells are inverted back
+    /// in `get_cell_state`. The cell counts still count the cells that
+    /// differ from the background.
+    ///
+    /// The default is `false`.
+    fn is_inverted(&self) -> bool {
+        false
+    }
+
     /// The number of states.
     fn gen(&self) -> usize;
 
+    /// Whether the rule is unchanged by the rotations and reflections
+    /// that preserve its neighborhood.
+    ///
+    /// This must be `false` for non-isotropic rules, e.g., MAP rules
+    /// without such symmetries.
+    ///
+    /// For such rules, consistifying a cell gives the same deductions
+    /// as consistifying its symmetric cells, which allows
+    /// `Config::fundamental_domain`.
+    ///
+    /// The default is `false`.
+    fn is_isotropic(&self) -> bool {
+        false
+    }
+
+    /// Undoes the deductions about the rule itself that were made when
+    /// the `set_stack` of the world was longer than `stack_len`.
+    ///
+    /// This is called in the backtracking, for rules whose transitions
+    /// are deduced during the search, e.g., `LifeRange`.
+    ///
+    /// The default does nothing.
+    fn backtrack(&mut self, _stack_len: usize) {}
+
+    /// The minimal and the maximal rules that agree with
+    /// the transitions deduced so far.
+    ///
+    /// The default is `None`, which means that the rule is fully known.
+    fn deduced_rules(&self) -> Option<(String, String)> {
+        None
+    }
+
     /// Generates a neighborhood descriptor which says that all neighboring
     /// cells have states `state`, and the successor has state `succ_state`.
     fn new_desc(state: State, succ_state: State) -> Self::Desc;
@@ -57,3 +273,288 @@ pub trait Rule: Sized {
     /// `true` if the cells are consistent.
     fn consistify<'a>(world: &mut World<'a, Self>, cell: CellRef<'a, Self>) -> bool;
 }
+
+/// A rule of any type supported by this crate.
+///
+/// Rule strings are parsed into the most specific rule type.
+/// Generations rules with only 2 states are converted to
+/// the corresponding non-Generations rules.
+///
+/// Hexagonal rules and rules with von Neumann neighborhoods
+/// are parsed as non-totalistic rules when their features are disabled.
+///
+/// `Custom` rules are only used for built-in neighborhoods.
+#[derive(Clone)]
+pub(crate) enum AnyRule {
+    Life(Life),
+    #[cfg(feature = "hex")]
+    Hex(Hex),
+    #[cfg(feature = "von-neumann")]
+    VonNeumann(VonNeumann),
+    #[cfg(feature = "von-neumann")]
+    VonNeumann2(VonNeumann2),
+    #[cfg(feature = "triangle")]
+    Triangle(Triangle),
+    #[cfg(feature = "margolus")]
+    Margolus(Margolus),
+    #[cfg(feature = "hex")]
+    NtHex(NtHex),
+    NtLife(NtLife),
+    #[cfg(feature = "rule-range")]
+    LifeRange(LifeRange),
+    LifeGen(LifeGen),
+    #[cfg(feature = "hex")]
+    HexGen(HexGen),
+    #[cfg(feature = "von-neumann")]
+    VonNeumannGen(VonNeumannGen),
+    #[cfg(feature = "von-neumann")]
+    VonNeumann2Gen(VonNeumann2Gen),
+    #[cfg(feature = "triangle")]
+    TriangleGen(TriangleGen),
+    #[cfg(feature = "hex")]
+    NtHexGen(NtHexGen),
+    NtLifeGen(NtLifeGen),
+    #[cfg(feature = "bsfkl")]
+    Bsfkl(Bsfkl),
+    #[cfg(feature = "custom")]
+    Custom(Custom),
+}
+
+impl AnyRule {
+    /// Parses a rule string, or the name of a rule.
+    ///
+    /// Names registered with `register_alias` are tried first.
+    /// The suffix of a bounded grid, e.g., `:T40,20`, is ignored here.
+    pub(crate) fn parse(rule_string: &str) -> Result<Self, Error> {
+        AnyRule::parse_rule_string(&Self::resolve(rule_string))
+    }
+
+    /// Removes the suffix of a bounded grid, and looks up the aliases.
+    fn resolve(rule_string: &str) -> String {
+        let rule_string = rule_string.split(':').next().unwrap();
+        resolve_alias(rule_string).unwrap_or_else(|| rule_string.to_string())
+    }
+
+    /// Parses the rule of a configuration.
+    ///
+    /// This is the same as `parse`, unless the configuration has
+    /// a built-in neighborhood, which requires the `custom` feature.
+    pub(crate) fn from_config(config: &Config) -> Result<Self, Error> {
+        match config.neighborhood {
+            #[cfg(feature = "custom")]
+            Some(neighborhood) => Ok(AnyRule::Custom(Custom::with_neighborhood(
+                neighborhood,
+                &Self::resolve(&config.rule_string),
+            )?)),
+            #[cfg(not(feature = "custom"))]
+            Some(neighborhood) => Err(Error::NbhdError(format!(
+                "{:?} requires the `custom` feature",
+                neighborhood
+            ))),
+            None => AnyRule::parse(&config.rule_string),
+        }
+    }
+
+    /// Parses a rule string, without looking up the aliases.
+    pub(crate) fn parse_rule_string(rule_string: &str) -> Result<Self, Error> {
+        #[cfg(feature = "rule-range")]
+        {
+            if rule_string.contains("..") {
+                return Ok(AnyRule::LifeRange(rule_string.parse()?));
+            }
+        }
+        if let Ok(rule) = rule_string.parse::<Life>() {
+            return Ok(AnyRule::Life(rule));
+        }
+        if let Ok((b, s, gen)) = parse_hrot(rule_string, 1, "NM", 8) {
+            return Ok(if gen > 2 {
+                AnyRule::LifeGen(LifeGen::new(b, s, gen))
+            } else {
+                AnyRule::Life(Life::new_inverted(b, s))
+            });
+        }
+        #[cfg(feature = "hex")]
+        {
+            if let Ok(rule) = rule_string.parse::<Hex>() {
+                return Ok(AnyRule::Hex(rule));
+            }
+        }
+        #[cfg(feature = "von-neumann")]
+        {
+            if let Ok(rule) = rule_string.parse::<VonNeumann>() {
+                return Ok(AnyRule::VonNeumann(rule));
+            }
+            if let Ok(rule) = rule_string.parse::<VonNeumann2>() {
+                return Ok(AnyRule::VonNeumann2(rule));
+            }
+        }
+        #[cfg(feature = "triangle")]
+        {
+            if let Ok(rule) = rule_string.parse::<Triangle>() {
+                return Ok(AnyRule::Triangle(rule));
+            }
+        }
+        #[cfg(feature = "margolus")]
+        {
+            if let Ok(rule) = rule_string.parse::<Margolus>() {
+                return Ok(AnyRule::Margolus(rule));
+            }
+        }
+        #[cfg(feature = "hex")]
+        {
+            if let Ok(rule) = rule_string.parse::<NtHex>() {
+                return Ok(AnyRule::NtHex(rule));
+            }
+        }
+        if let Ok(rule) = rule_string.parse::<NtLife>() {
+            return Ok(AnyRule::NtLife(rule));
+        }
+        if let Ok(rule) = rule_string.parse::<LifeGen>() {
+            return Ok(if rule.gen() > 2 {
+                AnyRule::LifeGen(rule)
+            } else {
+                AnyRule::Life(rule.non_gen())
+            });
+        }
+        #[cfg(feature = "hex")]
+        {
+            if let Ok(rule) = rule_string.parse::<HexGen>() {
+                return Ok(if rule.gen() > 2 {
+                    AnyRule::HexGen(rule)
+                } else {
+                    AnyRule::Hex(rule.non_gen())
+                });
+            }
+        }
+        #[cfg(feature = "von-neumann")]
+        {
+            if let Ok(rule) = rule_string.parse::<VonNeumannGen>() {
+                return Ok(if rule.gen() > 2 {
+                    AnyRule::VonNeumannGen(rule)
+                } else {
+                    AnyRule::VonNeumann(rule.non_gen())
+                });
+            }
+            if let Ok(rule) = rule_string.parse::<VonNeumann2Gen>() {
+                return Ok(if rule.gen() > 2 {
+                    AnyRule::VonNeumann2Gen(rule)
+                } else {
+                    AnyRule::VonNeumann2(rule.non_gen())
+                });
+            }
+        }
+        #[cfg(feature = "triangle")]
+        {
+            if let Ok(rule) = rule_string.parse::<TriangleGen>() {
+                return Ok(if rule.gen() > 2 {
+                    AnyRule::TriangleGen(rule)
+                } else {
+                    AnyRule::Triangle(rule.non_gen())
+                });
+            }
+        }
+        #[cfg(feature = "hex")]
+        {
+            if let Ok(rule) = rule_string.parse::<NtHexGen>() {
+                return Ok(if rule.gen() > 2 {
+                    AnyRule::NtHexGen(rule)
+                } else {
+                    AnyRule::NtHex(rule.non_gen())
+                });
+            }
+        }
+        #[cfg(feature = "bsfkl")]
+        {
+            if let Ok(rule) = rule_string.parse::<Bsfkl>() {
+                return Ok(AnyRule::Bsfkl(rule));
+            }
+        }
+        let rule = rule_string.parse::<NtLifeGen>()?;
+        Ok(if rule.gen() > 2 {
+            AnyRule::NtLifeGen(rule)
+        } else {
+            AnyRule::NtLife(rule.non_gen())
+        })
+    }
+
+    /// Checks the parts of the configuration that depend on this rule,
+    /// without creating the world.
+    ///
+    /// See `check_rule` for the errors.
+    pub(crate) fn check(&self, config: &Config) -> Result<(), Error> {
+        match self {
+            AnyRule::Life(rule) => check_rule(config, rule),
+            #[cfg(feature = "hex")]
+            AnyRule::Hex(rule) => check_rule(config, rule),
+            #[cfg(feature = "von-neumann")]
+            AnyRule::VonNeumann(rule) => check_rule(config, rule),
+            #[cfg(feature = "von-neumann")]
+            AnyRule::VonNeumann2(rule) => check_rule(config, rule),
+            #[cfg(feature = "triangle")]
+            AnyRule::Triangle(rule) => check_rule(config, rule),
+            #[cfg(feature = "margolus")]
+            AnyRule::Margolus(rule) => check_rule(config, rule),
+            #[cfg(feature = "hex")]
+            AnyRule::NtHex(rule) => check_rule(config, rule),
+            AnyRule::NtLife(rule) => check_rule(config, rule),
+            #[cfg(feature = "rule-range")]
+            AnyRule::LifeRange(rule) => check_rule(config, rule),
+            AnyRule::LifeGen(rule) => check_rule(config, rule),
+            #[cfg(feature = "hex")]
+            AnyRule::HexGen(rule) => check_rule(config, rule),
+            #[cfg(feature = "von-neumann")]
+            AnyRule::VonNeumannGen(rule) => check_rule(config, rule),
+            #[cfg(feature = "von-neumann")]
+            AnyRule::VonNeumann2Gen(rule) => check_rule(config, rule),
+            #[cfg(feature = "triangle")]
+            AnyRule::TriangleGen(rule) => check_rule(config, rule),
+            #[cfg(feature = "hex")]
+            AnyRule::NtHexGen(rule) => check_rule(config, rule),
+            AnyRule::NtLifeGen(rule) => check_rule(config, rule),
+            #[cfg(feature = "bsfkl")]
+            AnyRule::Bsfkl(rule) => check_rule(config, rule),
+            #[cfg(feature = "custom")]
+            AnyRule::Custom(rule) => check_rule(config, rule),
+        }
+    }
+
+    /// Creates a new world from the configuration with this rule.
+    ///
+    /// See `new_world` for the errors.
+    pub(crate) fn world(self, config: &Config) -> Result<Box<dyn Search>, Error> {
+        match self {
+            AnyRule::Life(rule) => new_world(config, rule),
+            #[cfg(feature = "hex")]
+            AnyRule::Hex(rule) => new_world(config, rule),
+            #[cfg(feature = "von-neumann")]
+            AnyRule::VonNeumann(rule) => new_world(config, rule),
+            #[cfg(feature = "von-neumann")]
+            AnyRule::VonNeumann2(rule) => new_world(config, rule),
+            #[cfg(feature = "triangle")]
+            AnyRule::Triangle(rule) => new_world(config, rule),
+            #[cfg(feature = "margolus")]
+            AnyRule::Margolus(rule) => new_world(config, rule),
+            #[cfg(feature = "hex")]
+            AnyRule::NtHex(rule) => new_world(config, rule),
+            AnyRule::NtLife(rule) => new_world(config, rule),
+            #[cfg(feature = "rule-range")]
+            AnyRule::LifeRange(rule) => new_world(config, rule),
+            AnyRule::LifeGen(rule) => new_world(config, rule),
+            #[cfg(feature = "hex")]
+            AnyRule::HexGen(rule) => new_world(config, rule),
+            #[cfg(feature = "von-neumann")]
+            AnyRule::VonNeumannGen(rule) => new_world(config, rule),
+            #[cfg(feature = "von-neumann")]
+            AnyRule::VonNeumann2Gen(rule) => new_world(config, rule),
+            #[cfg(feature = "triangle")]
+            AnyRule::TriangleGen(rule) => new_world(config, rule),
+            #[cfg(feature = "hex")]
+            AnyRule::NtHexGen(rule) => new_world(config, rule),
+            AnyRule::NtLifeGen(rule) => new_world(config, rule),
+            #[cfg(feature = "bsfkl")]
+            AnyRule::Bsfkl(rule) => new_world(config, rule),
+            #[cfg(feature = "custom")]
+            AnyRule::Custom(rule) => new_world(config, rule),
+        }
+    }
+}
@@ -0,0 +1,31 @@
+//! Totalistic rules with von Neumann neighborhood.
+
+use crate::{
+    cells::{CellRef, State, ALIVE, DEAD},
+    rules::Rule,
+    search::Reason,
+    world::World,
+};
+use bitflags::bitflags;
+use ca_rules::{ParseNeumann, ParseNeumannGen, ParseRuleError};
+use std::{str::FromStr, sync::Arc};
+
+/// Offsets of the neighbors in the von Neumann neighborhood.
+const VON_NEUMANN: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+
+impl_totalistic! {
+    /// Totalistic rules with von Neumann neighborhood.
+    ///
+    /// Each cell has 4 neighbors: the cells directly above, below,
+    /// to the left and to the right.
+    pub struct VonNeumann {
+        Parser: ParseNeumann,
+        Nbhd: &VON_NEUMANN,
+        Size: 4,
+    }
+
+    /// Totalistic Generations rules with von Neumann neighborhood.
+    pub struct VonNeumannGen {
+        Parser: ParseNeumannGen,
+    }
+}
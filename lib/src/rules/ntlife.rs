@@ -2,13 +2,13 @@
 
 use crate::{
     cells::{CellRef, State, ALIVE, DEAD},
-    rules::Rule,
+    rules::{is_isotropic_nt, Rule, MOORE},
     search::Reason,
     world::World,
 };
 use bitflags::bitflags;
 use ca_rules::{ParseNtLife, ParseNtLifeGen, ParseRuleError};
-use std::str::FromStr;
+use std::{str::FromStr, sync::Arc};
 
 bitflags! {
     /// Flags to imply the state of a cell and its neighbors.
@@ -63,6 +63,7 @@ impl_rule! {
     pub struct NtLife {
         Parser: ParseNtLife,
         impl_table: Vec<ImplFlags>,
+        Full: 0xff,
     }
 
     /// Non-totalistic Life-like Generations rules.
@@ -84,7 +85,7 @@ impl_rule! {
             Some(_) => 0x0100,
             _ => 0x0000,
         };
-        for (i, &neigh) in cell.nbhd.iter().rev().enumerate() {
+        for (i, &neigh) in cell.nbhd.iter().take(8).rev().enumerate() {
             let neigh = neigh.unwrap();
             let mut desc = neigh.desc.get();
             desc.0 ^= nbhd_change_num << i << 4;
@@ -93,7 +94,7 @@ impl_rule! {
     }
 
     fn consistify<'a>(world, cell, flags) {
-        for (i, &neigh) in cell.nbhd.iter().enumerate() {
+        for (i, &neigh) in cell.nbhd.iter().take(8).enumerate() {
             if flags.intersects(ImplFlags::from_bits(3 << (2 * i + 6)).unwrap()) {
                 if let Some(neigh) = neigh {
                     let state =
@@ -112,7 +113,7 @@ impl_rule! {
 
     fn consistify_gen<'a>(world, cell, flags) {
         if flags.intersects(ImplFlags::NBHD) {
-            for (i, &neigh) in cell.nbhd.iter().enumerate() {
+            for (i, &neigh) in cell.nbhd.iter().take(8).enumerate() {
                 if flags.intersects(ImplFlags::from_bits(1 << (2 * i + 6)).unwrap()) {
                     if let Some(neigh) = neigh {
                         if !world.set_cell(neigh, ALIVE, Reason::Deduce) {
@@ -129,34 +130,74 @@ impl NtLife {
     /// Constructs a new rule from the `b` and `s` data.
     pub fn new(b: Vec<u8>, s: Vec<u8>) -> Self {
         let b0 = b.contains(&0);
+        let isotropic = is_isotropic_nt(&MOORE, &b, &s);
 
         let impl_table = vec![ImplFlags::empty(); 1 << 20];
 
-        NtLife { b0, impl_table }
-            .init_trans(b, s)
-            .init_conflict()
-            .init_impl()
-            .init_impl_nbhd()
+        NtLife {
+            b0,
+            inverted: false,
+            isotropic,
+            impl_table: Arc::new(impl_table),
+        }
+        .init_trans(b, s)
+        .init_conflict()
+        .init_impl()
+        .init_impl_nbhd()
+    }
+
+    /// Constructs a new rule from a transition function.
+    ///
+    /// The function takes the state of a cell and the states of its
+    /// eight neighbors, in the order of `Rule::NBHD`, and returns the state
+    /// of the cell in the next generation. Any state other than `ALIVE`
+    /// is regarded as `DEAD`.
+    ///
+    /// The function is evaluated on all 512 configurations of a cell and
+    /// its neighbors. This allows rules without a standard notation,
+    /// e.g., rules that are not isotropic.
+    ///
+    /// As in `new_inverted`, if the rule contains both `B0` and `S8`,
+    /// the states are inverted.
+    pub fn from_fn<F: Fn(State, [State; 8]) -> State>(trans: F) -> Self {
+        let mut b = Vec::new();
+        let mut s = Vec::new();
+        for alives in 0..=0xff_u8 {
+            let mut nbhd = [DEAD; 8];
+            for (i, state) in nbhd.iter_mut().enumerate() {
+                if alives >> i & 1 != 0 {
+                    *state = ALIVE;
+                }
+            }
+            if trans(DEAD, nbhd) == ALIVE {
+                b.push(alives);
+            }
+            if trans(ALIVE, nbhd) == ALIVE {
+                s.push(alives);
+            }
+        }
+        Self::new_inverted(b, s)
     }
 
     /// Deduces the implication for the successor.
     fn init_trans(mut self, b: Vec<u8>, s: Vec<u8>) -> Self {
+        let impl_table = Arc::make_mut(&mut self.impl_table);
         // Fills in the positions of the neighborhood descriptors
         // that have no unknown neighbors.
         for alives in 0..=0xff {
             let desc = (0xff & !alives) << 12 | alives << 4;
             let alives = alives as u8;
-            self.impl_table[desc | 0b10] |= if b.contains(&alives) {
+            impl_table[desc | 0b10] |= if b.contains(&alives) {
                 ImplFlags::SUCC_ALIVE
             } else {
                 ImplFlags::SUCC_DEAD
             };
-            self.impl_table[desc | 0b01] |= if s.contains(&alives) {
+            impl_table[desc | 0b01] |= if s.contains(&alives) {
                 ImplFlags::SUCC_ALIVE
             } else {
                 ImplFlags::SUCC_DEAD
             };
-            self.impl_table[desc] |= if b.contains(&alives) && s.contains(&alives) {
+            impl_table[desc] |= if b.contains(&alives) && s.contains(&alives) {
                 ImplFlags::SUCC_ALIVE
             } else if !b.contains(&alives) && !s.contains(&alives) {
                 ImplFlags::SUCC_DEAD
@@ -175,10 +216,10 @@ impl NtLife {
                 let desc1 = (0xff & !alives & !unknowns) << 12 | (alives | n) << 4;
 
                 for state in 0..=2 {
-                    let trans0 = self.impl_table[desc0 | state];
+                    let trans0 = impl_table[desc0 | state];
 
-                    if trans0 == self.impl_table[desc1 | state] {
-                        self.impl_table[desc | state] |= trans0;
+                    if trans0 == impl_table[desc1 | state] {
+                        impl_table[desc | state] |= trans0;
                     }
                 }
             }
@@ -189,14 +230,15 @@ impl NtLife {
 
     /// Deduces the conflicts.
     fn init_conflict(mut self) -> Self {
+        let impl_table = Arc::make_mut(&mut self.impl_table);
         for nbhd_state in 0..0xffff {
             for state in 0..=2 {
                 let desc = nbhd_state << 4 | state;
 
-                if self.impl_table[desc].contains(ImplFlags::SUCC_ALIVE) {
-                    self.impl_table[desc | 0b10 << 2] = ImplFlags::CONFLICT;
-                } else if self.impl_table[desc].contains(ImplFlags::SUCC_DEAD) {
-                    self.impl_table[desc | 0b01 << 2] = ImplFlags::CONFLICT;
+                if impl_table[desc].contains(ImplFlags::SUCC_ALIVE) {
+                    impl_table[desc | 0b10 << 2] = ImplFlags::CONFLICT;
+                } else if impl_table[desc].contains(ImplFlags::SUCC_DEAD) {
+                    impl_table[desc | 0b01 << 2] = ImplFlags::CONFLICT;
                 }
             }
         }
@@ -205,6 +247,7 @@ impl NtLife {
 
     /// Deduces the implication for the cell itself.
     fn init_impl(mut self) -> Self {
+        let impl_table = Arc::make_mut(&mut self.impl_table);
         for unknowns in 0..=0xff {
             for alives in (0..=0xff).filter(|a| a & unknowns == 0) {
                 let desc = (0xff & !alives & !unknowns) << 12 | alives << 4;
@@ -216,16 +259,16 @@ impl NtLife {
                         ImplFlags::SUCC_DEAD | ImplFlags::CONFLICT
                     };
 
-                    let possibly_dead = !self.impl_table[desc | 0b10].intersects(flag);
-                    let possibly_alive = !self.impl_table[desc | 0b01].intersects(flag);
+                    let possibly_dead = !impl_table[desc | 0b10].intersects(flag);
+                    let possibly_alive = !impl_table[desc | 0b01].intersects(flag);
 
                     let index = desc | succ_state << 2;
                     if possibly_dead && !possibly_alive {
-                        self.impl_table[index] |= ImplFlags::SELF_DEAD;
+                        impl_table[index] |= ImplFlags::SELF_DEAD;
                     } else if !possibly_dead && possibly_alive {
-                        self.impl_table[index] |= ImplFlags::SELF_ALIVE;
+                        impl_table[index] |= ImplFlags::SELF_ALIVE;
                     } else if !possibly_dead && !possibly_alive {
-                        self.impl_table[index] = ImplFlags::CONFLICT;
+                        impl_table[index] = ImplFlags::CONFLICT;
                     }
                 }
             }
@@ -236,6 +279,7 @@ impl NtLife {
 
     ///  Deduces the implication for the neighbors.
     fn init_impl_nbhd(mut self) -> Self {
+        let impl_table = Arc::make_mut(&mut self.impl_table);
         for unknowns in 1usize..=0xff {
             // `n` runs through all the non-zero binary digits of `unknowns`.
             for n in (0..8).map(|i| 1 << i).filter(|n| unknowns & n != 0) {
@@ -254,17 +298,17 @@ impl NtLife {
                         let index = desc | succ_state << 2;
 
                         for state in 0..=2 {
-                            let possibly_dead = !self.impl_table[desc0 | state].intersects(flag);
-                            let possibly_alive = !self.impl_table[desc1 | state].intersects(flag);
+                            let possibly_dead = !impl_table[desc0 | state].intersects(flag);
+                            let possibly_alive = !impl_table[desc1 | state].intersects(flag);
 
                             if possibly_dead && !possibly_alive {
-                                self.impl_table[index | state] |=
+                                impl_table[index | state] |=
                                     ImplFlags::from_bits((n.pow(2) << 7) as u32).unwrap();
                             } else if !possibly_dead && possibly_alive {
-                                self.impl_table[index | state] |=
+                                impl_table[index | state] |=
                                     ImplFlags::from_bits((n.pow(2) << 6) as u32).unwrap();
                             } else if !possibly_dead && !possibly_alive {
-                                self.impl_table[index | state] = ImplFlags::CONFLICT;
+                                impl_table[index | state] = ImplFlags::CONFLICT;
                             }
                         }
                     }
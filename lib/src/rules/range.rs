@@ -0,0 +1,303 @@
+//! Totalistic Life-like rules with unknown transitions.
+
+use crate::{
+    cells::{CellRef, State, ALIVE, DEAD},
+    error::Error,
+    rules::Rule,
+    search::Reason,
+    world::World,
+};
+use ca_rules::{ParseLife, ParseRuleError};
+use std::str::FromStr;
+
+/// The neighborhood descriptor.
+///
+/// It is a 12-bit integer of the form `0b_abcd_efgh_ij_kl`,
+/// where:
+///
+/// * `0b_abcd` is the number of dead cells in the neighborhood.
+/// * `0b_efgh` is the number of living cells in the neighborhood.
+/// * `0b_ij` is the state of the successor.
+/// * `0b_kl` is the state of the cell itself.
+///
+/// For `0b_ij` and `0b_kl`:
+/// * `0b_10` means dead,
+/// * `0b_01` means alive,
+/// * `0b_00` means unknown.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NbhdDesc(u16);
+
+impl NbhdDesc {
+    /// Encodes a state as 2 bits.
+    fn encode(state: Option<State>) -> u16 {
+        match state {
+            Some(ALIVE) => 0b01,
+            Some(_) => 0b10,
+            None => 0,
+        }
+    }
+
+    /// Increment of the descriptor for a neighbor in this state.
+    fn count(state: State) -> u16 {
+        match state {
+            ALIVE => 0x010,
+            _ => 0x100,
+        }
+    }
+}
+
+/// A range of totalistic Life-like rules, e.g., `B3/S23..B38/S238`.
+///
+/// The transitions of the first rule are known to be in the rule,
+/// and the transitions that are not in the second rule are known to be not
+/// in the rule. The other transitions are unknown, and are deduced during
+/// the search, like the states of the cells.
+///
+/// Transitions are numbered from `0` to `17`: `Bn` is `n`, and `Sn` is
+/// `9 + n`. Rules with `B0` are not supported.
+#[derive(Clone)]
+pub struct LifeRange {
+    /// Transitions that are known to be in the rule, as a bitmask.
+    min: u32,
+    /// Transitions that are possibly in the rule, as a bitmask.
+    max: u32,
+    /// Unknown transitions that are deduced to be in the rule.
+    on: u32,
+    /// Unknown transitions that are deduced to be not in the rule.
+    off: u32,
+    /// Deduced transitions, with the length of the `set_stack`
+    /// at the time of the deduction.
+    trail: Vec<(usize, usize)>,
+}
+
+impl ParseLife for LifeRange {
+    fn from_bs(b: Vec<u8>, s: Vec<u8>) -> Self {
+        let bits = b.iter().fold(0, |acc, &i| acc | 1 << i)
+            | s.iter().fold(0, |acc, &i| acc | 1 << (9 + i));
+        LifeRange {
+            min: bits,
+            max: bits,
+            on: 0,
+            off: 0,
+            trail: Vec::new(),
+        }
+    }
+}
+
+impl LifeRange {
+    /// Parses a rule range of the form `MIN..MAX`.
+    ///
+    /// Returns an error if `MIN` is not contained in `MAX`,
+    /// or if `MAX` contains `B0`.
+    pub fn parse_range(input: &str) -> Result<Self, Error> {
+        let mut split = input.splitn(2, "..");
+        let min_str = split.next().unwrap();
+        let max_str = split.next().ok_or(ParseRuleError::Missing('.'))?;
+        let min = LifeRange::parse_rule(min_str)?.min;
+        let max = LifeRange::parse_rule(max_str)?.max;
+        if min & !max != 0 {
+            return Err(Error::RuleRangeError(
+                min_str.to_string(),
+                max_str.to_string(),
+            ));
+        }
+        if max & 1 != 0 {
+            return Err(Error::RangeB0Error(input.to_string()));
+        }
+        Ok(LifeRange {
+            min,
+            max,
+            on: 0,
+            off: 0,
+            trail: Vec::new(),
+        })
+    }
+
+    /// Whether the transition is in the rule, or `None` if unknown.
+    fn get(&self, index: usize) -> Option<bool> {
+        if (self.min | self.on) >> index & 1 == 1 {
+            Some(true)
+        } else if (!self.max | self.off) >> index & 1 == 1 {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    /// Sets an unknown transition, and records it in the `trail`.
+    fn set(&mut self, index: usize, value: bool, stack_len: usize) {
+        if value {
+            self.on |= 1 << index;
+        } else {
+            self.off |= 1 << index;
+        }
+        self.trail.push((stack_len, index));
+    }
+}
+
+impl FromStr for LifeRange {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        LifeRange::parse_range(input)
+    }
+}
+
+/// Writes a rule string from a bitmask of transitions.
+fn rule_string(bits: u32) -> String {
+    let digits = |offset: usize| -> String {
+        (0..=8)
+            .filter(|i| bits >> (offset + i) & 1 == 1)
+            .map(|i| (b'0' + i as u8) as char)
+            .collect()
+    };
+    format!("B{}/S{}", digits(0), digits(9))
+}
+
+impl Rule for LifeRange {
+    type Desc = NbhdDesc;
+
+    const IS_GEN: bool = false;
+
+    fn has_b0(&self) -> bool {
+        false
+    }
+
+    fn gen(&self) -> usize {
+        2
+    }
+
+    fn is_isotropic(&self) -> bool {
+        true
+    }
+
+    fn backtrack(&mut self, stack_len: usize) {
+        while let Some(&(len, index)) = self.trail.last() {
+            if len <= stack_len {
+                break;
+            }
+            self.on &= !(1 << index);
+            self.off &= !(1 << index);
+            self.trail.pop();
+        }
+    }
+
+    fn deduced_rules(&self) -> Option<(String, String)> {
+        Some((
+            rule_string(self.min | self.on),
+            rule_string(self.max & !self.off),
+        ))
+    }
+
+    fn new_desc(state: State, succ_state: State) -> Self::Desc {
+        let nbhd_state = NbhdDesc::count(state) * 8;
+        let succ_state = NbhdDesc::encode(Some(succ_state));
+        let state = NbhdDesc::encode(Some(state));
+        NbhdDesc(nbhd_state | succ_state << 2 | state)
+    }
+
+    fn update_desc(cell: CellRef<Self>, state: Option<State>, new: bool) {
+        if let Some(state) = state {
+            for &neigh in cell.nbhd.iter().take(8) {
+                let neigh = neigh.unwrap();
+                let mut desc = neigh.desc.get();
+                if new {
+                    desc.0 += NbhdDesc::count(state);
+                } else {
+                    desc.0 -= NbhdDesc::count(state);
+                }
+                neigh.desc.set(desc);
+            }
+        }
+        let code = if new { NbhdDesc::encode(state) } else { 0 };
+        if let Some(pred) = cell.pred {
+            let mut desc = pred.desc.get();
+            desc.0 = (desc.0 & !0b1100) | code << 2;
+            pred.desc.set(desc);
+        }
+        let mut desc = cell.desc.get();
+        desc.0 = (desc.0 & !0b11) | code;
+        cell.desc.set(desc);
+    }
+
+    /// Tries all possible states of the cell and numbers of living
+    /// neighbors, and keeps the ones that agree with the known transitions
+    /// and the state of the successor.
+    ///
+    /// If only one of them is left and the successor is known,
+    /// the corresponding transition is deduced.
+    fn consistify<'a>(world: &mut World<'a, Self>, cell: CellRef<'a, Self>) -> bool {
+        let desc = cell.desc.get().0;
+        let alives = (desc >> 4 & 0xf) as usize;
+        let deads = (desc >> 8 & 0xf) as usize;
+        let unknowns = 8 - alives - deads;
+        let state = cell.state.get();
+        // Cells outside the world are dead, since the rule has no `B0`.
+        let succ_state = cell.succ.map_or(Some(DEAD), |succ| succ.state.get());
+
+        let mut possible = Vec::new();
+        for &s in [DEAD, ALIVE].iter() {
+            if state.is_some() && state != Some(s) {
+                continue;
+            }
+            for n in alives..=alives + unknowns {
+                let index = if s == ALIVE { 9 + n } else { n };
+                let trans = world.rule.get(index);
+                let agrees = match (trans, succ_state) {
+                    (Some(trans), Some(succ)) => trans == (succ == ALIVE),
+                    _ => true,
+                };
+                if agrees {
+                    possible.push((s, n, index, trans));
+                }
+            }
+        }
+
+        let first = match possible.first() {
+            Some(&first) => first,
+            None => return false,
+        };
+        if possible.len() == 1 && first.3.is_none() {
+            if let Some(succ) = succ_state {
+                let stack_len = world.set_stack.len();
+                world.rule.set(first.2, succ == ALIVE, stack_len);
+            }
+        }
+        if let Some(succ) = cell.succ {
+            if succ.state.get().is_none() {
+                if possible.iter().all(|p| p.3 == Some(true)) {
+                    return world.set_cell(succ, ALIVE, Reason::Deduce);
+                } else if possible.iter().all(|p| p.3 == Some(false)) {
+                    return world.set_cell(succ, DEAD, Reason::Deduce);
+                }
+            }
+        }
+        if state.is_none()
+            && possible.iter().all(|p| p.0 == first.0)
+            && !world.set_cell(cell, first.0, Reason::Deduce)
+        {
+            return false;
+        }
+        if unknowns > 0 {
+            let nbhd_state = if possible.iter().all(|p| p.1 == alives) {
+                Some(DEAD)
+            } else if possible.iter().all(|p| p.1 == alives + unknowns) {
+                Some(ALIVE)
+            } else {
+                None
+            };
+            if let Some(nbhd_state) = nbhd_state {
+                for &neigh in cell.nbhd.iter() {
+                    if let Some(neigh) = neigh {
+                        if neigh.state.get().is_none()
+                            && !world.set_cell(neigh, nbhd_state, Reason::Deduce)
+                        {
+                            return false;
+                        }
+                    }
+                }
+            }
+        }
+        true
+    }
+}
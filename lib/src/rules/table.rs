@@ -0,0 +1,413 @@
+//! Golly rule tables.
+//!
+//! Reads the `@TABLE` section of a [Golly rule file](http://golly.sourceforge.net/Help/formats.html#rule),
+//! and converts it to a [MAP rule string](https://conwaylife.com/wiki/Non-isotropic_rule).
+//!
+//! Only rule tables that can be expressed as non-totalistic Life-like rules,
+//! or their corresponding Generations rules, are supported.
+
+use crate::error::Error;
+use std::collections::HashMap;
+
+/// The maximal number of states of a supported rule table.
+pub const MAX_TABLE_STATES: usize = 4;
+
+/// Positions of the neighbors in a MAP rule, in the order of the
+/// neighbors in a rule table.
+///
+/// The position of the cell itself is `4`.
+static MOORE_MAP: [usize; 8] = [7, 6, 3, 0, 1, 2, 5, 8];
+static VON_NEUMANN_MAP: [usize; 4] = [7, 3, 1, 5];
+static HEX_MAP: [usize; 6] = [7, 3, 0, 1, 5, 8];
+
+/// A term in a transition.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Term {
+    /// A state.
+    State(usize),
+    /// A variable, represented by its index.
+    Var(usize),
+}
+
+/// A transition in the rule table.
+#[derive(Clone, Debug)]
+struct Transition {
+    center: Term,
+    nbhd: Vec<Term>,
+    result: Term,
+}
+
+/// Symmetries of the rule table.
+enum Symmetries {
+    /// A list of permutations of the neighbors.
+    Perms(Vec<Vec<usize>>),
+    /// All permutations of the neighbors.
+    Permute,
+}
+
+/// A parsed rule table.
+struct Table {
+    n_states: usize,
+    /// Positions of the neighbors in a MAP rule.
+    map: &'static [usize],
+    vars: Vec<Vec<usize>>,
+    transitions: Vec<Transition>,
+    symmetries: Symmetries,
+}
+
+fn table_error<S: ToString>(msg: S) -> Error {
+    Error::ParseTableError(msg.to_string())
+}
+
+impl Table {
+    /// Parses the `@TABLE` section of a rule file.
+    fn parse(input: &str) -> Result<Self, Error> {
+        let mut lines = input.lines().map(|l| l.split('#').next().unwrap().trim());
+        if input.contains("@TABLE") {
+            lines.by_ref().find(|&l| l.starts_with("@TABLE"));
+        }
+
+        let mut n_states = None;
+        let mut map = None;
+        let mut symmetries = None;
+        let mut var_names: HashMap<String, usize> = HashMap::new();
+        let mut vars: Vec<Vec<usize>> = Vec::new();
+        let mut transitions = Vec::new();
+
+        for line in lines {
+            if line.starts_with('@') {
+                break;
+            } else if line.is_empty() {
+                continue;
+            } else if let Some(value) = line.strip_prefix("n_states:") {
+                let n = value.trim().parse().map_err(table_error)?;
+                if !(2..=MAX_TABLE_STATES).contains(&n) {
+                    return Err(table_error(format!(
+                        "only rule tables with 2 to {} states are supported",
+                        MAX_TABLE_STATES
+                    )));
+                }
+                n_states = Some(n);
+            } else if let Some(value) = line.strip_prefix("neighborhood:") {
+                map = Some(match value.trim() {
+                    "Moore" => &MOORE_MAP[..],
+                    "vonNeumann" => &VON_NEUMANN_MAP[..],
+                    "hexagonal" => &HEX_MAP[..],
+                    n => return Err(table_error(format!("unsupported neighborhood {}", n))),
+                });
+            } else if let Some(value) = line.strip_prefix("symmetries:") {
+                let len = map
+                    .ok_or_else(|| table_error("neighborhood is not defined"))?
+                    .len();
+                symmetries = Some(Self::parse_symmetries(value.trim(), len)?);
+            } else if let Some(value) = line.strip_prefix("var ") {
+                let n = n_states.ok_or_else(|| table_error("n_states is not defined"))?;
+                let mut iter = value.splitn(2, '=');
+                let name = iter.next().unwrap().trim();
+                let set = iter
+                    .next()
+                    .ok_or_else(|| table_error(format!("invalid variable {}", name)))?
+                    .trim()
+                    .trim_start_matches('{')
+                    .trim_end_matches('}');
+                let mut values = Vec::new();
+                for item in set.split(',').map(str::trim) {
+                    if let Some(&i) = var_names.get(item) {
+                        values.extend_from_slice(&vars[i]);
+                    } else {
+                        values.push(Self::parse_state(item, n)?);
+                    }
+                }
+                var_names.insert(name.to_string(), vars.len());
+                vars.push(values);
+            } else {
+                let n = n_states.ok_or_else(|| table_error("n_states is not defined"))?;
+                let len = map
+                    .ok_or_else(|| table_error("neighborhood is not defined"))?
+                    .len();
+                let items: Vec<&str> = if line.contains(',') {
+                    line.split(',').map(str::trim).collect()
+                } else {
+                    line.char_indices()
+                        .map(|(i, c)| &line[i..i + c.len_utf8()])
+                        .collect()
+                };
+                if items.len() != len + 2 {
+                    return Err(table_error(format!("invalid transition {}", line)));
+                }
+                let mut terms = Vec::with_capacity(items.len());
+                for item in items {
+                    terms.push(if let Some(&i) = var_names.get(item) {
+                        Term::Var(i)
+                    } else {
+                        Term::State(Self::parse_state(item, n)?)
+                    });
+                }
+                transitions.push(Transition {
+                    center: terms[0],
+                    nbhd: terms[1..=len].to_vec(),
+                    result: terms[len + 1],
+                });
+            }
+        }
+
+        Ok(Table {
+            n_states: n_states.ok_or_else(|| table_error("n_states is not defined"))?,
+            map: map.ok_or_else(|| table_error("neighborhood is not defined"))?,
+            vars,
+            transitions,
+            symmetries: symmetries.unwrap_or_else(|| Symmetries::Perms(vec![vec![]])),
+        })
+    }
+
+    /// Parses a state.
+    fn parse_state(item: &str, n_states: usize) -> Result<usize, Error> {
+        match item.parse() {
+            Ok(state) if state < n_states => Ok(state),
+            _ => Err(table_error(format!("invalid state {}", item))),
+        }
+    }
+
+    /// Parses the symmetries of a neighborhood with `len` neighbors.
+    ///
+    /// An empty permutation means the identity.
+    fn parse_symmetries(s: &str, len: usize) -> Result<Symmetries, Error> {
+        let reflect =
+            |perm: &Vec<usize>| -> Vec<usize> { (0..len).map(|i| perm[(len - i) % len]).collect() };
+        let rotations = |m: usize| -> Vec<Vec<usize>> {
+            (0..m)
+                .map(|j| (0..len).map(|i| (i + j * len / m) % len).collect())
+                .collect()
+        };
+        let perms = match s {
+            "none" => vec![vec![]],
+            "permute" => return Ok(Symmetries::Permute),
+            "reflect_horizontal" => {
+                let id = (0..len).collect();
+                let flip = reflect(&id);
+                vec![id, flip]
+            }
+            _ => {
+                let (m, with_reflect) = if let Some(m) = s.strip_suffix("reflect") {
+                    (m, true)
+                } else {
+                    (s, false)
+                };
+                let m = m
+                    .strip_prefix("rotate")
+                    .and_then(|m| m.parse::<usize>().ok())
+                    .filter(|&m| m > 0 && len.is_multiple_of(m))
+                    .ok_or_else(|| table_error(format!("unsupported symmetries {}", s)))?;
+                let mut perms = rotations(m);
+                if with_reflect {
+                    let flips: Vec<_> = perms.iter().map(reflect).collect();
+                    perms.extend(flips);
+                }
+                perms
+            }
+        };
+        Ok(Symmetries::Perms(perms))
+    }
+
+    /// Matches a term with a state, and binds the variable if necessary.
+    fn match_term(&self, term: Term, state: usize, bindings: &mut [Option<usize>]) -> bool {
+        match term {
+            Term::State(s) => s == state,
+            Term::Var(i) => match bindings[i] {
+                Some(s) => s == state,
+                None => {
+                    if self.vars[i].contains(&state) {
+                        bindings[i] = Some(state);
+                        true
+                    } else {
+                        false
+                    }
+                }
+            },
+        }
+    }
+
+    /// Matches the neighbors in any order.
+    fn match_permute(
+        &self,
+        terms: &[Term],
+        nbhd: &[usize],
+        used: &mut Vec<bool>,
+        bindings: &mut Vec<Option<usize>>,
+    ) -> bool {
+        if let Some((&term, rest)) = terms.split_first() {
+            for i in 0..nbhd.len() {
+                if !used[i] {
+                    let old_bindings = bindings.clone();
+                    if self.match_term(term, nbhd[i], bindings) {
+                        used[i] = true;
+                        if self.match_permute(rest, nbhd, used, bindings) {
+                            return true;
+                        }
+                        used[i] = false;
+                    }
+                    *bindings = old_bindings;
+                }
+            }
+            false
+        } else {
+            true
+        }
+    }
+
+    /// Matches a transition with a cell and its neighbors.
+    ///
+    /// Returns the bindings of the variables if it matches.
+    fn match_transition(
+        &self,
+        transition: &Transition,
+        center: usize,
+        nbhd: &[usize],
+    ) -> Option<Vec<Option<usize>>> {
+        let mut bindings = vec![None; self.vars.len()];
+        if !self.match_term(transition.center, center, &mut bindings) {
+            return None;
+        }
+        match &self.symmetries {
+            Symmetries::Permute => {
+                let mut used = vec![false; nbhd.len()];
+                if self.match_permute(&transition.nbhd, nbhd, &mut used, &mut bindings) {
+                    Some(bindings)
+                } else {
+                    None
+                }
+            }
+            Symmetries::Perms(perms) => perms.iter().find_map(|perm| {
+                let mut bindings = bindings.clone();
+                let matched = transition.nbhd.iter().enumerate().all(|(i, &term)| {
+                    let j = if perm.is_empty() { i } else { perm[i] };
+                    self.match_term(term, nbhd[j], &mut bindings)
+                });
+                if matched {
+                    Some(bindings)
+                } else {
+                    None
+                }
+            }),
+        }
+    }
+
+    /// Computes the next state of a cell.
+    ///
+    /// `nbhd` is the states of the neighbors, in the order of the rule table.
+    ///
+    /// The first matching transition is used. If no transition matches,
+    /// the state is unchanged.
+    fn next_state(&self, center: usize, nbhd: &[usize]) -> usize {
+        for transition in self.transitions.iter() {
+            if let Some(bindings) = self.match_transition(transition, center, nbhd) {
+                return match transition.result {
+                    Term::State(s) => s,
+                    Term::Var(i) => bindings[i].unwrap_or(center),
+                };
+            }
+        }
+        center
+    }
+
+    /// Converts the rule table to a MAP rule string.
+    ///
+    /// Returns an error if the rule table cannot be expressed as a non-totalistic
+    /// Life-like rule or the corresponding Generations rule.
+    fn to_rule_string(&self) -> Result<String, Error> {
+        let n = self.n_states;
+        let len = self.map.len();
+        let not_supported = || table_error("the rule table is not a Life-like or Generations rule");
+
+        // Positions outside the neighborhood can be anything.
+        let extras: Vec<usize> = (0..512)
+            .filter(|&extra| {
+                extra & 1 << 4 == 0 && self.map.iter().all(|&pos| extra & 1 << pos == 0)
+            })
+            .collect();
+
+        // Whether a cell with state `0` or `1` will become alive,
+        // when the living neighbors are given by the MAP index.
+        let mut trans = [[None; 512]; 2];
+        let mut nbhd = vec![0; len];
+        for _ in 0..n.pow(len as u32) {
+            let index = nbhd
+                .iter()
+                .zip(self.map.iter())
+                .filter(|&(&s, _)| s == 1)
+                .fold(0, |acc, (_, &pos)| acc | 1 << pos);
+            for center in 0..n {
+                let next = self.next_state(center, &nbhd);
+                let expected = match center {
+                    0 => next <= 1,
+                    1 => next == 1 || next == 2 % n,
+                    c => next == (c + 1) % n,
+                };
+                if !expected {
+                    return Err(not_supported());
+                }
+                if let Some(trans) = trans.get_mut(center) {
+                    let alive = next == 1;
+                    for &extra in extras.iter() {
+                        match trans[index | extra] {
+                            Some(a) if a != alive => return Err(not_supported()),
+                            _ => trans[index | extra] = Some(alive),
+                        }
+                    }
+                }
+            }
+
+            // Goes to the next neighborhood.
+            for s in nbhd.iter_mut() {
+                *s += 1;
+                if *s < n {
+                    break;
+                }
+                *s = 0;
+            }
+        }
+
+        let mut bytes = [0u8; 64];
+        for i in 0..512 {
+            let center = i >> 4 & 1;
+            if trans[center][i & !(1 << 4)] == Some(true) {
+                bytes[i / 8] |= 0x80 >> (i % 8);
+            }
+        }
+        let map = base64(&bytes);
+        if n > 2 {
+            Ok(format!("MAP{}/{}", map, n))
+        } else {
+            Ok(format!("MAP{}", map))
+        }
+    }
+}
+
+/// Encodes bytes in base64, without padding.
+fn base64(bytes: &[u8]) -> String {
+    const CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut s = String::new();
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |acc, (i, &b)| acc | u32::from(b) << (16 - 8 * i));
+        for i in 0..=chunk.len() {
+            s.push(CHARS[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+        }
+    }
+    s
+}
+
+/// Reads the `@TABLE` section of a Golly rule file,
+/// and converts it to a MAP rule string.
+///
+/// If the input contains no `@TABLE` line, the whole input is read as a table.
+///
+/// Supports `Moore`, `vonNeumann` and `hexagonal` neighborhoods,
+/// and all the symmetries of these neighborhoods in Golly.
+/// Rule tables with more than 2 states must behave like Generations rules,
+/// and can have at most [`MAX_TABLE_STATES`](constant.MAX_TABLE_STATES.html) states.
+pub fn table_to_rule_string(input: &str) -> Result<String, Error> {
+    Table::parse(input)?.to_rule_string()
+}
@@ -0,0 +1,324 @@
+//! An alternative search backend using a SAT solver.
+
+use crate::{
+    cells::{CellRef, Coord, State, ALIVE, DEAD},
+    config::{Config, KnownCell},
+    error::Error,
+    rules::{AnyRule, Life, Rule},
+    traits::Search,
+    world::World,
+};
+use std::{collections::HashMap, ops::Not};
+use varisat::{ExtendFormula, Lit, Solver};
+
+/// Whether an unknown cell is alive, or a constant for a known cell.
+#[derive(Clone, Copy, Debug)]
+enum Value {
+    Const(bool),
+    Lit(Lit),
+}
+
+impl Not for Value {
+    type Output = Value;
+
+    fn not(self) -> Value {
+        match self {
+            Value::Const(b) => Value::Const(!b),
+            Value::Lit(lit) => Value::Lit(!lit),
+        }
+    }
+}
+
+/// A formula in conjunctive normal form, fed to the solver clause by clause.
+struct Cnf {
+    solver: Solver<'static>,
+
+    /// The variables of the unknown cells, which are true
+    /// if the cells are alive.
+    vars: HashMap<Coord, Lit>,
+
+    /// Number of variables, including the auxiliary ones.
+    var_count: isize,
+}
+
+impl Cnf {
+    fn new() -> Self {
+        Cnf {
+            solver: Solver::new(),
+            vars: HashMap::new(),
+            var_count: 0,
+        }
+    }
+
+    /// A new auxiliary variable.
+    fn new_lit(&mut self) -> Lit {
+        self.var_count += 1;
+        Lit::from_dimacs(self.var_count)
+    }
+
+    /// Whether the cell is alive.
+    fn alive<R: Rule>(&mut self, cell: CellRef<R>) -> Value {
+        if let Some(state) = cell.state.get() {
+            return Value::Const(state == ALIVE);
+        }
+        if let Some(&lit) = self.vars.get(&cell.coord) {
+            return Value::Lit(lit);
+        }
+        let lit = self.new_lit();
+        self.vars.insert(cell.coord, lit);
+        Value::Lit(lit)
+    }
+
+    /// Whether the cell is not in its background state.
+    fn not_background<R: Rule>(&mut self, cell: CellRef<R>) -> Value {
+        let alive = self.alive(cell);
+        if cell.background == ALIVE {
+            !alive
+        } else {
+            alive
+        }
+    }
+
+    /// Adds a clause, i.e., at least one of the values must be true.
+    fn add_clause(&mut self, values: &[Value]) {
+        let mut lits = Vec::with_capacity(values.len());
+        for &value in values {
+            match value {
+                Value::Const(true) => return,
+                Value::Const(false) => (),
+                Value::Lit(lit) => lits.push(lit),
+            }
+        }
+        self.solver.add_clause(&lits);
+    }
+
+    /// The state of a cell in a model.
+    ///
+    /// Variables that do not appear in any clause are false.
+    fn state<R: Rule>(&self, cell: CellRef<R>, model: &HashMap<Lit, bool>) -> State {
+        let alive = self
+            .vars
+            .get(&cell.coord)
+            .and_then(|lit| model.get(lit))
+            .copied()
+            .unwrap_or(false);
+        match cell.state.get() {
+            Some(state) => state,
+            None if alive => ALIVE,
+            None => DEAD,
+        }
+    }
+}
+
+/// Searches for a pattern with a SAT solver, instead of the built-in search.
+///
+/// The whole problem, including the transitions of the rule, the symmetry,
+/// the period, the translation and the known cells, is encoded in
+/// conjunctive normal form and solved by [varisat](https://docs.rs/varisat).
+/// This may be faster for hard problems where clause learning helps.
+///
+/// Only totalistic Life-like rules are supported. Population bounds,
+/// e.g., `max_cell_count`, and agars are not supported. Options that only affect
+/// the built-in search, e.g., `search_order` and `new_state`, are ignored.
+///
+/// Returns a world of the result, where the states of all cells are known,
+/// or `None` if there is no such pattern. Calling `search` on the world
+/// would return `Status::None`.
+pub fn solve_sat(config: &Config) -> Result<Option<Box<dyn Search>>, Error> {
+    // Checks the configuration in the same way as a normal search.
+    config.world()?;
+    if config.max_cell_count.is_some()
+        || config.max_gen0_cell_count.is_some()
+        || config.max_gen_cell_count.is_some()
+        || config.max_row_cell_count.is_some()
+        || config.max_column_cell_count.is_some()
+        || config.max_rotor_cell_count.is_some()
+    {
+        return Err(Error::SatUnsupportedError(String::from(
+            "population bounds",
+        )));
+    }
+    if config.agar.is_some() {
+        return Err(Error::SatUnsupportedError(String::from("agars")));
+    }
+    let rule = match AnyRule::from_config(config)? {
+        AnyRule::Life(rule) => rule,
+        _ => {
+            return Err(Error::SatUnsupportedError(format!(
+                "rule {}",
+                config.rule_string
+            )))
+        }
+    };
+    let inverted = rule.is_inverted();
+    let applied = config.apply_bounded_grid()?;
+    let world = World::new(&applied, rule);
+    let mut cnf = Cnf::new();
+
+    encode_transitions(&world, &mut cnf);
+    encode_symmetry(&world, &mut cnf);
+    encode_nontrivial(&world, &mut cnf);
+
+    let found = cnf
+        .solver
+        .solve()
+        .map_err(|e| Error::SatSolverError(e.to_string()))?;
+    if !found {
+        return Ok(None);
+    }
+    let model = cnf
+        .solver
+        .model()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|lit| (lit.var().positive(), lit.is_positive()))
+        .collect::<HashMap<_, _>>();
+
+    let mut known_cells = Vec::new();
+    for x in 0..applied.width {
+        for y in 0..applied.height {
+            for t in 0..applied.period {
+                let coord = (x, y, t);
+                let state = cnf.state(world.find_cell(coord).unwrap(), &model);
+                let state = if inverted { !state } else { state };
+                known_cells.push(KnownCell { coord, state });
+            }
+        }
+    }
+    config
+        .clone()
+        .set_known_cells(known_cells)
+        .world()
+        .map(Some)
+}
+
+/// Encodes the transitions of the rule.
+///
+/// For each cell, and each combination of the states of the unknown cells
+/// among the cell and its neighbors, the successor must have the state
+/// given by the rule.
+fn encode_transitions(world: &World<Life>, cnf: &mut Cnf) {
+    for cell in world.cells() {
+        let succ = match cell.succ {
+            Some(succ) if !cell.outside => succ,
+            _ => continue,
+        };
+        // Neighbors out of the world are in the background state.
+        let inputs = Some(cell)
+            .into_iter()
+            .chain(cell.nbhd.iter().take(8).copied().flatten())
+            .map(|c| cnf.alive(c))
+            .chain(
+                cell.nbhd
+                    .iter()
+                    .take(8)
+                    .filter(|neigh| neigh.is_none())
+                    .map(|_| Value::Const(cell.background == ALIVE)),
+            )
+            .collect::<Vec<_>>();
+        let unknowns = inputs
+            .iter()
+            .filter(|value| matches!(value, Value::Lit(_)))
+            .count();
+        let succ_alive = cnf.alive(succ);
+        for mask in 0..1_usize << unknowns {
+            let mut clause = Vec::with_capacity(unknowns + 1);
+            let mut states = Vec::with_capacity(inputs.len());
+            let mut i = 0;
+            for &value in inputs.iter() {
+                match value {
+                    Value::Const(alive) => states.push(alive),
+                    Value::Lit(lit) => {
+                        let alive = mask >> i & 1 == 1;
+                        i += 1;
+                        states.push(alive);
+                        clause.push(if alive {
+                            Value::Lit(!lit)
+                        } else {
+                            Value::Lit(lit)
+                        });
+                    }
+                }
+            }
+            let state = if states[0] { ALIVE } else { DEAD };
+            let alives = states[1..].iter().filter(|&&alive| alive).count();
+            clause.push(if world.rule().successor(state, alives) == ALIVE {
+                succ_alive
+            } else {
+                !succ_alive
+            });
+            cnf.add_clause(&clause);
+        }
+    }
+}
+
+/// Encodes the symmetry.
+///
+/// Symmetric cells must have the same state relative to their backgrounds.
+fn encode_symmetry(world: &World<Life>, cnf: &mut Cnf) {
+    for cell in world.cells() {
+        for &sym in cell.sym.iter() {
+            let a = cnf.alive(cell);
+            let b = cnf.alive(sym);
+            let b = if sym.background == cell.background {
+                b
+            } else {
+                !b
+            };
+            cnf.add_clause(&[!a, b]);
+            cnf.add_clause(&[a, !b]);
+        }
+    }
+}
+
+/// Encodes that the pattern is nonempty, and that its minimal period
+/// equals the given period, as in `World::nontrivial`.
+///
+/// Also encodes `non_empty_front`.
+fn encode_nontrivial(world: &World<Life>, cnf: &mut Cnf) {
+    let config = &world.config;
+    let mut coords = Vec::new();
+    for x in 0..config.width {
+        for y in 0..config.height {
+            coords.push((x, y));
+        }
+    }
+
+    let nonempty = coords
+        .iter()
+        .map(|&(x, y)| world.find_cell((x, y, 0)).unwrap())
+        .filter(|cell| !cell.dont_care)
+        .map(|cell| cnf.not_background(cell))
+        .collect::<Vec<_>>();
+    cnf.add_clause(&nonempty);
+
+    for t in 1..config.period {
+        if config.period % t != 0 {
+            continue;
+        }
+        let mut differs = Vec::new();
+        for &(x, y) in coords.iter() {
+            let c0 = world.find_cell((x, y, 0)).unwrap();
+            let c1 = world.find_cell((x, y, t)).unwrap();
+            if c0.dont_care || c1.dont_care {
+                continue;
+            }
+            let a = cnf.alive(c0);
+            let b = cnf.alive(c1);
+            let d = Value::Lit(cnf.new_lit());
+            cnf.add_clause(&[!d, a, b]);
+            cnf.add_clause(&[!d, !a, !b]);
+            differs.push(d);
+        }
+        cnf.add_clause(&differs);
+    }
+
+    if config.non_empty_front {
+        let front = world
+            .cells()
+            .filter(|cell| cell.is_front)
+            .map(|cell| cnf.not_background(cell))
+            .collect::<Vec<_>>();
+        cnf.add_clause(&front);
+    }
+}
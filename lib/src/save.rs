@@ -2,18 +2,78 @@
 //! Saves the world.
 
 use crate::{
-    cells::{Coord, State},
-    config::Config,
+    cells::{CellRef, Coord, State},
+    config::{Config, SearchOrder},
+    endgame::Endgame,
     error::Error,
-    rules::{Life, LifeGen, NtLife, NtLifeGen, Rule},
-    search::{Reason, SetCell},
+    header::FORMAT_VERSION,
+    rows::RowLevel,
+    rules::{AnyRule, Rule},
+    search::{Cause, Reason, SetCell},
     traits::Search,
     world::World,
 };
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// A representation of `Cause` which can be easily serialized.
+///
+/// The cells are represented by their coordinates.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+enum CauseSer {
+    /// Deduced by consistifying the cell.
+    Rule(Coord),
+    /// Deduced by the symmetry from the cell.
+    Sym(Coord),
+    /// Deduced by a learnt nogood, from the states of these cells.
+    Nogood(Vec<Coord>),
+    /// Deduced because these living cells reach the bound of
+    /// a row or a column.
+    Count(Vec<Coord>),
+    /// Deduced by backjumping, from the decisions at these positions
+    /// in the `set_stack`.
+    Decisions(Vec<usize>),
+    /// Unknown.
+    #[default]
+    Unknown,
+}
+
+impl<'a, R: Rule> Cause<'a, R> {
+    fn ser(&self) -> CauseSer {
+        let coords = |cells: &Vec<CellRef<'a, R>>| -> Vec<Coord> {
+            cells.iter().map(|cell| cell.coord).collect()
+        };
+        match self {
+            Cause::Rule(cell) => CauseSer::Rule(cell.coord),
+            Cause::Sym(cell) => CauseSer::Sym(cell.coord),
+            Cause::Nogood(cells) => CauseSer::Nogood(coords(cells)),
+            Cause::Count(cells) => CauseSer::Count(coords(cells)),
+            Cause::Decisions(positions) => CauseSer::Decisions(positions.clone()),
+            Cause::Unknown => CauseSer::Unknown,
+        }
+    }
+}
+
+impl CauseSer {
+    /// Restores the `Cause` in the world.
+    fn cause<'a, R: Rule>(&self, world: &World<'a, R>) -> Result<Cause<'a, R>, Error> {
+        let find = |coord: Coord| world.find_cell(coord).ok_or(Error::SetCellError(coord));
+        let cells = |coords: &Vec<Coord>| -> Result<Vec<_>, Error> {
+            coords.iter().map(|&coord| find(coord)).collect()
+        };
+        Ok(match self {
+            CauseSer::Rule(coord) => Cause::Rule(find(*coord)?),
+            CauseSer::Sym(coord) => Cause::Sym(find(*coord)?),
+            CauseSer::Nogood(coords) => Cause::Nogood(cells(coords)?),
+            CauseSer::Count(coords) => Cause::Count(cells(coords)?),
+            CauseSer::Decisions(positions) => Cause::Decisions(positions.clone()),
+            CauseSer::Unknown => Cause::Unknown,
+        })
+    }
+}
 
 /// A representation of `SetCell` which can be easily serialized.
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 struct SetCellSer {
     /// The coordinates of the set cell.
     coord: Coord,
@@ -23,6 +83,16 @@ struct SetCellSer {
 
     /// The reason for setting a cell.
     reason: Reason,
+
+    /// The cause of a deduced cell.
+    ///
+    /// Worlds saved before it was introduced restore it as `Unknown`.
+    #[serde(default)]
+    cause: CauseSer,
+
+    /// Whether the cell is the last branch of a decision.
+    #[serde(default)]
+    flipped: bool,
 }
 
 impl<'a, R: Rule> SetCell<'a, R> {
@@ -31,13 +101,49 @@ impl<'a, R: Rule> SetCell<'a, R> {
             coord: self.cell.coord,
             state: self.cell.state.get().unwrap(),
             reason: self.reason,
+            cause: self.cause.ser(),
+            flipped: self.flipped,
         }
     }
 }
 
-/// A representation of the world which can be easily serialized.
+/// A representation of a learnt nogood which can be easily serialized.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct NogoodSer {
+    /// The coordinates of the cells and their states.
+    cells: Vec<(Coord, State)>,
+
+    /// The number of conflicts and deductions caused by this nogood.
+    activity: u64,
+}
+
+/// A representation of the state of the endgame which can be easily serialized.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct EndgameSer {
+    /// The coordinates of the cells that were unknown when the endgame started.
+    cells: Vec<Coord>,
+
+    /// Length of the `set_stack` when the endgame started.
+    len: usize,
+
+    /// Index of the next assignment to try.
+    next: u64,
+}
+
+/// A representation of the world which can be easily serialized.
+///
+/// It contains the whole state of the search, so that a restored world
+/// continues the search in the same way as the original one.
+/// Worlds saved by older versions can still be restored,
+/// but the missing parts of the state are reset.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct WorldSer {
+    /// The version of the format. See `FORMAT_VERSION`.
+    ///
+    /// Worlds saved before the version was introduced have version `0`.
+    #[serde(default)]
+    version: u32,
+
     /// World configuration.
     config: Config,
 
@@ -58,65 +164,232 @@ pub struct WorldSer {
 
     /// The position in the `search_list` of the last decided cell.
     search_index: usize,
+
+    /// Number of random states chosen during the search.
+    ///
+    /// Together with the seed in the configuration, it ensures that
+    /// a restored search finds the results in the same order.
+    #[serde(default)]
+    random_count: u64,
+
+    /// Whether a result has been found.
+    ///
+    /// Restarts are disabled after a result is found.
+    #[serde(default)]
+    found: bool,
+
+    /// Number of restarts during the search.
+    #[serde(default)]
+    restarts: u64,
+
+    /// Number of conflicts at the last restart.
+    #[serde(default)]
+    last_restart: u64,
+
+    /// The search order, if it has been switched by `order_switch_interval`.
+    #[serde(default)]
+    search_order: Option<SearchOrder>,
+
+    /// Number of re-evaluations of the search order.
+    #[serde(default)]
+    order_switches: u64,
+
+    /// Number of conflicts at the last re-evaluation of the search order.
+    #[serde(default)]
+    last_order_switch: u64,
+
+    /// The cap on the number of decisions for `deepening`.
+    #[serde(default)]
+    depth_cap: usize,
+
+    /// The cap in the previous round of `deepening`.
+    #[serde(default)]
+    depth_floor: usize,
+
+    /// Whether some branches are cut by the cap in this round of `deepening`.
+    #[serde(default)]
+    depth_cut: bool,
+
+    /// Number of decisions during the search.
+    #[serde(default)]
+    decisions: u64,
+
+    /// Number of cells deduced during the search.
+    #[serde(default)]
+    propagations: u64,
+
+    /// The maximal number of known cells at the same time.
+    #[serde(default)]
+    max_depth: usize,
+
+    /// The total time spent in the search.
+    #[serde(default)]
+    elapsed: Duration,
+
+    /// The learnt nogoods.
+    #[serde(default)]
+    nogoods: Vec<NogoodSer>,
+
+    /// The activities of the cells which are not zero,
+    /// when `activity_order` or `order_switch_interval` is enabled.
+    #[serde(default)]
+    activities: Vec<(Coord, f64)>,
+
+    /// The increment of the activities.
+    #[serde(default = "default_activity_inc")]
+    activity_inc: f64,
+
+    /// The states of the cells in the `search_list` in the result with
+    /// the smallest cell count so far, when `minimize` is enabled.
+    #[serde(default)]
+    best: Option<Vec<State>>,
+
+    /// Whether the search of `Engine::Rows` has started.
+    #[serde(default)]
+    rows_started: bool,
+
+    /// The lines on the search path of `Engine::Rows`.
+    #[serde(default)]
+    row_levels: Vec<RowLevel>,
+
+    /// The state of the endgame, when `endgame_threshold` is set.
+    #[serde(default)]
+    endgame: Option<EndgameSer>,
+}
+
+/// The initial increment of the activities.
+fn default_activity_inc() -> f64 {
+    1.0
 }
 
 impl WorldSer {
+    /// The configuration of the saved world.
+    ///
+    /// It can be compared with the current configuration
+    /// before resuming a search.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
     /// Restores the world from the `WorldSer`, with the given rule.
     fn world_with_rule<'a, R: Rule>(&self, rule: R) -> Result<World<'a, R>, Error> {
         let mut world = World::new(&self.config, rule);
-        for &SetCellSer {
-            coord,
-            state,
-            reason,
-        } in self.set_stack.iter()
-        {
+        for set_cell in self.set_stack.iter() {
+            let coord = set_cell.coord;
             let cell = world.find_cell(coord).ok_or(Error::SetCellError(coord))?;
             if let Some(old_state) = cell.state.get() {
-                if old_state != state {
+                if old_state != set_cell.state {
                     return Err(Error::SetCellError(coord));
                 }
             } else {
-                world.set_cell(cell, state, reason);
+                world.cause = set_cell.cause.cause(&world)?;
+                world.set_cell(cell, set_cell.state, set_cell.reason);
+                world.cause = Cause::Unknown;
+                world.set_stack.last_mut().unwrap().flipped = set_cell.flipped;
             }
         }
+        if let Some(search_order) = self.search_order {
+            world.set_search_order(search_order);
+        }
+        for nogood in self.nogoods.iter() {
+            let cells = nogood
+                .cells
+                .iter()
+                .map(|&(coord, state)| {
+                    let cell = world.find_cell(coord).ok_or(Error::SetCellError(coord))?;
+                    Ok((cell, state))
+                })
+                .collect::<Result<_, Error>>()?;
+            world.nogoods.restore(cells, nogood.activity);
+        }
+        for &(coord, activity) in self.activities.iter() {
+            let cell = world.find_cell(coord).ok_or(Error::SetCellError(coord))?;
+            cell.activity.set(activity);
+        }
+        world.activity_inc = self.activity_inc;
         world.conflicts = self.conflicts;
+        world.restarts = self.restarts;
+        world.last_restart = self.last_restart;
+        world.order_switches = self.order_switches;
+        world.last_order_switch = self.last_order_switch;
+        if self.depth_cap > 0 {
+            world.depth_cap = self.depth_cap;
+            world.depth_floor = self.depth_floor;
+            world.depth_cut = self.depth_cut;
+        }
+        world.decisions = self.decisions;
+        world.propagations = self.propagations;
+        world.max_depth = self.max_depth;
+        world.elapsed = self.elapsed;
         world.check_index = self.check_index;
         world.search_index = self.search_index;
+        world.found = self.found;
+        world.best = self.best.clone();
+        world.rows.started = self.rows_started;
+        world.rows.levels = self.row_levels.clone();
+        if let Some(endgame) = self.endgame.as_ref() {
+            let cells = endgame
+                .cells
+                .iter()
+                .map(|&coord| world.find_cell(coord).ok_or(Error::SetCellError(coord)))
+                .collect::<Result<_, Error>>()?;
+            world.endgame = Some(Endgame {
+                cells,
+                len: endgame.len,
+                next: endgame.next,
+            });
+        }
+        for _ in 0..self.random_count {
+            world.random_state();
+        }
         Ok(world)
     }
 
     /// Restores the world from the `WorldSer`.
+    ///
+    /// Returns an error if the world is saved in a newer format version,
+    /// or if the rule is a rule range, whose deduced transitions are not saved.
     pub fn world(&self) -> Result<Box<dyn Search>, Error> {
-        if let Ok(rule) = self.config.rule_string.parse::<Life>() {
-            let world = self.world_with_rule(rule)?;
-            Ok(Box::new(world))
-        } else if let Ok(rule) = self.config.rule_string.parse::<NtLife>() {
-            let world = self.world_with_rule(rule)?;
-            Ok(Box::new(world))
-        } else if let Ok(rule) = self.config.rule_string.parse::<LifeGen>() {
-            if rule.gen() > 2 {
-                let world = self.world_with_rule(rule)?;
-                Ok(Box::new(world))
-            } else {
-                let rule = rule.non_gen();
-                let world = self.world_with_rule(rule)?;
-                Ok(Box::new(world))
-            }
-        } else {
-            let rule = self
-                .config
-                .rule_string
-                .parse::<NtLifeGen>()
-                .map_err(Error::ParseRuleError)?;
-            if rule.gen() > 2 {
-                let world = self.world_with_rule(rule)?;
-                Ok(Box::new(world))
-            } else {
-                let rule = rule.non_gen();
-                let world = self.world_with_rule(rule)?;
-                Ok(Box::new(world))
-            }
+        if self.version > FORMAT_VERSION {
+            return Err(Error::FormatVersionError(self.version));
         }
+        let world: Box<dyn Search> = match AnyRule::from_config(&self.config)? {
+            AnyRule::Life(rule) => Box::new(self.world_with_rule(rule)?),
+            #[cfg(feature = "hex")]
+            AnyRule::Hex(rule) => Box::new(self.world_with_rule(rule)?),
+            #[cfg(feature = "von-neumann")]
+            AnyRule::VonNeumann(rule) => Box::new(self.world_with_rule(rule)?),
+            #[cfg(feature = "von-neumann")]
+            AnyRule::VonNeumann2(rule) => Box::new(self.world_with_rule(rule)?),
+            #[cfg(feature = "triangle")]
+            AnyRule::Triangle(rule) => Box::new(self.world_with_rule(rule)?),
+            #[cfg(feature = "margolus")]
+            AnyRule::Margolus(rule) => Box::new(self.world_with_rule(rule)?),
+            #[cfg(feature = "hex")]
+            AnyRule::NtHex(rule) => Box::new(self.world_with_rule(rule)?),
+            AnyRule::NtLife(rule) => Box::new(self.world_with_rule(rule)?),
+            #[cfg(feature = "rule-range")]
+            AnyRule::LifeRange(_) => {
+                return Err(Error::RestoreRangeError(self.config.rule_string.clone()))
+            }
+            AnyRule::LifeGen(rule) => Box::new(self.world_with_rule(rule)?),
+            #[cfg(feature = "hex")]
+            AnyRule::HexGen(rule) => Box::new(self.world_with_rule(rule)?),
+            #[cfg(feature = "von-neumann")]
+            AnyRule::VonNeumannGen(rule) => Box::new(self.world_with_rule(rule)?),
+            #[cfg(feature = "von-neumann")]
+            AnyRule::VonNeumann2Gen(rule) => Box::new(self.world_with_rule(rule)?),
+            #[cfg(feature = "triangle")]
+            AnyRule::TriangleGen(rule) => Box::new(self.world_with_rule(rule)?),
+            #[cfg(feature = "hex")]
+            AnyRule::NtHexGen(rule) => Box::new(self.world_with_rule(rule)?),
+            AnyRule::NtLifeGen(rule) => Box::new(self.world_with_rule(rule)?),
+            #[cfg(feature = "bsfkl")]
+            AnyRule::Bsfkl(rule) => Box::new(self.world_with_rule(rule)?),
+            #[cfg(feature = "custom")]
+            AnyRule::Custom(rule) => Box::new(self.world_with_rule(rule)?),
+        };
+        Ok(world)
     }
 }
 
@@ -124,11 +397,62 @@ impl<'a, R: Rule> World<'a, R> {
     /// Saves the world as a `WorldSer`.
     pub fn ser(&self) -> WorldSer {
         WorldSer {
+            version: FORMAT_VERSION,
             config: self.config.clone(),
             conflicts: self.conflicts,
             set_stack: self.set_stack.iter().map(|s| s.ser()).collect(),
             check_index: self.check_index,
-            search_index: self.search_index,
+            // The `search_index` is not maintained when there is a chooser,
+            // or when the cells are ordered by activity.
+            search_index: if self.chooser.is_some() || self.config.activity_order {
+                0
+            } else {
+                self.search_index
+            },
+            random_count: self.random_count,
+            found: self.found,
+            restarts: self.restarts,
+            last_restart: self.last_restart,
+            search_order: Some(self.search_order)
+                .filter(|&search_order| search_order != self.config.auto_search_order()),
+            order_switches: self.order_switches,
+            last_order_switch: self.last_order_switch,
+            depth_cap: self.depth_cap,
+            depth_floor: self.depth_floor,
+            depth_cut: self.depth_cut,
+            decisions: self.decisions,
+            propagations: self.propagations,
+            max_depth: self.max_depth,
+            elapsed: self.elapsed,
+            nogoods: self
+                .nogoods
+                .iter()
+                .map(|(cells, activity)| NogoodSer {
+                    cells: cells
+                        .iter()
+                        .map(|&(cell, state)| (cell.coord, state))
+                        .collect(),
+                    activity,
+                })
+                .collect(),
+            activities: if self.config.activity_order || self.config.order_switch_interval.is_some()
+            {
+                self.cells()
+                    .filter(|cell| cell.activity.get() != 0.0)
+                    .map(|cell| (cell.coord, cell.activity.get()))
+                    .collect()
+            } else {
+                Vec::new()
+            },
+            activity_inc: self.activity_inc,
+            best: self.best.clone(),
+            rows_started: self.rows.started,
+            row_levels: self.rows.levels.clone(),
+            endgame: self.endgame.as_ref().map(|endgame| EndgameSer {
+                cells: endgame.cells.iter().map(|cell| cell.coord).collect(),
+                len: endgame.len,
+                next: endgame.next,
+            }),
         }
     }
 }
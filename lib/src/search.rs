@@ -1,11 +1,26 @@
 //! The search process.
 use crate::{
-    cells::{CellRef, State},
-    config::NewState,
+    cells::{CellRef, Coord, State},
+    choose::ChooseCell,
+    config::{Engine, NewState, SearchOrder},
+    guide::Guide,
+    hooks::Hooks,
+    nogood::Nogoods,
     rules::Rule,
+    trace::{Features, Policy, Trace, TraceEntry},
     world::World,
 };
-use rand::{thread_rng, Rng};
+use derivative::Derivative;
+use rand::Rng;
+use std::{
+    collections::{BTreeSet, HashSet},
+    mem,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
 
 #[cfg(feature = "serialize")]
 use serde::{Deserialize, Serialize};
@@ -24,6 +39,102 @@ pub enum Status {
     Searching,
     /// Paused.
     Paused,
+    /// The `time_limit` is reached. The world shows the deepest
+    /// partial result.
+    TimedOut,
+}
+
+/// Limits on the work of a single call of `Search::search_with_budget`.
+///
+/// When any of the limits is exceeded, the search returns `Status::Searching`,
+/// and can be resumed by another call. The number of steps is cheap to
+/// count, but the time of a step varies a lot. Conflicts and decisions
+/// give a better idea of the progress of the search.
+///
+/// `None` means that there is no limit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct Budget {
+    /// The maximal number of steps, as `max_step` in `Search::search`.
+    pub steps: Option<u64>,
+    /// The maximal number of conflicts.
+    pub conflicts: Option<u64>,
+    /// The maximal number of decisions.
+    pub decisions: Option<u64>,
+}
+
+impl Budget {
+    /// Sets the maximal number of steps.
+    pub fn set_steps(mut self, steps: Option<u64>) -> Self {
+        self.steps = steps;
+        self
+    }
+
+    /// Sets the maximal number of conflicts.
+    pub fn set_conflicts(mut self, conflicts: Option<u64>) -> Self {
+        self.conflicts = conflicts;
+        self
+    }
+
+    /// Sets the maximal number of decisions.
+    pub fn set_decisions(mut self, decisions: Option<u64>) -> Self {
+        self.decisions = decisions;
+        self
+    }
+}
+
+/// Statistics of a search.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct Stats {
+    /// Number of decisions, including those that are
+    /// deduced by probing instead.
+    pub decisions: u64,
+    /// Number of conflicts.
+    pub conflicts: u64,
+    /// Number of cells deduced from other cells.
+    pub propagations: u64,
+    /// The maximal number of known cells at the same time,
+    /// including the cells known before the search.
+    pub max_depth: usize,
+    /// Number of restarts.
+    pub restarts: u64,
+    /// The total time spent in `Search::search` and its variants.
+    ///
+    /// It is always zero on WebAssembly, where the time is not available.
+    pub elapsed: Duration,
+}
+
+/// A handle to stop a search from another thread,
+/// e.g., a signal handler.
+///
+/// The clones of a `Stop` share the same flag. When it is set,
+/// the search returns `Status::Paused` at the end of the current step.
+/// The world is not changed, so the search can be resumed
+/// after the flag is reset.
+#[derive(Clone, Debug, Default)]
+pub struct Stop(Arc<AtomicBool>);
+
+impl Stop {
+    /// Creates a new handle, which is not stopped.
+    pub fn new() -> Self {
+        Stop::default()
+    }
+
+    /// Asks the searches to stop.
+    pub fn stop(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Resets the flag, so that the searches can continue.
+    pub fn reset(&self) {
+        self.0.store(false, Ordering::Relaxed);
+    }
+
+    /// Whether the searches are asked to stop.
+    pub fn is_stopped(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
 }
 
 /// Reasons for setting a cell.
@@ -43,22 +154,69 @@ pub(crate) enum Reason {
     /// Remembers its position in the `search_list` of the world,
     /// and the number of remaining states to try.
     TryAnother(usize, usize),
+
+    /// The state of the cell is known before the search.
+    Known,
+}
+
+/// The causes of deduced cells.
+///
+/// Used in backjumping to find the decisions that are responsible
+/// for a conflict.
+#[derive(Derivative)]
+#[derivative(Clone(bound = ""))]
+pub(crate) enum Cause<'a, R: Rule> {
+    /// Deduced by consistifying the cell.
+    Rule(CellRef<'a, R>),
+
+    /// Deduced by the symmetry from the cell.
+    Sym(CellRef<'a, R>),
+
+    /// Deduced by a learnt nogood, from the states of the other cells
+    /// in the nogood.
+    Nogood(Vec<CellRef<'a, R>>),
+
+    /// Deduced because these living cells reach the bound of
+    /// a row or a column.
+    Count(Vec<CellRef<'a, R>>),
+
+    /// Deduced by backjumping, because the other state leads to
+    /// a conflict caused by the decisions at these positions in
+    /// the `set_stack`.
+    Decisions(Vec<usize>),
+
+    /// Unknown. It might depend on all earlier decisions.
+    Unknown,
 }
 
 /// Records the cells whose values are set and their reasons.
-#[derive(Clone, Copy)]
+#[derive(Derivative)]
+#[derivative(Clone(bound = ""))]
 pub(crate) struct SetCell<'a, R: Rule> {
     /// The set cell.
     pub(crate) cell: CellRef<'a, R>,
 
     /// The reason for setting a cell.
     pub(crate) reason: Reason,
+
+    /// The cause of a deduced cell.
+    pub(crate) cause: Cause<'a, R>,
+
+    /// Whether the cell is deduced because all the other states
+    /// of a decision lead to conflicts, i.e., it is the last branch
+    /// of the decision.
+    pub(crate) flipped: bool,
 }
 
 impl<'a, R: Rule> SetCell<'a, R> {
     /// Get a reference to the set cell.
     pub(crate) fn new(cell: CellRef<'a, R>, reason: Reason) -> Self {
-        SetCell { cell, reason }
+        SetCell {
+            cell,
+            reason,
+            cause: Cause::Unknown,
+            flipped: false,
+        }
     }
 }
 
@@ -72,69 +230,361 @@ impl<'a, R: Rule> World<'a, R> {
     ///
     /// Returns `false` if there is a conflict,
     /// `true` if the cells are consistent.
+    ///
+    /// Cells outside a bounded grid or outside the fundamental domain
+    /// of the symmetry are skipped.
     fn consistify(&mut self, cell: CellRef<'a, R>) -> bool {
-        Rule::consistify(self, cell)
+        if cell.outside || cell.redundant {
+            return true;
+        }
+        self.cause = Cause::Rule(cell);
+        let result = Rule::consistify(self, cell);
+        self.cause = Cause::Unknown;
+        if !result && (self.analyze_conflicts() || self.bump_conflicts()) {
+            self.conflict = if self.global_conflict() {
+                None
+            } else {
+                Some(
+                    self.line_conflict()
+                        .unwrap_or_else(|| self.antecedents(cell)),
+                )
+            };
+        }
+        result
+    }
+
+    /// Whether to analyze the conflicts for backjumping
+    /// or learning nogoods.
+    ///
+    /// Generations rules and rule ranges are not supported.
+    fn analyze_conflicts(&self) -> bool {
+        (self.config.backjump || self.config.nogood_limit > 0)
+            && !R::IS_GEN
+            && self.rule.deduced_rules().is_none()
+    }
+
+    /// Whether to find the cells involved in the conflicts
+    /// for `activity_order` or `order_switch_interval`.
+    ///
+    /// The same rules as in `analyze_conflicts` are supported.
+    fn bump_conflicts(&self) -> bool {
+        (self.config.activity_order || self.config.order_switch_interval.is_some())
+            && !R::IS_GEN
+            && self.rule.deduced_rules().is_none()
+    }
+
+    /// Increases the activities of the cells involved in the last conflict,
+    /// and the increment for later conflicts.
+    ///
+    /// When the activities become too large, all of them are scaled down.
+    fn bump_activity(&mut self) {
+        if let Some(conflict) = self.conflict.as_ref() {
+            for &cell in conflict.iter() {
+                cell.activity.set(cell.activity.get() + self.activity_inc);
+            }
+        }
+        self.activity_inc /= ACTIVITY_DECAY;
+        if self.activity_inc > 1e100 {
+            for cell in self.search_list.iter() {
+                cell.activity.set(cell.activity.get() * 1e-100);
+            }
+            self.activity_inc *= 1e-100;
+        }
+    }
+
+    /// The cells whose states are examined when consistifying a cell:
+    /// the cell, its neighbors, and its successor.
+    ///
+    /// For rules with parity, e.g., Margolus rules, the successors
+    /// of the neighbors are also included.
+    fn antecedents(&self, cell: CellRef<'a, R>) -> Vec<CellRef<'a, R>> {
+        let mut cells = vec![cell];
+        cells.extend(cell.succ);
+        for &neigh in cell.nbhd.iter().flatten() {
+            cells.push(neigh);
+            if self.rule.has_parity() {
+                cells.extend(neigh.succ);
+            }
+        }
+        cells
+    }
+
+    /// Whether the current states violate a constraint on the whole
+    /// world, i.e., `max_cell_count`, `max_gen0_cell_count`,
+    /// `max_gen_cell_count`, `max_rotor_cell_count` or `non_empty_front`.
+    fn global_conflict(&self) -> bool {
+        self.config
+            .max_cell_count
+            .is_some_and(|max| self.cell_count() > max)
+            || (0..self.config.period).any(|t| {
+                self.gen_max_cell_count(t)
+                    .is_some_and(|max| self.cell_count[t as usize] > max)
+            })
+            || self
+                .config
+                .max_rotor_cell_count
+                .is_some_and(|max| self.rotor_cell_count > max)
+            || self.config.non_empty_front && self.front_cell_count == 0
+    }
+
+    /// The living cells in a row or a column whose number exceeds
+    /// `max_row_cell_count` or `max_column_cell_count`, if any.
+    fn line_conflict(&self) -> Option<Vec<CellRef<'a, R>>> {
+        let (width, height) = (self.config.width, self.config.height);
+        let row = self.config.max_row_cell_count.and_then(|max| {
+            let i = self.row_cell_count.iter().position(|&n| n > max)? as isize;
+            Some(self.line_cells((0..width).map(|x| (x, i % height, i / height))))
+        });
+        let column = || {
+            self.config.max_column_cell_count.and_then(|max| {
+                let i = self.column_cell_count.iter().position(|&n| n > max)? as isize;
+                Some(self.line_cells((0..height).map(|y| (i % width, y, i / width))))
+            })
+        };
+        row.or_else(column)
+            .map(|cells| cells.into_iter().filter(|&c| self.is_counted(c)).collect())
+    }
+
+    /// The cells at the given coordinates.
+    fn line_cells(&self, coords: impl Iterator<Item = Coord>) -> Vec<CellRef<'a, R>> {
+        coords.filter_map(|coord| self.find_cell(coord)).collect()
+    }
+
+    /// Whether the cell is a known living cell that is counted
+    /// in the cell counts.
+    fn is_counted(&self, cell: CellRef<'a, R>) -> bool {
+        cell.state.get() == Some(!cell.background) && !cell.dont_care
+    }
+
+    /// The cells involved in a conflict when `set_cell` fails,
+    /// which happens when the cells violate a bound of the cell count
+    /// or `non_empty_front`.
+    ///
+    /// Returns `None` if the bound is on the whole world.
+    pub(crate) fn count_conflict(&self) -> Option<Vec<CellRef<'a, R>>> {
+        if self.global_conflict() {
+            None
+        } else {
+            self.line_conflict()
+        }
     }
 
     /// Consistifies a cell, its neighbors, and its predecessor.
     ///
+    /// Only the dirty cells are consistified, i.e., the cells whose
+    /// neighborhoods have changed since they were last consistified.
+    /// When several cells around a cell are set at once, it is only
+    /// consistified once.
+    ///
     /// Returns `false` if there is a conflict,
     /// `true` if the cells are consistent.
     fn consistify10(&mut self, cell: CellRef<'a, R>) -> bool {
-        self.consistify(cell)
-            && {
-                if let Some(pred) = cell.pred {
-                    self.consistify(pred)
-                } else {
-                    true
-                }
-            }
-            && cell
-                .nbhd
-                .iter()
-                .all(|&neigh| self.consistify(neigh.unwrap()))
+        Some(cell)
+            .into_iter()
+            .chain(cell.pred)
+            .chain(cell.nbhd.iter().copied().flatten())
+            .all(|cell| self.consistify_dirty(cell))
+    }
+
+    /// Consistifies a cell if it is dirty.
+    ///
+    /// The cell is marked as clean first, so that it becomes dirty again
+    /// if `consistify` changes its neighborhood. It stays dirty
+    /// if there is a conflict.
+    fn consistify_dirty(&mut self, cell: CellRef<'a, R>) -> bool {
+        if !cell.dirty.replace(false) {
+            return true;
+        }
+        let result = self.consistify(cell);
+        if !result {
+            cell.dirty.set(true);
+        }
+        result
     }
 
-    /// Deduces all the consequences by `consistify` and symmetry.
+    /// Deduces all the consequences by `consistify` and symmetry,
+    /// and then checks the constraints of `symmetry_breaking`
+    /// and the `dead_end_cache`.
     ///
     /// Returns `false` if there is a conflict,
     /// `true` if the cells are consistent.
-    fn proceed(&mut self) -> bool {
-        while self.check_index < self.set_stack.len() {
+    pub(crate) fn proceed(&mut self) -> bool {
+        self.proceed_bounded(usize::MAX) && self.check_lex_leader() && self.check_dead_ends()
+    }
+
+    /// Checks the constraints on the whole world, and then `proceed`s.
+    ///
+    /// These constraints are otherwise only checked when a cell is set,
+    /// but the known cells might already violate them when the world
+    /// is created. A `known_conflict` is also checked here.
+    pub(crate) fn proceed_checked(&mut self) -> bool {
+        if self.known_conflict || self.global_conflict() {
+            self.conflict = None;
+            return false;
+        }
+        self.proceed()
+    }
+
+    /// Deduces the consequences by `consistify` and symmetry,
+    /// but examines at most `max` cells in the `set_stack`.
+    ///
+    /// Returns `false` if there is a conflict,
+    /// `true` if no conflict is found.
+    fn proceed_bounded(&mut self, max: usize) -> bool {
+        let end = self.check_index.saturating_add(max);
+        while self.check_index < self.set_stack.len() && self.check_index < end {
             let cell = self.set_stack[self.check_index].cell;
             let state = cell.state.get().unwrap();
 
             // Determines some cells by symmetry.
+            //
+            // Symmetric cells might have different backgrounds, e.g.,
+            // in different generations of a rule with `B0`. Then a cell
+            // in its background state corresponds to a cell in its own
+            // background state.
             for &sym in cell.sym.iter() {
+                let state = if sym.background == cell.background {
+                    state
+                } else {
+                    !state
+                };
                 if let Some(old_state) = sym.state.get() {
                     if state != old_state {
+                        self.conflict = Some(vec![cell, sym]);
+                        return false;
+                    }
+                } else {
+                    self.cause = Cause::Sym(cell);
+                    let result = self.set_cell(sym, state, Reason::Deduce);
+                    self.cause = Cause::Unknown;
+                    if !result {
+                        self.conflict = self.count_conflict();
                         return false;
                     }
-                } else if !self.set_cell(sym, state, Reason::Deduce) {
-                    return false;
                 }
             }
 
+            // Determines some cells by the population bounds.
+            if !self.fill_gen(cell) || !self.fill_lines(cell) {
+                return false;
+            }
+
             // Determines some cells by `consistify`.
             if !self.consistify10(cell) {
                 return false;
             }
 
+            // Determines some cells by the learnt nogoods.
+            if !self.check_nogoods(cell) {
+                return false;
+            }
+
             self.check_index += 1;
         }
         true
     }
 
+    /// If the cell is a living cell, and the number of living cells
+    /// in its generation reaches the bound (see `gen_max_cell_count`),
+    /// sets all the other unknown cells in that generation
+    /// to the background.
+    ///
+    /// Returns `false` if there is a conflict,
+    /// `true` if the cells are consistent.
+    fn fill_gen(&mut self, cell: CellRef<'a, R>) -> bool {
+        let t = cell.coord.2;
+        match self.gen_max_cell_count(t) {
+            Some(max) if self.is_counted(cell) && self.cell_count[t as usize] == max => {}
+            _ => return true,
+        }
+        for i in 0..self.search_list.len() {
+            let cell = self.search_list[i];
+            if cell.coord.2 == t
+                && cell.state.get().is_none()
+                && !cell.dont_care
+                && !self.set_cell(cell, cell.background, Reason::Deduce)
+            {
+                self.conflict = None;
+                return false;
+            }
+        }
+        true
+    }
+
+    /// If the cell is a living cell, and the number of living cells
+    /// in its row or column reaches `max_row_cell_count` or
+    /// `max_column_cell_count`, sets all the other unknown cells
+    /// in that row or column to the background.
+    ///
+    /// Returns `false` if there is a conflict,
+    /// `true` if the cells are consistent.
+    fn fill_lines(&mut self, cell: CellRef<'a, R>) -> bool {
+        if !self.is_counted(cell) {
+            return true;
+        }
+        let (x, y, t) = cell.coord;
+        let (width, height) = (self.config.width, self.config.height);
+        let (row, column) = self.line_indices(cell.coord);
+        if self.config.max_row_cell_count == Some(self.row_cell_count[row]) {
+            let cells = self.line_cells((0..width).map(|x| (x, y, t)));
+            if !self.fill_line(cells) {
+                return false;
+            }
+        }
+        if self.config.max_column_cell_count == Some(self.column_cell_count[column]) {
+            let cells = self.line_cells((0..height).map(|y| (x, y, t)));
+            if !self.fill_line(cells) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Sets all the unknown cells in a row or a column to the background,
+    /// because the living cells in it reach the bound.
+    ///
+    /// Returns `false` if there is a conflict,
+    /// `true` if the cells are consistent.
+    fn fill_line(&mut self, cells: Vec<CellRef<'a, R>>) -> bool {
+        let living = cells
+            .iter()
+            .copied()
+            .filter(|&c| self.is_counted(c))
+            .collect::<Vec<_>>();
+        for cell in cells {
+            if cell.state.get().is_none() && !cell.dont_care {
+                self.cause = Cause::Count(living.clone());
+                let result = self.set_cell(cell, cell.background, Reason::Deduce);
+                self.cause = Cause::Unknown;
+                if !result {
+                    self.conflict = None;
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
     /// Backtracks to the last time when a unknown cell is decided by choice,
     /// and switch that cell to the other state.
     ///
     /// Returns `true` if it backtracks successfully,
     /// `false` if it goes back to the time before the first cell is set.
-    fn backup(&mut self) -> bool {
+    pub(crate) fn backup(&mut self) -> bool {
+        self.endgame = None;
         while let Some(set_cell) = self.set_stack.pop() {
             let cell = set_cell.cell;
             match set_cell.reason {
                 Reason::Decide(i) => {
+                    self.decision_depth -= 1;
+                    self.exhaust_dead_ends(self.set_stack.len());
+                    if let Some(trace) = self.trace.as_mut() {
+                        trace.backtrack();
+                    }
+                    if let Some(hooks) = self.hooks.as_mut() {
+                        hooks.on_backtrack(self.set_stack.len());
+                    }
+                    self.rule.backtrack(self.set_stack.len());
                     self.check_index = self.set_stack.len();
                     self.search_index = i + 1;
                     if R::IS_GEN {
@@ -145,14 +595,25 @@ impl<'a, R: Rule> World<'a, R> {
                             return true;
                         }
                     } else {
+                        // The cause is set by `backjump`, and only applies
+                        // to the first decision.
                         let state = !cell.state.get().unwrap();
                         self.clear_cell(cell);
-                        if self.set_cell(cell, state, Reason::Deduce) {
+                        let result = self.set_cell(cell, state, Reason::Deduce);
+                        self.cause = Cause::Unknown;
+                        self.set_stack.last_mut().unwrap().flipped = true;
+                        if result {
                             return true;
                         }
                     }
                 }
                 Reason::TryAnother(i, n) => {
+                    self.decision_depth -= 1;
+                    self.exhaust_dead_ends(self.set_stack.len());
+                    if let Some(hooks) = self.hooks.as_mut() {
+                        hooks.on_backtrack(self.set_stack.len());
+                    }
+                    self.rule.backtrack(self.set_stack.len());
                     self.check_index = self.set_stack.len();
                     self.search_index = i + 1;
                     let State(j) = cell.state.get().unwrap();
@@ -163,20 +624,172 @@ impl<'a, R: Rule> World<'a, R> {
                     } else {
                         Reason::TryAnother(i, n - 1)
                     };
-                    if self.set_cell(cell, state, reason) {
+                    let result = self.set_cell(cell, state, reason);
+                    self.set_stack.last_mut().unwrap().flipped = n == 1;
+                    if result {
                         return true;
                     }
                 }
                 Reason::Deduce => {
                     self.clear_cell(cell);
                 }
+                Reason::Known => {
+                    self.set_stack.push(set_cell);
+                    break;
+                }
             }
         }
+        self.exhaust_dead_ends(self.set_stack.len());
+        self.rule.backtrack(self.set_stack.len());
         self.check_index = 0;
         self.search_index = 0;
         false
     }
 
+    /// Backtracks to the last decision that is responsible for the last
+    /// conflict, and switch that cell to the other state.
+    ///
+    /// The decisions between them are undone without trying their other
+    /// states, because they would lead to the same conflict.
+    /// The switched cell is recorded as deduced from the other
+    /// responsible decisions. If `nogood_limit` is positive, the states
+    /// of the responsible decisions are also learnt as a nogood.
+    ///
+    /// Falls back to `backup` if the conflicts are not analyzed
+    /// (see `analyze_conflicts`), or the cells involved in the conflict
+    /// are unknown.
+    ///
+    /// Returns `true` if it backtracks successfully,
+    /// `false` if it goes back to the time before the first cell is set.
+    fn backjump(&mut self) -> bool {
+        let conflict = match self.conflict.take() {
+            Some(conflict) if self.analyze_conflicts() => conflict,
+            _ => return self.backup(),
+        };
+        let (mut decisions, exact) = self.responsible_decisions(conflict);
+        if exact && self.config.nogood_limit > 0 && !decisions.is_empty() {
+            let cells = decisions
+                .iter()
+                .map(|&i| {
+                    let cell = self.set_stack[i].cell;
+                    (cell, cell.state.get().unwrap())
+                })
+                .collect();
+            self.nogoods.learn(cells, self.config.nogood_limit);
+        }
+        if let Some(last) = decisions.pop() {
+            // The conflict follows from the decisions up to the last one.
+            self.exhaust_dead_ends(last);
+            self.pop_until(last + 1);
+            // If the decisions are not exact, they are all the decisions
+            // before the last one, which is the same as an unknown cause.
+            self.cause = if exact {
+                Cause::Decisions(decisions)
+            } else {
+                Cause::Unknown
+            };
+        } else if let Some(first) = self
+            .set_stack
+            .iter()
+            .position(|set_cell| matches!(set_cell.reason, Reason::Decide(_)))
+        {
+            self.pop_until(first);
+        }
+        self.backup()
+    }
+
+    /// The positions in the `set_stack` of the decisions that are
+    /// responsible for a conflict among the given cells, in increasing order.
+    ///
+    /// Follows the causes of the deduced cells backwards in the `set_stack`.
+    ///
+    /// Also returns whether the conflict is a logical consequence of
+    /// these decisions. It is not when some cell involved has an unknown
+    /// cause, e.g., when it is switched after a result is found.
+    /// Then all the earlier decisions are considered responsible.
+    fn responsible_decisions(&self, conflict: Vec<CellRef<'a, R>>) -> (Vec<usize>, bool) {
+        let mut marked = conflict
+            .into_iter()
+            .map(|cell| cell.coord)
+            .collect::<HashSet<Coord>>();
+        let mut decisions = BTreeSet::new();
+        let mut exact = true;
+        for (i, set_cell) in self.set_stack.iter().enumerate().rev() {
+            if !marked.contains(&set_cell.cell.coord) {
+                continue;
+            }
+            match (set_cell.reason, &set_cell.cause) {
+                (Reason::Decide(_), _) | (Reason::TryAnother(_, _), _) => {
+                    decisions.insert(i);
+                }
+                (Reason::Known, _) => (),
+                (Reason::Deduce, Cause::Rule(cell)) => {
+                    marked.extend(self.antecedents(*cell).into_iter().map(|cell| cell.coord));
+                }
+                (Reason::Deduce, Cause::Sym(cell)) => {
+                    marked.insert(cell.coord);
+                }
+                (Reason::Deduce, Cause::Nogood(cells)) | (Reason::Deduce, Cause::Count(cells)) => {
+                    marked.extend(cells.iter().map(|cell| cell.coord));
+                }
+                (Reason::Deduce, Cause::Decisions(positions)) => {
+                    decisions.extend(positions.iter().copied());
+                }
+                (Reason::Deduce, Cause::Unknown) => {
+                    let earlier = self.set_stack[..i]
+                        .iter()
+                        .enumerate()
+                        .filter_map(|(j, set_cell)| match set_cell.reason {
+                            Reason::Decide(_) | Reason::TryAnother(_, _) => Some(j),
+                            _ => None,
+                        })
+                        .collect::<Vec<_>>();
+                    // Cells deduced before the first decision are facts.
+                    exact = earlier.is_empty();
+                    decisions.extend(earlier);
+                    break;
+                }
+            }
+        }
+        (decisions.into_iter().collect(), exact)
+    }
+
+    /// Undoes the cells in the `set_stack` after the first `len` cells.
+    pub(crate) fn pop_until(&mut self, len: usize) {
+        self.leave_dead_ends(len);
+        if self
+            .endgame
+            .as_ref()
+            .is_some_and(|endgame| endgame.len > len)
+        {
+            self.endgame = None;
+        }
+        while self.set_stack.len() > len {
+            let set_cell = self.set_stack.pop().unwrap();
+            match set_cell.reason {
+                Reason::Decide(_) => {
+                    self.decision_depth -= 1;
+                    if let Some(trace) = self.trace.as_mut() {
+                        trace.backtrack();
+                    }
+                }
+                Reason::TryAnother(_, _) => self.decision_depth -= 1,
+                _ => (),
+            }
+            self.clear_cell(set_cell.cell);
+        }
+    }
+
+    /// Undoes the cells in the `set_stack` after the first `len` cells,
+    /// and the deductions about the rule made since then,
+    /// without trying other states.
+    pub(crate) fn undo_to(&mut self, len: usize) {
+        self.conflict = None;
+        self.pop_until(len);
+        self.rule.backtrack(len);
+        self.check_index = len;
+    }
+
     /// Keeps proceeding and backtracking,
     /// until there are no more cells to examine (and returns `true`),
     /// or the backtracking goes back to the time before the first cell is set
@@ -190,33 +803,337 @@ impl<'a, R: Rule> World<'a, R> {
     fn go(&mut self, step: &mut u64) -> bool {
         loop {
             *step += 1;
-            if self.proceed() {
+            if self.proceed_checked() {
                 return true;
             } else {
                 self.conflicts += 1;
-                if !self.backup() {
+                if let Some(hooks) = self.hooks.as_mut() {
+                    hooks.on_conflict(self.conflicts);
+                }
+                if self.bump_conflicts() {
+                    self.bump_activity();
+                }
+                if !self.backjump() && !self.deepen() {
                     return false;
                 }
+                self.restart_if_due();
+            }
+        }
+    }
+
+    /// Restarts the search if the number of conflicts since the last
+    /// restart reaches the budget given by `restart_interval` and
+    /// the Luby sequence.
+    ///
+    /// A restart undoes all the decisions, but keeps the cells that are
+    /// known before the first decision, and the learnt nogoods.
+    ///
+    /// Restarts are disabled after a result is found, so that the same
+    /// result is not found again.
+    fn restart_if_due(&mut self) {
+        let interval = match self.config.restart_interval {
+            Some(interval) if !self.found => interval,
+            _ => return,
+        };
+        if self.conflicts - self.last_restart < interval.saturating_mul(luby(self.restarts + 1)) {
+            return;
+        }
+        if let Some(first) = self
+            .set_stack
+            .iter()
+            .position(|set_cell| matches!(set_cell.reason, Reason::Decide(_)))
+        {
+            self.pop_until(first);
+            self.rule.backtrack(first);
+            self.check_index = self.check_index.min(first);
+            self.search_index = 0;
+        }
+        self.restarts += 1;
+        self.last_restart = self.conflicts;
+    }
+
+    /// Re-evaluates the search order if the number of conflicts since
+    /// the last re-evaluation reaches the budget given by
+    /// `order_switch_interval`, doubled after each re-evaluation.
+    ///
+    /// If another order is chosen, all the decisions are undone
+    /// as in a restart, since their positions in the `search_list`
+    /// are changed.
+    fn switch_order_if_due(&mut self) {
+        let interval = match self.config.order_switch_interval {
+            Some(interval) if !self.found => interval,
+            _ => return,
+        };
+        let budget = interval.saturating_mul(1 << self.order_switches.min(63));
+        if self.conflicts - self.last_order_switch < budget {
+            return;
+        }
+        self.order_switches += 1;
+        self.last_order_switch = self.conflicts;
+        let search_order = self.best_search_order();
+        if search_order == self.search_order {
+            return;
+        }
+        if let Some(first) = self
+            .set_stack
+            .iter()
+            .position(|set_cell| matches!(set_cell.reason, Reason::Decide(_)))
+        {
+            self.pop_until(first);
+            self.rule.backtrack(first);
+            self.check_index = self.check_index.min(first);
+        }
+        self.set_search_order(search_order);
+    }
+
+    /// The search order that puts the most active cells first.
+    ///
+    /// It minimizes the sum of the activities of the cells weighted by
+    /// their positions in the order. Ties are broken in favor of the
+    /// current order.
+    ///
+    /// Center-out is not a candidate, since it decides the front last.
+    fn best_search_order(&self) -> SearchOrder {
+        let width = self.config.width;
+        let mut activities = vec![0.0; (width * self.config.height) as usize];
+        for cell in self.search_list.iter() {
+            let (x, y, _) = cell.coord;
+            activities[(y * width + x) as usize] += cell.activity.get();
+        }
+        let score = |search_order| -> f64 {
+            self.config
+                .ordered_coords(search_order)
+                .into_iter()
+                .enumerate()
+                .map(|(i, (x, y))| i as f64 * activities[(y * width + x) as usize])
+                .sum()
+        };
+        let mut best = (self.search_order, score(self.search_order));
+        for &search_order in [
+            SearchOrder::RowFirst,
+            SearchOrder::ColumnFirst,
+            SearchOrder::Diagonal,
+        ]
+        .iter()
+        {
+            let score = score(search_order);
+            if score < best.1 {
+                best = (search_order, score);
+            }
+        }
+        best.0
+    }
+
+    /// Chooses a random state.
+    pub(crate) fn random_state(&mut self) -> State {
+        self.random_count += 1;
+        // Samples a `u32` instead of a `usize`,
+        // so that the result does not depend on the pointer width.
+        State(self.rng.gen_range(0, self.rule.gen() as u32) as usize)
+    }
+
+    /// Chooses a random integer in `0..100`.
+    ///
+    /// It takes a single `u32` from the random number generator,
+    /// the same as `random_state` for rules with 2 states, so that
+    /// a restored search can replay it by `random_state`.
+    fn random_percent(&mut self) -> u32 {
+        self.random_count += 1;
+        ((self.rng.gen::<u32>() as u64 * 100) >> 32) as u32
+    }
+
+    /// Asks the `guide`, if there is one, which unknown cell among
+    /// the first `limit` cells in the `search_list` to decide.
+    ///
+    /// Returns the cell and its position in the `search_list`,
+    /// and the state chosen by the guide if it is valid.
+    /// Returns `None` if the guide leaves the choice to the search.
+    fn ask_guide(&mut self, limit: usize) -> Option<(usize, CellRef<'a, R>, Option<State>)> {
+        let mut guide = self.guide.take()?;
+        let decision = guide.decide(self);
+        self.guide = Some(guide);
+        let (coord, state) = decision?;
+        let cell = self
+            .find_cell(coord)
+            .filter(|cell| cell.state.get().is_none())?;
+        let i = self.search_list[..limit].iter().position(|&c| c == cell)?;
+        let state = state.filter(|state| state.0 < self.rule.gen());
+        Some((i, cell, state))
+    }
+
+    /// Chooses an unknown cell among the first `limit` cells
+    /// in the `search_list`, and its position in the `search_list`.
+    ///
+    /// Uses the `chooser` if there is one, then the activities of the cells
+    /// if `activity_order` is enabled, or the search order otherwise.
+    ///
+    /// When there is a `chooser` or a `guide`, or `activity_order` is enabled,
+    /// the cells before `search_index` might be unknown, so it is not used.
+    fn choose_cell(&mut self, limit: usize) -> Option<(usize, CellRef<'a, R>)> {
+        if let Some(mut chooser) = self.chooser.take() {
+            let coord = chooser.choose(self);
+            self.chooser = Some(chooser);
+            coord
+                .and_then(|coord| self.find_cell(coord))
+                .filter(|cell| cell.state.get().is_none())
+                .and_then(|cell| {
+                    self.search_list[..limit]
+                        .iter()
+                        .position(|&c| c == cell)
+                        .map(|i| (i, cell))
+                })
+                .or_else(|| self.get_unknown(0).filter(|&(i, _)| i < limit))
+        } else if self.config.activity_order {
+            let mut chosen: Option<(usize, CellRef<'a, R>)> = None;
+            for (i, &cell) in self.search_list[..limit].iter().enumerate() {
+                if cell.state.get().is_none()
+                    && chosen.is_none_or(|(_, c)| cell.activity.get() > c.activity.get())
+                {
+                    chosen = Some((i, cell));
+                }
+            }
+            chosen
+        } else {
+            let start = if self.guide.is_some() {
+                0
+            } else {
+                self.search_index
+            };
+            let chosen = self.get_unknown(start).filter(|&(i, _)| i < limit);
+            if let Some((i, _)) = chosen {
+                self.search_index = i + 1;
             }
+            chosen
         }
     }
 
+    /// Whether probing is enabled and due for the current decision.
+    fn probe_due(&self) -> bool {
+        self.config.probe_depth > 0
+            && !R::IS_GEN
+            && self
+                .decisions
+                .is_multiple_of(self.config.probe_interval.max(1))
+    }
+
+    /// Tries both states of an unknown cell, and deduces the consequences
+    /// of each for at most `probe_depth` cells.
+    ///
+    /// Returns the state that the cell must have if the other state
+    /// leads to a conflict, or `None` if neither state does.
+    ///
+    /// This should only be called when all the cells in the `set_stack`
+    /// are examined.
+    fn probe(&mut self, cell: CellRef<'a, R>) -> Option<State> {
+        for &state in [cell.background, !cell.background].iter() {
+            let len = self.set_stack.len();
+            let consistent = self.set_cell(cell, state, Reason::Deduce)
+                && self.proceed_bounded(self.config.probe_depth);
+            self.undo_to(len);
+            if !consistent {
+                return Some(!state);
+            }
+        }
+        None
+    }
+
+    /// Whether the `decision_depth` reaches the cap of `deepening`,
+    /// and there is still an unknown cell among the first `limit` cells
+    /// in the `search_list`, so the next decision would exceed the cap.
+    fn depth_capped(&self, limit: usize) -> bool {
+        self.config.deepening.is_some()
+            && self.decision_depth >= self.depth_cap
+            && self.get_unknown(0).is_some_and(|(i, _)| i < limit)
+    }
+
+    /// Called when the search under the cap of `deepening` is exhausted.
+    ///
+    /// If some branches were cut by the cap, raises the cap,
+    /// forgets the learnt nogoods, which might depend on the cut branches,
+    /// and returns `true`, so that the search starts again
+    /// from the beginning. Otherwise returns `false`.
+    fn deepen(&mut self) -> bool {
+        let step = match self.config.deepening {
+            Some(step) if self.depth_cut => step.max(1),
+            _ => return false,
+        };
+        self.depth_floor = self.depth_cap;
+        self.depth_cap += step;
+        self.depth_cut = false;
+        self.nogoods = Nogoods::default();
+        true
+    }
+
     /// Makes a decision.
     ///
     /// Chooses an unknown cell, assigns a state for it,
     /// and push a reference to it to the `set_stack`.
     ///
+    /// If probing shows that the cell can only have one state,
+    /// sets it to that state without a decision.
+    ///
+    /// Only the first `limit` cells in the `search_list` are considered.
+    ///
     /// Returns `None` is there is no unknown cell,
     /// `Some(false)` if the new state leads to an immediate conflict.
-    fn decide(&mut self) -> Option<bool> {
-        if let Some((i, cell)) = self.get_unknown(self.search_index) {
-            self.search_index = i + 1;
-            let state = match self.config.new_state {
-                NewState::ChooseDead => cell.background,
-                NewState::ChooseAlive => !cell.background,
-                NewState::Random => State(thread_rng().gen_range(0, self.rule.gen())),
+    fn decide(&mut self, limit: usize) -> Option<bool> {
+        // At the cap, the first state of the next decision would need
+        // one more decision, so it is skipped as if it led to a conflict,
+        // and the other states are tried as usual. Above the cap,
+        // which only happens in Generations rules, the branch is cut.
+        let capped = self.depth_capped(limit);
+        if capped && self.decision_depth > self.depth_cap {
+            self.depth_cut = true;
+            return Some(false);
+        }
+        let (chosen, guide_state) = match self.ask_guide(limit) {
+            Some((i, cell, state)) => (Some((i, cell)), state),
+            None => (self.choose_cell(limit), None),
+        };
+        if let Some((i, cell)) = chosen {
+            let probe = self.probe_due();
+            self.decisions += 1;
+            if probe {
+                if let Some(state) = self.probe(cell) {
+                    return Some(self.set_cell(cell, state, Reason::Deduce));
+                }
+            }
+            let features = if self.trace.is_some() || self.policy.is_some() {
+                Some(Features::new(cell))
+            } else {
+                None
             };
-            Some(self.set_cell(cell, state, Reason::Decide(i)))
+            let policy_state = match (guide_state, self.policy.as_mut(), features) {
+                (None, Some(policy), Some(features)) => policy.choose(&features),
+                _ => None,
+            };
+            let state = match guide_state
+                .or(policy_state)
+                .filter(|state| state.0 < self.rule.gen())
+            {
+                Some(state) => state,
+                None => match self.config.new_state {
+                    NewState::ChooseDead => cell.background,
+                    NewState::ChooseAlive => !cell.background,
+                    NewState::Random => self.random_state(),
+                    NewState::Biased(percent) => {
+                        if self.random_percent() < percent as u32 {
+                            !cell.background
+                        } else {
+                            cell.background
+                        }
+                    }
+                },
+            };
+            if let (Some(trace), Some(features)) = (self.trace.as_mut(), features) {
+                trace.decide(features, state);
+            }
+            let result = self.set_cell(cell, state, Reason::Decide(i));
+            if capped {
+                self.depth_cut = true;
+                return Some(false);
+            }
+            Some(result)
         } else {
             None
         }
@@ -227,33 +1144,355 @@ impl<'a, R: Rule> World<'a, R> {
     /// Returns `Found` if a result is found,
     /// `None` if such pattern does not exist,
     /// `Searching` if the number of steps exceeds `max_step`
-    /// and no results are found.
+    /// and no results are found,
+    /// `TimedOut` if the `time_limit` in the configuration is reached,
+    /// `Paused` if it is stopped by the `Stop` handle.
     pub fn search(&mut self, max_step: Option<u64>) -> Status {
+        self.search_with_budget(Budget::default().set_steps(max_step))
+    }
+
+    /// The search function, with limits on the numbers of steps,
+    /// conflicts and decisions in this call.
+    ///
+    /// The return values are the same as `search`, where `Searching`
+    /// means that some limit in the `budget` is exceeded.
+    pub fn search_with_budget(&mut self, budget: Budget) -> Status {
+        match self.config.engine {
+            Engine::Cells => {
+                self.timed(|world| world.search_until(world.search_list.len(), budget))
+            }
+            Engine::Rows => self.timed(|world| world.search_rows(budget)),
+        }
+    }
+
+    /// Abandons the subtree of the last decision, as if it led to
+    /// a conflict, and tries its next state.
+    ///
+    /// This is for steering the search away from a hopeless-looking
+    /// branch by hand. The results in the subtree are never found.
+    /// The next `search` continues from the new branch.
+    ///
+    /// Returns `false` if there is no other branch to try,
+    /// i.e., there are no more results. Nothing is done if there is
+    /// no decision at all.
+    pub fn skip_subtree(&mut self) -> bool {
+        if self.decision_depth == 0 {
+            return false;
+        }
+        self.conflict = None;
+        self.cause = Cause::Unknown;
+        self.revive_dead_ends();
+        self.backup()
+    }
+
+    /// Searches for a partial result, where only the first `lines`
+    /// rows, columns, diagonals or rings are known.
+    ///
+    /// Here the choice of lines depends on the search order.
+    /// The other cells might still be unknown.
+    ///
+    /// A partial result is not checked to be nonempty or to have
+    /// the correct period, unless all lines are known.
+    ///
+    /// The return values are the same as `search`.
+    pub fn search_partial(&mut self, lines: isize, max_step: Option<u64>) -> Status {
+        // The `search_list` is sorted by lines.
+        let limit = self
+            .search_list
+            .iter()
+            .take_while(|cell| self.config.line_index(cell.coord.0, cell.coord.1) < lines)
+            .count();
+        self.timed(|world| world.search_until(limit, Budget::default().set_steps(max_step)))
+    }
+
+    /// Runs the search, and adds its time to `elapsed`.
+    ///
+    /// The time is not measured on WebAssembly, where `Instant::now` panics.
+    fn timed<F: FnOnce(&mut Self) -> Status>(&mut self, f: F) -> Status {
+        #[cfg(not(target_arch = "wasm32"))]
+        let start = Instant::now();
+        let status = f(self);
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.elapsed += start.elapsed();
+        }
+        status
+    }
+
+    /// The current search order, which is the one in the configuration,
+    /// unless it has been switched by `order_switch_interval`.
+    pub fn search_order(&self) -> SearchOrder {
+        self.search_order
+    }
+
+    /// Statistics of the search.
+    pub fn stats(&self) -> Stats {
+        Stats {
+            decisions: self.decisions,
+            conflicts: self.conflicts,
+            propagations: self.propagations,
+            max_depth: self.max_depth,
+            restarts: self.restarts,
+            elapsed: self.elapsed,
+        }
+    }
+
+    /// An estimate of the percentage of the search tree that has been
+    /// explored.
+    ///
+    /// Each decision splits its subtree into one branch for each state.
+    /// The estimate assumes that all the branches at the same depth
+    /// have the same size, and counts the branches to the left of the
+    /// current branch of each decision on the `set_stack` as explored.
+    /// Subtrees that are skipped by backjumping are also counted,
+    /// since the decisions are flipped as if they were exhausted.
+    ///
+    /// It is only a rough estimate: deduced cells make the real tree
+    /// very unbalanced. It starts again from 0 after a restart,
+    /// and is also 0 when the search is exhausted.
+    pub fn progress(&self) -> f64 {
+        let branches = if R::IS_GEN { self.rule.gen() } else { 2 } as f64;
+        let mut weight = 100.0;
+        let mut progress = 0.0;
+        for set_cell in self.set_stack.iter() {
+            let branch = match set_cell.reason {
+                Reason::Decide(_) => 0.0,
+                Reason::TryAnother(_, n) => branches - 1.0 - n as f64,
+                Reason::Deduce if set_cell.flipped => branches - 1.0,
+                _ => continue,
+            };
+            weight /= branches;
+            progress += weight * branch;
+        }
+        progress
+    }
+
+    /// Deduces the states of the cells that are forced by the configuration,
+    /// without deciding any unknown cell.
+    ///
+    /// This includes the consequences of the known cells, the symmetry,
+    /// the boundary and the background.
+    ///
+    /// It should be called before the search starts. A later `search`
+    /// continues from the deduced states.
+    ///
+    /// Returns `false` if the configuration is contradictory.
+    pub fn preview(&mut self) -> bool {
+        !self.known_conflict && self.proceed()
+    }
+
+    /// Searches until the first `limit` cells in the `search_list`
+    /// are all known.
+    fn search_until(&mut self, limit: usize, budget: Budget) -> Status {
+        let complete = limit == self.search_list.len();
         let mut step_count = 0;
-        if self.get_unknown(0).is_none() && !self.backup() {
-            return Status::None;
+        let (conflicts, decisions) = (self.conflicts, self.decisions);
+        if let Some(status) = self.start_deadline() {
+            return status;
+        }
+        let all_known = self.get_unknown(0).is_none_or(|(i, _)| i >= limit);
+        if all_known && self.endgame.is_none() && !self.backup() && !self.deepen() {
+            return self.exhausted();
         }
         while self.go(&mut step_count) {
-            if let Some(result) = self.decide() {
-                if !result && !self.backup() {
-                    return Status::None;
+            // The `limit` of a partial search depends on the search order.
+            if complete && self.endgame.is_none() {
+                self.switch_order_if_due();
+            }
+            self.max_depth = self.max_depth.max(self.set_stack.len());
+            self.record_deepest();
+            let decision = if complete && self.endgame_due() {
+                if self.next_endgame() {
+                    None
+                } else {
+                    Some(false)
+                }
+            } else {
+                self.decide(limit)
+            };
+            if decision.is_none() {
+                // All cells are known, whether it is a result or not.
+                self.revive_dead_ends();
+            }
+            if let Some(result) = decision {
+                if !result && !self.backup() && !self.deepen() {
+                    return self.exhausted();
                 }
-            } else if self.nontrivial() {
-                if self.config.reduce_max {
+            } else if !complete {
+                return self.result_found();
+            } else if self.nontrivial()
+                && !self.is_blocked()
+                && (self.config.deepening.is_none() || self.decision_depth > self.depth_floor)
+            {
+                if self.config.reduce_max || self.config.minimize {
                     self.config.max_cell_count = Some(self.cell_count() - 1);
                 }
-                return Status::Found;
-            } else if !self.backup() {
-                return Status::None;
+                if !self.config.minimize {
+                    return self.result_found();
+                }
+                self.best = Some(
+                    self.search_list
+                        .iter()
+                        .map(|cell| cell.state.get().unwrap())
+                        .collect(),
+                );
+                // In the endgame, the next assignment is tried instead.
+                if self.endgame.is_none() && !self.backup() && !self.deepen() {
+                    return self.exhausted();
+                }
+            } else if self.endgame.is_none() && !self.backup() && !self.deepen() {
+                return self.exhausted();
+            }
+
+            if let Some(status) = self.check_limits(budget, step_count, conflicts, decisions) {
+                return status;
             }
+        }
+        self.exhausted()
+    }
+
+    /// Sets the deadline of the `time_limit` when the first search starts.
+    ///
+    /// Returns `TimedOut` if the deadline has already passed.
+    pub(crate) fn start_deadline(&mut self) -> Option<Status> {
+        let time_limit = self.config.time_limit?;
+        let deadline = *self
+            .deadline
+            .get_or_insert_with(|| Instant::now() + time_limit);
+        if Instant::now() >= deadline {
+            Some(self.timed_out())
+        } else {
+            None
+        }
+    }
+
+    /// Checks whether the search should stop, because the `budget` is
+    /// exceeded, it is stopped by the `Stop` handle, or the deadline
+    /// is reached.
+    ///
+    /// Here `step_count` is the number of steps in this call, and
+    /// `conflicts` and `decisions` are the numbers when it started.
+    /// Returns the status to return if it should stop.
+    pub(crate) fn check_limits(
+        &mut self,
+        budget: Budget,
+        step_count: u64,
+        conflicts: u64,
+        decisions: u64,
+    ) -> Option<Status> {
+        if budget.steps.is_some_and(|max| step_count > max)
+            || budget
+                .conflicts
+                .is_some_and(|max| self.conflicts - conflicts > max)
+            || budget
+                .decisions
+                .is_some_and(|max| self.decisions - decisions > max)
+        {
+            return Some(Status::Searching);
+        }
+        if self.stop.as_ref().is_some_and(Stop::is_stopped) {
+            return Some(Status::Paused);
+        }
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            return Some(self.timed_out());
+        }
+        None
+    }
+
+    /// Records the `set_stack` if more cells are known than ever before,
+    /// when `time_limit` is set.
+    fn record_deepest(&mut self) {
+        if self.deadline.is_some() && self.set_stack.len() > self.deepest.len() {
+            self.deepest = self
+                .set_stack
+                .iter()
+                .map(|set_cell| (set_cell.clone(), set_cell.cell.state.get().unwrap()))
+                .collect();
+        }
+    }
 
-            if let Some(max) = max_step {
-                if step_count > max {
-                    return Status::Searching;
+    /// Called when the `time_limit` is reached.
+    ///
+    /// Undoes the cells that are not in the deepest partial result,
+    /// and sets the cells in it again, with the same reasons and causes,
+    /// so that the world is the same as when it was reached.
+    fn timed_out(&mut self) -> Status {
+        let deepest = mem::take(&mut self.deepest);
+        if deepest.len() > self.set_stack.len() {
+            let common = self
+                .set_stack
+                .iter()
+                .zip(deepest.iter())
+                .take_while(|(set_cell, (cell, state))| {
+                    set_cell.cell == cell.cell && set_cell.cell.state.get() == Some(*state)
+                })
+                .count();
+            self.pop_until(common);
+            self.rule.backtrack(common);
+            self.check_index = self.check_index.min(common);
+            self.search_index = 0;
+            for (set_cell, state) in deepest[common..].iter() {
+                let cell = set_cell.cell;
+                if let (Reason::Decide(_), Some(trace)) = (set_cell.reason, self.trace.as_mut()) {
+                    trace.decide(Features::new(cell), *state);
                 }
+                self.cause = set_cell.cause.clone();
+                let result = self.set_cell(cell, *state, set_cell.reason);
+                self.cause = Cause::Unknown;
+                self.set_stack.last_mut().unwrap().flipped = set_cell.flipped;
+                if !result {
+                    // The bounds might have been lowered by `reduce_max`.
+                    self.pop_until(self.set_stack.len() - 1);
+                    break;
+                }
+            }
+            // Deduces the rule for rule ranges.
+            self.proceed();
+        }
+        self.deepest = deepest;
+        Status::TimedOut
+    }
+
+    /// Called when there are no more results.
+    ///
+    /// If `minimize` is enabled and some result has been found,
+    /// sets the cells to the result with the smallest cell count,
+    /// and returns `Found`. Otherwise returns `None`.
+    ///
+    /// The restored cells are deduced rather than decided,
+    /// so the next search will return `None`.
+    pub(crate) fn exhausted(&mut self) -> Status {
+        let best = match self.best.take() {
+            Some(best) => best,
+            None => return Status::None,
+        };
+        for (i, &state) in best.iter().enumerate() {
+            let cell = self.search_list[i];
+            if cell.state.get().is_none() {
+                self.set_cell(cell, state, Reason::Deduce);
             }
         }
-        Status::None
+        // Deduces the rule for rule ranges.
+        self.proceed();
+        self.result_found()
+    }
+
+    /// Called when a result is found.
+    ///
+    /// Records it in the trace, and calls the hooks.
+    pub(crate) fn result_found(&mut self) -> Status {
+        if let Some(trace) = self.trace.as_mut() {
+            trace.found();
+        }
+        self.found = true;
+        if let Some(mut hooks) = self.hooks.take() {
+            hooks.on_solution(self);
+            self.hooks = Some(hooks);
+        }
+        Status::Found
     }
 
     /// Set the max cell counts.
@@ -267,4 +1506,63 @@ impl<'a, R: Rule> World<'a, R> {
             }
         }
     }
+
+    /// Starts or stops recording the decisions during the search.
+    ///
+    /// Stopping the recording discards the recorded decisions.
+    pub(crate) fn set_trace(&mut self, trace: bool) {
+        self.trace = if trace { Some(Trace::default()) } else { None };
+    }
+
+    /// Sets a handle to stop the search from another thread.
+    pub(crate) fn set_stop(&mut self, stop: Option<Stop>) {
+        self.stop = stop;
+    }
+
+    /// Takes the recorded decisions whose outcomes are known.
+    pub(crate) fn take_trace(&mut self) -> Vec<TraceEntry> {
+        self.trace
+            .as_mut()
+            .map_or_else(Vec::new, |trace| trace.take())
+    }
+
+    /// Sets a branching heuristic that chooses the states of unknown cells.
+    pub(crate) fn set_policy(&mut self, policy: Option<Box<dyn Policy>>) {
+        self.policy = policy;
+    }
+
+    /// Sets a branching heuristic that chooses the next unknown cell.
+    pub(crate) fn set_chooser(&mut self, chooser: Option<Box<dyn ChooseCell>>) {
+        self.chooser = chooser;
+        // The `search_index` is not maintained when there is a chooser.
+        self.search_index = 0;
+    }
+
+    /// Sets an external agent that makes the decisions.
+    pub(crate) fn set_guide(&mut self, guide: Option<Box<dyn Guide>>) {
+        self.guide = guide;
+        // The `search_index` is not maintained when there is a guide.
+        self.search_index = 0;
+    }
+
+    /// Sets the callbacks on the events in the search.
+    pub(crate) fn set_hooks(&mut self, hooks: Option<Box<dyn Hooks>>) {
+        self.hooks = hooks;
+    }
+}
+
+/// The factor by which the activities of the cells decay after each conflict.
+const ACTIVITY_DECAY: f64 = 0.95;
+
+/// The `i`-th term of the Luby sequence `1, 1, 2, 1, 1, 2, 4, 1, 1, 2, ...`,
+/// starting from `i = 1`.
+fn luby(mut i: u64) -> u64 {
+    loop {
+        // The smallest `k` such that `i <= 2^k - 1`.
+        let k = 64 - i.leading_zeros();
+        if i == (1 << k) - 1 {
+            return 1 << (k - 1);
+        }
+        i -= (1 << (k - 1)) - 1;
+    }
 }
@@ -0,0 +1,68 @@
+//! A context for repeated searches.
+
+use crate::{
+    config::{Config, Neighborhood},
+    error::Error,
+    rules::AnyRule,
+    traits::Search,
+};
+use std::collections::HashMap;
+
+/// A context for creating many worlds.
+///
+/// Parsing a rule and generating its tables of implications
+/// can take much longer than creating a small world,
+/// especially for non-totalistic rules.
+///
+/// A `Session` keeps the parsed rules, so that worlds with the same rule
+/// string only generate the tables once. This is useful when searching
+/// with many different configurations.
+///
+/// The tables are shared by all the worlds created from the same rule,
+/// rather than copied. The cells of each world are still allocated
+/// separately.
+#[derive(Clone, Default)]
+pub struct Session {
+    /// Parsed rules, indexed by their rule strings and built-in neighborhoods.
+    rules: HashMap<(String, Option<Neighborhood>), AnyRule>,
+}
+
+impl Session {
+    /// Creates a new empty session.
+    pub fn new() -> Self {
+        Session::default()
+    }
+
+    /// Creates a new world from the configuration.
+    ///
+    /// The rule is parsed only if it is not used in this session before.
+    ///
+    /// Returns an error if the rule string is invalid,
+    /// if the width, the height or the period is not positive,
+    /// if the size of the world is invalid for the symmetry
+    /// or the transformation, if the glide symmetry does not agree with
+    /// the period or the translation, or if the rule contains `B0`
+    /// and the period is odd.
+    pub fn world(&mut self, config: &Config) -> Result<Box<dyn Search>, Error> {
+        let config = &config.checked()?;
+        let key = (config.rule_string.clone(), config.neighborhood);
+        let rule = if let Some(rule) = self.rules.get(&key) {
+            rule.clone()
+        } else {
+            let rule = AnyRule::from_config(config)?;
+            self.rules.insert(key, rule.clone());
+            rule
+        };
+        rule.world(config)
+    }
+
+    /// Number of rules kept in this session.
+    pub fn rule_count(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// Forgets all the parsed rules.
+    pub fn clear(&mut self) {
+        self.rules.clear();
+    }
+}
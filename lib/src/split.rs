@@ -0,0 +1,250 @@
+//! Splitting the search into subtrees, and searching them in parallel.
+
+use crate::{
+    cells::State,
+    config::{Config, KnownCell},
+    error::Error,
+    search::Status,
+    traits::Search,
+};
+use std::{
+    collections::BTreeMap,
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+/// A search which is split into subtrees by the states of the first
+/// few unknown cells, which are searched independently.
+///
+/// Each subtree fixes the states of the first `depth` cells that are
+/// still unknown after `Search::preview`, in the search order.
+/// There is a subtree for each combination of their states,
+/// so each result is found in exactly one subtree.
+///
+/// The subtrees are distributed across worker threads,
+/// and the results are sent back through a channel as they are found,
+/// so they are not in the same order as in a normal search,
+/// unless `ordered` is set.
+/// Options that depend on the previous results, e.g., `reduce_max`
+/// and `minimize`, only apply within a subtree.
+#[derive(Clone, Debug)]
+pub struct Split {
+    /// World configuration.
+    config: Config,
+
+    /// Number of cells whose states are fixed in each subtree.
+    depth: usize,
+
+    /// Whether the results are sent in the order of the subtrees.
+    ordered: bool,
+}
+
+impl Split {
+    /// Creates a new split search from the configuration.
+    ///
+    /// By default the states of 4 cells are fixed in each subtree.
+    pub fn new(config: Config) -> Self {
+        Split {
+            config,
+            depth: 4,
+            ordered: false,
+        }
+    }
+
+    /// Sets the number of cells whose states are fixed in each subtree.
+    pub fn set_depth(mut self, depth: usize) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    /// Sets whether the results are sent in the order of the subtrees,
+    /// as in `Split::subtrees`.
+    ///
+    /// Then the results are always in the same order, regardless of
+    /// the number of threads, so a parallel search is reproducible,
+    /// provided that the search in each subtree is deterministic,
+    /// e.g., the seed is given if the states are chosen randomly.
+    ///
+    /// The results of a subtree are held back until all the earlier
+    /// subtrees are exhausted, so they may arrive in bursts.
+    pub fn set_ordered(mut self, ordered: bool) -> Self {
+        self.ordered = ordered;
+        self
+    }
+
+    /// The known cells of each subtree, in addition to the known cells
+    /// in the configuration.
+    ///
+    /// Returns an empty list if the configuration is contradictory.
+    pub fn subtrees(&self) -> Result<Vec<Vec<KnownCell>>, Error> {
+        let mut search = self.config.world()?;
+        if !search.preview() {
+            return Ok(Vec::new());
+        }
+        let order = self
+            .config
+            .search_order
+            .unwrap_or_else(|| self.config.auto_search_order());
+        let mut coords = Vec::new();
+        for (x, y) in self.config.ordered_coords(order) {
+            for t in 0..self.config.period {
+                if coords.len() < self.depth && search.get_cell_state((x, y, t))?.is_none() {
+                    coords.push((x, y, t));
+                }
+            }
+        }
+        let mut subtrees = vec![Vec::new()];
+        for coord in coords {
+            subtrees = subtrees
+                .into_iter()
+                .flat_map(|known_cells: Vec<KnownCell>| {
+                    (0..search.gen()).map(move |state| {
+                        let mut known_cells = known_cells.clone();
+                        known_cells.push(KnownCell {
+                            coord,
+                            state: State(state),
+                        });
+                        known_cells
+                    })
+                })
+                .collect();
+        }
+        Ok(subtrees)
+    }
+
+    /// Searches all the subtrees with `threads` worker threads.
+    ///
+    /// Each result is sent through the returned channel as the states
+    /// of all the known cells, which can be turned into a world by
+    /// `Split::world`. The channel is closed when all the subtrees
+    /// are exhausted. Dropping the receiver stops the workers
+    /// after their current results.
+    ///
+    /// The `time_limit` applies to the search in each subtree. If it is
+    /// reached, a `SplitTimeLimitError` is sent instead of the remaining
+    /// results of the subtree.
+    pub fn run(&self, threads: usize) -> Result<Receiver<Result<Vec<KnownCell>, Error>>, Error> {
+        // Reversed, so that the subtrees are taken in order.
+        let queue: Vec<_> = self.subtrees()?.into_iter().enumerate().rev().collect();
+        let queue = Arc::new(Mutex::new(queue));
+        let (sender, receiver) = mpsc::channel();
+        let (message_sender, message_receiver) = mpsc::channel();
+        for _ in 0..threads.max(1) {
+            let config = self.config.clone();
+            let queue = Arc::clone(&queue);
+            let sender = message_sender.clone();
+            thread::spawn(move || work(&config, &queue, &sender));
+        }
+        let ordered = self.ordered;
+        thread::spawn(move || collect(message_receiver, &sender, ordered));
+        Ok(receiver)
+    }
+
+    /// Creates a world from a result sent by `Split::run`.
+    ///
+    /// The states of all cells are known. Calling `search` on this world
+    /// would return `Status::None`.
+    pub fn world(&self, known_cells: Vec<KnownCell>) -> Result<Box<dyn Search>, Error> {
+        let mut all_known_cells = self.config.known_cells.clone();
+        all_known_cells.extend(known_cells);
+        self.config.clone().set_known_cells(all_known_cells).world()
+    }
+}
+
+/// A message from a worker thread.
+enum Message {
+    /// A result in the subtree at this position.
+    Result(usize, Result<Vec<KnownCell>, Error>),
+
+    /// The subtree at this position is done, either exhausted,
+    /// or stopped with an error.
+    Done(usize),
+}
+
+/// Takes subtrees from the queue and searches them, until the queue
+/// is empty or the receiver is dropped.
+fn work(config: &Config, queue: &Mutex<Vec<(usize, Vec<KnownCell>)>>, sender: &Sender<Message>) {
+    loop {
+        let (index, subtree) = match queue.lock().expect("a search thread panicked").pop() {
+            Some(subtree) => subtree,
+            None => return,
+        };
+        let mut known_cells = config.known_cells.clone();
+        known_cells.extend(subtree);
+        let mut search = match config.clone().set_known_cells(known_cells).world() {
+            Ok(search) => search,
+            Err(e) => {
+                let _ = sender.send(Message::Result(index, Err(e)));
+                let _ = sender.send(Message::Done(index));
+                return;
+            }
+        };
+        let mut solutions = search.solutions();
+        for known_cells in &mut solutions {
+            if sender
+                .send(Message::Result(index, Ok(known_cells)))
+                .is_err()
+            {
+                return;
+            }
+        }
+        if solutions.status() != Status::None
+            && sender
+                .send(Message::Result(
+                    index,
+                    Err(Error::SplitTimeLimitError(index)),
+                ))
+                .is_err()
+        {
+            return;
+        }
+        if sender.send(Message::Done(index)).is_err() {
+            return;
+        }
+    }
+}
+
+/// Passes the results from the workers on to the receiver of `Split::run`,
+/// until all the workers finish or the receiver is dropped.
+///
+/// If `ordered` is true, the results of a subtree are held back
+/// until all the earlier subtrees are done.
+fn collect(
+    receiver: Receiver<Message>,
+    sender: &Sender<Result<Vec<KnownCell>, Error>>,
+    ordered: bool,
+) {
+    // The first subtree that is not done yet.
+    let mut next = 0;
+    // The held back results of the later subtrees, and whether they are done.
+    let mut pending = BTreeMap::<usize, (Vec<_>, bool)>::new();
+    for message in receiver {
+        match message {
+            Message::Result(index, result) if !ordered || index == next => {
+                if sender.send(result).is_err() {
+                    return;
+                }
+            }
+            Message::Result(index, result) => pending.entry(index).or_default().0.push(result),
+            Message::Done(_) if !ordered => (),
+            Message::Done(index) => {
+                pending.entry(index).or_default().1 = true;
+                while let Some((results, done)) = pending.get_mut(&next) {
+                    for result in results.drain(..) {
+                        if sender.send(result).is_err() {
+                            return;
+                        }
+                    }
+                    if !*done {
+                        break;
+                    }
+                    pending.remove(&next);
+                    next += 1;
+                }
+            }
+        }
+    }
+}
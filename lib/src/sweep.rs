@@ -0,0 +1,90 @@
+//! Searching with the same configuration in many rules.
+
+use crate::{config::Config, error::Error, search::Status, session::Session};
+use ca_rules::ParseLife;
+
+/// The `b` / `s` data of a totalistic Life-like rule.
+struct LifeBs {
+    b: Vec<u8>,
+    s: Vec<u8>,
+}
+
+impl ParseLife for LifeBs {
+    fn from_bs(b: Vec<u8>, s: Vec<u8>) -> Self {
+        LifeBs { b, s }
+    }
+}
+
+/// Writes a totalistic Life-like rule string from the `b` / `s` data.
+fn rule_string(b: u16, s: u16) -> String {
+    let digits = |set: u16| -> String {
+        (0..=8)
+            .filter(|i| set >> i & 1 == 1)
+            .map(|i| (b'0' + i as u8) as char)
+            .collect()
+    };
+    format!("B{}/S{}", digits(b), digits(s))
+}
+
+/// Lists all totalistic Life-like rules between two rules.
+///
+/// A rule is between `min` and `max` if it contains all the transitions
+/// of `min`, and only contains transitions of `max`. For example, there
+/// are 4 rules between `B3/S23` and `B36/S238`: `B3/S23`, `B36/S23`,
+/// `B3/S238` and `B36/S238`.
+///
+/// Returns an error if either rule is not a totalistic Life-like rule,
+/// or if `min` is not contained in `max`.
+pub fn rule_range(min: &str, max: &str) -> Result<Vec<String>, Error> {
+    let to_bits = |input: &str| -> Result<(u16, u16), Error> {
+        let LifeBs { b, s } = LifeBs::parse_rule(input)?;
+        let bits = |set: Vec<u8>| set.iter().fold(0, |acc, &i| acc | 1 << i);
+        Ok((bits(b), bits(s)))
+    };
+    let (min_b, min_s) = to_bits(min)?;
+    let (max_b, max_s) = to_bits(max)?;
+    if min_b & !max_b != 0 || min_s & !max_s != 0 {
+        return Err(Error::RuleRangeError(min.to_string(), max.to_string()));
+    }
+
+    // Enumerates the subsets of the free transitions.
+    let free = (max_b ^ min_b) as u32 | ((max_s ^ min_s) as u32) << 9;
+    let mut rules = Vec::new();
+    let mut subset = 0_u32;
+    loop {
+        let bits = subset | min_b as u32 | (min_s as u32) << 9;
+        rules.push(rule_string(bits as u16 & 0x1ff, (bits >> 9) as u16));
+        if subset == free {
+            break;
+        }
+        subset = (subset | !free).wrapping_add(1) & free;
+    }
+    Ok(rules)
+}
+
+impl Session {
+    /// Searches with the same configuration in each of the rules,
+    /// replacing the rule string of the configuration.
+    ///
+    /// Rules are parsed in this session, so that their tables are reused
+    /// in later searches.
+    ///
+    /// Returns the status of the search for each rule: `Found` if the rule
+    /// admits a result, `None` if it does not, and `Searching` if the number
+    /// of steps exceeds `max_step`.
+    pub fn sweep(
+        &mut self,
+        config: &Config,
+        rule_strings: &[String],
+        max_step: Option<u64>,
+    ) -> Result<Vec<Status>, Error> {
+        rule_strings
+            .iter()
+            .map(|rule_string| {
+                let config = config.clone().set_rule_string(rule_string);
+                let mut search = self.world(&config)?;
+                Ok(search.search(max_step))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,255 @@
+//! Recording the decisions during the search, and choosing states by a policy.
+//!
+//! This is for experiments on learned branching heuristics:
+//! a trace of the decisions and their outcomes can be exported
+//! in a compact binary format, and a model trained on the trace
+//! can be plugged back in as a `Policy`.
+
+use crate::{
+    cells::{CellRef, Coord, State, ALIVE, DEAD},
+    config::Config,
+    header::{FileKind, Header},
+    rules::Rule,
+};
+use std::io::{self, Read, Write};
+
+/// Encodes a state as 2 bits.
+///
+/// * `0b00` means unknown,
+/// * `0b01` means dead,
+/// * `0b10` means alive,
+/// * `0b11` means any other state, e.g., dying in Generations rules.
+fn encode(state: Option<State>) -> u32 {
+    match state {
+        None => 0b00,
+        Some(DEAD) => 0b01,
+        Some(ALIVE) => 0b10,
+        Some(_) => 0b11,
+    }
+}
+
+/// The states around an unknown cell when it is decided.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Features {
+    /// The coordinates of the cell.
+    pub coord: Coord,
+
+    /// The states of the neighbors, the predecessor and the successor.
+    ///
+    /// It is a 28-bit integer, with 2 bits for each cell, encoded as:
+    ///
+    /// * `0b00` means unknown,
+    /// * `0b01` means dead,
+    /// * `0b10` means alive,
+    /// * `0b11` means any other state, e.g., dying in Generations rules.
+    ///
+    /// The `i`-th neighbor is in bits `2 * i` and `2 * i + 1`.
+    /// See `Rule::nbhd` for the order of the neighbors.
+    /// The predecessor is in bits 24 and 25, and the successor is in
+    /// bits 26 and 27.
+    pub desc: u32,
+}
+
+impl Features {
+    /// Collects the features of a cell.
+    pub(crate) fn new<R: Rule>(cell: CellRef<R>) -> Self {
+        let mut desc = 0;
+        for (i, neigh) in cell.nbhd.iter().enumerate() {
+            desc |= encode(neigh.and_then(|n| n.state.get())) << (2 * i);
+        }
+        desc |= encode(cell.pred.and_then(|c| c.state.get())) << 24;
+        desc |= encode(cell.succ.and_then(|c| c.state.get())) << 26;
+        Features {
+            coord: cell.coord,
+            desc,
+        }
+    }
+
+    /// The encoded state of the `i`-th neighbor.
+    pub fn neighbor(&self, i: usize) -> u8 {
+        (self.desc >> (2 * i) & 0b11) as u8
+    }
+
+    /// The encoded state of the predecessor.
+    pub fn pred(&self) -> u8 {
+        (self.desc >> 24 & 0b11) as u8
+    }
+
+    /// The encoded state of the successor.
+    pub fn succ(&self) -> u8 {
+        (self.desc >> 26 & 0b11) as u8
+    }
+}
+
+/// The eventual outcome of a decision.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// The decision is backtracked without finding any result.
+    Conflict,
+    /// A result is found before the decision is backtracked.
+    Found,
+}
+
+/// A decision and its outcome.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TraceEntry {
+    /// The states around the cell when it is decided.
+    pub features: Features,
+
+    /// The state chosen for the cell.
+    pub state: State,
+
+    /// The eventual outcome of the decision.
+    pub outcome: Outcome,
+}
+
+impl TraceEntry {
+    /// Size of an entry in the binary format, in bytes.
+    pub const SIZE: usize = 18;
+
+    /// Writes the entry in the binary format.
+    ///
+    /// An entry consists of the 3 coordinates as `i32`s, the `desc`
+    /// of the features as a `u32`, the state as a `u8` and the outcome
+    /// as a `u8` (`0` for `Conflict`, `1` for `Found`).
+    /// All numbers are little-endian.
+    ///
+    /// States larger than 255 are written as 255.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let mut bytes = [0; Self::SIZE];
+        let (x, y, t) = self.features.coord;
+        bytes[0..4].copy_from_slice(&(x as i32).to_le_bytes());
+        bytes[4..8].copy_from_slice(&(y as i32).to_le_bytes());
+        bytes[8..12].copy_from_slice(&(t as i32).to_le_bytes());
+        bytes[12..16].copy_from_slice(&self.features.desc.to_le_bytes());
+        bytes[16] = self.state.0.min(255) as u8;
+        bytes[17] = match self.outcome {
+            Outcome::Conflict => 0,
+            Outcome::Found => 1,
+        };
+        writer.write_all(&bytes)
+    }
+
+    /// Reads an entry in the binary format.
+    pub fn read_from<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let mut bytes = [0; Self::SIZE];
+        reader.read_exact(&mut bytes)?;
+        let int = |i: usize| {
+            let mut int = [0; 4];
+            int.copy_from_slice(&bytes[i..i + 4]);
+            int
+        };
+        let coord = (
+            i32::from_le_bytes(int(0)) as isize,
+            i32::from_le_bytes(int(4)) as isize,
+            i32::from_le_bytes(int(8)) as isize,
+        );
+        let desc = u32::from_le_bytes(int(12));
+        let outcome = match bytes[17] {
+            0 => Outcome::Conflict,
+            1 => Outcome::Found,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "invalid outcome",
+                ))
+            }
+        };
+        Ok(TraceEntry {
+            features: Features { coord, desc },
+            state: State(bytes[16] as usize),
+            outcome,
+        })
+    }
+}
+
+/// Writes the entries of a trace in the binary format.
+///
+/// The entries are preceded by a `Header` of kind `FileKind::Trace`,
+/// describing the configuration of the search.
+pub fn write_trace<W: Write>(
+    config: &Config,
+    entries: &[TraceEntry],
+    writer: &mut W,
+) -> io::Result<()> {
+    Header::new(FileKind::Trace, config).write_to(writer)?;
+    for entry in entries {
+        entry.write_to(writer)?;
+    }
+    Ok(())
+}
+
+/// Reads the header and all the entries of a trace in the binary format.
+///
+/// Returns an error if the header is invalid. See `Header::read_from`.
+pub fn read_trace<R: Read>(reader: &mut R) -> io::Result<(Header, Vec<TraceEntry>)> {
+    let header = Header::read_from(reader, FileKind::Trace)?;
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    if bytes.len() % TraceEntry::SIZE != 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "incomplete trace entry",
+        ));
+    }
+    let entries = bytes
+        .chunks(TraceEntry::SIZE)
+        .map(|mut chunk| TraceEntry::read_from(&mut chunk))
+        .collect::<io::Result<_>>()?;
+    Ok((header, entries))
+}
+
+/// A branching heuristic that chooses the state of an unknown cell.
+pub trait Policy {
+    /// Chooses a state for an unknown cell.
+    ///
+    /// Returns `None` to fall back to the `new_state` in the configuration.
+    /// Invalid states are also ignored.
+    fn choose(&mut self, features: &Features) -> Option<State>;
+}
+
+/// Records the decisions during the search.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Trace {
+    /// Decisions that are still on the `set_stack`,
+    /// and whether a result is found after each of them.
+    pending: Vec<(Features, State, bool)>,
+
+    /// Decisions whose outcomes are known.
+    entries: Vec<TraceEntry>,
+}
+
+impl Trace {
+    /// Records a new decision.
+    pub(crate) fn decide(&mut self, features: Features, state: State) {
+        self.pending.push((features, state, false));
+    }
+
+    /// Records that the last decision is backtracked.
+    pub(crate) fn backtrack(&mut self) {
+        if let Some((features, state, found)) = self.pending.pop() {
+            let outcome = if found {
+                Outcome::Found
+            } else {
+                Outcome::Conflict
+            };
+            self.entries.push(TraceEntry {
+                features,
+                state,
+                outcome,
+            });
+        }
+    }
+
+    /// Records that a result is found.
+    pub(crate) fn found(&mut self) {
+        for pending in self.pending.iter_mut() {
+            pending.2 = true;
+        }
+    }
+
+    /// Takes the decisions whose outcomes are known.
+    pub(crate) fn take(&mut self) -> Vec<TraceEntry> {
+        std::mem::take(&mut self.entries)
+    }
+}
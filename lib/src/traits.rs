@@ -1,13 +1,22 @@
 //! A trait for `World`.
 use crate::{
+    analysis::Analysis,
     cells::{Coord, State, ALIVE, DEAD},
-    config::Config,
+    choose::ChooseCell,
+    config::{Config, KnownCell, SearchOrder},
     error::Error,
-    rules::Rule,
-    search::Status,
+    guide::Guide,
+    hooks::Hooks,
+    rules::{resolve_alias, CanonicalRule, Rule},
+    search::{Budget, Stats, Status, Stop},
+    trace::{Policy, TraceEntry},
     world::World,
 };
-use std::fmt::Write;
+use std::{
+    collections::HashSet,
+    io::{self, Write},
+    ops::Range,
+};
 
 #[cfg(feature = "serialize")]
 use crate::save::WorldSer;
@@ -21,9 +30,46 @@ pub trait Search {
     /// Returns `Found` if a result is found,
     /// `None` if such pattern does not exist,
     /// `Searching` if the number of steps exceeds `max_step`
-    /// and no results are found.
+    /// and no results are found,
+    /// `TimedOut` if the `time_limit` in the configuration is reached,
+    /// `Paused` if it is stopped by the `Stop` handle.
     fn search(&mut self, max_step: Option<u64>) -> Status;
 
+    /// The search function, with limits on the numbers of steps,
+    /// conflicts and decisions in this call.
+    ///
+    /// The return values are the same as `search`, where `Searching`
+    /// means that some limit in the `budget` is exceeded.
+    fn search_with_budget(&mut self, budget: Budget) -> Status;
+
+    /// Abandons the subtree of the last decision, as if it led to
+    /// a conflict, and tries its next state.
+    ///
+    /// Returns `false` if there is no other branch to try,
+    /// i.e., there are no more results.
+    fn skip_subtree(&mut self) -> bool;
+
+    /// An iterator over the results, which continues the search
+    /// after each result.
+    ///
+    /// See `Solutions` for details.
+    fn solutions(&mut self) -> Solutions<'_>;
+
+    /// Searches for a partial result, where only the first `lines`
+    /// rows or columns are known.
+    ///
+    /// Here the choice of rows or columns depends on the search order.
+    /// The other cells might still be unknown.
+    ///
+    /// The return values are the same as `search`.
+    fn search_partial(&mut self, lines: isize, max_step: Option<u64>) -> Status;
+
+    /// Deduces the states of the cells that are forced by the configuration,
+    /// without deciding any unknown cell.
+    ///
+    /// Returns `false` if the configuration is contradictory.
+    fn preview(&mut self) -> bool;
+
     /// Gets the state of a cell. Returns `Err(())` if there is no such cell.
     fn get_cell_state(&self, coord: Coord) -> Result<Option<State>, Error>;
 
@@ -33,12 +79,24 @@ pub trait Search {
     /// Whether the rule is a Generations rule.
     fn is_gen_rule(&self) -> bool;
 
+    /// Number of states of the rule.
+    ///
+    /// It is 2 for rules that are not Generations rules.
+    fn gen(&self) -> usize;
+
     /// Whether the rule contains `B0`.
     ///
     /// In other words, whether a cell would become `Alive` in the next
     /// generation, if all its neighbors in this generation are dead.
     fn is_b0_rule(&self) -> bool;
 
+    /// The minimal and the maximal rules that agree with the transitions
+    /// deduced so far, for a rule range like `B3/S23..B38/S238`.
+    ///
+    /// When a result is found, both rules support it, and so does every
+    /// rule between them. Returns `None` if the rule is not a rule range.
+    fn deduced_rules(&self) -> Option<(String, String)>;
+
     /// Number of known living cells in some generation.
     ///
     /// For Generations rules, dying cells are not counted.
@@ -52,12 +110,109 @@ pub trait Search {
     /// Number of conflicts during the search.
     fn conflicts(&self) -> u64;
 
+    /// Number of restarts during the search.
+    fn restarts(&self) -> u64;
+
+    /// The current search order, which is the one in the configuration,
+    /// unless it has been switched by `order_switch_interval`.
+    fn search_order(&self) -> SearchOrder;
+
+    /// Statistics of the search.
+    fn stats(&self) -> Stats;
+
+    /// An estimate of the percentage of the search tree that has been
+    /// explored, from the depths of the decisions that have been
+    /// flipped by backtracking.
+    ///
+    /// It is only a rough estimate, and starts again from 0
+    /// after a restart.
+    fn progress(&self) -> f64;
+
+    /// Analyzes the actual period, transformation, translation
+    /// and symmetry of the found pattern, which may be different from
+    /// the configuration.
+    ///
+    /// Unknown cells are considered to be in the background state.
+    fn analyze(&self) -> Analysis;
+
+    /// A canonical form of the found pattern, as a sorted list of
+    /// the coordinates and states of the cells that are not in
+    /// the background state.
+    ///
+    /// It is the same for all the phases, translations, rotations
+    /// and reflections of the pattern, so it can be used to find
+    /// duplicate results. Only the transformations of the square grid
+    /// are considered.
+    ///
+    /// Unknown cells are considered to be in the background state.
+    fn canonical_form(&self) -> Vec<(isize, isize, State)>;
+
     /// Set the max cell counts.
     ///
     /// Currently this is the only parameter that you can change
     /// during the search.
     fn set_max_cell_count(&mut self, max_cell_count: Option<usize>);
 
+    /// Starts or stops recording the decisions during the search.
+    ///
+    /// Each decision is recorded with the states around the cell,
+    /// and whether a result is found before it is backtracked.
+    /// Stopping the recording discards the recorded decisions.
+    fn set_trace(&mut self, trace: bool);
+
+    /// Sets a handle to stop the search from another thread,
+    /// or removes it.
+    ///
+    /// When the handle is stopped, the search returns `Paused` at the end
+    /// of the current step, and can be resumed after the handle is reset.
+    fn set_stop(&mut self, stop: Option<Stop>);
+
+    /// Takes the recorded decisions whose outcomes are known.
+    ///
+    /// Decisions that are not backtracked yet are not included.
+    fn take_trace(&mut self) -> Vec<TraceEntry>;
+
+    /// Sets a branching heuristic that chooses the states of unknown cells.
+    ///
+    /// If it is `None`, the states are chosen according to `new_state`
+    /// in the configuration.
+    fn set_policy(&mut self, policy: Option<Box<dyn Policy>>);
+
+    /// Sets a branching heuristic that chooses the next unknown cell
+    /// to decide.
+    ///
+    /// If it is `None`, the cells are chosen according to `search_order`
+    /// in the configuration.
+    fn set_chooser(&mut self, chooser: Option<Box<dyn ChooseCell>>);
+
+    /// Sets an external agent that makes the decisions, or removes it.
+    ///
+    /// The guide is asked before the `chooser` and the `policy`,
+    /// which are used for whatever it leaves open.
+    fn set_guide(&mut self, guide: Option<Box<dyn Guide>>);
+
+    /// Sets the callbacks on the events in the search, or removes them.
+    fn set_hooks(&mut self, hooks: Option<Box<dyn Hooks>>);
+
+    /// The cells whose states are chosen on the current search path,
+    /// in order, i.e., the decisions, and the cells whose other states
+    /// are tried after a decision leads to a conflict.
+    ///
+    /// They can be written to a file by `write_decisions`,
+    /// and replayed by `Search::replay`.
+    fn decision_path(&self) -> Vec<KnownCell>;
+
+    /// Replays the decisions, e.g., from `Search::decision_path`,
+    /// to reach the same search position.
+    ///
+    /// The deductions from learnt nogoods and from probing are not
+    /// recorded in the path, so the position is only reproduced exactly
+    /// if the search that recorded it learnt no nogood and did no probing.
+    ///
+    /// Returns `Ok(false)` if the decisions lead to a conflict.
+    /// Returns an error if some decision is invalid.
+    fn replay(&mut self, decisions: &[KnownCell]) -> Result<bool, Error>;
+
     #[cfg(feature = "serialize")]
     /// Saves the world as a `WorldSer`,
     /// which can be easily serialized.
@@ -74,40 +229,103 @@ pub trait Search {
     /// * **Unknown** cells are represented by `?`;
     /// * Each line is ended with `$`;
     /// * The whole pattern is ended with `!`.
+    ///
+    /// When the states are chosen randomly, the output starts with
+    /// a comment line `#C seed <seed>`, so that the search can be reproduced.
+    ///
+    /// For large worlds, consider `write_rle_gen` instead,
+    /// which does not build the whole string in memory.
     fn rle_gen(&self, t: isize) -> String {
-        let mut str = String::new();
+        let mut buf = Vec::new();
+        self.write_rle_gen(t, &mut buf).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    /// Writes the whole world in some generation into a writer,
+    /// in the same format as `rle_gen`.
+    fn write_rle_gen(&self, t: isize, writer: &mut dyn Write) -> io::Result<()> {
+        let (width, height) = (self.config().width, self.config().height);
+        self.write_rle_rect(t, 0, 0, width, height, writer)
+    }
+
+    /// Writes a rectangle of the world in some generation into a writer,
+    /// in the same format as `rle_gen`.
+    ///
+    /// The rectangle has its top-left corner at `(x, y)`, and is clipped
+    /// to the search range.
+    ///
+    /// The output is written line by line, so that very large worlds
+    /// can be displayed without building the whole string in memory.
+    ///
+    /// The rule in the header is the canonical form of the rule string
+    /// (or of the rule string of the alias) for rules with the Moore neighborhood.
+    /// The suffix of the bounded grid, if any, is kept. See `CanonicalRule` for details.
+    /// For a rule range, it is the minimal deduced rule.
+    fn write_rle_rect(
+        &self,
+        t: isize,
+        x: isize,
+        y: isize,
+        width: isize,
+        height: isize,
+        writer: &mut dyn Write,
+    ) -> io::Result<()> {
+        let (x_range, y_range) = clip_rect(self.config(), x, y, width, height);
+        let rule_string = self.deduced_rules().map_or_else(
+            || {
+                let mut split = self.config().rule_string.splitn(2, ':');
+                let rule_string = split.next().unwrap();
+                let rule_string =
+                    resolve_alias(rule_string).unwrap_or_else(|| rule_string.to_string());
+                let rule_string = rule_string
+                    .parse::<CanonicalRule>()
+                    .map_or(rule_string, |rule| rule.to_string());
+                match split.next() {
+                    Some(grid) => format!("{}:{}", rule_string, grid),
+                    None => rule_string,
+                }
+            },
+            |(min, _)| min,
+        );
+        if self.config().new_state.is_random() {
+            if let Some(seed) = self.config().seed {
+                writeln!(writer, "#C seed {}", seed)?;
+            }
+        }
         writeln!(
-            str,
+            writer,
             "x = {}, y = {}, rule = {}",
-            self.config().width,
-            self.config().height,
-            self.config().rule_string
-        )
-        .unwrap();
-        for y in 0..self.config().height {
-            for x in 0..self.config().width {
+            x_range.len(),
+            y_range.len(),
+            rule_string
+        )?;
+        let mut line = String::with_capacity(x_range.len() + 2);
+        for y in y_range.clone() {
+            line.clear();
+            for x in x_range.clone() {
                 let state = self.get_cell_state((x, y, t)).unwrap();
                 match state {
-                    Some(DEAD) => str.push('.'),
+                    Some(DEAD) => line.push('.'),
                     Some(ALIVE) => {
                         if self.is_gen_rule() {
-                            str.push('A')
+                            line.push('A')
                         } else {
-                            str.push('o')
+                            line.push('o')
                         }
                     }
-                    Some(State(i)) => str.push((b'A' + i as u8 - 1) as char),
-                    _ => str.push('?'),
+                    Some(State(i)) => line.push((b'A' + i as u8 - 1) as char),
+                    _ => line.push('?'),
                 };
             }
-            if y == self.config().height - 1 {
-                str.push('!')
+            if y == y_range.end - 1 {
+                line.push('!')
             } else {
-                str.push('$')
+                line.push('$')
             };
-            str.push('\n');
+            line.push('\n');
+            writer.write_all(line.as_bytes())?;
         }
-        str
+        Ok(())
     }
 
     /// Displays the whole world in some generation in
@@ -116,21 +334,90 @@ pub trait Search {
     /// * **Dead** cells are represented by `.`;
     /// * **Living** and **Dying** cells are represented by `o`;
     /// * **Unknown** cells are represented by `?`.
+    ///
+    /// For large worlds, consider `write_plaintext_gen` instead,
+    /// which does not build the whole string in memory.
     fn plaintext_gen(&self, t: isize) -> String {
-        let mut str = String::new();
-        for y in 0..self.config().height {
-            for x in 0..self.config().width {
+        let mut buf = Vec::new();
+        self.write_plaintext_gen(t, &mut buf).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    /// Writes the whole world in some generation into a writer,
+    /// in the same format as `plaintext_gen`.
+    fn write_plaintext_gen(&self, t: isize, writer: &mut dyn Write) -> io::Result<()> {
+        let (width, height) = (self.config().width, self.config().height);
+        self.write_plaintext_rect(t, 0, 0, width, height, writer)
+    }
+
+    /// Writes a rectangle of the world in some generation into a writer,
+    /// in the same format as `plaintext_gen`.
+    ///
+    /// The rectangle has its top-left corner at `(x, y)`, and is clipped
+    /// to the search range.
+    fn write_plaintext_rect(
+        &self,
+        t: isize,
+        x: isize,
+        y: isize,
+        width: isize,
+        height: isize,
+        writer: &mut dyn Write,
+    ) -> io::Result<()> {
+        let (x_range, y_range) = clip_rect(self.config(), x, y, width, height);
+        let mut line = String::with_capacity(x_range.len() + 1);
+        for y in y_range {
+            line.clear();
+            for x in x_range.clone() {
                 let state = self.get_cell_state((x, y, t)).unwrap();
                 match state {
-                    Some(DEAD) => str.push('.'),
-                    Some(_) => str.push('o'),
-                    None => str.push('?'),
+                    Some(DEAD) => line.push('.'),
+                    Some(_) => line.push('o'),
+                    None => line.push('?'),
                 };
             }
-            str.push('\n');
+            line.push('\n');
+            writer.write_all(line.as_bytes())?;
         }
-        str
+        Ok(())
     }
+
+    /// The states of all the known cells in the world.
+    ///
+    /// Setting them as the known cells of the configuration
+    /// gives a world with the same pattern.
+    fn known_cells(&self) -> Vec<KnownCell> {
+        let config = self.config();
+        let mut known_cells = Vec::new();
+        for t in 0..config.period {
+            for x in 0..config.width {
+                for y in 0..config.height {
+                    let coord = (x, y, t);
+                    if let Ok(Some(state)) = self.get_cell_state(coord) {
+                        known_cells.push(KnownCell { coord, state });
+                    }
+                }
+            }
+        }
+        known_cells
+    }
+}
+
+/// Clips a rectangle to the search range.
+///
+/// Returns the ranges of the x and y coordinates.
+fn clip_rect(
+    config: &Config,
+    x: isize,
+    y: isize,
+    width: isize,
+    height: isize,
+) -> (Range<isize>, Range<isize>) {
+    let x_start = x.max(0).min(config.width);
+    let y_start = y.max(0).min(config.height);
+    let x_end = (x + width.max(0)).max(x_start).min(config.width);
+    let y_end = (y + height.max(0)).max(y_start).min(config.height);
+    (x_start..x_end, y_start..y_end)
 }
 
 /// The `Search` trait is implemented for every `World`.
@@ -139,6 +426,26 @@ impl<'a, R: Rule> Search for World<'a, R> {
         self.search(max_step)
     }
 
+    fn search_with_budget(&mut self, budget: Budget) -> Status {
+        self.search_with_budget(budget)
+    }
+
+    fn skip_subtree(&mut self) -> bool {
+        self.skip_subtree()
+    }
+
+    fn solutions(&mut self) -> Solutions<'_> {
+        Solutions::new(self)
+    }
+
+    fn search_partial(&mut self, lines: isize, max_step: Option<u64>) -> Status {
+        self.search_partial(lines, max_step)
+    }
+
+    fn preview(&mut self) -> bool {
+        self.preview()
+    }
+
     fn get_cell_state(&self, coord: Coord) -> Result<Option<State>, Error> {
         self.get_cell_state(coord)
     }
@@ -151,8 +458,16 @@ impl<'a, R: Rule> Search for World<'a, R> {
         R::IS_GEN
     }
 
+    fn gen(&self) -> usize {
+        self.rule.gen()
+    }
+
     fn is_b0_rule(&self) -> bool {
-        self.rule.has_b0()
+        self.rule.has_b0() || self.rule.is_inverted()
+    }
+
+    fn deduced_rules(&self) -> Option<(String, String)> {
+        self.rule.deduced_rules()
     }
 
     fn cell_count_gen(&self, t: isize) -> usize {
@@ -167,12 +482,147 @@ impl<'a, R: Rule> Search for World<'a, R> {
         self.conflicts
     }
 
+    fn restarts(&self) -> u64 {
+        self.restarts
+    }
+
+    fn search_order(&self) -> SearchOrder {
+        self.search_order()
+    }
+
+    fn stats(&self) -> Stats {
+        self.stats()
+    }
+
+    fn progress(&self) -> f64 {
+        self.progress()
+    }
+
+    fn analyze(&self) -> Analysis {
+        self.analyze()
+    }
+
+    fn canonical_form(&self) -> Vec<(isize, isize, State)> {
+        self.canonical_form()
+    }
+
     fn set_max_cell_count(&mut self, max_cell_count: Option<usize>) {
         self.set_max_cell_count(max_cell_count)
     }
 
+    fn set_trace(&mut self, trace: bool) {
+        self.set_trace(trace)
+    }
+
+    fn set_stop(&mut self, stop: Option<Stop>) {
+        self.set_stop(stop)
+    }
+
+    fn take_trace(&mut self) -> Vec<TraceEntry> {
+        self.take_trace()
+    }
+
+    fn set_policy(&mut self, policy: Option<Box<dyn Policy>>) {
+        self.set_policy(policy)
+    }
+
+    fn set_chooser(&mut self, chooser: Option<Box<dyn ChooseCell>>) {
+        self.set_chooser(chooser)
+    }
+
+    fn set_guide(&mut self, guide: Option<Box<dyn Guide>>) {
+        self.set_guide(guide)
+    }
+
+    fn set_hooks(&mut self, hooks: Option<Box<dyn Hooks>>) {
+        self.set_hooks(hooks)
+    }
+
+    fn decision_path(&self) -> Vec<KnownCell> {
+        self.decision_path()
+    }
+
+    fn replay(&mut self, decisions: &[KnownCell]) -> Result<bool, Error> {
+        self.replay(decisions)
+    }
+
     #[cfg(feature = "serialize")]
     fn ser(&self) -> WorldSer {
         self.ser()
     }
 }
+
+/// An iterator over the results of a search, created by `Search::solutions`.
+///
+/// Each result is given as the states of all the known cells,
+/// as in `Search::known_cells`. The search continues from its current
+/// state, so the results that are already found are not repeated.
+///
+/// The iterator ends when there are no more results,
+/// when the `time_limit` in the configuration is reached,
+/// or when the search is stopped by a `Stop` handle.
+/// `Solutions::status` tells which one happened. A stopped iterator
+/// continues the search if `next` is called again after the handle
+/// is reset.
+pub struct Solutions<'a> {
+    /// The search.
+    search: &'a mut dyn Search,
+
+    /// The status of the last search.
+    status: Status,
+
+    /// The canonical forms of the results so far,
+    /// if duplicates are suppressed.
+    seen: Option<HashSet<Vec<(isize, isize, State)>>>,
+}
+
+impl<'a> Solutions<'a> {
+    /// Creates an iterator over the results of the search.
+    pub(crate) fn new(search: &'a mut dyn Search) -> Self {
+        Solutions {
+            search,
+            status: Status::Initial,
+            seen: None,
+        }
+    }
+
+    /// Skips the results that are the same as an earlier result,
+    /// up to phase, translation, rotation and reflection.
+    ///
+    /// See `Search::canonical_form`. The canonical forms of all
+    /// the results are kept in memory.
+    pub fn dedup(mut self) -> Self {
+        self.seen.get_or_insert_with(HashSet::new);
+        self
+    }
+
+    /// The status of the last search.
+    ///
+    /// It is `None` after all the results are found,
+    /// and `TimedOut` if the `time_limit` is reached.
+    pub fn status(&self) -> Status {
+        self.status
+    }
+}
+
+impl<'a> Iterator for Solutions<'a> {
+    type Item = Vec<KnownCell>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Status::None | Status::TimedOut = self.status {
+            return None;
+        }
+        loop {
+            self.status = self.search.search(None);
+            if self.status != Status::Found {
+                return None;
+            }
+            if let Some(seen) = self.seen.as_mut() {
+                if !seen.insert(self.search.canonical_form()) {
+                    continue;
+                }
+            }
+            return Some(self.search.known_cells());
+        }
+    }
+}
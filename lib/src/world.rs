@@ -1,11 +1,27 @@
 //! The world.
 
 use crate::{
+    blocklist::Pattern,
     cells::{CellRef, Coord, LifeCell, State, DEAD},
-    config::{Config, SearchOrder, Symmetry, Transform},
+    choose::ChooseCell,
+    config::{BoundedGrid, Config, KnownCell, SearchOrder, Symmetry, Topology, Transform},
+    dead_end::DeadEnds,
+    endgame::Endgame,
     error::Error,
+    guide::Guide,
+    hooks::Hooks,
+    nogood::Nogoods,
+    rows::Rows,
     rules::Rule,
-    search::{Reason, SetCell},
+    search::{Cause, Reason, SetCell, Stop},
+    trace::{Policy, Trace},
+    traits::Search,
+};
+use rand::{thread_rng, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant},
 };
 
 /// The world.
@@ -23,22 +39,92 @@ pub struct World<'a, R: Rule> {
     // So the unsafe code below is actually safe.
     cells: Vec<LifeCell<'a, R>>,
 
+    /// The width of the margin around the search range.
+    ///
+    /// Cells in the margin are always in the background state.
+    /// It is the radius of the neighborhood of the rule,
+    /// and is at least 1. It is larger when there is an agar.
+    margin: isize,
+
+    /// The bounded grid given by the suffix of the rule string, if any.
+    grid: Option<BoundedGrid>,
+
     /// A list of references to cells sorted by the search order.
     ///
     /// Used to find unknown cells.
-    search_list: Vec<CellRef<'a, R>>,
+    pub(crate) search_list: Vec<CellRef<'a, R>>,
+
+    /// The search order of the `search_list`.
+    ///
+    /// It is the one in the configuration, unless it has been switched
+    /// by `order_switch_interval`.
+    pub(crate) search_order: SearchOrder,
 
     /// Number of known living cells in each generation.
     ///
     /// For Generations rules, dying cells are not counted.
     pub(crate) cell_count: Vec<usize>,
 
+    /// Number of known living cells in each row of each generation,
+    /// where the row `y` of generation `t` is at the index `t * height + y`.
+    pub(crate) row_cell_count: Vec<usize>,
+
+    /// Number of known living cells in each column of each generation,
+    /// where the column `x` of generation `t` is at the index `t * width + x`.
+    pub(crate) column_cell_count: Vec<usize>,
+
+    /// Number of known cells at each position, in its background state
+    /// and in other states, in all generations.
+    ///
+    /// The position `(x, y)` is at the index `y * width + x`.
+    /// Cells whose states are irrelevant are not counted.
+    ///
+    /// It starts from the world where all cells are in the background
+    /// state, before the unknown cells are cleared in `init_state`.
+    pub(crate) rotor_states: Vec<(usize, usize)>,
+
+    /// Number of positions whose known cells are in the background state
+    /// in some generations and not in others, i.e., the known rotor cells.
+    pub(crate) rotor_cell_count: usize,
+
     /// Number of unknown or living cells on the first row or column.
     pub(crate) front_cell_count: usize,
 
+    /// Whether the known cells contradict each other, or violate
+    /// a bound on the living cells when they are set.
+    pub(crate) known_conflict: bool,
+
     /// Number of conflicts during the search.
     pub(crate) conflicts: u64,
 
+    /// Number of restarts during the search.
+    pub(crate) restarts: u64,
+
+    /// Number of conflicts before the last restart.
+    pub(crate) last_restart: u64,
+
+    /// Number of re-evaluations of the search order.
+    pub(crate) order_switches: u64,
+
+    /// Number of conflicts before the last re-evaluation of the search order.
+    pub(crate) last_order_switch: u64,
+
+    /// Whether a result has been found.
+    pub(crate) found: bool,
+
+    /// Number of cells chosen by `decide`, including those that are
+    /// deduced by probing instead.
+    pub(crate) decisions: u64,
+
+    /// Number of cells deduced during the search.
+    pub(crate) propagations: u64,
+
+    /// The maximal number of known cells at the same time.
+    pub(crate) max_depth: usize,
+
+    /// The total time spent in the search.
+    pub(crate) elapsed: Duration,
+
     /// A stack to record the cells whose values are set during the search.
     ///
     /// The cells in this stack always have known states.
@@ -51,10 +137,112 @@ pub struct World<'a, R: Rule> {
     /// See `proceed` for details.
     pub(crate) check_index: usize,
 
+    /// Number of decisions on the `set_stack`, i.e., cells whose reasons
+    /// are `Decide` or `TryAnother`.
+    ///
+    /// Cells whose states are flipped by backtracking are not counted.
+    pub(crate) decision_depth: usize,
+
+    /// The cap on `decision_depth` for `deepening`.
+    pub(crate) depth_cap: usize,
+
+    /// The cap in the previous round of `deepening`.
+    ///
+    /// Results within it have been found in the earlier rounds.
+    pub(crate) depth_floor: usize,
+
+    /// Whether some branches are cut by the `depth_cap` in this round
+    /// of `deepening`.
+    pub(crate) depth_cut: bool,
+
+    /// The random number generator used when choosing a random state.
+    pub(crate) rng: ChaCha8Rng,
+
+    /// Number of random states chosen by `rng`.
+    pub(crate) random_count: u64,
+
     /// The starting position in the `search_list` to look for an unknown cell.
     ///
     /// Cells before this position are all known.
     pub(crate) search_index: usize,
+
+    /// Records the decisions during the search, if enabled.
+    pub(crate) trace: Option<Trace>,
+
+    /// A branching heuristic that chooses the states of unknown cells.
+    ///
+    /// If it is `None`, the states are chosen according to `new_state`
+    /// in the configuration.
+    pub(crate) policy: Option<Box<dyn Policy>>,
+
+    /// A branching heuristic that chooses the next unknown cell.
+    ///
+    /// If it is `None`, the cells are chosen according to `search_order`
+    /// in the configuration.
+    pub(crate) chooser: Option<Box<dyn ChooseCell>>,
+
+    /// An external agent that makes the decisions.
+    ///
+    /// If it is `None`, or it leaves a choice open, the decisions are
+    /// made by the `chooser` and the `policy`, or by the configuration.
+    pub(crate) guide: Option<Box<dyn Guide>>,
+
+    /// Callbacks on the events in the search.
+    pub(crate) hooks: Option<Box<dyn Hooks>>,
+
+    /// The patterns in the `blocklist` of the configuration,
+    /// each with its images under all transformations.
+    pub(crate) blocklist: Vec<Vec<Pattern>>,
+
+    /// The cells in a fixed order paired with their images, for each
+    /// transformation used in `symmetry_breaking`.
+    pub(crate) lex_pairs: Vec<Vec<(CellRef<'a, R>, CellRef<'a, R>)>>,
+
+    /// The amount added to the activity of a cell when it is involved
+    /// in a conflict.
+    ///
+    /// It grows after each conflict, so that older conflicts
+    /// are weighted less.
+    pub(crate) activity_inc: f64,
+
+    /// The cause of the cells that are being deduced.
+    ///
+    /// It is recorded in the `set_stack` for backjumping.
+    pub(crate) cause: Cause<'a, R>,
+
+    /// The cells whose states lead to the last conflict,
+    /// or `None` if they are unknown, e.g., when the conflict is
+    /// caused by `max_cell_count`.
+    pub(crate) conflict: Option<Vec<CellRef<'a, R>>>,
+
+    /// The nogoods learnt from the conflicts.
+    pub(crate) nogoods: Nogoods<'a, R>,
+
+    /// The dead ends remembered for `dead_end_cache`.
+    pub(crate) dead_ends: DeadEnds<'a, R>,
+
+    /// The state of the search for `Engine::Rows`.
+    pub(crate) rows: Rows<'a, R>,
+
+    /// The direct enumeration of the last unknown cells,
+    /// when the search is in the endgame. See `endgame_threshold`.
+    pub(crate) endgame: Option<Endgame<'a, R>>,
+
+    /// The states of the cells in the `search_list` in the result with
+    /// the smallest cell count so far, when `minimize` is enabled.
+    pub(crate) best: Option<Vec<State>>,
+
+    /// When the search times out, if `time_limit` is set.
+    ///
+    /// It is set when the search starts.
+    pub(crate) deadline: Option<Instant>,
+
+    /// The `set_stack` when the most cells were known,
+    /// and the states of the cells, when `time_limit` is set.
+    pub(crate) deepest: Vec<(SetCell<'a, R>, State)>,
+
+    /// A handle to stop the search from another thread.
+    pub(crate) stop: Option<Stop>,
 }
 
 impl<'a, R: Rule> World<'a, R> {
@@ -67,14 +255,42 @@ impl<'a, R: Rule> World<'a, R> {
     /// After the last generation, the pattern will return to
     /// the first generation, applying the transformation first,
     /// and then the translation defined by `dx` and `dy`.
+    ///
+    /// On a bounded grid with joined edges, e.g., a torus, the translation
+    /// wraps around the world.
     pub fn new(config: &Config, rule: R) -> Self {
         let search_order = config.auto_search_order();
+        let grid = config.bounded_grid().ok().flatten();
+
+        // A random seed is recorded in the configuration of the world,
+        // so that the search can be reproduced.
+        let seed = config.seed.unwrap_or_else(|| thread_rng().gen());
+        let rng = ChaCha8Rng::seed_from_u64(seed);
+
+        let margin = rule
+            .nbhd()
+            .iter()
+            .chain(rule.nbhd_odd())
+            .map(|&(x, y)| x.abs().max(y.abs()))
+            .fold(1, isize::max);
+
+        // With an agar, the cells in the margin that are consistified,
+        // i.e., the neighbors and the predecessors of the cells in the
+        // search range, must also have all their neighbors and successors,
+        // with correct neighborhood descriptors.
+        let margin = if config.agar.is_some() {
+            3 * margin + 2 * config.dx.abs().max(config.dy.abs())
+        } else {
+            margin
+        };
 
-        let size = ((config.width + 2) * (config.height + 2) * config.period) as usize;
+        let size =
+            ((config.width + 2 * margin) * (config.height + 2 * margin) * config.period) as usize;
         let mut cells = Vec::with_capacity(size);
 
         // Whether to consider only the first generation of the front.
         let front_gen0 = !rule.has_b0()
+            && config.shift == (0, 0)
             && match search_order {
                 SearchOrder::ColumnFirst => {
                     config.dy == 0
@@ -88,28 +304,47 @@ impl<'a, R: Rule> World<'a, R> {
                         && (config.transform == Transform::Id
                             || config.transform == Transform::FlipCol)
                 }
+                SearchOrder::Diagonal | SearchOrder::CenterOut => false,
             };
 
         // Whether to consider only half of the first generation of the front.
-        let front_half = match config.symmetry {
-            Symmetry::D2Diag | Symmetry::D2Antidiag | Symmetry::D4Diag => false,
-            _ => front_gen0,
-        };
+        //
+        // Glide symmetries do not make the generation 0 symmetric,
+        // and user-defined symmetries, time-reversal symmetries
+        // and off-center axes might not fix the first half.
+        let front_half = config.symmetry_maps.is_empty()
+            && config.time_reversal.is_none()
+            && config.axis_offset == (0, 0)
+            && match config.symmetry {
+                Symmetry::D2Diag
+                | Symmetry::D2Antidiag
+                | Symmetry::D4Diag
+                | Symmetry::C3
+                | Symmetry::D6 => false,
+                sym if sym.is_glide() => false,
+                _ => front_gen0,
+            };
 
         // Fills the vector with dead cells,
         // and checks whether it is on the first row or column.
         //
         // If the rule contains `B0`, then fills the odd generations
-        // with living cells instead.
-        for x in -1..=config.width {
-            for y in -1..=config.height {
+        // with living cells instead. An agar is filled in later,
+        // starting from dead cells.
+        let b0 = rule.has_b0() && config.agar.is_none();
+        for x in -margin..config.width + margin {
+            for y in -margin..config.height + margin {
                 for t in 0..config.period {
-                    let state = if rule.has_b0() {
+                    let state = if b0 {
                         State(t as usize % rule.gen())
                     } else {
                         DEAD
                     };
-                    let mut cell = LifeCell::new((x, y, t), state, rule.has_b0());
+                    let mut cell = LifeCell::new((x, y, t), state, b0);
+                    if let Some(BoundedGrid { width, height, .. }) = grid {
+                        cell.outside = width > 0 && (x < 0 || x >= config.width)
+                            || height > 0 && (y < 0 || y >= config.height);
+                    }
                     match search_order {
                         SearchOrder::ColumnFirst => {
                             if front_gen0 {
@@ -135,6 +370,13 @@ impl<'a, R: Rule> World<'a, R> {
                                 cell.is_front = true
                             }
                         }
+                        // Every pattern can be moved to touch the first row,
+                        // so this does not miss any result.
+                        SearchOrder::Diagonal | SearchOrder::CenterOut => {
+                            if x == 0 || y == 0 {
+                                cell.is_front = true
+                            }
+                        }
                     }
                     cells.push(cell);
                 }
@@ -142,47 +384,125 @@ impl<'a, R: Rule> World<'a, R> {
         }
 
         World {
-            config: config.clone(),
+            config: config.clone().set_seed(Some(seed)),
             rule,
             cells,
+            margin,
+            grid,
             search_list: Vec::with_capacity(size),
+            search_order,
             cell_count: vec![0; config.period as usize],
+            row_cell_count: vec![0; (config.period * config.height) as usize],
+            column_cell_count: vec![0; (config.period * config.width) as usize],
+            rotor_states: vec![
+                (config.period as usize, 0);
+                (config.width * config.height) as usize
+            ],
+            rotor_cell_count: 0,
             front_cell_count: 0,
+            known_conflict: false,
             conflicts: 0,
+            restarts: 0,
+            last_restart: 0,
+            order_switches: 0,
+            last_order_switch: 0,
+            found: false,
+            decisions: 0,
+            propagations: 0,
+            max_depth: 0,
+            elapsed: Duration::default(),
             set_stack: Vec::with_capacity(size),
             check_index: 0,
+            decision_depth: 0,
+            depth_cap: config.deepening.map_or(0, |step| step.max(1)),
+            depth_floor: 0,
+            depth_cut: false,
+            rng,
+            random_count: 0,
             search_index: 0,
+            trace: None,
+            policy: None,
+            chooser: None,
+            guide: None,
+            hooks: None,
+            blocklist: Vec::new(),
+            lex_pairs: Vec::new(),
+            activity_inc: 1.0,
+            cause: Cause::Unknown,
+            conflict: None,
+            nogoods: Nogoods::default(),
+            dead_ends: DeadEnds::default(),
+            rows: Rows::default(),
+            endgame: None,
+            best: None,
+            deadline: None,
+            deepest: Vec::new(),
+            stop: None,
         }
+        .init_dont_care()
         .init_nbhd()
         .init_pred_succ()
+        .init_agar()
         .init_sym()
+        .init_stator()
+        .init_diagonal_width()
+        .init_shape()
         .init_state()
+        .init_known_cells()
         .init_search_order(search_order)
+        .init_blocklist()
+        .init_lex_leader()
+        .init_dead_ends()
+        .init_rows()
+    }
+
+    /// Marks the cells whose states are irrelevant.
+    ///
+    /// Such cells are never on the front, and are not counted
+    /// in `rotor_states`.
+    fn init_dont_care(mut self) -> Self {
+        for i in 0..self.config.dont_care_cells.len() {
+            let coord = self.config.dont_care_cells[i];
+            let (x, y, _) = coord;
+            let width = self.config.width;
+            let inside = 0 <= x && x < width && 0 <= y && y < self.config.height;
+            if let Some(cell_ptr) = self.find_cell_mut(coord) {
+                unsafe {
+                    let cell = cell_ptr.as_mut().unwrap();
+                    if inside && !cell.dont_care {
+                        self.rotor_states[(y * width + x) as usize].0 -= 1;
+                    }
+                    cell.dont_care = true;
+                    cell.is_front = false;
+                }
+            }
+        }
+        self
     }
 
     /// Links the cells to their neighbors.
     ///
     /// Note that for cells on the edges of the search range,
     /// some neighbors might point to `None`.
+    ///
+    /// On a bounded grid with joined edges, e.g., a torus, cells on the
+    /// joined edges are linked.
     fn init_nbhd(mut self) -> Self {
-        const NBHD: [(isize, isize); 8] = [
-            (-1, -1),
-            (-1, 0),
-            (-1, 1),
-            (0, -1),
-            (0, 1),
-            (1, -1),
-            (1, 0),
-            (1, 1),
-        ];
-        for x in -1..=self.config.width {
-            for y in -1..=self.config.height {
+        let nbhd_even = self.rule.nbhd().to_vec();
+        let nbhd_odd = self.rule.nbhd_odd().to_vec();
+        for x in -self.margin..self.config.width + self.margin {
+            for y in -self.margin..self.config.height + self.margin {
                 for t in 0..self.config.period {
                     let cell_ptr = self.find_cell_mut((x, y, t)).unwrap();
-                    for (i, (nx, ny)) in NBHD.iter().enumerate() {
+                    let nbhd = if (x + y) & 1 == 0 {
+                        &nbhd_even
+                    } else {
+                        &nbhd_odd
+                    };
+                    for (i, (nx, ny)) in nbhd.iter().enumerate() {
                         unsafe {
                             let cell = cell_ptr.as_mut().unwrap();
-                            cell.nbhd[i] = self.find_cell((x + nx, y + ny, t));
+                            cell.nbhd[i] = self.find_cell(self.wrap((x + nx, y + ny, t)));
                         }
                     }
                 }
@@ -198,20 +518,24 @@ impl<'a, R: Rule> World<'a, R> {
     ///
     /// If the successor is out of the search range,
     /// then sets it to `None`.
+    ///
+    /// The successor is moved by the `shift` in every generation.
     fn init_pred_succ(mut self) -> Self {
-        for x in -1..=self.config.width {
-            for y in -1..=self.config.height {
+        let (sx, sy) = self.config.shift;
+        for x in -self.margin..self.config.width + self.margin {
+            for y in -self.margin..self.config.height + self.margin {
                 for t in 0..self.config.period {
                     let cell_ptr = self.find_cell_mut((x, y, t)).unwrap();
                     let cell = self.find_cell((x, y, t)).unwrap();
 
-                    if t != 0 {
+                    if t != 0 && (sx, sy) == (0, 0) {
                         unsafe {
                             let cell = cell_ptr.as_mut().unwrap();
                             cell.pred = self.find_cell((x, y, t - 1));
                         }
                     } else {
-                        let pred = self.find_cell(self.config.translate((x, y, t - 1)));
+                        let (px, py, pt) = self.config.translate((x, y, t - 1));
+                        let pred = self.find_cell(self.wrap((px - sx, py - sy, pt)));
                         if pred.is_some() {
                             unsafe {
                                 let cell = cell_ptr.as_mut().unwrap();
@@ -227,15 +551,68 @@ impl<'a, R: Rule> World<'a, R> {
                         }
                     }
 
-                    if t != self.config.period - 1 {
+                    if t != self.config.period - 1 && (sx, sy) == (0, 0) {
                         unsafe {
                             let cell = cell_ptr.as_mut().unwrap();
                             cell.succ = self.find_cell((x, y, t + 1));
                         }
                     } else {
+                        let succ = self.config.translate((x + sx, y + sy, t + 1));
+                        unsafe {
+                            let cell = cell_ptr.as_mut().unwrap();
+                            cell.succ = self.find_cell(self.wrap(succ));
+                        }
+                    }
+                }
+            }
+        }
+        self
+    }
+
+    /// Fills the background with the agar, if there is one.
+    ///
+    /// The cells are created in the vacuum, and the cells whose states
+    /// in the agar are different are changed one by one, so that the
+    /// neighborhood descriptors are updated as when the states are set.
+    /// Cells on the outer edge of the margin, which do not have all
+    /// their neighbors, are changed without updating the descriptors.
+    /// So the descriptors of the cells near the outer edge of the margin
+    /// might not be correct, but such cells are never consistified.
+    ///
+    /// If the rule is inverted, the states are inverted.
+    fn init_agar(mut self) -> Self {
+        let agar = match self.config.agar.clone() {
+            Some(agar) => agar,
+            None => return self,
+        };
+        let tile = match agar.parse() {
+            Ok(tile) => tile,
+            Err(_) => return self,
+        };
+        let nbhd_size = self.rule.nbhd().len().max(self.rule.nbhd_odd().len());
+        for x in -self.margin..self.config.width + self.margin {
+            for y in -self.margin..self.config.height + self.margin {
+                for t in 0..self.config.period {
+                    let state = agar.state(&tile, (x, y, t));
+                    let state = if self.rule.is_inverted() {
+                        !state
+                    } else {
+                        state
+                    };
+                    let cell_ptr = self.find_cell_mut((x, y, t)).unwrap();
+                    let cell = self.find_cell((x, y, t)).unwrap();
+                    if cell.background != state {
+                        let edge = cell.nbhd[..nbhd_size].iter().any(Option::is_none);
+                        if !edge {
+                            cell.update_desc(Some(cell.background), false);
+                        }
+                        cell.state.set(Some(state));
+                        if !edge {
+                            cell.update_desc(Some(state), true);
+                        }
                         unsafe {
                             let cell = cell_ptr.as_mut().unwrap();
-                            cell.succ = self.find_cell(self.config.translate((x, y, t + 1)));
+                            cell.background = state;
                         }
                     }
                 }
@@ -244,18 +621,122 @@ impl<'a, R: Rule> World<'a, R> {
         self
     }
 
+    /// Whether the agar returns to itself after the period, with the
+    /// transformation and the translation applied, i.e., the successor
+    /// of each cell in the last generation has the background state of
+    /// the agar in the next generation.
+    fn agar_returns(&self) -> bool {
+        let agar = match self.config.agar.as_ref() {
+            Some(agar) => agar,
+            None => return true,
+        };
+        let tile = match agar.parse() {
+            Ok(tile) => tile,
+            Err(_) => return false,
+        };
+        let t = self.config.period - 1;
+        (-self.margin..self.config.width + self.margin).all(|x| {
+            (-self.margin..self.config.height + self.margin).all(|y| {
+                let cell = self.find_cell((x, y, t)).unwrap();
+                let state = agar.state(&tile, (x, y, t + 1));
+                let state = if self.rule.is_inverted() {
+                    !state
+                } else {
+                    state
+                };
+                cell.succ.is_none_or(|succ| succ.background == state)
+            })
+        })
+    }
+
     /// Links a cell to the symmetric cells.
     ///
     /// If some symmetric cell is out of the search range,
     /// then  marks the current cell as known.
+    ///
+    /// For glide symmetries, the symmetric cell is half a period away.
+    ///
+    /// User-defined symmetries link a cell to its images.
+    /// The time-reversal symmetry links generation `t` to generation
+    /// `period - t`.
+    ///
+    /// If `symmetry_gen0_only` is set, spatial symmetries only link
+    /// the cells in generation 0.
+    ///
+    /// Cells on the axis of a gutter symmetry are marked as known.
+    ///
+    /// Reflections across the middle row or column are moved by
+    /// the `axis_offset`, and reflections across the diagonal or the
+    /// antidiagonal are moved by the `diagonal_offset`.
+    ///
+    /// On a bounded grid with joined edges, symmetric cells across the joined
+    /// edges are wrapped around, e.g., for glide symmetries and translations.
     fn init_sym(mut self) -> Self {
-        for x in -1..=self.config.width {
-            for y in -1..=self.config.height {
+        let (width, height) = (self.config.width, self.config.height);
+        let (ox, oy) = self.config.axis_offset;
+        // A cell at `x` is reflected to `mirror_x - x`, and so on.
+        let (mirror_x, mirror_y) = (width - 1 + ox, height - 1 + oy);
+        // The diagonal is moved to `y = x + diag`, and the antidiagonal
+        // to `x + y = width - 1 + antidiag`.
+        let (diag, antidiag) = self.config.diagonal_offset;
+        let reflect_diag = |x: isize, y: isize| (y - diag, x + diag);
+        let reflect_antidiag =
+            |x: isize, y: isize| (height - 1 + antidiag - y, width - 1 + antidiag - x);
+        let half = self.config.period / 2;
+        let (hx, hy) = (self.config.dx / 2, self.config.dy / 2);
+        // Hexagonal symmetries rotate around the cell `(cx, cy)` on the
+        // hexagonal grid, and around the vertex `(width + 1, height) / 2`
+        // on the triangular grid. See `triangle_map` for the coordinates.
+        let triangle = self.rule.nbhd() != self.rule.nbhd_odd();
+        let (cx, cy) = ((width - 1) / 2, (height - 1) / 2);
+        let vertex = ((width + 1) / 2, height / 2);
+        let hex_matrices = if triangle {
+            self.config
+                .symmetry
+                .hex_matrices(TRIANGLE_ROTATE, TRIANGLE_FLIP)
+        } else {
+            self.config.symmetry.matrices()
+        };
+        // Whether to skip consistifying the cells outside the fundamental
+        // domain, i.e., the cells with a symmetric cell that comes earlier.
+        // Every element of the symmetry must map the neighborhood to itself.
+        let domain = self.config.fundamental_domain
+            && self.rule.is_isotropic()
+            && !self.rule.has_parity()
+            && self.config.symmetry.preserves(self.rule.nbhd())
+            && matches!(self.config.bounded_grid(), Ok(None))
+            && self.config.time_reversal.is_none()
+            && self.config.agar.is_none()
+            && !self.config.symmetry_gen0_only
+            && self.config.axis_offset == (0, 0)
+            && self.config.diagonal_offset == (0, 0)
+            && !self.config.symmetry.is_glide()
+            && !self.config.symmetry.is_hex();
+        let inside = |x: isize, y: isize| 0 <= x && x < width && 0 <= y && y < height;
+        for x in -self.margin..self.config.width + self.margin {
+            for y in -self.margin..self.config.height + self.margin {
                 for t in 0..self.config.period {
                     let cell_ptr = self.find_cell_mut((x, y, t)).unwrap();
                     let cell = self.find_cell((x, y, t)).unwrap();
 
-                    let sym_coords = match self.config.symmetry {
+                    // Generation `t + half` at `(x, y)` is generation `t`
+                    // at the reflection of `(x + hx, y + hy)`.
+                    let glide = |reflect: &dyn Fn(isize, isize) -> (isize, isize)| {
+                        if t < half {
+                            let (x, y) = reflect(x, y);
+                            (x - hx, y - hy, t + half)
+                        } else {
+                            let (x, y) = reflect(x + hx, y + hy);
+                            (x, y, t - half)
+                        }
+                    };
+
+                    // Spatial symmetries are skipped in later generations
+                    // if they are only required in generation 0.
+                    let spatial = t == 0 || !self.config.symmetry_gen0_only;
+
+                    let mut sym_coords = match self.config.symmetry {
+                        sym if !spatial && !sym.is_glide() => vec![],
                         Symmetry::C1 => vec![],
                         Symmetry::C2 => {
                             vec![(self.config.width - 1 - x, self.config.height - 1 - y, t)]
@@ -265,22 +746,27 @@ impl<'a, R: Rule> World<'a, R> {
                             (self.config.width - 1 - x, self.config.height - 1 - y, t),
                             (self.config.height - 1 - y, x, t),
                         ],
-                        Symmetry::D2Row => vec![(x, self.config.height - 1 - y, t)],
-                        Symmetry::D2Col => vec![(self.config.width - 1 - x, y, t)],
-                        Symmetry::D2Diag => vec![(y, x, t)],
+                        Symmetry::D2Row | Symmetry::GutterRow => vec![(x, mirror_y - y, t)],
+                        Symmetry::D2Col | Symmetry::GutterCol => vec![(mirror_x - x, y, t)],
+                        Symmetry::D2Diag => {
+                            let (x, y) = reflect_diag(x, y);
+                            vec![(x, y, t)]
+                        }
                         Symmetry::D2Antidiag => {
-                            vec![(self.config.height - 1 - y, self.config.width - 1 - x, t)]
+                            let (x, y) = reflect_antidiag(x, y);
+                            vec![(x, y, t)]
                         }
                         Symmetry::D4Ortho => vec![
-                            (self.config.width - 1 - x, y, t),
-                            (x, self.config.height - 1 - y, t),
-                            (self.config.width - 1 - x, self.config.height - 1 - y, t),
-                        ],
-                        Symmetry::D4Diag => vec![
-                            (y, x, t),
-                            (self.config.height - 1 - y, self.config.width - 1 - x, t),
-                            (self.config.width - 1 - x, self.config.height - 1 - y, t),
+                            (mirror_x - x, y, t),
+                            (x, mirror_y - y, t),
+                            (mirror_x - x, mirror_y - y, t),
                         ],
+                        Symmetry::D4Diag => {
+                            let (x0, y0) = reflect_diag(x, y);
+                            let (x1, y1) = reflect_antidiag(x, y);
+                            let (x2, y2) = reflect_antidiag(x0, y0);
+                            vec![(x0, y0, t), (x1, y1, t), (x2, y2, t)]
+                        }
                         Symmetry::D8 => vec![
                             (y, self.config.width - 1 - x, t),
                             (self.config.height - 1 - y, x, t),
@@ -290,8 +776,69 @@ impl<'a, R: Rule> World<'a, R> {
                             (self.config.height - 1 - y, self.config.width - 1 - x, t),
                             (self.config.width - 1 - x, self.config.height - 1 - y, t),
                         ],
+                        Symmetry::GlideRow => vec![glide(&|x, y| (x, mirror_y - y))],
+                        Symmetry::GlideCol => vec![glide(&|x, y| (mirror_x - x, y))],
+                        Symmetry::GlideDiag => vec![glide(&reflect_diag)],
+                        Symmetry::GlideAntidiag => vec![glide(&reflect_antidiag)],
+                        Symmetry::C3 | Symmetry::C6 | Symmetry::D6 | Symmetry::D12 => hex_matrices
+                            .iter()
+                            .skip(1)
+                            .map(|&m| {
+                                if triangle {
+                                    let (x, y) = triangle_map(m, vertex, (x, y));
+                                    (x, y, t)
+                                } else {
+                                    let (u, v) = (x - cx, y - cy);
+                                    (
+                                        m[0][0] * u + m[0][1] * v + cx,
+                                        m[1][0] * u + m[1][1] * v + cy,
+                                        t,
+                                    )
+                                }
+                            })
+                            .collect(),
                     };
+                    if domain
+                        && inside(x, y)
+                        && sym_coords
+                            .iter()
+                            .any(|&(x1, y1, _)| inside(x1, y1) && (x1, y1) < (x, y))
+                    {
+                        unsafe {
+                            let cell = cell_ptr.as_mut().unwrap();
+                            cell.redundant = true;
+                        }
+                    }
+                    if spatial {
+                        sym_coords.extend(
+                            self.config
+                                .symmetry_maps
+                                .iter()
+                                .map(|map| map.apply((x, y, t))),
+                        );
+                    }
+                    // Cells on the axis of a gutter symmetry are in the background state.
+                    let gutter = spatial
+                        && match self.config.symmetry {
+                            Symmetry::GutterRow => 2 * y == mirror_y,
+                            Symmetry::GutterCol => 2 * x == mirror_x,
+                            _ => false,
+                        };
+                    if gutter
+                        && 0 <= x
+                        && x < self.config.width
+                        && 0 <= y
+                        && y < self.config.height
+                        && !self.set_stack.iter().any(|s| s.cell == cell)
+                    {
+                        self.set_stack.push(SetCell::new(cell, Reason::Deduce));
+                    }
+                    if let Some(transform) = self.config.time_reversal {
+                        let (x, y) = self.config.apply_transform(transform, x, y);
+                        sym_coords.push(self.config.translate((x, y, self.config.period - t)));
+                    }
                     for coord in sym_coords {
+                        let coord = self.wrap(coord);
                         if 0 <= coord.0
                             && coord.0 < self.config.width
                             && 0 <= coord.1
@@ -316,10 +863,76 @@ impl<'a, R: Rule> World<'a, R> {
         self
     }
 
+    /// Relates each of the `stator_cells` in each generation to the same
+    /// cell in the next generation, so that it has the same state
+    /// in all generations.
+    fn init_stator(mut self) -> Self {
+        for i in 0..self.config.stator_cells.len() {
+            let (x, y) = self.config.stator_cells[i];
+            if x < 0 || x >= self.config.width || y < 0 || y >= self.config.height {
+                continue;
+            }
+            for t in 1..self.config.period {
+                let prev = self.find_cell((x, y, t - 1)).unwrap();
+                let next = self.find_cell((x, y, t)).unwrap();
+                unsafe {
+                    let prev_ptr = self.find_cell_mut((x, y, t - 1)).unwrap();
+                    prev_ptr.as_mut().unwrap().sym.push(next);
+                    let next_ptr = self.find_cell_mut((x, y, t)).unwrap();
+                    next_ptr.as_mut().unwrap().sym.push(prev);
+                }
+            }
+        }
+        self
+    }
+
+    /// Marks the cells outside the diagonal strip as known,
+    /// if `diagonal_width` is set.
+    fn init_diagonal_width(mut self) -> Self {
+        if let Some(diagonal_width) = self.config.diagonal_width {
+            for x in 0..self.config.width {
+                for y in 0..self.config.height {
+                    if (x - y).abs() < diagonal_width {
+                        continue;
+                    }
+                    for t in 0..self.config.period {
+                        let cell = self.find_cell((x, y, t)).unwrap();
+                        if !self.set_stack.iter().any(|s| s.cell == cell) {
+                            self.set_stack.push(SetCell::new(cell, Reason::Deduce));
+                        }
+                    }
+                }
+            }
+        }
+        self
+    }
+
+    /// Marks the cells outside the `shape` as known, if it is set.
+    fn init_shape(mut self) -> Self {
+        if let Some(shape) = self.config.shape.as_ref() {
+            let shape = shape.iter().copied().collect::<HashSet<_>>();
+            for x in 0..self.config.width {
+                for y in 0..self.config.height {
+                    if shape.contains(&(x, y)) {
+                        continue;
+                    }
+                    for t in 0..self.config.period {
+                        let cell = self.find_cell((x, y, t)).unwrap();
+                        if !self.set_stack.iter().any(|s| s.cell == cell) {
+                            self.set_stack.push(SetCell::new(cell, Reason::Deduce));
+                        }
+                    }
+                }
+            }
+        }
+        self
+    }
+
     /// Sets states for the cells.
     ///
     /// All cells are set to unknown unless they are on the boundary,
-    /// or are marked as known in `init_pred_succ` or `init_sym`.
+    /// or are marked as known in `init_pred_succ`, `init_sym`,
+    /// `init_diagonal_width` or `init_shape`.
     fn init_state(mut self) -> Self {
         for x in 0..self.config.width {
             for y in 0..self.config.height {
@@ -334,44 +947,176 @@ impl<'a, R: Rule> World<'a, R> {
         self
     }
 
-    /// Sets the search order.
-    fn init_search_order(mut self, search_order: SearchOrder) -> Self {
-        match search_order {
-            SearchOrder::ColumnFirst => {
-                for x in 0..self.config.width {
-                    for y in 0..self.config.height {
-                        for t in 0..self.config.period {
-                            let cell = self.find_cell((x, y, t)).unwrap();
-                            self.search_list.push(cell);
+    /// Sets the states of the known cells, and of the `dead_cells`
+    /// in all generations.
+    ///
+    /// Cells outside the search range are skipped. A cell whose state
+    /// is already known to be different, e.g., from an earlier known cell,
+    /// is a `known_conflict`.
+    ///
+    /// If the rule is inverted, the states are inverted.
+    fn init_known_cells(mut self) -> Self {
+        let period = self.config.period;
+        let dead_cells = self.config.dead_cells.iter().flat_map(|&(x, y)| {
+            (0..period).map(move |t| KnownCell {
+                coord: (x, y, t),
+                state: DEAD,
+            })
+        });
+        let known_cells = self
+            .config
+            .known_cells
+            .iter()
+            .copied()
+            .chain(dead_cells)
+            .collect::<Vec<_>>();
+        for KnownCell { coord, state } in known_cells {
+            if let Some(cell) = self.find_cell(coord) {
+                if 0 <= coord.0
+                    && coord.0 < self.config.width
+                    && 0 <= coord.1
+                    && coord.1 < self.config.height
+                    && state.0 < self.rule.gen()
+                {
+                    let state = if self.rule.is_inverted() {
+                        !state
+                    } else {
+                        state
+                    };
+                    match cell.state.get() {
+                        Some(old_state) => self.known_conflict |= old_state != state,
+                        None => {
+                            if !self.set_cell(cell, state, Reason::Known) {
+                                self.known_conflict = true;
+                            }
                         }
                     }
                 }
             }
-            SearchOrder::RowFirst => {
-                for y in 0..self.config.height {
-                    for x in 0..self.config.width {
-                        for t in 0..self.config.period {
-                            let cell = self.find_cell((x, y, t)).unwrap();
-                            self.search_list.push(cell);
-                        }
-                    }
-                }
+        }
+        self
+    }
+
+    /// Sets the search order.
+    fn init_search_order(mut self, search_order: SearchOrder) -> Self {
+        for (x, y) in self.config.ordered_coords(search_order) {
+            for t in 0..self.config.period {
+                let cell = self.find_cell((x, y, t)).unwrap();
+                self.search_list.push(cell);
             }
         }
         self
     }
 
+    /// Sorts the `search_list` by another search order.
+    ///
+    /// The `best` result is sorted in the same way.
+    /// The positions of the decided cells in the `search_list`,
+    /// which are recorded in their reasons, are not updated.
+    pub(crate) fn set_search_order(&mut self, search_order: SearchOrder) {
+        let positions = self
+            .search_list
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| (cell.coord, i))
+            .collect::<HashMap<_, _>>();
+        let mut order = Vec::with_capacity(self.search_list.len());
+        for (x, y) in self.config.ordered_coords(search_order) {
+            for t in 0..self.config.period {
+                order.push(positions[&(x, y, t)]);
+            }
+        }
+        self.search_list = order.iter().map(|&i| self.search_list[i]).collect();
+        if let Some(best) = self.best.as_mut() {
+            *best = order.iter().map(|&i| best[i]).collect();
+        }
+        self.search_order = search_order;
+        self.search_index = 0;
+    }
+
+    /// Moves the coordinates into the world along the joined dimensions,
+    /// if the world is a torus, a Klein bottle, a cross-surface or a sphere.
+    ///
+    /// Going across a twisted edge reverses the other coordinate.
+    ///
+    /// Coordinates beyond a corner of a cross-surface or a sphere are
+    /// unchanged, so that they point to a cell outside the grid,
+    /// which is always in the background state.
+    fn wrap(&self, coord: Coord) -> Coord {
+        let (x, y, t) = coord;
+        let grid = match self.grid {
+            Some(grid) => grid,
+            None => return coord,
+        };
+        let (width, height) = (grid.width, grid.height);
+        let (kx, ky) = match grid.topology {
+            Topology::Torus => {
+                let x = if width > 0 {
+                    x.rem_euclid(self.config.width)
+                } else {
+                    x
+                };
+                let y = if height > 0 {
+                    y.rem_euclid(self.config.height)
+                } else {
+                    y
+                };
+                return (x, y, t);
+            }
+            Topology::Plane => return coord,
+            _ => (x.div_euclid(width), y.div_euclid(height)),
+        };
+        let flip = |z: isize, size: isize, twisted: bool, crossed: isize| {
+            let z = z.rem_euclid(size);
+            if twisted && crossed % 2 != 0 {
+                size - 1 - z
+            } else {
+                z
+            }
+        };
+        match grid.topology {
+            Topology::KleinBottle { twisted_width } => (
+                flip(x, width, twisted_width, ky),
+                flip(y, height, !twisted_width, kx),
+                t,
+            ),
+            _ if kx != 0 && ky != 0 => coord,
+            Topology::CrossSurface => (flip(x, width, true, ky), flip(y, height, true, kx), t),
+            _ => {
+                let (x, y) = if y < 0 {
+                    (-y - 1, x)
+                } else if y >= height {
+                    (width + height - 1 - y, x)
+                } else if x < 0 {
+                    (y, -x - 1)
+                } else if x >= width {
+                    (y, width + height - 1 - x)
+                } else {
+                    (x, y)
+                };
+                if 0 <= x && x < width && 0 <= y && y < height {
+                    (x, y, t)
+                } else {
+                    coord
+                }
+            }
+        }
+    }
+
     /// Finds a cell by its coordinates. Returns a `CellRef`.
     pub(crate) fn find_cell(&self, coord: Coord) -> Option<CellRef<'a, R>> {
         let (x, y, t) = coord;
-        if x >= -1
-            && x <= self.config.width
-            && y >= -1
-            && y <= self.config.height
+        let margin = self.margin;
+        if x >= -margin
+            && x < self.config.width + margin
+            && y >= -margin
+            && y < self.config.height + margin
             && t >= 0
             && t < self.config.period
         {
-            let index = ((x + 1) * (self.config.height + 2) + y + 1) * self.config.period + t;
+            let index = ((x + margin) * (self.config.height + 2 * margin) + y + margin)
+                * self.config.period
+                + t;
             let cell = &self.cells[index as usize];
             Some(cell.borrow())
         } else {
@@ -379,41 +1124,101 @@ impl<'a, R: Rule> World<'a, R> {
         }
     }
 
+    /// All the cells, including those in the margin.
+    #[cfg(any(feature = "sat", feature = "serialize"))]
+    pub(crate) fn cells(&self) -> impl Iterator<Item = CellRef<'a, R>> + '_ {
+        self.cells.iter().map(|cell| cell.borrow())
+    }
+
     /// Finds a cell by its coordinates. Returns a mutable pointer.
     fn find_cell_mut(&mut self, coord: Coord) -> Option<*mut LifeCell<'a, R>> {
         let (x, y, t) = coord;
-        if x >= -1
-            && x <= self.config.width
-            && y >= -1
-            && y <= self.config.height
+        let margin = self.margin;
+        if x >= -margin
+            && x < self.config.width + margin
+            && y >= -margin
+            && y < self.config.height + margin
             && t >= 0
             && t < self.config.period
         {
-            let index = ((x + 1) * (self.config.height + 2) + y + 1) * self.config.period + t;
+            let index = ((x + margin) * (self.config.height + 2 * margin) + y + margin)
+                * self.config.period
+                + t;
             Some(&mut self.cells[index as usize])
         } else {
             None
         }
     }
 
+    /// The rule of the world.
+    ///
+    /// This is for `Rule::consistify`, which may need the data of the rule.
+    pub fn rule(&self) -> &R {
+        &self.rule
+    }
+
+    /// Deduces the state of a cell in `Rule::consistify`.
+    ///
+    /// If the cell is unknown, sets its state. The consequences are then
+    /// examined later by the search.
+    ///
+    /// Returns `false` if there is a conflict: the cell is already known
+    /// with another state, or the number of living cells exceeds the
+    /// `max_cell_count` or the bound of its generation, row or column,
+    /// or the front becomes empty.
+    pub fn deduce(&mut self, cell: CellRef<'a, R>, state: State) -> bool {
+        match cell.state.get() {
+            Some(old_state) => old_state == state,
+            None => self.set_cell(cell, state, Reason::Deduce),
+        }
+    }
+
     /// Sets the `state` of a cell, push it to the `set_stack`,
     /// and update the neighborhood descriptor of its neighbors.
     ///
     /// The original state of the cell must be unknown.
     ///
     /// Return `false` if the number of living cells exceeds the `max_cell_count`
-    /// or the front becomes empty.
+    /// or the bound of its generation (see `gen_max_cell_count`),
+    /// or the bound of its row or column, or the number of rotor cells
+    /// exceeds the `max_rotor_cell_count`, or the front becomes empty.
     pub(crate) fn set_cell(&mut self, cell: CellRef<'a, R>, state: State, reason: Reason) -> bool {
         cell.state.set(Some(state));
         let mut result = true;
         cell.update_desc(Some(state), true);
-        if state == !cell.background {
+        if state == !cell.background && !cell.dont_care {
             self.cell_count[cell.coord.2 as usize] += 1;
             if let Some(max) = self.config.max_cell_count {
                 if self.cell_count() > max {
                     result = false;
                 }
             }
+            if let Some(max) = self.gen_max_cell_count(cell.coord.2) {
+                if self.cell_count[cell.coord.2 as usize] > max {
+                    result = false;
+                }
+            }
+            let (row, column) = self.line_indices(cell.coord);
+            self.row_cell_count[row] += 1;
+            self.column_cell_count[column] += 1;
+            if self
+                .config
+                .max_row_cell_count
+                .is_some_and(|max| self.row_cell_count[row] > max)
+                || self
+                    .config
+                    .max_column_cell_count
+                    .is_some_and(|max| self.column_cell_count[column] > max)
+            {
+                result = false;
+            }
+        }
+        if !cell.dont_care && self.update_rotor(cell, state, true) {
+            if let Some(max) = self.config.max_rotor_cell_count {
+                if self.rotor_cell_count > max {
+                    result = false;
+                }
+            }
         }
         if cell.is_front && state == cell.background {
             self.front_cell_count -= 1;
@@ -421,7 +1226,26 @@ impl<'a, R: Rule> World<'a, R> {
                 result = false;
             }
         }
-        self.set_stack.push(SetCell::new(cell, reason));
+        let cause = match reason {
+            Reason::Deduce => {
+                self.propagations += 1;
+                self.cause.clone()
+            }
+            _ => Cause::Unknown,
+        };
+        let decision = matches!(reason, Reason::Decide(_) | Reason::TryAnother(_, _));
+        if decision {
+            self.decision_depth += 1;
+        }
+        if let Some(hooks) = self.hooks.as_mut() {
+            hooks.on_set_cell(cell.coord, state, decision);
+        }
+        self.set_stack.push(SetCell {
+            cell,
+            reason,
+            cause,
+            flipped: false,
+        });
         result
     }
 
@@ -429,10 +1253,16 @@ impl<'a, R: Rule> World<'a, R> {
     /// and update the neighborhood descriptor of its neighbors.
     pub(crate) fn clear_cell(&mut self, cell: CellRef<'a, R>) {
         let old_state = cell.state.take();
-        if old_state != None {
+        if let Some(state) = old_state {
             cell.update_desc(old_state, false);
-            if old_state == Some(!cell.background) {
+            if old_state == Some(!cell.background) && !cell.dont_care {
                 self.cell_count[cell.coord.2 as usize] -= 1;
+                let (row, column) = self.line_indices(cell.coord);
+                self.row_cell_count[row] -= 1;
+                self.column_cell_count[column] -= 1;
+            }
+            if !cell.dont_care {
+                self.update_rotor(cell, state, false);
             }
             if cell.is_front && old_state == Some(cell.background) {
                 self.front_cell_count += 1;
@@ -440,6 +1270,39 @@ impl<'a, R: Rule> World<'a, R> {
         }
     }
 
+    /// Updates `rotor_states` and `rotor_cell_count` when the state
+    /// of a cell is set or cleared.
+    ///
+    /// Returns `true` if the number of rotor cells increases.
+    fn update_rotor(&mut self, cell: CellRef<'a, R>, state: State, set: bool) -> bool {
+        let (x, y, _) = cell.coord;
+        let index = (y * self.config.width + x) as usize;
+        let states = &mut self.rotor_states[index];
+        let was_rotor = states.0 > 0 && states.1 > 0;
+        let count = if state == cell.background {
+            &mut states.0
+        } else {
+            &mut states.1
+        };
+        if set {
+            *count += 1;
+        } else {
+            *count -= 1;
+        }
+        let is_rotor = states.0 > 0 && states.1 > 0;
+        match (was_rotor, is_rotor) {
+            (false, true) => {
+                self.rotor_cell_count += 1;
+                true
+            }
+            (true, false) => {
+                self.rotor_cell_count -= 1;
+                false
+            }
+            _ => false,
+        }
+    }
+
     /// Gets a references to the first unknown cell since `index` in the `search_list`.
     pub(crate) fn get_unknown(&self, index: usize) -> Option<(usize, CellRef<'a, R>)> {
         self.search_list[index..]
@@ -456,21 +1319,34 @@ impl<'a, R: Rule> World<'a, R> {
 
     /// Tests whether the world is nonempty,
     /// and whether the minimal period of the pattern equals to the given period.
+    ///
+    /// Cells whose states are irrelevant are ignored.
     pub(crate) fn nontrivial(&self) -> bool {
         self.cell_count[0] > 0
             && (1..self.config.period).all(|t| {
                 self.config.period % t != 0
-                    || self
-                        .cells
-                        .chunks(self.config.period as usize)
-                        .any(|c| c[0].state.get() != c[t as usize].state.get())
+                    || self.cells.chunks(self.config.period as usize).any(|c| {
+                        !c[0].dont_care
+                            && !c[t as usize].dont_care
+                            && c[0].state.get() != c[t as usize].state.get()
+                    })
             })
     }
 
     /// Gets the state of a cell. Returns `Err(())` if there is no such cell.
+    ///
+    /// If the rule is inverted, the state is inverted back.
     pub fn get_cell_state(&self, coord: Coord) -> Result<Option<State>, Error> {
+        let inverted = self.rule.is_inverted();
         self.find_cell(self.config.translate(coord))
-            .map(|cell| cell.state.get())
+            .map(|cell| {
+                let state = cell.state.get();
+                if inverted {
+                    state.map(|state| !state)
+                } else {
+                    state
+                }
+            })
             .ok_or(Error::GetCellError(coord))
     }
 
@@ -480,4 +1356,156 @@ impl<'a, R: Rule> World<'a, R> {
     pub(crate) fn cell_count(&self) -> usize {
         *self.cell_count.iter().min().unwrap()
     }
+
+    /// The indices of the row and the column of a cell
+    /// in `row_cell_count` and `column_cell_count`.
+    pub(crate) fn line_indices(&self, coord: Coord) -> (usize, usize) {
+        let (x, y, t) = coord;
+        (
+            (t * self.config.height + y) as usize,
+            (t * self.config.width + x) as usize,
+        )
+    }
+
+    /// The maximal number of living cells in generation `t`,
+    /// given by `max_gen0_cell_count` and `max_gen_cell_count`.
+    pub(crate) fn gen_max_cell_count(&self, t: isize) -> Option<usize> {
+        let gen0 = self.config.max_gen0_cell_count.filter(|_| t == 0);
+        match (gen0, self.config.max_gen_cell_count) {
+            (Some(m), Some(n)) => Some(m.min(n)),
+            (m, n) => m.or(n),
+        }
+    }
+}
+
+/// The 60° rotation and the reflection across a vertical line
+/// of the triangular grid, in the coordinates of `triangle_map`.
+const TRIANGLE_ROTATE: [[isize; 2]; 2] = [[0, -1], [1, 1]];
+const TRIANGLE_FLIP: [[isize; 2]; 2] = [[-1, -1], [0, 1]];
+
+/// Applies a rotation or a reflection of the triangular grid
+/// around a vertex to a cell.
+///
+/// The triangle `(x, y)` spans from `x` to `x + 2` horizontally, in units
+/// of half an edge, and from `y` to `y + 1` vertically, in units of
+/// the height of a triangle. It points up if `x + y` is even, and down
+/// otherwise. A vertex `(u, v)` in these units has an odd `u + v`.
+///
+/// The matrix acts on 3 times the coordinates of the centroid of the triangle
+/// relative to the vertex, in the basis of the edges `(2, 0)` and `(1, 1)`.
+fn triangle_map(
+    m: [[isize; 2]; 2],
+    (u, v): (isize, isize),
+    (x, y): (isize, isize),
+) -> (isize, isize) {
+    let b = 3 * (y - v) + if (x + y).rem_euclid(2) == 0 { 2 } else { 1 };
+    let a = (3 * (x + 1 - u) - b) / 2;
+    let (a, b) = (m[0][0] * a + m[0][1] * b, m[1][0] * a + m[1][1] * b);
+    (u - 1 + (2 * a + b) / 3, v + b.div_euclid(3))
+}
+
+/// Checks the parts of the configuration that depend on the rule.
+///
+/// The size of the bounded grid, if any, should be already applied
+/// to the configuration.
+///
+/// Returns an error if the rule contains `B0` and the period is odd,
+/// unless there is an agar. Since the background alternates between
+/// `Dead` and `Alive`, it would never return to the first generation.
+/// For the same reason, a glide symmetry in a Generations rule with `B0`
+/// requires the half period to be even. In rules with 2 states,
+/// the symmetric cells in such generations have inverted states.
+///
+/// Returns an error if the symmetry does not preserve the neighborhood
+/// of the rule, e.g., a hexagonal symmetry for a rule on the square grid,
+/// or if the center of the world is not a center of a hexagonal symmetry.
+/// For rules on the triangular grid, the parities of the cells are
+/// also considered. Other rules that depend on the parity of
+/// the coordinates are only checked for hexagonal symmetries.
+///
+/// Also returns an error if the bounded grid is a bounded plane and the rule
+/// contains `B0`, or if it is a torus whose joined dimensions are odd and
+/// the rule depends on the parity of the coordinates, or if its other edges
+/// are joined in a way that does not preserve the neighborhood.
+///
+/// With an agar, returns an error if the rule has more than 2 states.
+pub(crate) fn check_rule<R: Rule>(config: &Config, rule: &R) -> Result<(), Error> {
+    if rule.has_b0() && config.period % 2 != 0 && config.agar.is_none() {
+        return Err(Error::B0OddPeriodError(config.period));
+    }
+    if config.agar.is_some() && rule.gen() > 2 {
+        return Err(Error::AgarError(format!(
+            "Generations rule {}",
+            config.rule_string
+        )));
+    }
+    if rule.has_b0() && rule.gen() > 2 && config.symmetry.is_glide() && config.period % 4 != 0 {
+        return Err(Error::GlideSymmetryError(config.symmetry));
+    }
+    let (nbhd, nbhd_odd) = (rule.nbhd(), rule.nbhd_odd());
+    let triangle = nbhd != nbhd_odd;
+    let preserved = if triangle {
+        config.preserves_parity_nbhd(nbhd, nbhd_odd)
+    } else if rule.has_parity() {
+        !config.symmetry.is_hex()
+    } else {
+        config.symmetry.preserves(nbhd)
+    };
+    if !preserved {
+        return Err(Error::NbhdSymmetryError(config.symmetry));
+    }
+    if config.symmetry.is_hex() && !config.hex_centered(triangle) {
+        return Err(Error::HexSymmetryError(config.symmetry));
+    }
+    match config.bounded_grid()? {
+        Some(BoundedGrid {
+            topology: Topology::Plane,
+            ..
+        }) if rule.has_b0() || rule.is_inverted() => return Err(Error::GridB0Error),
+        Some(
+            grid @ BoundedGrid {
+                topology: Topology::Torus,
+                ..
+            },
+        ) if rule.has_parity() && (grid.width % 2 != 0 || grid.height % 2 != 0) => {
+            return Err(Error::GridError(format!(
+                "T{},{} (the size must be even for this rule)",
+                grid.width, grid.height
+            )))
+        }
+        Some(grid)
+            if grid.nbhd_symmetry() != Symmetry::C1
+                && (rule.has_parity() || !grid.nbhd_symmetry().preserves(nbhd)) =>
+        {
+            return Err(Error::GridError(format!(
+                "{} (the neighborhood must be symmetric for this rule)",
+                grid
+            )));
+        }
+        _ => (),
+    }
+    Ok(())
+}
+
+/// Creates a new world from the configuration and the rule,
+/// as a trait object.
+///
+/// The size of the bounded grid, if any, should be already applied
+/// to the configuration.
+///
+/// Returns an error if the configuration does not agree with the rule,
+/// see `check_rule`, or if the agar does not return to itself
+/// after the period.
+pub(crate) fn new_world<R: Rule + 'static>(
+    config: &Config,
+    rule: R,
+) -> Result<Box<dyn Search>, Error> {
+    check_rule(config, &rule)?;
+    let world = World::new(config, rule);
+    if !world.agar_returns() {
+        return Err(Error::AgarError(String::from(
+            "the agar does not return to itself after the period",
+        )));
+    }
+    Ok(Box::new(world))
 }
@@ -1,4 +1,11 @@
-use rlifesrc_lib::{Config, Error, Status, Symmetry, Transform};
+use rlifesrc_lib::{
+    config_digest, read_decisions, read_trace, rule_range, write_decisions, write_trace, Agar,
+    BoundedGrid, Budget, ChooseCell, Config, Coord, Engine, Error, Features, FileKind, Growth,
+    Guide, Header, Hooks, KnownCell, NewState, Outcome, Policy, Search, SearchOrder, Session,
+    State, Stats, Status, Stop, Symmetry, SymmetryMap, Topology, Transform, ALIVE, DEAD,
+    FORMAT_VERSION,
+};
+use std::{cell::RefCell, rc::Rc, time::Duration};
 
 #[test]
 fn default() -> Result<(), Error> {
@@ -25,6 +32,85 @@ fn max_cell_count() -> Result<(), Error> {
     Ok(())
 }
 
+#[test]
+fn max_gen0_cell_count() -> Result<(), Error> {
+    let config = Config::new(5, 5, 2).set_max_gen0_cell_count(Some(3));
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    assert_eq!(search.rle_gen(0).matches('o').count(), 3);
+
+    let config = Config::new(5, 5, 2).set_max_gen0_cell_count(Some(2));
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::None);
+    Ok(())
+}
+
+#[test]
+fn max_gen_cell_count() -> Result<(), Error> {
+    let config = Config::new(5, 5, 2).set_max_gen_cell_count(Some(3));
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    for t in 0..2 {
+        assert_eq!(search.rle_gen(t).matches('o').count(), 3);
+    }
+
+    let config = Config::new(5, 5, 2).set_max_gen_cell_count(Some(2));
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::None);
+    Ok(())
+}
+
+#[test]
+fn max_line_cell_count() -> Result<(), Error> {
+    let config = Config::new(6, 6, 1)
+        .set_max_row_cell_count(Some(2))
+        .set_max_column_cell_count(Some(2));
+    for &backjump in &[false, true] {
+        let mut search = config.clone().set_backjump(backjump).world()?;
+        assert_eq!(search.search(None), Status::Found);
+        let rle = search.rle_gen(0);
+        let rows = rle.lines().skip(1).collect::<Vec<_>>();
+        for row in rows.iter() {
+            assert!(row.matches('o').count() <= 2);
+        }
+        for x in 0..6 {
+            assert!(rows.iter().filter(|row| row.as_bytes()[x] == b'o').count() <= 2);
+        }
+    }
+
+    let config = Config::new(5, 5, 1).set_max_row_cell_count(Some(1));
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::None);
+    Ok(())
+}
+
+#[test]
+fn max_rotor_cell_count() -> Result<(), Error> {
+    // The rotor of the beacon has 2 cells.
+    let config = Config::new(5, 5, 2).set_max_rotor_cell_count(Some(2));
+    for &backjump in &[false, true] {
+        let mut search = config.clone().set_backjump(backjump).world()?;
+        assert_eq!(search.search(None), Status::Found);
+        let mut rotor = 0;
+        for x in 0..5 {
+            for y in 0..5 {
+                if search.get_cell_state((x, y, 0))? != search.get_cell_state((x, y, 1))? {
+                    rotor += 1;
+                }
+            }
+        }
+        assert!(rotor <= 2);
+    }
+
+    // The rotor of the blinker has 4 cells.
+    let config = Config::new(3, 3, 2).set_max_rotor_cell_count(Some(4));
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    let mut search = config.set_max_rotor_cell_count(Some(3)).world()?;
+    assert_eq!(search.search(None), Status::None);
+    Ok(())
+}
+
 #[test]
 fn reduce_max() -> Result<(), Error> {
     let config = Config::new(5, 5, 1)
@@ -36,6 +122,226 @@ fn reduce_max() -> Result<(), Error> {
     Ok(())
 }
 
+#[test]
+fn minimize() -> Result<(), Error> {
+    let config = Config::new(6, 6, 1).set_minimize(true);
+    let mut search = config.world()?;
+    let mut status = Status::Searching;
+    while status == Status::Searching {
+        status = search.search(Some(100));
+    }
+    assert_eq!(status, Status::Found);
+    assert_eq!(search.rle_gen(0).matches('o').count(), 4);
+    assert_eq!(search.config().max_cell_count, Some(3));
+    assert_eq!(search.search(None), Status::None);
+    Ok(())
+}
+
+#[test]
+fn stats() -> Result<(), Error> {
+    let config = Config::new(16, 5, 3).set_translate(0, 1);
+    let mut search = config.world()?;
+    assert_eq!(search.stats(), Stats::default());
+    assert_eq!(search.search(None), Status::Found);
+    let stats = search.stats();
+    assert!(stats.decisions > 0);
+    assert!(stats.propagations > 0);
+    assert_eq!(stats.conflicts, search.conflicts());
+    assert_eq!(stats.restarts, 0);
+    assert!(stats.max_depth >= 16 * 5 * 3);
+    Ok(())
+}
+
+#[test]
+fn progress() -> Result<(), Error> {
+    let config = Config::new(5, 5, 1).set_max_cell_count(Some(4));
+    let mut search = config.world()?;
+    assert_eq!(search.progress(), 0.0);
+    let mut last = 0.0;
+    loop {
+        let status = search.search(None);
+        let progress = search.progress();
+        if status == Status::None {
+            break;
+        }
+        assert!(progress >= last);
+        assert!(progress < 100.0);
+        last = progress;
+    }
+    assert!(last > 0.0);
+    Ok(())
+}
+
+#[test]
+fn stop() -> Result<(), Error> {
+    let config = Config::new(16, 5, 3).set_translate(0, 1);
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    let expected = search.rle_gen(0);
+
+    let mut search = config.world()?;
+    let stop = Stop::new();
+    search.set_stop(Some(stop.clone()));
+    stop.stop();
+    assert_eq!(search.search(None), Status::Paused);
+    stop.reset();
+    assert_eq!(search.search(None), Status::Found);
+    assert_eq!(search.rle_gen(0), expected);
+    Ok(())
+}
+
+#[test]
+fn hooks() -> Result<(), Error> {
+    #[derive(Default)]
+    struct Counts {
+        decisions: usize,
+        backtracks: usize,
+        conflicts: u64,
+        solutions: Vec<String>,
+    }
+
+    struct Counter(Rc<RefCell<Counts>>, Stop);
+
+    impl Hooks for Counter {
+        fn on_set_cell(&mut self, _coord: Coord, _state: State, decision: bool) {
+            if decision {
+                self.0.borrow_mut().decisions += 1;
+            }
+        }
+
+        fn on_backtrack(&mut self, _depth: usize) {
+            self.0.borrow_mut().backtracks += 1;
+        }
+
+        fn on_conflict(&mut self, conflicts: u64) {
+            self.0.borrow_mut().conflicts = conflicts;
+        }
+
+        fn on_solution(&mut self, search: &dyn Search) {
+            self.0.borrow_mut().solutions.push(search.rle_gen(0));
+            self.1.stop();
+        }
+    }
+
+    let config = Config::new(16, 5, 3).set_translate(0, 1);
+    let mut search = config.world()?;
+    let counts = Rc::new(RefCell::new(Counts::default()));
+    let stop = Stop::new();
+    search.set_hooks(Some(Box::new(Counter(Rc::clone(&counts), stop.clone()))));
+    search.set_stop(Some(stop.clone()));
+    assert_eq!(search.search(None), Status::Found);
+    assert!(stop.is_stopped());
+    let counts = counts.borrow();
+    assert_eq!(counts.solutions, vec![search.rle_gen(0)]);
+    assert_eq!(counts.conflicts, search.conflicts());
+    assert!(counts.decisions > 0);
+    assert!(counts.backtracks > 0);
+    Ok(())
+}
+
+#[test]
+fn replay_decisions() -> Result<(), Error> {
+    let config = Config::new(16, 5, 3).set_translate(0, 1);
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    let path = search.decision_path();
+    assert!(!path.is_empty());
+
+    let mut buffer = Vec::new();
+    write_decisions(search.config(), &path, &mut buffer).unwrap();
+    let (header, decisions) = read_decisions(&mut buffer.as_slice()).unwrap();
+    assert!(header.matches(search.config()));
+    assert_eq!(decisions, path);
+
+    let mut replayed = config.world()?;
+    assert_eq!(replayed.replay(&decisions), Ok(true));
+    assert_eq!(replayed.rle_gen(0), search.rle_gen(0));
+    assert_eq!(replayed.decision_path(), path);
+    Ok(())
+}
+
+#[test]
+fn skip_subtree() -> Result<(), Error> {
+    let config = Config::new(16, 5, 3).set_translate(0, 1);
+    let mut search = config.world()?;
+    assert!(!search.skip_subtree());
+    assert_eq!(search.search(None), Status::Found);
+    let path = search.decision_path();
+    assert!(search.skip_subtree());
+    let skipped = search.decision_path();
+    assert_ne!(skipped, path);
+    assert!(skipped.len() <= path.len());
+    assert_eq!(skipped[..skipped.len() - 1], path[..skipped.len() - 1]);
+    Ok(())
+}
+
+#[test]
+fn guide() -> Result<(), Error> {
+    struct First(Coord);
+
+    impl Guide for First {
+        fn decide(&mut self, search: &dyn Search) -> Option<(Coord, Option<State>)> {
+            if search.get_cell_state(self.0) == Ok(None) {
+                Some((self.0, Some(ALIVE)))
+            } else {
+                None
+            }
+        }
+    }
+
+    let config = Config::new(16, 5, 3).set_translate(0, 1);
+    let mut search = config.world()?;
+    search.set_guide(Some(Box::new(First((4, 2, 0)))));
+    assert_eq!(search.search(None), Status::Found);
+    assert_eq!(search.decision_path()[0].coord, (4, 2, 0));
+    Ok(())
+}
+
+#[test]
+fn budget() -> Result<(), Error> {
+    let config = Config::new(16, 5, 3).set_translate(0, 1);
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    let expected = search.rle_gen(0);
+
+    for budget in [
+        Budget::default().set_conflicts(Some(10)),
+        Budget::default().set_decisions(Some(10)),
+    ]
+    .iter()
+    {
+        let mut search = config.world()?;
+        let mut calls = 0;
+        let mut status = Status::Searching;
+        while status == Status::Searching {
+            status = search.search_with_budget(*budget);
+            calls += 1;
+        }
+        assert_eq!(status, Status::Found);
+        assert_eq!(search.rle_gen(0), expected);
+        assert!(calls > 1);
+    }
+    Ok(())
+}
+
+#[test]
+fn time_limit() -> Result<(), Error> {
+    let config = Config::new(16, 5, 3)
+        .set_translate(0, 1)
+        .set_time_limit(Some(Duration::from_secs(3600)));
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+
+    let config = Config::new(40, 40, 5)
+        .set_translate(0, 1)
+        .set_time_limit(Some(Duration::from_millis(1)));
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::TimedOut);
+    assert!(search.rle_gen(0).contains('?'));
+    assert_eq!(search.search(None), Status::TimedOut);
+    Ok(())
+}
+
 #[test]
 fn p3_spaceship() -> Result<(), Error> {
     let config = Config::new(16, 5, 3).set_translate(0, 1);
@@ -56,74 +362,1616 @@ fn p3_spaceship() -> Result<(), Error> {
 }
 
 #[test]
-fn lwss() -> Result<(), Error> {
-    let config = Config::new(6, 6, 4).set_translate(0, 2);
+fn backjump() -> Result<(), Error> {
+    let config = Config::new(16, 5, 3).set_translate(0, 1);
     let mut search = config.world()?;
+    let mut backjump = config.clone().set_backjump(true).world()?;
+    assert_eq!(backjump.search(None), Status::Found);
     assert_eq!(search.search(None), Status::Found);
+    assert_eq!(backjump.rle_gen(0), search.rle_gen(0));
+    assert!(backjump.conflicts() <= search.conflicts());
+
+    let config = Config::new(5, 5, 3);
+    let mut search = config.world()?;
+    let mut backjump = config.set_backjump(true).world()?;
+    assert_eq!(backjump.search(None), Status::None);
+    assert_eq!(search.search(None), Status::None);
+    assert!(backjump.conflicts() <= search.conflicts());
     Ok(())
 }
 
 #[test]
-fn lwss_flip() -> Result<(), Error> {
-    let config = Config::new(5, 5, 2)
+fn nogoods() -> Result<(), Error> {
+    let config = Config::new(16, 5, 3).set_translate(0, 1);
+    let expected = results(config.world()?.as_mut(), 2);
+    for &limit in &[4, 1000] {
+        let config = config.clone().set_nogood_limit(limit);
+        assert_eq!(results(config.world()?.as_mut(), 2), expected);
+    }
+
+    let config = Config::new(5, 5, 3).set_nogood_limit(1000);
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::None);
+    Ok(())
+}
+
+#[test]
+fn restarts() -> Result<(), Error> {
+    let config = Config::new(16, 5, 3)
         .set_translate(0, 1)
-        .set_transform(Transform::FlipCol);
+        .set_new_state(NewState::Random)
+        .set_seed(Some(42))
+        .set_nogood_limit(1000)
+        .set_restart_interval(Some(1));
     let mut search = config.world()?;
     assert_eq!(search.search(None), Status::Found);
+    assert!(search.restarts() > 0);
+
+    let config = Config::new(5, 5, 3)
+        .set_nogood_limit(1000)
+        .set_restart_interval(Some(1));
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::None);
     Ok(())
 }
 
 #[test]
-fn turtle() -> Result<(), Error> {
-    let config = Config::new(12, 13, 3)
+fn activity_order() -> Result<(), Error> {
+    let config = Config::new(16, 5, 3)
         .set_translate(0, 1)
-        .set_symmetry(Symmetry::D2Col);
+        .set_activity_order(true);
     let mut search = config.world()?;
     assert_eq!(search.search(None), Status::Found);
+    let mut backjump = config.set_backjump(true).world()?;
+    assert_eq!(backjump.search(None), Status::Found);
+
+    let config = Config::new(5, 5, 3).set_activity_order(true);
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::None);
     Ok(())
 }
 
 #[test]
-fn b0() -> Result<(), Error> {
-    let config = Config::new(3, 3, 2).set_rule_string("B026/S1");
+fn order_switch() -> Result<(), Error> {
+    let config = Config::new(16, 5, 3)
+        .set_translate(0, 1)
+        .set_order_switch_interval(Some(1));
     let mut search = config.world()?;
+    assert_eq!(search.search_order(), SearchOrder::ColumnFirst);
     assert_eq!(search.search(None), Status::Found);
+
+    let config = Config::new(5, 5, 3).set_order_switch_interval(Some(1));
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::None);
+
+    let config = Config::new(5, 5, 2).set_order_switch_interval(Some(1));
+    let mut expected = results(
+        config
+            .clone()
+            .set_order_switch_interval(None)
+            .world()?
+            .as_mut(),
+        usize::MAX,
+    );
+    let mut found = results(config.world()?.as_mut(), usize::MAX);
+    expected.sort();
+    found.sort();
+    assert_eq!(found, expected);
     Ok(())
 }
 
 #[test]
-fn p3_2333() -> Result<(), Error> {
-    let config = Config::new(4, 4, 3).set_rule_string("23/3/3");
+fn deepening() -> Result<(), Error> {
+    let config = Config::new(16, 5, 3)
+        .set_translate(0, 1)
+        .set_deepening(Some(4));
     let mut search = config.world()?;
     assert_eq!(search.search(None), Status::Found);
+
+    let config = Config::new(5, 5, 3).set_deepening(Some(1));
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::None);
+
+    let config = Config::new(5, 5, 2);
+    let mut expected = results(config.world()?.as_mut(), usize::MAX);
+    let mut found = results(config.set_deepening(Some(2)).world()?.as_mut(), usize::MAX);
+    expected.sort();
+    found.sort();
+    assert_eq!(found, expected);
     Ok(())
 }
 
 #[test]
-fn snowflakes() -> Result<(), Error> {
-    let config = Config::new(4, 4, 2)
+fn blocklist() -> Result<(), Error> {
+    let config = Config::new(5, 5, 2);
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    let canonical = search.canonical_form();
+    let all = results(config.world()?.as_mut(), usize::MAX);
+
+    let config = config.set_blocklist(vec![search.rle_gen(0)]);
+    let mut search = config.world()?;
+    let mut count = 0;
+    while search.search(None) == Status::Found {
+        assert_ne!(search.canonical_form(), canonical);
+        count += 1;
+    }
+    assert!(count < all.len());
+
+    let subpattern = results(
+        config
+            .clone()
+            .set_blocklist_subpattern(true)
+            .world()?
+            .as_mut(),
+        usize::MAX,
+    );
+    assert!(subpattern.len() <= count);
+
+    let config = config.set_blocklist(vec![String::from("x = 2, y = 1\n2?!")]);
+    assert!(matches!(config.world(), Err(Error::BlocklistError(_))));
+    Ok(())
+}
+
+#[test]
+fn presolve() -> Result<(), Error> {
+    let config = Config::new(16, 5, 3).set_translate(0, 1);
+    let presolve = config.presolve()?;
+    assert!(presolve.consistent);
+    assert_eq!(presolve.total, 240);
+    assert_eq!(presolve.known, 0);
+    assert_eq!(presolve.forced.len() + presolve.unknown, 240);
+
+    let known_cells = vec![KnownCell {
+        coord: (0, 0, 0),
+        state: ALIVE,
+    }];
+    let config = Config::new(1, 1, 1).set_known_cells(known_cells);
+    let presolve = config.presolve()?;
+    assert!(!presolve.consistent);
+    assert_eq!(presolve.known, 1);
+    assert!(presolve.forced.iter().all(|cell| cell.coord != (0, 0, 0)));
+    Ok(())
+}
+
+#[test]
+fn dead_end_cache() -> Result<(), Error> {
+    let config = Config::new(5, 5, 2);
+    let expected = results(config.world()?.as_mut(), usize::MAX);
+    for &limit in &[1, 1000] {
+        let config = config.clone().set_dead_end_cache(limit);
+        assert_eq!(results(config.world()?.as_mut(), usize::MAX), expected);
+    }
+
+    let config = Config::new(4, 10, 4).set_translate(0, 1);
+    let expected = results(config.world()?.as_mut(), 3);
+    let config = config.set_dead_end_cache(1000);
+    assert_eq!(results(config.world()?.as_mut(), 3), expected);
+    Ok(())
+}
+
+#[test]
+fn endgame() -> Result<(), Error> {
+    let config = Config::new(5, 5, 2);
+    let mut expected = results(config.world()?.as_mut(), usize::MAX);
+    expected.sort();
+    for &threshold in &[1, 8] {
+        let config = config.clone().set_endgame_threshold(threshold);
+        let mut found = results(config.world()?.as_mut(), usize::MAX);
+        found.sort();
+        assert_eq!(found, expected);
+    }
+
+    let config = Config::new(16, 5, 3)
         .set_translate(0, 1)
-        .set_rule_string("B2ci3ai4c8/S02ae3eijkq4iz5ar6i7e");
+        .set_endgame_threshold(8);
     let mut search = config.world()?;
     assert_eq!(search.search(None), Status::Found);
     Ok(())
 }
 
 #[test]
-#[cfg(feature = "serialize")]
-fn ser() -> Result<(), Error> {
+fn row_engine() -> Result<(), Error> {
+    let config = Config::new(5, 5, 2);
+    let mut expected = results(config.world()?.as_mut(), usize::MAX);
+    let mut found = results(
+        config.set_engine(Engine::Rows).world()?.as_mut(),
+        usize::MAX,
+    );
+    expected.sort();
+    found.sort();
+    assert_eq!(found, expected);
+
+    let config = Config::new(16, 5, 3)
+        .set_translate(0, 1)
+        .set_engine(Engine::Rows);
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    Ok(())
+}
+
+#[test]
+fn symmetry_breaking() -> Result<(), Error> {
+    let canonical_forms = |config: &Config| -> Result<_, Error> {
+        let mut search = config.world()?;
+        let mut forms = Vec::new();
+        while search.search(None) == Status::Found {
+            forms.push(search.canonical_form());
+        }
+        Ok(forms)
+    };
+    let config = Config::new(5, 5, 2).set_non_empty_front(false);
+    let all = canonical_forms(&config)?;
+    let broken = canonical_forms(&config.set_symmetry_breaking(true))?;
+    assert!(!broken.is_empty());
+    assert!(broken.len() < all.len());
+    for form in all.iter() {
+        assert!(broken.contains(form));
+    }
+
+    let config = Config::new(16, 5, 3)
+        .set_translate(0, 1)
+        .set_non_empty_front(false)
+        .set_symmetry_breaking(true);
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    Ok(())
+}
+
+#[test]
+fn probing() -> Result<(), Error> {
     let config = Config::new(16, 5, 3).set_translate(0, 1);
+    let expected = results(config.world()?.as_mut(), 2);
+    for &(depth, interval) in &[(1, 0), (100, 1), (100, 3)] {
+        let config = config
+            .clone()
+            .set_probe_depth(depth)
+            .set_probe_interval(interval);
+        assert_eq!(results(config.world()?.as_mut(), 2), expected);
+    }
+
+    let config = Config::new(5, 5, 3).set_probe_depth(100);
     let mut search = config.world()?;
-    assert_eq!(search.search(Some(100)), Status::Searching);
-    let count = search.cell_count();
-    let save = search.ser();
-    let mut new_search = save.world()?;
-    assert_eq!(new_search.cell_count(), count);
-    assert_eq!(new_search.search(None), Status::Found);
+    assert_eq!(search.search(None), Status::None);
+    Ok(())
+}
+
+#[test]
+fn search_orders() -> Result<(), Error> {
+    let config = Config::new(5, 5, 4)
+        .set_translate(1, 1)
+        .set_search_order(Some(SearchOrder::Diagonal));
+    let mut search = config.world()?;
+    assert_eq!(search.search_partial(3, None), Status::Found);
+    for x in 0..5 {
+        for y in 0..5 {
+            if x + y < 3 {
+                assert!(search.get_cell_state((x, y, 0))?.is_some());
+            }
+        }
+    }
+    assert_eq!(search.search(None), Status::Found);
+
+    let config = Config::new(5, 5, 2).set_search_order(Some(SearchOrder::CenterOut));
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    Ok(())
+}
+
+#[test]
+fn lwss() -> Result<(), Error> {
+    let config = Config::new(6, 6, 4).set_translate(0, 2);
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    Ok(())
+}
+
+#[test]
+fn lwss_flip() -> Result<(), Error> {
+    let config = Config::new(5, 5, 2)
+        .set_translate(0, 1)
+        .set_transform(Transform::FlipCol);
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    Ok(())
+}
+
+#[test]
+fn blinker_rotate() -> Result<(), Error> {
+    // The tub is also a result, so the population is limited.
+    let config = Config::new(3, 3, 1)
+        .set_transform(Transform::Rotate90)
+        .set_max_cell_count(Some(3));
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    assert_eq!(search.cell_count(), 3);
+
+    let analysis = search.analyze();
+    assert_eq!(analysis.period, 1);
+    assert_eq!(analysis.transform, Transform::Rotate90);
+    assert_eq!(analysis.symmetry, Symmetry::D4Ortho);
+    Ok(())
+}
+
+#[test]
+fn lwss_glide() -> Result<(), Error> {
+    let config = Config::new(5, 6, 4)
+        .set_translate(0, 2)
+        .set_symmetry(Symmetry::GlideCol);
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    let analysis = search.analyze();
+    assert_eq!(analysis.period, 2);
+    assert_eq!(analysis.transform, Transform::FlipCol);
+    assert_eq!((analysis.dx, analysis.dy), (0, 1));
+
+    let config = config.set_translate(0, 1);
     assert_eq!(
-        new_search.rle_gen(0),
-        String::from(
-            "x = 16, y = 5, rule = B3/S23\n\
+        config.world().err(),
+        Some(Error::GlideSymmetryError(Symmetry::GlideCol))
+    );
+    Ok(())
+}
+
+#[test]
+fn compatible_symmetries() {
+    let config = Config::new(5, 5, 4);
+    assert_eq!(config.max_symmetry(), Symmetry::D8);
+    assert!(config.is_compatible(Symmetry::GlideCol));
+
+    let config = config.set_translate(0, 2);
+    assert_eq!(config.max_symmetry(), Symmetry::D2Col);
+    assert_eq!(
+        config.compatible_symmetries(),
+        vec![
+            Symmetry::C1,
+            Symmetry::D2Col,
+            Symmetry::GlideCol,
+            Symmetry::GutterCol
+        ]
+    );
+
+    let config = config.set_translate(1, 1);
+    assert_eq!(config.max_symmetry(), Symmetry::D2Diag);
+
+    let config = Config::new(5, 5, 4)
+        .set_translate(1, 0)
+        .set_symmetry(Symmetry::D4Ortho);
+    assert_eq!(
+        config.world().err(),
+        Some(Error::SymmetryTranslationError(
+            Symmetry::D4Ortho,
+            vec![Symmetry::C1, Symmetry::D2Row, Symmetry::GutterRow]
+        ))
+    );
+
+    let config = Config::new(5, 5, 4).set_transform(Transform::Rotate90);
+    assert_eq!(config.max_symmetry(), Symmetry::D8);
+    assert!(!config.is_compatible(Symmetry::D2Row));
+    assert!(config.is_compatible(Symmetry::D4Ortho));
+    assert!(!config.is_compatible(Symmetry::C6));
+
+    let config = Config::new(5, 5, 2).set_transform(Transform::FlipDiag);
+    assert!(config.is_compatible(Symmetry::D6));
+    assert!(!Config::new(4, 4, 2).is_compatible(Symmetry::D6));
+}
+
+#[test]
+fn turtle() -> Result<(), Error> {
+    let config = Config::new(12, 13, 3)
+        .set_translate(0, 1)
+        .set_symmetry(Symmetry::D2Col);
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    Ok(())
+}
+
+#[test]
+fn axis_offset() -> Result<(), Error> {
+    let config = Config::new(4, 3, 2)
+        .set_symmetry(Symmetry::D2Col)
+        .set_axis_offset(-1, 0);
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    for y in 0..3 {
+        for t in 0..2 {
+            assert_eq!(
+                search.get_cell_state((0, y, t))?,
+                search.get_cell_state((2, y, t))?
+            );
+            assert_eq!(search.get_cell_state((3, y, t))?, Some(DEAD));
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn diagonal_offset() -> Result<(), Error> {
+    let config = Config::new(5, 5, 1)
+        .set_symmetry(Symmetry::D2Diag)
+        .set_diagonal_offset(1, 0);
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    for y in 0..5 {
+        for x in 0..5 {
+            let state = search.get_cell_state((x, y, 0))?;
+            if (0..5).contains(&(y - 1)) && (0..5).contains(&(x + 1)) {
+                assert_eq!(state, search.get_cell_state((y - 1, x + 1, 0))?);
+            } else {
+                assert_eq!(state, Some(DEAD));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn time_reversal() -> Result<(), Error> {
+    let config = Config::new(3, 3, 2).set_time_reversal(Some(Transform::FlipCol));
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    for t in 0..2 {
+        for y in 0..3 {
+            assert_eq!(
+                search.get_cell_state((0, y, t))?,
+                search.get_cell_state((2, y, t))?
+            );
+        }
+    }
+
+    let config = Config::new(4, 3, 2).set_time_reversal(Some(Transform::Rotate90));
+    assert_eq!(
+        config.world().err(),
+        Some(Error::TransformSizeError(Transform::Rotate90, 4, 3))
+    );
+    Ok(())
+}
+
+#[test]
+fn symmetry_gen0_only() -> Result<(), Error> {
+    let config = Config::new(3, 3, 2)
+        .set_symmetry(Symmetry::D2Col)
+        .set_symmetry_gen0_only(true);
+    assert!(config.is_compatible(Symmetry::D2Row));
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    for y in 0..3 {
+        assert_eq!(
+            search.get_cell_state((0, y, 0))?,
+            search.get_cell_state((2, y, 0))?
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn fundamental_domain() -> Result<(), Error> {
+    for &symmetry in &[Symmetry::C4, Symmetry::D4Diag, Symmetry::D8] {
+        let config = Config::new(8, 8, 2).set_symmetry(symmetry);
+        let expected = results(config.world()?.as_mut(), 3);
+        let config = config.set_fundamental_domain(true);
+        assert_eq!(results(config.world()?.as_mut(), 3), expected);
+    }
+
+    // `D2|` does not map the hexagonal neighborhood to itself.
+    for &symmetry in &[Symmetry::D2Col, Symmetry::D2Diag] {
+        let config = Config::new(6, 6, 1)
+            .set_rule_string("B2/S34H")
+            .set_symmetry(symmetry);
+        let expected = config.world().map(|mut search| results(search.as_mut(), 3));
+        let config = config.set_fundamental_domain(true);
+        assert_eq!(
+            config.world().map(|mut search| results(search.as_mut(), 3)),
+            expected
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn gutter() -> Result<(), Error> {
+    let config = Config::new(5, 4, 1).set_symmetry(Symmetry::GutterCol);
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    for y in 0..4 {
+        assert_eq!(search.get_cell_state((2, y, 0))?, Some(DEAD));
+        assert_eq!(
+            search.get_cell_state((0, y, 0))?,
+            search.get_cell_state((4, y, 0))?
+        );
+    }
+
+    let config = Config::new(4, 4, 1).set_symmetry(Symmetry::GutterCol);
+    assert_eq!(
+        config.world().err(),
+        Some(Error::GutterSymmetryError(Symmetry::GutterCol))
+    );
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "hex")]
+fn hex_symmetry() -> Result<(), Error> {
+    // A ring of six cells is a still life. It does not touch the front.
+    let config = Config::new(5, 5, 1)
+        .set_rule_string("B3/S2H")
+        .set_symmetry(Symmetry::C6)
+        .set_non_empty_front(false);
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    for y in 0..5 {
+        for x in 0..5 {
+            if 0 <= x - y + 2 && x - y + 2 < 5 {
+                assert_eq!(
+                    search.get_cell_state((x, y, 0))?,
+                    search.get_cell_state((x - y + 2, x, 0))?
+                );
+            }
+        }
+    }
+
+    let config = Config::new(4, 4, 1)
+        .set_rule_string("B3/S2H")
+        .set_symmetry(Symmetry::C3);
+    assert_eq!(
+        config.world().err(),
+        Some(Error::HexSymmetryError(Symmetry::C3))
+    );
+    let config = Config::new(5, 5, 1)
+        .set_rule_string("B3/S2H")
+        .set_symmetry(Symmetry::D2Row);
+    assert_eq!(
+        config.world().err(),
+        Some(Error::NbhdSymmetryError(Symmetry::D2Row))
+    );
+    let config = Config::new(5, 5, 1).set_symmetry(Symmetry::D12);
+    assert_eq!(
+        config.world().err(),
+        Some(Error::NbhdSymmetryError(Symmetry::D12))
+    );
+    Ok(())
+}
+
+#[test]
+fn turtle_symmetry_map() -> Result<(), Error> {
+    let config = Config::new(12, 13, 3)
+        .set_translate(0, 1)
+        .set_symmetry_maps(vec![SymmetryMap::new((-1, 0, 11), (0, 1, 0))]);
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    for y in 0..13 {
+        for x in 0..12 {
+            assert_eq!(
+                search.get_cell_state((x, y, 0))?,
+                search.get_cell_state((11 - x, y, 0))?
+            );
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn diagonal_width() -> Result<(), Error> {
+    let config = Config::new(5, 5, 4)
+        .set_translate(1, 1)
+        .set_diagonal_width(Some(3));
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+
+    // Only the cells on the diagonal can be alive.
+    let mut search = config.set_diagonal_width(Some(1)).world()?;
+    assert_eq!(search.search(None), Status::None);
+    Ok(())
+}
+
+#[test]
+fn shift() -> Result<(), Error> {
+    // A frame that moves by `(1, 0)` in every generation, and is moved
+    // back after the period, is the same as a fixed frame.
+    // The front is different, so it is not used.
+    let config = Config::new(4, 4, 1).set_non_empty_front(false);
+    let expected = results(config.world()?.as_mut(), usize::MAX);
+    let config = config.set_shift(1, 0).set_translate(-1, 0);
+    assert_eq!(results(config.world()?.as_mut(), usize::MAX), expected);
+
+    // There are no spaceships of speed c in Life.
+    let config = Config::new(6, 6, 1).set_shift(1, 0);
+    assert_eq!(config.world()?.search(None), Status::None);
+
+    let config = Config::new(6, 6, 2).set_shift(1, 0);
+    assert_eq!(
+        config
+            .clone()
+            .set_symmetry(Symmetry::D2Col)
+            .world()
+            .err()
+            .map(|e| matches!(e, Error::SymmetryTranslationError(..))),
+        Some(true)
+    );
+    assert!(config.clone().set_symmetry(Symmetry::D2Row).world().is_ok());
+    assert_eq!(
+        config
+            .clone()
+            .set_symmetry(Symmetry::GlideRow)
+            .world()
+            .err(),
+        Some(Error::GlideSymmetryError(Symmetry::GlideRow))
+    );
+    assert_eq!(
+        config.set_time_reversal(Some(Transform::Id)).world().err(),
+        Some(Error::ShiftError(String::from("time-reversal symmetries")))
+    );
+    Ok(())
+}
+
+#[test]
+fn shape() -> Result<(), Error> {
+    let config = Config::new(4, 4, 1).set_shape_plaintext(
+        "!An L-shape\n\
+         OO..\n\
+         OO..\n\
+         OOOO\n\
+         OOOO\n",
+    );
+    assert_eq!(config.shape.as_ref().map(Vec::len), Some(12));
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    for x in 2..4 {
+        for y in 0..2 {
+            assert_eq!(search.get_cell_state((x, y, 0))?, Some(DEAD));
+        }
+    }
+
+    // No still life fits in a single row.
+    let mut search = Config::new(4, 4, 1).add_shape_rect(0, 0, 4, 1).world()?;
+    assert_eq!(search.search(None), Status::None);
+    Ok(())
+}
+
+#[test]
+fn dead_cells() -> Result<(), Error> {
+    let config = Config::new(6, 6, 4)
+        .set_translate(1, 1)
+        .add_dead_rect(0, 0, 6, 2);
+    assert_eq!(config.dead_cells.len(), 12);
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    for &(x, y) in config.dead_cells.iter() {
+        for t in 0..4 {
+            assert_eq!(search.get_cell_state((x, y, t))?, Some(DEAD));
+        }
+    }
+    let presolve = config.presolve()?;
+    assert_eq!(presolve.known, 48);
+
+    // No cell can be alive.
+    let mut search = config.add_dead_rect(0, 2, 6, 4).world()?;
+    assert_eq!(search.search(None), Status::None);
+    Ok(())
+}
+
+#[test]
+fn stator_cells() -> Result<(), Error> {
+    let config = Config::new(5, 5, 2).add_stator_rect(0, 0, 5, 1);
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    for x in 0..5 {
+        assert_eq!(
+            search.get_cell_state((x, 0, 0))?,
+            search.get_cell_state((x, 0, 1))?
+        );
+    }
+
+    // A pattern whose cells are all stator cells has period 1.
+    let mut search = config.add_stator_rect(0, 0, 5, 5).world()?;
+    assert_eq!(search.search(None), Status::None);
+    Ok(())
+}
+
+#[test]
+fn b0() -> Result<(), Error> {
+    let config = Config::new(3, 3, 2).set_rule_string("B026/S1");
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    Ok(())
+}
+
+#[test]
+fn agar() -> Result<(), Error> {
+    let agar = |width, height, gens: &[&str]| Agar {
+        width,
+        height,
+        gens: gens.iter().map(|rle| rle.to_string()).collect(),
+    };
+
+    // The vacuum, and the alternating background of a rule with `B0`.
+    let config = Config::new(5, 5, 1);
+    let expected = results(config.world()?.as_mut(), usize::MAX);
+    let config = config.set_agar(Some(agar(1, 1, &["b!"])));
+    assert_eq!(results(config.world()?.as_mut(), usize::MAX), expected);
+    let config = Config::new(3, 3, 2).set_rule_string("B026/S1");
+    let expected = results(config.world()?.as_mut(), usize::MAX);
+    let config = config.set_agar(Some(agar(1, 1, &["b!", "o!"])));
+    assert_eq!(results(config.world()?.as_mut(), usize::MAX), expected);
+
+    // Zebra stripes are a still agar in Life.
+    let config = Config::new(6, 6, 1).set_agar(Some(agar(1, 2, &["o!"])));
+    assert!(config.presolve()?.consistent);
+    assert_eq!(
+        config.set_translate(0, 1).world().err(),
+        Some(Error::AgarError(String::from(
+            "the agar does not return to itself after the period"
+        )))
+    );
+
+    for (width, gens) in &[(0, &["o!"][..]), (1, &[][..]), (1, &["2o!"][..])] {
+        let config = Config::new(6, 6, 1).set_agar(Some(agar(*width, 2, gens)));
+        assert!(matches!(config.world().err(), Some(Error::AgarError(_))));
+    }
+    Ok(())
+}
+
+#[test]
+fn b0_odd_period() -> Result<(), Error> {
+    let config = Config::new(3, 3, 3).set_rule_string("B026/S1");
+    assert_eq!(config.world().err(), Some(Error::B0OddPeriodError(3)));
+
+    let config = config.set_translate(1, 0).double_period();
+    assert_eq!((config.period, config.dx, config.dy), (6, 2, 0));
+    assert!(config.world().is_ok());
+
+    let config = Config::new(4, 4, 3)
+        .set_transform(Transform::FlipCol)
+        .set_translate(0, 1)
+        .double_period();
+    assert_eq!(config.transform, Transform::Id);
+    assert_eq!((config.dx, config.dy), (0, 2));
+    Ok(())
+}
+
+#[test]
+fn b0_glide() {
+    let config = Config::new(4, 4, 2)
+        .set_rule_string("B026/S1")
+        .set_symmetry(Symmetry::GlideCol);
+    assert!(config.world().is_ok());
+
+    let config = config.set_rule_string("B02/S1/3");
+    assert_eq!(
+        config.world().err(),
+        Some(Error::GlideSymmetryError(Symmetry::GlideCol))
+    );
+}
+
+#[test]
+fn p3_2333() -> Result<(), Error> {
+    let config = Config::new(4, 4, 3).set_rule_string("23/3/3");
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    Ok(())
+}
+
+#[test]
+fn snowflakes() -> Result<(), Error> {
+    let config = Config::new(4, 4, 2)
+        .set_translate(0, 1)
+        .set_rule_string("B2ci3ai4c8/S02ae3eijkq4iz5ar6i7e");
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    Ok(())
+}
+
+#[test]
+fn hex() -> Result<(), Error> {
+    let config = Config::new(4, 4, 2).set_rule_string("B2/S34H");
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    Ok(())
+}
+
+#[test]
+fn nt_hex() -> Result<(), Error> {
+    let config = Config::new(5, 5, 2).set_rule_string("B2p3o/S2m3H");
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "triangle")]
+fn triangle() -> Result<(), Error> {
+    let config = Config::new(3, 3, 2).set_rule_string("B4/S345L");
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+
+    let config = Config::new(3, 2, 2).set_rule_string("B4/S345L");
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::None);
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "triangle")]
+fn triangle_symmetry() -> Result<(), Error> {
+    // Six triangles around a vertex form a still life.
+    // It does not touch the front.
+    let config = Config::new(5, 4, 1)
+        .set_rule_string("B4/S345L")
+        .set_symmetry(Symmetry::C6)
+        .set_non_empty_front(false);
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    for y in 0..4 {
+        for x in 0..5 {
+            assert_eq!(
+                search.get_cell_state((x, y, 0))?,
+                search.get_cell_state((4 - x, 3 - y, 0))?
+            );
+        }
+    }
+
+    let config = Config::new(5, 4, 1)
+        .set_rule_string("B4/S345L")
+        .set_symmetry(Symmetry::D4Ortho);
+    assert!(config.world().is_ok());
+    let config = Config::new(4, 4, 1)
+        .set_rule_string("B4/S345L")
+        .set_symmetry(Symmetry::D2Col);
+    assert_eq!(
+        config.world().err(),
+        Some(Error::NbhdSymmetryError(Symmetry::D2Col))
+    );
+    let config = Config::new(5, 5, 1)
+        .set_rule_string("B4/S345L")
+        .set_symmetry(Symmetry::D2Diag);
+    assert_eq!(
+        config.world().err(),
+        Some(Error::NbhdSymmetryError(Symmetry::D2Diag))
+    );
+    let config = Config::new(5, 5, 1)
+        .set_rule_string("B4/S345L")
+        .set_symmetry(Symmetry::D12);
+    assert_eq!(
+        config.world().err(),
+        Some(Error::HexSymmetryError(Symmetry::D12))
+    );
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "margolus")]
+fn margolus() -> Result<(), Error> {
+    // A single ball in the Billiard Ball Machine moves diagonally.
+    let config = Config::new(3, 3, 2)
+        .set_translate(-2, -2)
+        .set_rule_string("MS,D0;8;4;3;2;5;9;7;1;6;10;11;12;13;14;15");
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    assert_eq!(search.cell_count_gen(0), 1);
+
+    let config = Config::new(4, 4, 2).set_rule_string("MS,D0;8;4;3;2;5;9;7;1;6;10;11;12;13;14;15");
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::None);
+
+    // The glider in Critters.
+    let config = Config::new(5, 5, 4)
+        .set_translate(2, 0)
+        .set_rule_string("MS,D15;14;13;3;11;5;6;1;7;9;10;2;12;4;8;0");
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "bsfkl")]
+fn bsfkl() -> Result<(), Error> {
+    // The same as the Generations rule `B3/S23/3`.
+    let config = Config::new(6, 5, 3).set_rule_string("B3/S23/F012345678/K/L");
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+
+    let config = Config::new(5, 5, 2).set_rule_string("B3/S23/F012345678/K/L");
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::None);
+
+    let config = Config::new(3, 3, 3)
+        .set_rule_string("B4/S01257/F03467/K/L01378")
+        .set_non_empty_front(false);
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    Ok(())
+}
+
+#[test]
+fn von_neumann() -> Result<(), Error> {
+    let config = Config::new(4, 4, 2).set_rule_string("B2/S013V");
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "von-neumann")]
+fn von_neumann_2() -> Result<(), Error> {
+    let config = Config::new(6, 6, 2).set_rule_string("R2,C2,S2-3,B3,NN");
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+
+    let config = Config::new(6, 6, 2).set_rule_string("R2,C2,M3,S2-3,B3,NN");
+    assert!(config.world().is_err());
+    Ok(())
+}
+
+#[test]
+fn vote() -> Result<(), Error> {
+    let count = |rule_string: &str| -> Result<usize, Error> {
+        let config = Config::new(5, 5, 2)
+            .set_non_empty_front(false)
+            .set_rule_string(rule_string);
+        let mut search = config.world()?;
+        let mut count = 0;
+        while search.search(None) == Status::Found {
+            count += 1;
+        }
+        Ok(count)
+    };
+    assert_eq!(count("R1,C2,M1,S5-9,B5-9,NM")?, count("B5678/S45678")?);
+    assert_eq!(count("R1,C0,M1,S4,6-9,B4,6-9")?, count("Vote 4/5")?);
+    assert_eq!(count("R1,C3,M0,S2-3,B3,NM")?, count("B3/S23/C3")?);
+    #[cfg(feature = "von-neumann")]
+    assert_eq!(count("R2,C2,M1,S3-4,B3,NN")?, count("R2,C2,M0,S2-3,B3,NN")?);
+    assert!(Config::new(5, 5, 2)
+        .set_rule_string("R1,C2,M1,S5-10,B5-9,NM")
+        .world()
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn bounded_grid() -> Result<(), Error> {
+    let config = Config::new(16, 16, 2)
+        .set_translate(1, 0)
+        .set_rule_string("Life:T4,4");
+    assert_eq!(
+        config.bounded_grid()?,
+        Some(BoundedGrid {
+            topology: Topology::Torus,
+            width: 4,
+            height: 4,
+        })
+    );
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    assert_eq!(
+        search.rle_gen(0),
+        "x = 4, y = 4, rule = B3/S23:T4,4\noooo$\noooo$\n....$\n....!\n"
+    );
+
+    let config = Config::new(4, 4, 1).set_rule_string("B3/S23:T3");
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+
+    // A tube only wraps one dimension.
+    let search = Config::new(16, 5, 2)
+        .set_rule_string("B3/S23:T0,4")
+        .world()?;
+    assert_eq!((search.config().width, search.config().height), (16, 4));
+    let mut search = Config::new(16, 5, 2)
+        .set_rule_string("B3/S23:T4,0")
+        .world()?;
+    assert_eq!((search.config().width, search.config().height), (4, 5));
+    assert_eq!(search.search(None), Status::Found);
+
+    // The images of a glide symmetry wrap around the torus.
+    let config = Config::new(4, 4, 2)
+        .set_translate(2, 0)
+        .set_symmetry(Symmetry::GlideRow)
+        .set_rule_string("B3/S23:T4,4");
+    assert!(config.presolve()?.forced.is_empty());
+
+    let config = Config::new(2, 1, 1).set_rule_string("B1/S1");
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::None);
+    let config = Config::new(2, 1, 1).set_rule_string("B1/S1:P2,1");
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+
+    let config = Config::new(6, 6, 2).set_rule_string("B3/S23:K6,6*");
+    assert_eq!(
+        config.bounded_grid()?,
+        Some(BoundedGrid {
+            topology: Topology::KleinBottle {
+                twisted_width: false
+            },
+            width: 6,
+            height: 6,
+        })
+    );
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    // Hexagonal rules need the `hex` feature.
+    #[cfg(feature = "hex")]
+    assert_eq!(
+        Config::new(6, 6, 2)
+            .set_rule_string("B2/S34H:K6*,6")
+            .world()
+            .err(),
+        Some(Error::GridError(
+            "K6*,6 (the neighborhood must be symmetric for this rule)".to_string()
+        ))
+    );
+
+    for rule_string in &["B3/S23:C6,6", "B3/S23:S6"] {
+        let mut search = Config::new(4, 4, 2).set_rule_string(rule_string).world()?;
+        assert_eq!(search.config().width, 6);
+        assert_eq!(search.search(None), Status::Found);
+    }
+    assert_eq!(
+        Config::new(6, 6, 2)
+            .set_rule_string("B3/S23:S6")
+            .bounded_grid()?
+            .map(|grid| grid.topology),
+        Some(Topology::Sphere)
+    );
+    #[cfg(feature = "hex")]
+    assert_eq!(
+        Config::new(6, 6, 2)
+            .set_rule_string("B2/S34H:S6")
+            .world()
+            .err(),
+        Some(Error::GridError(
+            "S6 (the neighborhood must be symmetric for this rule)".to_string()
+        ))
+    );
+
+    for rule_string in &[
+        "B3/S23:K6,6",
+        "B3/S23:K6*,6*",
+        "B3/S23:K6*,0",
+        "B3/S23:T6*,6",
+        "B3/S23:C6,0",
+        "B3/S23:C6*,6",
+        "B3/S23:S6,4",
+        "B3/S23:T6,-6",
+        "B3/S23:T0,0",
+        "B3/S23:T",
+    ] {
+        assert_eq!(
+            Config::new(6, 6, 1)
+                .set_rule_string(rule_string)
+                .world()
+                .err(),
+            Some(Error::GridError(rule_string[7..].to_string()))
+        );
+    }
+    assert_eq!(
+        Config::new(6, 6, 2)
+            .set_rule_string("B0/S8:P6,6")
+            .world()
+            .err(),
+        Some(Error::GridB0Error)
+    );
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "custom")]
+fn custom_rule() -> Result<(), Error> {
+    use rlifesrc_lib::rules::Custom;
+
+    let moore = [
+        (-1, -1),
+        (-1, 0),
+        (-1, 1),
+        (0, -1),
+        (0, 1),
+        (1, -1),
+        (1, 0),
+        (1, 1),
+    ];
+    let life = |alive: bool, mask: u32| match mask.count_ones() {
+        3 => true,
+        2 => alive,
+        _ => false,
+    };
+    let count = |mut search: Box<dyn Search>| {
+        let mut count = 0;
+        while search.search(None) == Status::Found {
+            count += 1;
+        }
+        count
+    };
+    let config = Config::new(5, 5, 2).set_non_empty_front(false);
+    let custom = config.world_with_rule(Custom::new(&moore, life)?)?;
+    let life = config.clone().set_rule_string("B3/S23").world()?;
+    assert_eq!(count(custom), count(life));
+
+    let knight = [
+        (1, 2),
+        (2, 1),
+        (2, -1),
+        (1, -2),
+        (-1, -2),
+        (-2, -1),
+        (-2, 1),
+        (-1, 2),
+    ];
+    let config = Config::new(8, 8, 2);
+    let mut search = config.world_with_rule(Custom::new(&knight, |alive, mask| {
+        mask.count_ones() == 3 || alive && mask.count_ones() == 2
+    })?)?;
+    assert_eq!(search.search(None), Status::Found);
+
+    assert!(Custom::new(&[(1, 0)], |_, _| false).is_err());
+    assert!(Custom::new(&[(0, 0)], |_, _| false).is_err());
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "custom")]
+fn neighborhood() -> Result<(), Error> {
+    use rlifesrc_lib::Neighborhood;
+
+    let count = |mut search: Box<dyn Search>| {
+        let mut count = 0;
+        while search.search(None) == Status::Found {
+            count += 1;
+        }
+        count
+    };
+    let config = Config::new(4, 4, 1)
+        .set_non_empty_front(false)
+        .set_neighborhood(Some(Neighborhood::Knight));
+    assert_eq!(count(config.world()?), 201);
+    assert_eq!(count(Session::new().world(&config)?), 201);
+    let config = Config::new(3, 4, 2)
+        .set_non_empty_front(false)
+        .set_neighborhood(Some(Neighborhood::Cross));
+    assert_eq!(count(config.world()?), 182);
+    let config = config.set_neighborhood(Some(Neighborhood::Hash));
+    assert_eq!(count(config.set_rule_string("B34/S2").world()?), 6);
+
+    assert_eq!("far-edges".parse(), Ok(Neighborhood::FarEdges));
+    assert!(Config::new(4, 4, 1)
+        .set_rule_string("B2a/S")
+        .set_neighborhood(Some(Neighborhood::FarCorners))
+        .world()
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn external_rule() -> Result<(), Error> {
+    use rlifesrc_lib::{rules::Rule, CellRef, World};
+
+    /// Conway's Game of Life, which only checks the transitions
+    /// when all the cells involved are known.
+    struct SlowLife;
+
+    /// The numbers of known living and dead neighbors, the state of
+    /// the cell, and the state of the successor.
+    #[derive(Clone, Copy)]
+    struct Desc(u8, u8, Option<State>, Option<State>);
+
+    impl Rule for SlowLife {
+        type Desc = Desc;
+
+        const IS_GEN: bool = false;
+
+        fn has_b0(&self) -> bool {
+            false
+        }
+
+        fn gen(&self) -> usize {
+            2
+        }
+
+        fn new_desc(state: State, succ_state: State) -> Desc {
+            if state == ALIVE {
+                Desc(8, 0, Some(state), Some(succ_state))
+            } else {
+                Desc(0, 8, Some(state), Some(succ_state))
+            }
+        }
+
+        fn update_desc(cell: CellRef<Self>, state: Option<State>, new: bool) {
+            for &neigh in cell.nbhd().iter().flatten() {
+                let Desc(mut alive, mut dead, self_state, succ) = neigh.desc();
+                let count = if state == Some(ALIVE) {
+                    &mut alive
+                } else {
+                    &mut dead
+                };
+                if new {
+                    *count += 1;
+                } else {
+                    *count -= 1;
+                }
+                neigh.set_desc(Desc(alive, dead, self_state, succ));
+            }
+            let new_state = if new { state } else { None };
+            if let Some(pred) = cell.pred() {
+                let Desc(alive, dead, self_state, _) = pred.desc();
+                pred.set_desc(Desc(alive, dead, self_state, new_state));
+            }
+            let Desc(alive, dead, _, succ) = cell.desc();
+            cell.set_desc(Desc(alive, dead, new_state, succ));
+        }
+
+        fn consistify<'a>(world: &mut World<'a, Self>, cell: CellRef<'a, Self>) -> bool {
+            match cell.desc() {
+                Desc(alive, dead, Some(state), _) if alive + dead == 8 => {
+                    let next = match alive {
+                        3 => ALIVE,
+                        2 => state,
+                        _ => DEAD,
+                    };
+                    cell.succ().is_none_or(|succ| world.deduce(succ, next))
+                }
+                _ => true,
+            }
+        }
+    }
+
+    let count = |mut search: Box<dyn Search>| {
+        let mut count = 0;
+        while search.search(None) == Status::Found {
+            count += 1;
+        }
+        count
+    };
+    let config = Config::new(5, 5, 2).set_non_empty_front(false);
+    let slow = config.world_with_rule(SlowLife)?;
+    let life = config.world()?;
+    assert_eq!(count(slow), count(life));
+    Ok(())
+}
+
+#[test]
+fn rule_from_fn() -> Result<(), Error> {
+    use rlifesrc_lib::rules::NtLife;
+
+    let count = |mut search: Box<dyn Search>| {
+        let mut count = 0;
+        while search.search(None) == Status::Found {
+            count += 1;
+        }
+        count
+    };
+    let life =
+        NtLife::from_fn(
+            |state, nbhd| match nbhd.iter().filter(|&&state| state == ALIVE).count() {
+                3 => ALIVE,
+                2 => state,
+                _ => DEAD,
+            },
+        );
+    let config = Config::new(5, 5, 2).set_non_empty_front(false);
+    let from_fn = config.world_with_rule(life)?;
+    let life = config.clone().set_rule_string("B3/S23").world()?;
+    assert_eq!(count(from_fn), count(life));
+
+    // Each cell copies its neighbor on the left, so every pattern moves
+    // to the right.
+    let shift = |_, nbhd: [State; 8]| nbhd[1];
+    let config = Config::new(3, 3, 1).set_translate(-1, 0);
+    let mut search = config.world_with_rule(NtLife::from_fn(shift))?;
+    assert_eq!(search.search(None), Status::Found);
+    let config = Config::new(3, 3, 1).set_translate(1, 0);
+    let mut search = config.world_with_rule(NtLife::from_fn(shift))?;
+    assert_eq!(search.search(None), Status::None);
+    Ok(())
+}
+
+#[test]
+fn canonical_rule() -> Result<(), Error> {
+    use rlifesrc_lib::rules::CanonicalRule;
+
+    let canonical = |rule_string: &str| rule_string.parse::<CanonicalRule>().unwrap().to_string();
+    assert_eq!(canonical("23/3"), "B3/S23");
+    assert_eq!(
+        canonical("MAPARYXfhZofugWaH7oaIDogBZofuhogOiAaIDogIAAgAAWaH7oaIDogGiA6ICAAIAAaIDogIAAgACAAIAAAAAAAA"),
+        "B3/S23"
+    );
+    assert_eq!(canonical("B2cekain/S1ce"), "B2/S1");
+    assert_eq!(canonical("B2ceikn/S3-jqr"), "B2-a/S3-jqr");
+    assert_eq!(canonical("B3kia/S4-ceaiknjqry"), "B3aik/S4twz");
+    assert_eq!(canonical("3457/357/5"), "B357/S3457/C5");
+    assert_eq!(canonical(&format!("MAP{}", "A".repeat(86))), "B/S");
+    assert_eq!(canonical(&format!("MAPg{}", "A".repeat(85))), "B0/S");
+    let non_isotropic = format!("MAPQ{}", "A".repeat(85));
+    assert_eq!(canonical(&non_isotropic), non_isotropic);
+    assert_eq!(
+        canonical(&format!("{}/3", non_isotropic)),
+        format!("{}/3", non_isotropic)
+    );
+    assert!("B2/S34H".parse::<CanonicalRule>().is_err());
+    assert!("B2/S013V".parse::<CanonicalRule>().is_err());
+
+    let config = Config::new(3, 3, 1).set_rule_string("23/3");
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    assert!(search
+        .rle_gen(0)
+        .starts_with("x = 3, y = 3, rule = B3/S23\n"));
+    Ok(())
+}
+
+#[test]
+fn rule_alias() -> Result<(), Error> {
+    use rlifesrc_lib::rules::{register_alias, resolve_alias};
+
+    assert_eq!(resolve_alias("HighLife"), Some(String::from("B36/S23")));
+    assert_eq!(
+        resolve_alias("day&night"),
+        Some(String::from("B3678/S34678"))
+    );
+    assert_eq!(resolve_alias("B3/S23"), None);
+
+    let config = Config::new(3, 3, 1).set_rule_string("tlife");
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    assert!(search
+        .rle_gen(0)
+        .starts_with("x = 3, y = 3, rule = B3/S2-i34q\n"));
+
+    register_alias("Test Alias", "B36/S125")?;
+    assert_eq!(resolve_alias("test alias"), Some(String::from("B36/S125")));
+    assert!(register_alias("Invalid Alias", "B3/S23/Q").is_err());
+    assert!(register_alias("Nested Alias", "Life").is_err());
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "table")]
+fn rule_table() -> Result<(), Error> {
+    use rlifesrc_lib::rules::table_to_rule_string;
+
+    let table = "@RULE Life\n\
+                 @TABLE\n\
+                 n_states:2\n\
+                 neighborhood:Moore\n\
+                 symmetries:permute\n\
+                 var a={0,1}\n\
+                 var b={0,1}\n\
+                 var c={0,1}\n\
+                 var d={0,1}\n\
+                 var e={0,1}\n\
+                 var f={0,1}\n\
+                 var g={0,1}\n\
+                 var h={0,1}\n\
+                 0,1,1,1,0,0,0,0,0,1\n\
+                 1,1,1,0,0,0,0,0,0,1\n\
+                 1,1,1,1,0,0,0,0,0,1\n\
+                 1,a,b,c,d,e,f,g,h,0\n";
+    let rule_string = table_to_rule_string(table)?;
+    assert_eq!(
+        rule_string,
+        "MAPARYXfhZofugWaH7oaIDogBZofuhogOiAaIDogIAAgAAWaH7oaIDogGiA6ICAAIAAaIDogIAAgACAAIAAAAAAAA"
+    );
+    let config = Config::new(16, 5, 3)
+        .set_translate(0, 1)
+        .set_rule_string(rule_string);
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    Ok(())
+}
+
+#[test]
+fn session() -> Result<(), Error> {
+    let mut session = Session::new();
+    let config = Config::new(16, 5, 3).set_translate(0, 1);
+    let mut search = session.world(&config)?;
+    assert_eq!(search.search(None), Status::Found);
+    let config = Config::new(5, 5, 3);
+    let mut search = session.world(&config)?;
+    assert_eq!(search.search(None), Status::None);
+    assert_eq!(session.rule_count(), 1);
+    Ok(())
+}
+
+#[test]
+fn sweep() -> Result<(), Error> {
+    let rules = rule_range("B3/S23", "B36/S238")?;
+    assert_eq!(rules, vec!["B3/S23", "B36/S23", "B3/S238", "B36/S238"]);
+    assert_eq!(
+        rule_range("B36/S23", "B3/S23").err(),
+        Some(Error::RuleRangeError(
+            String::from("B36/S23"),
+            String::from("B3/S23")
+        ))
+    );
+
+    let rules = rule_range("B3/S2", "B36/S23")?;
+    let mut session = Session::new();
+    let config = Config::new(2, 2, 1);
+    let results = session.sweep(&config, &rules, None)?;
+    assert_eq!(
+        results,
+        vec![Status::None, Status::None, Status::Found, Status::Found]
+    );
+    assert_eq!(session.rule_count(), 4);
+    Ok(())
+}
+
+#[test]
+fn grow() -> Result<(), Error> {
+    // The smallest oscillator of period 2 is the blinker.
+    let mut session = Session::new();
+    let config = Config::new(1, 1, 2);
+    let (search, status) = session.grow(&config, Growth::new(1, 1), None)?;
+    assert_eq!(status, Status::Found);
+    assert_eq!((search.config().width, search.config().height), (3, 3));
+    assert_eq!(session.rule_count(), 1);
+
+    let growth = Growth::new(1, 1).set_alternate(true);
+    let (search, status) = session.grow(&config, growth, None)?;
+    assert_eq!(status, Status::Found);
+    assert_eq!((search.config().width, search.config().height), (3, 3));
+
+    let growth = Growth::new(1, 1).set_max_size(Some(2), Some(2));
+    let (search, status) = session.grow(&config, growth, None)?;
+    assert_eq!(status, Status::None);
+    assert_eq!((search.config().width, search.config().height), (2, 2));
+
+    // The size of a bounded grid is never enlarged.
+    let config = config.set_rule_string("B3/S23:P2,2");
+    let (search, status) = session.grow(&config, Growth::new(1, 1), None)?;
+    assert_eq!(status, Status::None);
+    assert_eq!((search.config().width, search.config().height), (2, 2));
+    Ok(())
+}
+
+#[test]
+fn b0_s8() -> Result<(), Error> {
+    // The black/white reversal of this rule is B3/S23.
+    let config = Config::new(16, 5, 3)
+        .set_translate(0, 1)
+        .set_rule_string(String::from("B0123478/S01234678"));
+    let mut search = config.world()?;
+    assert!(search.is_b0_rule());
+    assert_eq!(search.search(None), Status::Found);
+    assert_eq!(
+        search.rle_gen(0),
+        String::from(
+            "x = 16, y = 5, rule = B0123478/S01234678\n\
+             oooooooo.ooooooo$\n\
+             o..o...o...ooooo$\n\
+             o..oooo.oo..o..o$\n\
+             .oo.o..ooo.oo..o$\n\
+             oooooooooooo.oo.!\n"
+        )
+    );
+
+    let config = Config::new(3, 3, 1)
+        .set_rule_string(String::from("B0123478/S01234678"))
+        .set_known_cells(vec![KnownCell {
+            coord: (1, 1, 0),
+            state: DEAD,
+        }]);
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    assert_eq!(search.get_cell_state((1, 1, 0))?, Some(DEAD));
+    assert_eq!(search.cell_count(), 4);
+    Ok(())
+}
+
+#[test]
+fn write_rect() -> Result<(), Error> {
+    let config = Config::new(16, 5, 3).set_translate(0, 1);
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    let mut buf = Vec::new();
+    search.write_rle_rect(0, 1, 1, 6, 10, &mut buf).unwrap();
+    assert_eq!(
+        String::from_utf8(buf).unwrap(),
+        String::from(
+            "x = 6, y = 4, rule = B3/S23\n\
+             oo.ooo$\n\
+             oo....$\n\
+             ..o.oo$\n\
+             ......!\n"
+        )
+    );
+    let mut buf = Vec::new();
+    search.write_plaintext_gen(0, &mut buf).unwrap();
+    assert_eq!(String::from_utf8(buf).unwrap(), search.plaintext_gen(0));
+    Ok(())
+}
+
+#[test]
+fn known_cells() -> Result<(), Error> {
+    let config = Config::new(5, 5, 1).set_known_cells(vec![KnownCell {
+        coord: (0, 0, 0),
+        state: ALIVE,
+    }]);
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    assert_eq!(search.get_cell_state((0, 0, 0))?, Some(ALIVE));
+
+    // Known cells that contradict each other.
+    let config = Config::new(5, 5, 1).set_known_cells(vec![
+        KnownCell {
+            coord: (0, 0, 0),
+            state: ALIVE,
+        },
+        KnownCell {
+            coord: (0, 0, 0),
+            state: DEAD,
+        },
+    ]);
+    assert!(config.preview()?.is_none());
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::None);
+
+    // Known cells that exceed a bound.
+    let config = Config::new(5, 5, 1)
+        .set_max_row_cell_count(Some(1))
+        .set_known_cells(vec![
+            KnownCell {
+                coord: (0, 0, 0),
+                state: ALIVE,
+            },
+            KnownCell {
+                coord: (1, 0, 0),
+                state: ALIVE,
+            },
+        ]);
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::None);
+    Ok(())
+}
+
+#[test]
+fn preview() -> Result<(), Error> {
+    let known_cells = (0..5)
+        .flat_map(|x| {
+            (0..5).map(move |y| KnownCell {
+                coord: (x, y, 0),
+                state: DEAD,
+            })
+        })
+        .collect();
+    // The known cells leave the front empty, so it is not used.
+    let config = Config::new(5, 5, 2)
+        .set_non_empty_front(false)
+        .set_known_cells(known_cells);
+    let search = config.preview()?.unwrap();
+    assert_eq!(search.get_cell_state((2, 2, 1))?, Some(DEAD));
+    assert_eq!(search.conflicts(), 0);
+
+    let config = Config::new(1, 1, 1).set_known_cells(vec![KnownCell {
+        coord: (0, 0, 0),
+        state: ALIVE,
+    }]);
+    assert!(config.preview()?.is_none());
+    Ok(())
+}
+
+#[test]
+fn dont_care_cells() -> Result<(), Error> {
+    let config = Config::new(3, 3, 2).set_dont_care_cells(vec![(1, 1, 0), (1, 1, 1)]);
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    assert_eq!(search.cell_count_gen(0), 2);
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "serialize")]
+fn ser() -> Result<(), Error> {
+    let config = Config::new(16, 5, 3).set_translate(0, 1);
+    let mut search = config.world()?;
+    assert_eq!(search.search(Some(100)), Status::Searching);
+    let count = search.cell_count();
+    let save = search.ser();
+    let mut new_search = save.world()?;
+    assert_eq!(new_search.cell_count(), count);
+    assert_eq!(new_search.search(None), Status::Found);
+    assert_eq!(
+        new_search.rle_gen(0),
+        String::from(
+            "x = 16, y = 5, rule = B3/S23\n\
              ........o.......$\n\
              .oo.ooo.ooo.....$\n\
              .oo....o..oo.oo.$\n\
@@ -133,3 +1981,535 @@ fn ser() -> Result<(), Error> {
     );
     Ok(())
 }
+
+#[test]
+fn square_world() {
+    let config = Config::new(5, 4, 1).set_symmetry(Symmetry::C4);
+    assert_eq!(
+        config.world().err(),
+        Some(Error::SymmetrySizeError(Symmetry::C4, 5, 4))
+    );
+    let config = config.expand_size();
+    assert_eq!((config.width, config.height), (5, 5));
+    assert!(config.check_size().is_ok());
+}
+
+#[test]
+fn build() {
+    let config = Config::new(20, 16, 7).set_translate(0, 3);
+    assert_eq!(config.clone().build(), Ok(config));
+
+    let config = Config::new(5, 0, 1);
+    assert_eq!(config.build().err(), Some(Error::SizeError(5, 0, 1)));
+    let config = Config::new(5, 5, 0).set_rule_string("B3/S23:T0,8");
+    assert_eq!(config.check().err(), Some(Error::SizeError(5, 8, 0)));
+
+    // Errors that depend on the rule are found without creating the world.
+    let config = Config::new(5, 5, 3).set_rule_string("B026/S1");
+    assert_eq!(config.check().err(), Some(Error::B0OddPeriodError(3)));
+    #[cfg(feature = "hex")]
+    {
+        let config = Config::new(6, 6, 1).set_rule_string("B2/S34H:K6*,6");
+        assert!(matches!(config.check(), Err(Error::GridError(_))));
+    }
+    let config = Config::new(5, 4, 1).set_symmetry(Symmetry::C4);
+    assert_eq!(
+        config.check().err(),
+        Some(Error::SymmetrySizeError(Symmetry::C4, 5, 4))
+    );
+}
+
+/// Finds at most `n` results, and returns their generation 0.
+fn results(search: &mut dyn Search, n: usize) -> Vec<String> {
+    let mut results = Vec::new();
+    while results.len() < n && search.search(None) == Status::Found {
+        results.push(search.rle_gen(0));
+    }
+    results
+}
+
+#[test]
+fn solutions() -> Result<(), Error> {
+    let config = Config::new(5, 5, 1);
+    let mut search = config.world()?;
+    let expected = results(search.as_mut(), usize::MAX);
+
+    let mut search = config.world()?;
+    let mut solutions = search.solutions();
+    let mut found = Vec::new();
+    for known_cells in &mut solutions {
+        found.push(
+            config
+                .clone()
+                .set_known_cells(known_cells)
+                .world()?
+                .rle_gen(0),
+        );
+    }
+    assert_eq!(solutions.status(), Status::None);
+    assert!(solutions.next().is_none());
+    assert_eq!(found, expected);
+    Ok(())
+}
+
+#[test]
+fn dedup_solutions() -> Result<(), Error> {
+    let config = Config::new(5, 5, 2).set_non_empty_front(true);
+    let all = config.world()?.solutions().count();
+    let mut search = config.world()?;
+    let unique = search
+        .solutions()
+        .dedup()
+        .map(|known_cells| {
+            config
+                .clone()
+                .set_known_cells(known_cells)
+                .world()
+                .unwrap()
+                .canonical_form()
+        })
+        .collect::<Vec<_>>();
+    assert!(unique.len() < all);
+    for (i, form) in unique.iter().enumerate() {
+        assert!(!unique[..i].contains(form));
+    }
+    Ok(())
+}
+
+#[test]
+fn seed() -> Result<(), Error> {
+    let config = Config::new(6, 6, 2)
+        .set_new_state(NewState::Random)
+        .set_seed(Some(42));
+    let first = results(config.world()?.as_mut(), 5);
+    let second = results(config.world()?.as_mut(), 5);
+    assert_eq!(first.len(), 5);
+    assert_eq!(first, second);
+    // The results must not depend on the platform.
+    assert_eq!(
+        first[0],
+        String::from(
+            "#C seed 42\n\
+             x = 6, y = 6, rule = B3/S23\n\
+             .oo...$\n\
+             o.o...$\n\
+             oo....$\n\
+             ......$\n\
+             ...ooo$\n\
+             ......!\n"
+        )
+    );
+
+    // A random seed is recorded, so that the search can be reproduced.
+    let config = config.set_seed(None);
+    let mut search = config.world()?;
+    let seed = search.config().seed;
+    assert!(seed.is_some());
+    let first = results(search.as_mut(), 5);
+    let second = results(config.set_seed(seed).world()?.as_mut(), 5);
+    assert_eq!(first, second);
+    Ok(())
+}
+
+#[test]
+fn biased() -> Result<(), Error> {
+    let config = Config::new(6, 6, 2).set_seed(Some(42));
+    for &(percent, new_state) in &[(0, NewState::ChooseDead), (100, NewState::ChooseAlive)] {
+        let biased = config.clone().set_new_state(NewState::Biased(percent));
+        let expected = config.clone().set_new_state(new_state);
+        let results_biased = results(biased.world()?.as_mut(), 3)
+            .into_iter()
+            .map(|rle| rle.replace("#C seed 42\n", ""))
+            .collect::<Vec<_>>();
+        assert_eq!(results_biased, results(expected.world()?.as_mut(), 3));
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "serialize")]
+fn full_state_ser() -> Result<(), Error> {
+    let config = Config::new(16, 5, 3)
+        .set_translate(0, 1)
+        .set_backjump(true)
+        .set_nogood_limit(100)
+        .set_activity_order(true)
+        .set_restart_interval(Some(10));
+    let expected = results(config.world()?.as_mut(), 2);
+
+    let mut search = config.world()?;
+    assert_eq!(search.search(Some(100)), Status::Searching);
+    let save = search.ser();
+    let mut new_search = save.world()?;
+    assert_eq!(new_search.ser(), save);
+    assert_eq!(new_search.conflicts(), search.conflicts());
+    assert_eq!(new_search.restarts(), search.restarts());
+    assert_eq!(results(new_search.as_mut(), 2), expected);
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "serialize")]
+fn checkpoint() -> Result<(), Box<dyn std::error::Error>> {
+    use rlifesrc_lib::{read_checkpoint, write_checkpoint, Checkpoint};
+    use std::fs;
+
+    let config = Config::new(16, 5, 3).set_translate(0, 1);
+    let expected = results(config.world()?.as_mut(), 1);
+
+    let mut search = config.world()?;
+    assert_eq!(search.search(Some(100)), Status::Searching);
+    let mut buf = Vec::new();
+    write_checkpoint(search.as_ref(), &mut buf)?;
+    let (header, save) = read_checkpoint(&mut buf.as_slice())?;
+    assert!(header.matches(search.config()));
+    assert_eq!(save, search.ser());
+    assert_eq!(results(save.world()?.as_mut(), 1), expected);
+
+    let path =
+        std::env::temp_dir().join(format!("rlifesrc-test-{}.checkpoint", std::process::id()));
+    let mut checkpoint = Checkpoint::new(&path)
+        .set_interval(None)
+        .set_conflicts(Some(1));
+    let mut search = config.world()?;
+    assert!(!checkpoint.is_due(search.as_ref()));
+    while !checkpoint.update(search.as_ref())? {
+        assert_eq!(search.search(Some(10)), Status::Searching);
+    }
+    checkpoint.write(search.as_ref())?;
+    assert!(checkpoint.prev_path().exists());
+    let (_, save) = checkpoint.read()?;
+    assert_eq!(save, search.ser());
+    fs::remove_file(checkpoint.path())?;
+    let (_, save) = checkpoint.read()?;
+    assert_eq!(results(save.world()?.as_mut(), 1), expected);
+    fs::remove_file(checkpoint.prev_path())?;
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "serialize")]
+fn seed_ser() -> Result<(), Error> {
+    let config = Config::new(6, 6, 2)
+        .set_new_state(NewState::Random)
+        .set_seed(Some(42));
+    let mut search = config.world()?;
+    let expected = results(search.as_mut(), 5);
+
+    let mut search = config.world()?;
+    let mut found = results(search.as_mut(), 2);
+    let mut search = search.ser().world()?;
+    found.extend(results(search.as_mut(), 3));
+    assert_eq!(found, expected);
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "pipeline")]
+fn pipeline() -> Result<(), Error> {
+    use rlifesrc_lib::Pipeline;
+
+    let config = Config::new(16, 5, 3).set_translate(0, 1);
+    let mut pipeline = Pipeline::new(config).set_band(2).set_branches(64);
+    let search = pipeline.run(4)?.unwrap();
+    assert!(search.cell_count_gen(0) > 0);
+    assert!(search.get_cell_state((15, 4, 2))?.is_some());
+
+    let config = Config::new(5, 5, 3);
+    let mut pipeline = Pipeline::new(config);
+    assert!(pipeline.run(4)?.is_none());
+
+    // A single band with several complete results.
+    let config = Config::new(4, 4, 1);
+    let mut expected = results(config.world()?.as_mut(), usize::MAX);
+    let mut pipeline = Pipeline::new(config).set_band(4);
+    let mut found = Vec::new();
+    while let Some(search) = pipeline.run(1)? {
+        found.push(search.rle_gen(0));
+    }
+    expected.sort();
+    found.sort();
+    assert_eq!(found, expected);
+    Ok(())
+}
+
+#[test]
+#[cfg(all(feature = "pipeline", feature = "serialize"))]
+fn pipeline_checkpoint() -> Result<(), Box<dyn std::error::Error>> {
+    use rlifesrc_lib::Pipeline;
+    use std::fs;
+
+    let config = Config::new(16, 5, 3).set_translate(0, 1);
+    let path = std::env::temp_dir().join(format!("rlifesrc-test-{}.pipeline", std::process::id()));
+    let mut pipeline = Pipeline::new(config.clone())
+        .set_band(2)
+        .set_checkpoint(Some(&path));
+    let expected = pipeline.run(2)?.unwrap().rle_gen(0);
+
+    let mut buf = Vec::new();
+    pipeline.write_checkpoint(&mut buf)?;
+    let (header, saved) = Pipeline::read_checkpoint(&mut buf.as_slice())?;
+    assert!(header.matches(&config));
+    assert_eq!(saved.prefixes(), pipeline.prefixes());
+
+    let (_, mut resumed) = Pipeline::read_checkpoint(&mut fs::File::open(&path)?)?;
+    assert_eq!(resumed.prefixes(), pipeline.prefixes());
+    let next = resumed.run(2)?.map(|search| search.rle_gen(0));
+    assert_eq!(next, pipeline.run(2)?.map(|search| search.rle_gen(0)));
+    assert_ne!(next, Some(expected));
+    fs::remove_file(&path)?;
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "split")]
+fn split() -> Result<(), Error> {
+    use rlifesrc_lib::Split;
+
+    let config = Config::new(5, 5, 1);
+    let mut expected = Vec::new();
+    let mut search = config.world()?;
+    while search.search(None) == Status::Found {
+        expected.push(search.rle_gen(0));
+    }
+    expected.sort();
+
+    let split = Split::new(config.clone()).set_depth(3);
+    assert_eq!(split.subtrees()?.len(), 8);
+    let mut found = Vec::new();
+    for known_cells in split.run(4)? {
+        found.push(split.world(known_cells?)?.rle_gen(0));
+    }
+    found.sort();
+    assert_eq!(found, expected);
+
+    let split = Split::new(config.set_time_limit(Some(Duration::from_secs(0)))).set_depth(3);
+    let results = split.run(4)?.into_iter().collect::<Vec<_>>();
+    assert_eq!(results.len(), 8);
+    for result in results {
+        assert!(matches!(result, Err(Error::SplitTimeLimitError(_))));
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "split")]
+fn split_ordered() -> Result<(), Error> {
+    use rlifesrc_lib::Split;
+
+    let split = Split::new(Config::new(5, 5, 1))
+        .set_depth(3)
+        .set_ordered(true);
+    let mut expected = Vec::new();
+    for subtree in split.subtrees()? {
+        let mut search = split.world(subtree)?;
+        while search.search(None) == Status::Found {
+            expected.push(search.rle_gen(0));
+        }
+    }
+    for &threads in [1, 4].iter() {
+        let mut found = Vec::new();
+        for known_cells in split.run(threads)? {
+            found.push(split.world(known_cells?)?.rle_gen(0));
+        }
+        assert_eq!(found, expected);
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "portfolio")]
+fn portfolio() -> Result<(), Error> {
+    use rlifesrc_lib::Portfolio;
+
+    let config = Config::new(16, 5, 3)
+        .set_translate(0, 1)
+        .set_new_state(NewState::Random);
+    let winner = Portfolio::with_seeds(&config, 4)
+        .set_step(100)
+        .run()?
+        .unwrap();
+    assert!(winner.index < 4);
+    assert_eq!(winner.config.seed, Some(winner.index as u64));
+    let search = winner.search.unwrap();
+    assert_eq!(search.analyze().period, 3);
+    assert!(search
+        .rle_gen(0)
+        .starts_with(&format!("#C seed {}", winner.index)));
+
+    let configs = vec![
+        Config::new(5, 5, 3),
+        Config::new(5, 5, 3).set_search_order(Some(SearchOrder::ColumnFirst)),
+    ];
+    let winner = Portfolio::new(configs).run()?.unwrap();
+    assert_eq!(winner.status, Status::None);
+    assert!(winner.search.is_none());
+    assert!(Portfolio::new(Vec::new()).run()?.is_none());
+
+    let config = config.set_time_limit(Some(Duration::from_secs(0)));
+    let winner = Portfolio::with_seeds(&config, 4).run()?.unwrap();
+    assert_eq!(winner.status, Status::TimedOut);
+    assert!(winner.search.is_some());
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "sat")]
+fn sat() -> Result<(), Error> {
+    use rlifesrc_lib::solve_sat;
+
+    let config = Config::new(16, 5, 3).set_translate(0, 1);
+    let search = solve_sat(&config)?.unwrap();
+    assert_eq!(search.analyze().period, 3);
+    assert_eq!((search.analyze().dx, search.analyze().dy), (0, 1));
+
+    let config = Config::new(5, 5, 3);
+    assert!(solve_sat(&config)?.is_none());
+
+    let config = Config::new(5, 5, 3).set_rule_string("B3/S23/3");
+    assert!(solve_sat(&config).is_err());
+    Ok(())
+}
+
+#[test]
+fn trace() -> Result<(), Error> {
+    let config = Config::new(16, 5, 3).set_translate(0, 1);
+    let mut search = config.world()?;
+    search.set_trace(true);
+    assert_eq!(search.search(None), Status::Found);
+    let mut trace = search.take_trace();
+    assert!(!trace.is_empty());
+    assert!(trace.iter().all(|entry| entry.outcome == Outcome::Conflict));
+    search.search(None);
+    trace.extend(search.take_trace());
+    assert!(trace.iter().any(|entry| entry.outcome == Outcome::Found));
+
+    let mut bytes = Vec::new();
+    write_trace(&config, &trace, &mut bytes).unwrap();
+    let (header, entries) = read_trace(&mut bytes.as_slice()).unwrap();
+    assert!(header.matches(&config));
+    assert_eq!(entries, trace);
+    Ok(())
+}
+
+/// A policy that always chooses `ALIVE`.
+struct AlwaysAlive;
+
+impl Policy for AlwaysAlive {
+    fn choose(&mut self, _features: &Features) -> Option<State> {
+        Some(ALIVE)
+    }
+}
+
+#[test]
+fn policy() -> Result<(), Error> {
+    let config = Config::new(6, 6, 2);
+    let mut search = config
+        .clone()
+        .set_new_state(NewState::ChooseAlive)
+        .world()?;
+    let expected = results(search.as_mut(), 3);
+    let mut search = config.world()?;
+    search.set_policy(Some(Box::new(AlwaysAlive)));
+    assert_eq!(results(search.as_mut(), 3), expected);
+    Ok(())
+}
+
+/// Chooses the last unknown cell in the world.
+struct LastCell;
+
+impl ChooseCell for LastCell {
+    fn choose(&mut self, search: &dyn Search) -> Option<Coord> {
+        let config = search.config();
+        let mut coords = Vec::new();
+        for x in 0..config.width {
+            for y in 0..config.height {
+                for t in 0..config.period {
+                    coords.push((x, y, t));
+                }
+            }
+        }
+        coords
+            .into_iter()
+            .rev()
+            .find(|&coord| search.get_cell_state(coord).unwrap().is_none())
+    }
+}
+
+#[test]
+fn chooser() -> Result<(), Error> {
+    let config = Config::new(16, 5, 3).set_translate(0, 1);
+    let expected = results(config.world()?.as_mut(), 2);
+    let mut search = config.world()?;
+    search.set_chooser(Some(Box::new(SearchOrder::ColumnFirst)));
+    assert_eq!(results(search.as_mut(), 2), expected);
+
+    let mut search = config.world()?;
+    search.set_chooser(Some(Box::new(LastCell)));
+    assert_eq!(search.search(None), Status::Found);
+    Ok(())
+}
+
+#[test]
+fn header() {
+    let config = Config::new(16, 5, 3).set_translate(0, 1);
+    let header = Header::new(FileKind::Gallery, &config);
+    assert_eq!(header.version, FORMAT_VERSION);
+    assert_eq!(header.digest, config_digest(&config));
+    assert!(!header.matches(&config.clone().set_translate(1, 0)));
+
+    let line = format!("<!-- {} -->\n<!DOCTYPE html>", header);
+    let mut reader = line.as_bytes();
+    assert_eq!(
+        Header::read_from(&mut reader, FileKind::Gallery).unwrap(),
+        header
+    );
+    assert_eq!(reader, b"<!DOCTYPE html>");
+    let mut reader = line.as_bytes();
+    assert!(Header::read_from(&mut reader, FileKind::Trace).is_err());
+
+    let newer = Header {
+        version: FORMAT_VERSION + 1,
+        ..header
+    };
+    let mut bytes = Vec::new();
+    newer.write_to(&mut bytes).unwrap();
+    assert!(Header::read_from(&mut bytes.as_slice(), FileKind::Gallery).is_err());
+    assert!(Header::read_from(&mut &b"x = 1, y = 1\n"[..], FileKind::Gallery).is_err());
+}
+
+#[test]
+#[cfg(feature = "rule-range")]
+fn unknown_rule() -> Result<(), Error> {
+    let config = Config::new(2, 2, 1).set_rule_string("B3/S..B3/S23");
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    assert_eq!(
+        search.deduced_rules(),
+        Some((String::from("B3/S3"), String::from("B3/S23")))
+    );
+    assert_eq!(search.rle_gen(0), "x = 2, y = 2, rule = B3/S3\noo$\noo!\n");
+    assert_eq!(search.search(None), Status::None);
+
+    let config = Config::new(4, 4, 4)
+        .set_translate(1, 1)
+        .set_rule_string("B3/S..B3/S23");
+    let mut search = config.world()?;
+    assert_eq!(search.search(None), Status::Found);
+    assert_eq!(
+        search.deduced_rules(),
+        Some((String::from("B3/S23"), String::from("B3/S23")))
+    );
+
+    assert_eq!(
+        Config::new(2, 2, 1)
+            .set_rule_string("B36/S23..B3/S23")
+            .world()
+            .err(),
+        Some(Error::RuleRangeError(
+            String::from("B36/S23"),
+            String::from("B3/S23")
+        ))
+    );
+    Ok(())
+}
@@ -1,19 +1,105 @@
 //! Parsing command-line arguments.
 
+use crate::image::{dead_cells_from_image, known_cells_from_image};
 use clap::{App, AppSettings, Arg, Error, ErrorKind, Result as ClapResult};
-use rlifesrc_lib::{rules::NtLifeGen, Config, NewState, Search, SearchOrder, Symmetry, Transform};
+use rlifesrc_lib::{
+    read_decisions, rule_range, rules::table_to_rule_string, Checkpoint, Config, Error as LibError,
+    Growth, NewState, Search, SearchOrder, Symmetry, Transform,
+};
+use std::{
+    fs::{self, File},
+    io::BufReader,
+    time::Duration,
+};
 
 fn is_positive(s: &str) -> bool {
     s.chars().all(|c| c.is_ascii_digit()) && s != "0" && !s.starts_with('-')
 }
 
+/// Parses the brightness thresholds in the form `LOW,HIGH`.
+fn parse_threshold(s: &str) -> Result<(u8, u8), String> {
+    let mut iter = s.split(',').map(|n| n.trim().parse::<u8>());
+    match (iter.next(), iter.next(), iter.next()) {
+        (Some(Ok(low)), Some(Ok(high)), None) if low <= high => Ok((low, high)),
+        _ => Err(String::from(
+            "thresholds must be in the form LOW,HIGH, where 0 <= LOW <= HIGH <= 255",
+        )),
+    }
+}
+
+/// Parses a rectangle in the form `X,Y,WIDTH,HEIGHT`.
+fn parse_rect(s: &str) -> Result<(isize, isize, isize, isize), String> {
+    let numbers = s
+        .split(',')
+        .map(|n| n.trim().parse::<isize>())
+        .collect::<Result<Vec<_>, _>>();
+    match numbers.as_deref() {
+        Ok(&[x, y, w, h]) if w >= 0 && h >= 0 => Ok((x, y, w, h)),
+        _ => Err(String::from(
+            "rectangles must be in the form X,Y,WIDTH,HEIGHT, where WIDTH and HEIGHT \
+             are non-negative",
+        )),
+    }
+}
+
+/// Parses a pair of non-negative numbers in the form `WIDTH,HEIGHT`.
+fn parse_size(s: &str) -> Result<(isize, isize), String> {
+    let numbers = s
+        .split(',')
+        .map(|n| n.trim().parse::<isize>())
+        .collect::<Result<Vec<_>, _>>();
+    match numbers.as_deref() {
+        Ok(&[w, h]) if w >= 0 && h >= 0 => Ok((w, h)),
+        _ => Err(String::from(
+            "sizes must be in the form WIDTH,HEIGHT, where WIDTH and HEIGHT \
+             are non-negative",
+        )),
+    }
+}
+
+/// Parses a translation in the form `X,Y`.
+fn parse_shift(s: &str) -> Result<(isize, isize), String> {
+    let numbers = s
+        .split(',')
+        .map(|n| n.trim().parse::<isize>())
+        .collect::<Result<Vec<_>, _>>();
+    match numbers.as_deref() {
+        Ok(&[x, y]) => Ok((x, y)),
+        _ => Err(String::from("translations must be in the form X,Y")),
+    }
+}
+
+/// Parses a comma-separated list of rules,
+/// where `MIN..MAX` means all the rules between `MIN` and `MAX`.
+fn parse_rule_list(list: &str) -> Result<Vec<String>, LibError> {
+    let mut rules = Vec::new();
+    for item in list.split(',').map(str::trim) {
+        if let Some(i) = item.find("..") {
+            rules.extend(rule_range(&item[..i], &item[i + 2..])?);
+        } else {
+            rules.push(item.to_string());
+        }
+    }
+    Ok(rules)
+}
+
 /// A struct to store the parse results.
 pub(crate) struct Args {
     pub(crate) search: Box<dyn Search>,
     pub(crate) all: bool,
+    pub(crate) gallery: Option<String>,
+    pub(crate) unique: bool,
+    pub(crate) stats: bool,
+    pub(crate) census: bool,
+    pub(crate) presolve_only: bool,
+    pub(crate) sweep: Option<Vec<String>>,
+    pub(crate) grow: Option<Growth>,
+    pub(crate) checkpoint: Option<Checkpoint>,
     #[cfg(feature = "tui")]
     pub(crate) reset: bool,
     #[cfg(feature = "tui")]
+    pub(crate) save_decisions: Option<String>,
+    #[cfg(feature = "tui")]
     pub(crate) no_tui: bool,
 }
 
@@ -93,6 +179,21 @@ impl Args {
                     .index(5)
                     .validator(|d| d.parse::<isize>().map(|_| ()).map_err(|e| e.to_string())),
             )
+            .arg(
+                Arg::with_name("SHIFT")
+                    .help("Translation in every generation")
+                    .long_help(
+                        "Translation in every generation\n\
+                         In the form X,Y. The reference frame moves by this translation \
+                         in every generation, in addition to the translation after \
+                         the last generation.\n",
+                    )
+                    .long("shift")
+                    .takes_value(true)
+                    .default_value("0,0")
+                    .allow_hyphen_values(true)
+                    .validator(|s| parse_shift(&s).map(|_| ())),
+            )
             .arg(
                 Arg::with_name("TRANSFORM")
                     .help("Transformation of the pattern")
@@ -120,13 +221,21 @@ impl Args {
                          You may need to add quotation marks for some of the symmetries.\n\
                          The usages of these symmetries are the same as Oscar Cunningham's \
                          Logic Life Search.\n\
-                         See [https://conwaylife.com/wiki/Symmetry] \n",
+                         See [https://conwaylife.com/wiki/Symmetry] \n\
+                         \"G\" means glide symmetry: after half a period, the pattern becomes \
+                         its own reflection across the axis, translated by half of DX and DY.\n\
+                         \"g\" means gutter symmetry: the axis is a row or column of dead cells.\n\
+                         C3, C6, D6 and D12 are symmetries of the hexagonal and triangular grids. \
+                         The center of the world must be a cell of the hexagonal grid, \
+                         i.e., both dimensions are odd, or a vertex of the triangular grid, \
+                         i.e., the height is even and the width plus the height is 1 modulo 4.\n",
                     )
                     .short("s")
                     .long("symmetry")
                     .takes_value(true)
                     .possible_values(&[
-                        "C1", "C2", "C4", "D2|", "D2-", "D2\\", "D2/", "D4+", "D4X", "D8",
+                        "C1", "C2", "C4", "D2|", "D2-", "D2\\", "D2/", "D4+", "D4X", "D8", "G|", "G-",
+                        "G\\", "G/", "D2|g", "D2-g", "C3", "C6", "D6", "D12",
                     ])
                     .default_value("C1"),
             )
@@ -135,30 +244,66 @@ impl Args {
                     .help("Rule of the cellular automaton")
                     .long_help(
                         "Rule of the cellular automaton\n\
-                         Supports Life-like, isotropic non-totalistic, hexagonal, MAP rules, \
-                         and their corresponding Generations rules.\n",
+                         Supports Life-like, isotropic non-totalistic, hexagonal, triangular, MAP rules, \
+                         and their corresponding Generations rules, and BSFKL rules.\n\
+                         Also supports Margolus rules in the MCell notation, e.g., `MS,D0;8;4;3;2;5;9;7;1;6;10;11;12;13;14;15`.\n\
+                         Rules with the range-2 von Neumann neighborhood are written in the HROT notation, \
+                         e.g., `R2,C2,S2-3,B3,NN`. Range-1 Moore rules can also be written in this notation, \
+                         and `M1` counts the cell itself, e.g., `R1,C2,M1,S5-9,B5-9,NM` for Vote.\n\
+                         A range of Life-like rules, e.g., `B3/S..B38/S238`, searches for a pattern \
+                         together with a rule in the range. The minimal such rule is printed.\n\
+                         Names of well-known rules, e.g., `Life`, `HighLife`, `Day&Night`, `tlife`, \
+                         are also accepted.\n\
+                         A suffix of a bounded grid, e.g., `B3/S23:T40,20` for a torus or `B3/S23:P40,20` \
+                         for a bounded plane, overrides the width and the height of the world. \
+                         A dimension of `0` is not wrapped, e.g., `B3/S23:T40,0` for a tube.\n",
                     )
                     .short("r")
                     .long("rule")
                     .takes_value(true)
                     .default_value("B3/S23")
                     .validator(|d| {
-                        d.parse::<NtLifeGen>()
+                        // A tiny world with an even period, so that rules with `B0`
+                        // are also accepted.
+                        Config::new(1, 1, 2)
+                            .set_rule_string(d)
+                            .world()
                             .map(|_| ())
                             .map_err(|e| e.to_string())
                     }),
             )
+            .arg(
+                Arg::with_name("TABLE")
+                    .help("Reads the rule from a Golly rule table")
+                    .long_help(
+                        "Reads the rule from a Golly rule table\n\
+                         Reads the @TABLE section of a .rule file, and overrides --rule. \
+                         Only rule tables equivalent to Life-like or Generations rules \
+                         are supported.\n",
+                    )
+                    .long("table")
+                    .takes_value(true),
+            )
             .arg(
                 Arg::with_name("ORDER")
                     .help("Search order")
                     .long_help(
                         "Search order\n\
-                         Row first or column first.\n",
+                         Row first, column first, diagonal, or from the center outwards.\n",
                     )
                     .short("o")
                     .long("order")
                     .takes_value(true)
-                    .possible_values(&["row", "column", "automatic", "r", "c", "a"])
+                    .possible_values(&[
+                        "row",
+                        "column",
+                        "diagonal",
+                        "center",
+                        "automatic",
+                        "r",
+                        "c",
+                        "a",
+                    ])
                     .default_value("automatic"),
             )
             .arg(
@@ -167,9 +312,39 @@ impl Args {
                     .short("c")
                     .long("choose")
                     .takes_value(true)
-                    .possible_values(&["dead", "alive", "random", "d", "a", "r"])
+                    .possible_values(&["dead", "alive", "random", "biased", "d", "a", "r", "b"])
                     .default_value("alive"),
             )
+            .arg(
+                Arg::with_name("BIAS")
+                    .help("Probability of choosing a living state, in percent")
+                    .long_help(
+                        "Probability of choosing a living state, in percent\n\
+                         Only useful when --choose is biased.\n",
+                    )
+                    .long("bias")
+                    .takes_value(true)
+                    .default_value("75")
+                    .validator(|d| match d.parse::<u8>() {
+                        Ok(d) if d <= 100 => Ok(()),
+                        Ok(_) => Err(String::from("must be at most 100")),
+                        Err(e) => Err(e.to_string()),
+                    }),
+            )
+            .arg(
+                Arg::with_name("SEED")
+                    .help("Seed of the random number generator")
+                    .long_help(
+                        "Seed of the random number generator\n\
+                         Only useful when --choose is random or biased. \
+                         With the same seed, the results are always found in the same order. \
+                         If it is not given, a random seed is chosen, \
+                         and is printed as a comment before the result.\n",
+                    )
+                    .long("seed")
+                    .takes_value(true)
+                    .validator(|d| d.parse::<u64>().map(|_| ()).map_err(|e| e.to_string())),
+            )
             .arg(
                 Arg::with_name("MAX")
                     .help("Upper bound of numbers of minimum living cells in all generations")
@@ -183,6 +358,127 @@ impl Args {
                     .default_value("0")
                     .validator(|d| d.parse::<u32>().map(|_| ()).map_err(|e| e.to_string())),
             )
+            .arg(
+                Arg::with_name("MAX_GEN0")
+                    .help("Upper bound of numbers of living cells in the first generation")
+                    .long_help(
+                        "Upper bound of numbers of living cells in the first generation\n\
+                         Once the bound is reached, the other cells in the first generation \
+                         are deduced to be dead.\n\
+                         If this value is set to 0, it means there is no limitation.\n",
+                    )
+                    .long("max-gen0")
+                    .takes_value(true)
+                    .default_value("0")
+                    .validator(|d| d.parse::<u32>().map(|_| ()).map_err(|e| e.to_string())),
+            )
+            .arg(
+                Arg::with_name("MAX_GEN")
+                    .help("Upper bound of numbers of living cells in every generation")
+                    .long_help(
+                        "Upper bound of numbers of living cells in every generation\n\
+                         Once the bound is reached in a generation, the other cells \
+                         in that generation are deduced to be dead.\n\
+                         If this value is set to 0, it means there is no limitation.\n",
+                    )
+                    .long("max-gen")
+                    .takes_value(true)
+                    .default_value("0")
+                    .validator(|d| d.parse::<u32>().map(|_| ()).map_err(|e| e.to_string())),
+            )
+            .arg(
+                Arg::with_name("MAX_ROW")
+                    .help("Upper bound of numbers of living cells in each row")
+                    .long_help(
+                        "Upper bound of numbers of living cells in each row of each generation\n\
+                         If this value is set to 0, it means there is no limitation.\n",
+                    )
+                    .long("max-row")
+                    .takes_value(true)
+                    .default_value("0")
+                    .validator(|d| d.parse::<u32>().map(|_| ()).map_err(|e| e.to_string())),
+            )
+            .arg(
+                Arg::with_name("MAX_COLUMN")
+                    .help("Upper bound of numbers of living cells in each column")
+                    .long_help(
+                        "Upper bound of numbers of living cells in each column of each generation\n\
+                         If this value is set to 0, it means there is no limitation.\n",
+                    )
+                    .long("max-column")
+                    .takes_value(true)
+                    .default_value("0")
+                    .validator(|d| d.parse::<u32>().map(|_| ()).map_err(|e| e.to_string())),
+            )
+            .arg(
+                Arg::with_name("MAX_ROTOR")
+                    .help("Upper bound of the number of rotor cells")
+                    .long_help(
+                        "Upper bound of the number of rotor cells\n\
+                         Rotor cells are the cells that change their states during \
+                         the period. This is useful for oscillators with small rotors.\n\
+                         If this value is set to 0, it means there is no limitation.\n",
+                    )
+                    .long("max-rotor")
+                    .takes_value(true)
+                    .default_value("0")
+                    .validator(|d| d.parse::<u32>().map(|_| ()).map_err(|e| e.to_string())),
+            )
+            .arg(
+                Arg::with_name("DIAGONAL")
+                    .help("Width of the diagonal strip that restricts the search range")
+                    .long_help(
+                        "Width of the diagonal strip that restricts the search range\n\
+                         Cells whose x and y coordinates differ by this value or more \
+                         are dead. This is useful for diagonal spaceships.\n\
+                         If this value is set to 0, the whole world is searched.\n",
+                    )
+                    .long("diagonal-width")
+                    .takes_value(true)
+                    .default_value("0")
+                    .validator(|d| d.parse::<u32>().map(|_| ()).map_err(|e| e.to_string())),
+            )
+            .arg(
+                Arg::with_name("SHAPE")
+                    .help("Restricts the search range to a shape read from a file")
+                    .long_help(
+                        "Restricts the search range to a shape read from a file\n\
+                         The file contains a pattern in Plaintext format, whose living cells \
+                         are in the shape. Cells outside the shape are in the background.\n",
+                    )
+                    .long("shape")
+                    .takes_value(true),
+            )
+            .arg(
+                Arg::with_name("DEAD")
+                    .help("A rectangle of cells that are dead in all generations")
+                    .long_help(
+                        "A rectangle of cells that are dead in all generations\n\
+                         In the form X,Y,WIDTH,HEIGHT, where (X, Y) is the top left corner. \
+                         This is useful to carve channels or to keep lanes clear. \
+                         Can be given multiple times.\n",
+                    )
+                    .long("dead")
+                    .takes_value(true)
+                    .multiple(true)
+                    .number_of_values(1)
+                    .validator(|r| parse_rect(&r).map(|_| ())),
+            )
+            .arg(
+                Arg::with_name("STATOR")
+                    .help("A rectangle of cells that have the same state in all generations")
+                    .long_help(
+                        "A rectangle of cells that have the same state in all generations\n\
+                         In the form X,Y,WIDTH,HEIGHT, where (X, Y) is the top left corner. \
+                         This is useful to fix the stator of an oscillator. \
+                         Can be given multiple times.\n",
+                    )
+                    .long("stator")
+                    .takes_value(true)
+                    .multiple(true)
+                    .number_of_values(1)
+                    .validator(|r| parse_rect(&r).map(|_| ())),
+            )
             .arg(
                 Arg::with_name("FRONT")
                     .help(
@@ -202,6 +498,279 @@ impl Args {
                          the current result minus one.",
                     )
                     .long("reduce"),
+            )
+            .arg(
+                Arg::with_name("MINIMIZE")
+                    .help("Search for the result with the smallest cell count")
+                    .long_help(
+                        "Search for the result with the smallest cell count\n\
+                         Whenever a result is found, the search continues with a smaller \
+                         max cell count, and only the last result is shown.",
+                    )
+                    .long("minimize"),
+            )
+            .arg(
+                Arg::with_name("TIMEOUT")
+                    .help("Stops the search after the given number of seconds")
+                    .long_help(
+                        "Stops the search after the given number of seconds\n\
+                         When the time is up, the deepest partial result reached so far, \
+                         i.e., the one with the most known cells, is shown instead.\n\
+                         If this value is set to 0, it means there is no limitation.\n",
+                    )
+                    .long("timeout")
+                    .takes_value(true)
+                    .default_value("0")
+                    .validator(|d| match d.parse::<f64>() {
+                        Ok(t) if t.is_finite() && t >= 0.0 => Ok(()),
+                        Ok(_) => Err(String::from("must be a non-negative number")),
+                        Err(e) => Err(e.to_string()),
+                    }),
+            )
+            .arg(
+                Arg::with_name("EXPAND")
+                    .help("Expands the world to a valid size for the symmetry")
+                    .long_help(
+                        "Expands the world to a valid size for the symmetry and the transformation\n\
+                         Some symmetries and transformations require the world to be square. \
+                         Without this flag, a non-square world is an error for them.",
+                    )
+                    .short("e")
+                    .long("expand"),
+            )
+            .arg(
+                Arg::with_name("IMAGE")
+                    .help("Reads known cells in generation 0 from an image")
+                    .long_help(
+                        "Reads known cells in generation 0 from an image\n\
+                         Supports PNG, PBM and PGM images. The pixel at (x, y) corresponds to \
+                         the cell at (x, y). Dark pixels are alive, light pixels are dead, \
+                         and pixels in between are unknown.\n",
+                    )
+                    .short("i")
+                    .long("image")
+                    .takes_value(true),
+            )
+            .arg(
+                Arg::with_name("IMAGE_MASK")
+                    .help("Reads a mask of cells that are dead in all generations from an image")
+                    .long_help(
+                        "Reads a mask of cells that are dead in all generations from an image\n\
+                         Supports PNG, PBM and PGM images. The pixel at (x, y) corresponds to \
+                         the cells at (x, y). Dark pixels are dead in all generations, \
+                         and other pixels are unknown. Can be combined with --image.\n",
+                    )
+                    .long("image-mask")
+                    .takes_value(true),
+            )
+            .arg(
+                Arg::with_name("THRESHOLD")
+                    .help("Brightness thresholds for reading the images")
+                    .long_help(
+                        "Brightness thresholds for reading the images\n\
+                         In the form LOW,HIGH, where the brightness is scaled to 0..=255. \
+                         Pixels darker than LOW are alive, pixels lighter than HIGH are dead. \
+                         In the mask, pixels darker than LOW are dead.\n",
+                    )
+                    .long("threshold")
+                    .takes_value(true)
+                    .default_value("64,192")
+                    .validator(|t| parse_threshold(&t).map(|_| ())),
+            )
+            .arg(
+                Arg::with_name("BLOCKLIST")
+                    .help("Skips the results that are known patterns in a file")
+                    .long_help(
+                        "Skips the results that are known patterns in a file\n\
+                         The file contains one or more patterns in RLE, each ended with `!`. \
+                         A result is skipped if some generation of it is one of the patterns, \
+                         up to translation, rotation and reflection.\n",
+                    )
+                    .long("blocklist")
+                    .takes_value(true),
+            )
+            .arg(
+                Arg::with_name("BLOCKLIST_SUBPATTERN")
+                    .help("Also skips the results that contain a known pattern")
+                    .long_help(
+                        "Also skips the results that contain a known pattern\n\
+                         A result is skipped if generation 0 contains a pattern in the blocklist, \
+                         i.e., all the cells in its bounding box have the same states.\n",
+                    )
+                    .long("blocklist-subpattern")
+                    .requires("BLOCKLIST"),
+            )
+            .arg(
+                Arg::with_name("GALLERY")
+                    .help("Writes an HTML gallery of all results to a file")
+                    .long_help(
+                        "Writes an HTML gallery of all results to a file\n\
+                         Each result is shown as a thumbnail, with its period, \
+                         population and apgcode.\n",
+                    )
+                    .long("gallery")
+                    .takes_value(true)
+                    .requires("ALL"),
+            )
+            .arg(
+                Arg::with_name("UNIQUE")
+                    .help("Skips results that are the same as an earlier result")
+                    .long_help(
+                        "Skips results that are the same as an earlier result\n\
+                         Two results are the same if they differ only by phase, \
+                         translation, rotation and reflection.\n",
+                    )
+                    .long("unique")
+                    .requires("ALL"),
+            )
+            .arg(
+                Arg::with_name("STATS")
+                    .help("Prints the statistics of the search at the end")
+                    .long_help(
+                        "Prints the statistics of the search at the end\n\
+                         The numbers of decisions, conflicts, deduced cells and restarts, \
+                         the maximal number of known cells, and the time are printed \
+                         to the standard error.\n",
+                    )
+                    .long("stats"),
+            )
+            .arg(
+                Arg::with_name("CENSUS")
+                    .help("Enumerates all small objects and prints a census report")
+                    .long_help(
+                        "Enumerates all small objects and prints a census report\n\
+                         Searches for all still lifes, oscillators and spaceships that fit \
+                         in the world, for all periods up to the given period, and all \
+                         displacements up to the speed of light. The population is limited \
+                         by --max. The translation, the transformation and the symmetry \
+                         are ignored.\n\
+                         The objects are grouped by their apgcodes, and printed in CSV format. \
+                         Not supported for Generations rules and rules with B0.\n",
+                    )
+                    .long("census")
+                    .conflicts_with_all(&["ALL", "GALLERY"]),
+            )
+            .arg(
+                Arg::with_name("PRESOLVE_ONLY")
+                    .help("Reports the cells forced by the constraints, without searching")
+                    .long_help(
+                        "Reports the cells forced by the constraints, without searching\n\
+                         Deduces the states of the cells from the rule, the symmetry, \
+                         the boundary and the known cells, without any decision, \
+                         and prints how many and which cells are forced. \
+                         Useful for sanity-checking a configuration.\n",
+                    )
+                    .long("presolve-only")
+                    .conflicts_with_all(&["ALL", "GALLERY", "CENSUS"]),
+            )
+            .arg(
+                Arg::with_name("SWEEP")
+                    .help("Searches in each of the rules, and reports which rules admit a result")
+                    .long_help(
+                        "Searches in each of the rules, and reports which rules admit a result\n\
+                         A comma-separated list of rules. An item of the form MIN..MAX means \
+                         all totalistic Life-like rules between MIN and MAX, \
+                         e.g., B3/S23..B38/S238. The rule given by --rule is ignored.\n",
+                    )
+                    .long("sweep")
+                    .takes_value(true)
+                    .conflicts_with_all(&["ALL", "GALLERY", "CENSUS", "TABLE"]),
+            )
+            .arg(
+                Arg::with_name("GROW")
+                    .help("Enlarges the world until a result is found")
+                    .long_help(
+                        "Enlarges the world until a result is found\n\
+                         In the form WIDTH,HEIGHT. Whenever the search is exhausted without \
+                         a result, adds this number of columns and rows to the world, \
+                         and searches again.\n",
+                    )
+                    .long("grow")
+                    .takes_value(true)
+                    .validator(|s| parse_size(&s).map(|_| ()))
+                    .conflicts_with_all(&["ALL", "GALLERY", "CENSUS", "SWEEP"]),
+            )
+            .arg(
+                Arg::with_name("GROW_ALTERNATE")
+                    .help("Adds columns and rows in turn when enlarging the world")
+                    .long("grow-alternate")
+                    .requires("GROW"),
+            )
+            .arg(
+                Arg::with_name("GROW_MAX")
+                    .help("The maximal size of the enlarged world")
+                    .long_help(
+                        "The maximal size of the enlarged world\n\
+                         In the form WIDTH,HEIGHT. If a value is set to 0, \
+                         it means there is no limitation.\n",
+                    )
+                    .long("grow-max")
+                    .takes_value(true)
+                    .default_value("0,0")
+                    .validator(|s| parse_size(&s).map(|_| ())),
+            )
+            .arg(
+                Arg::with_name("CHECKPOINT")
+                    .help("Writes checkpoints of the search to a file periodically")
+                    .long_help(
+                        "Writes checkpoints of the search to a file periodically\n\
+                         The previous checkpoint is kept in a file with the suffix .prev. \
+                         The search can be resumed with --resume.\n",
+                    )
+                    .long("checkpoint")
+                    .takes_value(true)
+                    .conflicts_with_all(&["CENSUS", "SWEEP", "GROW"]),
+            )
+            .arg(
+                Arg::with_name("CHECKPOINT_MINUTES")
+                    .help("Number of minutes between two checkpoints")
+                    .long_help(
+                        "Number of minutes between two checkpoints\n\
+                         If this value is set to 0, checkpoints are not written by time.\n\
+                         Only useful when --checkpoint is set.\n",
+                    )
+                    .long("checkpoint-minutes")
+                    .takes_value(true)
+                    .default_value("10")
+                    .validator(|d| d.parse::<u64>().map(|_| ()).map_err(|e| e.to_string())),
+            )
+            .arg(
+                Arg::with_name("CHECKPOINT_CONFLICTS")
+                    .help("Number of conflicts between two checkpoints")
+                    .long_help(
+                        "Number of conflicts between two checkpoints\n\
+                         If this value is set to 0, checkpoints are not written by conflicts.\n\
+                         Only useful when --checkpoint is set.\n",
+                    )
+                    .long("checkpoint-conflicts")
+                    .takes_value(true)
+                    .default_value("0")
+                    .validator(|d| d.parse::<u64>().map(|_| ()).map_err(|e| e.to_string())),
+            )
+            .arg(
+                Arg::with_name("RESUME")
+                    .help("Resumes the search from a checkpoint")
+                    .long_help(
+                        "Resumes the search from a checkpoint\n\
+                         The configuration is read from the checkpoint, \
+                         and the other search options are ignored. \
+                         If the checkpoint can not be read, the previous one is used.\n",
+                    )
+                    .long("resume")
+                    .takes_value(true)
+                    .conflicts_with_all(&["CENSUS", "SWEEP"]),
+            )
+            .arg(
+                Arg::with_name("REPLAY")
+                    .help("Replays the decisions in a file before searching")
+                    .long_help(
+                        "Replays the decisions in a file before searching\n\
+                         The file can be written by --save-decisions in the TUI, \
+                         and should be replayed with the same search options.\n",
+                    )
+                    .long("replay")
+                    .takes_value(true)
+                    .conflicts_with_all(&["CENSUS", "SWEEP", "RESUME"]),
             );
 
         #[cfg(feature = "tui")]
@@ -224,6 +793,17 @@ impl Args {
                         .long("reset-time")
                         .conflicts_with("NOTUI"),
                 )
+                .arg(
+                    Arg::with_name("SAVE_DECISIONS")
+                        .help("Writes the decisions to a file when quitting the TUI")
+                        .long_help(
+                            "Writes the decisions to a file when quitting the TUI\n\
+                             The search position can be reproduced with --replay.\n",
+                        )
+                        .long("save-decisions")
+                        .takes_value(true)
+                        .conflicts_with("NOTUI"),
+                )
                 .arg(
                     Arg::with_name("NOTUI")
                         .help("Starts searching immediately, without entering the TUI")
@@ -251,76 +831,257 @@ impl Args {
 
         let dx = matches.value_of("DX").unwrap().parse().unwrap();
         let dy = matches.value_of("DY").unwrap().parse().unwrap();
+        let (sx, sy) = parse_shift(matches.value_of("SHIFT").unwrap()).unwrap();
 
         let transform: Transform = matches.value_of("TRANSFORM").unwrap().parse().unwrap();
         let symmetry: Symmetry = matches.value_of("SYMMETRY").unwrap().parse().unwrap();
 
-        if width != height {
-            if transform.square_world() {
-                return Err(Error::with_description(
-                    &format!(
-                        "The transformation '{:?}' is only valid for square worlds",
-                        transform
-                    ),
-                    ErrorKind::InvalidValue,
-                ));
-            }
-            if symmetry.square_world() {
-                return Err(Error::with_description(
-                    &format!(
-                        "The symmetry '{:?}' is only valid for square worlds",
-                        symmetry
-                    ),
-                    ErrorKind::InvalidValue,
-                ));
-            }
-        }
-
         let all = matches.is_present("ALL");
+        let gallery = matches.value_of("GALLERY").map(String::from);
+        let unique = matches.is_present("UNIQUE");
+        let stats = matches.is_present("STATS");
+        let census = matches.is_present("CENSUS");
+        let presolve_only = matches.is_present("PRESOLVE_ONLY");
+        let sweep =
+            match matches.value_of("SWEEP") {
+                Some(list) => Some(parse_rule_list(list).map_err(|e| {
+                    Error::with_description(&e.to_string(), ErrorKind::InvalidValue)
+                })?),
+                None => None,
+            };
+        let grow = matches.value_of("GROW").map(|s| {
+            let (width, height) = parse_size(s).unwrap();
+            let (max_width, max_height) =
+                parse_size(matches.value_of("GROW_MAX").unwrap()).unwrap();
+            let max = |m| if m > 0 { Some(m) } else { None };
+            Growth::new(width, height)
+                .set_alternate(matches.is_present("GROW_ALTERNATE"))
+                .set_max_size(max(max_width), max(max_height))
+        });
         #[cfg(feature = "tui")]
         let reset = matches.is_present("RESET");
         #[cfg(feature = "tui")]
+        let save_decisions = matches.value_of("SAVE_DECISIONS").map(String::from);
+        #[cfg(feature = "tui")]
         let no_tui = matches.is_present("NOTUI");
         let search_order = match matches.value_of("ORDER").unwrap() {
             "row" | "r" => Some(SearchOrder::RowFirst),
             "column" | "c" => Some(SearchOrder::ColumnFirst),
+            "diagonal" => Some(SearchOrder::Diagonal),
+            "center" => Some(SearchOrder::CenterOut),
             _ => None,
         };
         let new_state = match matches.value_of("CHOOSE").unwrap() {
             "dead" | "d" => NewState::ChooseDead,
             "alive" | "a" => NewState::ChooseAlive,
             "random" | "r" => NewState::Random,
+            "biased" | "b" => NewState::Biased(matches.value_of("BIAS").unwrap().parse().unwrap()),
             _ => NewState::ChooseAlive,
         };
+        let seed = matches.value_of("SEED").map(|s| s.parse().unwrap());
         let max_cell_count = matches.value_of("MAX").unwrap().parse().unwrap();
         let max_cell_count = match max_cell_count {
             0 => None,
             i => Some(i),
         };
+        let max_gen0_cell_count = matches.value_of("MAX_GEN0").unwrap().parse().unwrap();
+        let max_gen0_cell_count = match max_gen0_cell_count {
+            0 => None,
+            i => Some(i),
+        };
+        let max_gen_cell_count = matches.value_of("MAX_GEN").unwrap().parse().unwrap();
+        let max_gen_cell_count = match max_gen_cell_count {
+            0 => None,
+            i => Some(i),
+        };
+        let max_row_cell_count = matches.value_of("MAX_ROW").unwrap().parse().unwrap();
+        let max_row_cell_count = match max_row_cell_count {
+            0 => None,
+            i => Some(i),
+        };
+        let max_column_cell_count = matches.value_of("MAX_COLUMN").unwrap().parse().unwrap();
+        let max_column_cell_count = match max_column_cell_count {
+            0 => None,
+            i => Some(i),
+        };
+        let max_rotor_cell_count = matches.value_of("MAX_ROTOR").unwrap().parse().unwrap();
+        let max_rotor_cell_count = match max_rotor_cell_count {
+            0 => None,
+            i => Some(i),
+        };
+        let diagonal_width = matches.value_of("DIAGONAL").unwrap().parse().unwrap();
+        let diagonal_width = match diagonal_width {
+            0 => None,
+            i => Some(i),
+        };
+        let dead_rects = matches.values_of("DEAD").map_or_else(Vec::new, |rects| {
+            rects.map(|r| parse_rect(r).unwrap()).collect()
+        });
+        let stator_rects = matches.values_of("STATOR").map_or_else(Vec::new, |rects| {
+            rects.map(|r| parse_rect(r).unwrap()).collect()
+        });
         let non_empty_front = matches.is_present("FRONT");
         let reduce_max = matches.is_present("REDUCE");
+        let minimize = matches.is_present("MINIMIZE");
+        let time_limit = matches.value_of("TIMEOUT").unwrap().parse().unwrap();
+        let time_limit = if time_limit > 0.0 {
+            Some(Duration::from_secs_f64(time_limit))
+        } else {
+            None
+        };
 
-        let rule_string = matches.value_of("RULE").unwrap().to_string();
+        let rule_string = if let Some(path) = matches.value_of("TABLE") {
+            let table = fs::read_to_string(path)
+                .map_err(|e| Error::with_description(&e.to_string(), ErrorKind::Io))?;
+            table_to_rule_string(&table)
+                .map_err(|e| Error::with_description(&e.to_string(), ErrorKind::InvalidValue))?
+        } else {
+            matches.value_of("RULE").unwrap().to_string()
+        };
+
+        let (low, high) = parse_threshold(matches.value_of("THRESHOLD").unwrap()).unwrap();
+        let known_cells = if let Some(path) = matches.value_of("IMAGE") {
+            known_cells_from_image(path, low, high)
+                .map_err(|e| Error::with_description(&e, ErrorKind::Io))?
+        } else {
+            Vec::new()
+        };
+        let dead_cells = if let Some(path) = matches.value_of("IMAGE_MASK") {
+            dead_cells_from_image(path, low)
+                .map_err(|e| Error::with_description(&e, ErrorKind::Io))?
+        } else {
+            Vec::new()
+        };
+
+        let blocklist = if let Some(path) = matches.value_of("BLOCKLIST") {
+            let patterns = fs::read_to_string(path)
+                .map_err(|e| Error::with_description(&e.to_string(), ErrorKind::Io))?;
+            patterns
+                .split('!')
+                .filter(|rle| rle.contains(|c: char| !c.is_whitespace()))
+                .map(|rle| format!("{}!", rle))
+                .collect()
+        } else {
+            Vec::new()
+        };
 
         let config = Config::new(width, height, period)
             .set_translate(dx, dy)
+            .set_shift(sx, sy)
             .set_transform(transform)
             .set_symmetry(symmetry)
             .set_search_order(search_order)
             .set_new_state(new_state)
+            .set_seed(seed)
             .set_max_cell_count(max_cell_count)
+            .set_max_gen0_cell_count(max_gen0_cell_count)
+            .set_max_gen_cell_count(max_gen_cell_count)
+            .set_max_row_cell_count(max_row_cell_count)
+            .set_max_column_cell_count(max_column_cell_count)
+            .set_max_rotor_cell_count(max_rotor_cell_count)
+            .set_diagonal_width(diagonal_width)
             .set_non_empty_front(non_empty_front)
             .set_reduce_max(reduce_max)
-            .set_rule_string(rule_string);
+            .set_minimize(minimize)
+            .set_time_limit(time_limit)
+            .set_rule_string(rule_string)
+            .set_known_cells(known_cells)
+            .set_dead_cells(dead_cells)
+            .set_blocklist(blocklist)
+            .set_blocklist_subpattern(matches.is_present("BLOCKLIST_SUBPATTERN"));
+        let config = match matches.value_of("SHAPE") {
+            Some(path) => {
+                let plaintext = fs::read_to_string(path)
+                    .map_err(|e| Error::with_description(&e.to_string(), ErrorKind::Io))?;
+                config.set_shape_plaintext(&plaintext)
+            }
+            None => config,
+        };
+        let config = dead_rects.into_iter().fold(config, |config, (x, y, w, h)| {
+            config.add_dead_rect(x, y, w, h)
+        });
+        let config = stator_rects
+            .into_iter()
+            .fold(config, |config, (x, y, w, h)| {
+                config.add_stator_rect(x, y, w, h)
+            });
+
+        let config = if matches.is_present("EXPAND") {
+            let new_config = config.clone().expand_size();
+            if (new_config.width, new_config.height) != (width, height) {
+                eprintln!(
+                    "Expanded the world from {} x {} to {} x {}.",
+                    width, height, new_config.width, new_config.height
+                );
+            }
+            new_config
+        } else {
+            config
+        };
 
-        let search = config.world().unwrap();
+        let checkpoint = matches.value_of("CHECKPOINT").map(|path| {
+            let minutes = matches
+                .value_of("CHECKPOINT_MINUTES")
+                .unwrap()
+                .parse::<u64>()
+                .unwrap();
+            let conflicts = matches
+                .value_of("CHECKPOINT_CONFLICTS")
+                .unwrap()
+                .parse()
+                .unwrap();
+            Checkpoint::new(path)
+                .set_interval(match minutes {
+                    0 => None,
+                    m => Some(Duration::from_secs(m * 60)),
+                })
+                .set_conflicts(match conflicts {
+                    0 => None,
+                    c => Some(c),
+                })
+        });
+
+        let mut search = if let Some(path) = matches.value_of("RESUME") {
+            let (_, world_ser) = Checkpoint::new(path)
+                .read()
+                .map_err(|e| Error::with_description(&e.to_string(), ErrorKind::Io))?;
+            world_ser.world()
+        } else {
+            config.world()
+        }
+        .map_err(|e| Error::with_description(&e.to_string(), ErrorKind::InvalidValue))?;
+
+        if let Some(path) = matches.value_of("REPLAY") {
+            let (header, decisions) = File::open(path)
+                .and_then(|file| read_decisions(&mut BufReader::new(file)))
+                .map_err(|e| Error::with_description(&e.to_string(), ErrorKind::Io))?;
+            if !header.matches(search.config()) {
+                eprintln!("Warning: the decisions were recorded with other search options.");
+            }
+            let consistent = search
+                .replay(&decisions)
+                .map_err(|e| Error::with_description(&e.to_string(), ErrorKind::InvalidValue))?;
+            if !consistent {
+                eprintln!("Warning: the decisions lead to a conflict.");
+            }
+        }
 
         Ok(Args {
             search,
             all,
+            gallery,
+            unique,
+            stats,
+            census,
+            presolve_only,
+            sweep,
+            grow,
+            checkpoint,
             #[cfg(feature = "tui")]
             reset,
             #[cfg(feature = "tui")]
+            save_decisions,
+            #[cfg(feature = "tui")]
             no_tui,
         })
     }
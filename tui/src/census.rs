@@ -0,0 +1,117 @@
+//! Enumerating all small objects of a rule.
+
+use crate::gallery::Solution;
+use rlifesrc_lib::{Config, FileKind, Header, Session, Status, Symmetry, Transform};
+use std::{
+    collections::BTreeMap,
+    io::{self, Write},
+};
+
+/// An object found in the census.
+pub(crate) struct CensusEntry {
+    /// The apgcode of the object.
+    apgcode: String,
+    /// The period.
+    period: isize,
+    /// The displacement in each period.
+    displacement: (isize, isize),
+    /// Minimum number of living cells in all generations.
+    population: usize,
+    /// Number of results with this apgcode,
+    /// counting different phases, positions and orientations.
+    count: usize,
+}
+
+/// Enumerates all still lifes, oscillators and spaceships that fit in
+/// the world of the configuration.
+///
+/// The period of the configuration is the maximal period, and the
+/// `max_cell_count` is the maximal population. The translation, the
+/// transformation and the symmetry of the configuration are ignored:
+/// all periods up to the maximal period, and all displacements up to
+/// the speed of light, are searched.
+///
+/// Results are grouped by their apgcodes, and sorted by period,
+/// displacement and population. Disjoint objects are not separated,
+/// so a result may be a combination of several smaller objects. Returns an error if the rule is a Generations rule or
+/// contains `B0`, where apgcodes are not supported.
+pub(crate) fn census(config: &Config) -> Result<Vec<CensusEntry>, String> {
+    let mut session = Session::new();
+    let search = session.world(config).map_err(|e| e.to_string())?;
+    if search.is_gen_rule() || search.is_b0_rule() {
+        return Err(String::from(
+            "Census is not supported for Generations rules and rules with B0",
+        ));
+    }
+    let mut entries = BTreeMap::new();
+    for period in 1..=config.period {
+        for dx in 0..=period {
+            for dy in 0..=period {
+                let config = Config {
+                    period,
+                    dx,
+                    dy,
+                    transform: Transform::Id,
+                    symmetry: Symmetry::C1,
+                    symmetry_maps: Vec::new(),
+                    time_reversal: None,
+                    non_empty_front: true,
+                    reduce_max: false,
+                    minimize: false,
+                    time_limit: None,
+                    ..config.clone()
+                };
+                let mut search = session.world(&config).map_err(|e| e.to_string())?;
+                loop {
+                    match search.search(None) {
+                        Status::Found => {
+                            let solution = Solution::new(search.as_ref());
+                            let apgcode = solution.apgcode().unwrap();
+                            entries
+                                .entry(apgcode.to_string())
+                                .or_insert_with(|| CensusEntry {
+                                    apgcode: apgcode.to_string(),
+                                    period,
+                                    displacement: (dx, dy),
+                                    population: solution.population(),
+                                    count: 0,
+                                })
+                                .count += 1;
+                        }
+                        Status::None => break,
+                        _ => (),
+                    }
+                }
+            }
+        }
+    }
+    let mut entries: Vec<_> = entries.into_values().collect();
+    entries.sort_by_key(|entry| (entry.period, entry.displacement, entry.population));
+    Ok(entries)
+}
+
+/// Writes a census report.
+///
+/// The report starts with a `Header`, followed by a line of column names,
+/// and a line for each object in CSV format.
+pub(crate) fn write_census<W: Write>(
+    writer: &mut W,
+    config: &Config,
+    entries: &[CensusEntry],
+) -> io::Result<()> {
+    Header::new(FileKind::Census, config).write_to(writer)?;
+    writeln!(writer, "apgcode,period,dx,dy,population,count")?;
+    for entry in entries {
+        writeln!(
+            writer,
+            "{},{},{},{},{},{}",
+            entry.apgcode,
+            entry.period,
+            entry.displacement.0,
+            entry.displacement.1,
+            entry.population,
+            entry.count
+        )?;
+    }
+    Ok(())
+}
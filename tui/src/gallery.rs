@@ -0,0 +1,218 @@
+//! Generating an HTML gallery of the search results.
+
+use rlifesrc_lib::{Config, FileKind, Header, Search, DEAD};
+use std::{collections::HashSet, fmt::Write, fs, io};
+
+/// The maximal width or height of a thumbnail, in pixels.
+const THUMBNAIL_SIZE: isize = 128;
+
+/// Digits used in the [extended Wechsler format](https://conwaylife.com/wiki/Apgcode).
+const DIGITS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+/// A search result to be shown in the gallery.
+pub(crate) struct Solution {
+    /// Width of the world.
+    width: isize,
+    /// Height of the world.
+    height: isize,
+    /// Living cells in each generation.
+    phases: Vec<Vec<(isize, isize)>>,
+    /// The apgcode of the pattern.
+    ///
+    /// `None` for Generations rules and rules with `B0`.
+    apgcode: Option<String>,
+}
+
+impl Solution {
+    /// Records the current result of the search.
+    pub(crate) fn new(search: &dyn Search) -> Self {
+        let config = search.config();
+        let phases: Vec<_> = (0..config.period)
+            .map(|t| {
+                let mut cells = Vec::new();
+                for y in 0..config.height {
+                    for x in 0..config.width {
+                        if let Ok(Some(state)) = search.get_cell_state((x, y, t)) {
+                            if state != DEAD {
+                                cells.push((x, y));
+                            }
+                        }
+                    }
+                }
+                cells
+            })
+            .collect();
+        let apgcode = if search.is_gen_rule() || search.is_b0_rule() {
+            None
+        } else {
+            let prefix = if config.dx != 0 || config.dy != 0 {
+                format!("xq{}", phases.len())
+            } else if phases.len() == 1 {
+                format!("xs{}", phases[0].len())
+            } else {
+                format!("xp{}", phases.len())
+            };
+            let code = phases
+                .iter()
+                .flat_map(|cells| (0..8).map(move |i| wechsler(cells, i)))
+                .min_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)))
+                .unwrap_or_default();
+            Some(format!("{}_{}", prefix, code))
+        };
+        Solution {
+            width: config.width,
+            height: config.height,
+            phases,
+            apgcode,
+        }
+    }
+
+    /// The apgcode of the pattern.
+    ///
+    /// `None` for Generations rules and rules with `B0`.
+    pub(crate) fn apgcode(&self) -> Option<&str> {
+        self.apgcode.as_deref()
+    }
+
+    /// Minimum number of living cells in all generations.
+    pub(crate) fn population(&self) -> usize {
+        self.phases.iter().map(Vec::len).min().unwrap_or(0)
+    }
+
+    /// Draws the generation 0 of the pattern as an SVG image.
+    fn thumbnail(&self) -> String {
+        let scale = (THUMBNAIL_SIZE / self.width.max(self.height)).max(1);
+        let mut svg = String::new();
+        write!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" \
+             viewBox=\"0 0 {} {}\" shape-rendering=\"crispEdges\">\
+             <rect width=\"{}\" height=\"{}\" fill=\"#fff\"/>",
+            self.width * scale,
+            self.height * scale,
+            self.width,
+            self.height,
+            self.width,
+            self.height
+        )
+        .unwrap();
+        for &(x, y) in self.phases[0].iter() {
+            write!(
+                svg,
+                "<rect x=\"{}\" y=\"{}\" width=\"1\" height=\"1\"/>",
+                x, y
+            )
+            .unwrap();
+        }
+        svg.push_str("</svg>");
+        svg
+    }
+}
+
+/// Applies one of the 8 rotations and reflections to the cells,
+/// and then moves them to the top left corner.
+fn transform(cells: &[(isize, isize)], i: u8) -> Vec<(isize, isize)> {
+    let cells: Vec<_> = cells
+        .iter()
+        .map(|&(x, y)| {
+            let (x, y) = if i & 4 == 0 { (x, y) } else { (y, x) };
+            let x = if i & 1 == 0 { x } else { -x };
+            let y = if i & 2 == 0 { y } else { -y };
+            (x, y)
+        })
+        .collect();
+    let min_x = cells.iter().map(|c| c.0).min().unwrap_or(0);
+    let min_y = cells.iter().map(|c| c.1).min().unwrap_or(0);
+    cells
+        .into_iter()
+        .map(|(x, y)| (x - min_x, y - min_y))
+        .collect()
+}
+
+/// Encodes a run of empty columns in the extended Wechsler format.
+fn push_zeros(code: &mut String, mut zeros: usize) {
+    while zeros > 39 {
+        code.push_str("yz");
+        zeros -= 39;
+    }
+    match zeros {
+        0 => (),
+        1 => code.push('0'),
+        2 => code.push('w'),
+        3 => code.push('x'),
+        _ => {
+            code.push('y');
+            code.push(DIGITS[zeros - 4] as char);
+        }
+    }
+}
+
+/// Encodes the cells in the extended Wechsler format,
+/// after applying the `i`-th transformation.
+fn wechsler(cells: &[(isize, isize)], i: u8) -> String {
+    let cells = transform(cells, i);
+    let width = cells.iter().map(|c| c.0 + 1).max().unwrap_or(0);
+    let height = cells.iter().map(|c| c.1 + 1).max().unwrap_or(0);
+    let set: HashSet<_> = cells.into_iter().collect();
+    let mut code = String::new();
+    for strip in 0..(height + 4) / 5 {
+        if strip > 0 {
+            code.push('z');
+        }
+        let mut zeros = 0;
+        for x in 0..width {
+            let column = (0..5)
+                .filter(|r| set.contains(&(x, 5 * strip + r)))
+                .fold(0, |acc, r| acc | 1 << r);
+            if column == 0 {
+                zeros += 1;
+            } else {
+                push_zeros(&mut code, zeros);
+                zeros = 0;
+                code.push(DIGITS[column] as char);
+            }
+        }
+    }
+    code
+}
+
+/// Writes an HTML gallery of the search results to a file.
+///
+/// Each result is shown as a thumbnail of its generation 0,
+/// with its period, population and apgcode.
+///
+/// The file starts with a `Header` in an HTML comment.
+pub(crate) fn write_gallery(path: &str, config: &Config, solutions: &[Solution]) -> io::Result<()> {
+    let mut html = format!("<!-- {} -->\n", Header::new(FileKind::Gallery, config));
+    html.push_str(
+        "<!DOCTYPE html>\n\
+         <html>\n\
+         <head>\n\
+         <meta charset=\"utf-8\">\n\
+         <title>rlifesrc results</title>\n\
+         <style>\n\
+         body { font-family: sans-serif; }\n\
+         .gallery { display: flex; flex-wrap: wrap; }\n\
+         figure { margin: 8px; padding: 8px; border: 1px solid #ccc; text-align: center; }\n\
+         svg { background: #fff; }\n\
+         </style>\n\
+         </head>\n\
+         <body>\n",
+    );
+    writeln!(html, "<h1>{} results</h1>", solutions.len()).unwrap();
+    html.push_str("<div class=\"gallery\">\n");
+    for (i, solution) in solutions.iter().enumerate() {
+        writeln!(
+            html,
+            "<figure>{}<figcaption>#{}: period {}, population {}<br><code>{}</code></figcaption></figure>",
+            solution.thumbnail(),
+            i + 1,
+            solution.phases.len(),
+            solution.phases[0].len(),
+            solution.apgcode.as_deref().unwrap_or("-")
+        )
+        .unwrap();
+    }
+    html.push_str("</div>\n</body>\n</html>\n");
+    fs::write(path, html)
+}
@@ -0,0 +1,217 @@
+//! Reading known cells and masks from images.
+//!
+//! Supports PNG images, and [Netpbm](https://en.wikipedia.org/wiki/Netpbm)
+//! images: PBM (`P1` and `P4`) and PGM (`P2` and `P5`).
+//! Most image editors can export them.
+
+use png::{ColorType, Decoder};
+use rlifesrc_lib::{KnownCell, ALIVE, DEAD};
+use std::fs;
+
+/// The first bytes of a PNG image.
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// A simple reader for the header and the data of a Netpbm image.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    /// Skips whitespaces and comments.
+    fn skip_whitespace(&mut self) {
+        while let Some(&b) = self.bytes.get(self.pos) {
+            if b == b'#' {
+                while let Some(&b) = self.bytes.get(self.pos) {
+                    if b == b'\n' {
+                        break;
+                    }
+                    self.pos += 1;
+                }
+            } else if b.is_ascii_whitespace() {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Reads a decimal number.
+    fn number(&mut self) -> Result<usize, String> {
+        self.skip_whitespace();
+        let start = self.pos;
+        while let Some(b) = self.bytes.get(self.pos) {
+            if b.is_ascii_digit() {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+        std::str::from_utf8(&self.bytes[start..self.pos])
+            .unwrap()
+            .parse()
+            .map_err(|_| String::from("invalid image header"))
+    }
+
+    /// Reads a single `0` or `1` in a plain PBM image.
+    fn bit(&mut self) -> Result<usize, String> {
+        self.skip_whitespace();
+        let b = self.bytes.get(self.pos).copied();
+        self.pos += 1;
+        match b {
+            Some(b'0') => Ok(0),
+            Some(b'1') => Ok(1),
+            _ => Err(String::from("invalid image data")),
+        }
+    }
+
+    /// Reads a byte in a raw image.
+    fn byte(&mut self) -> Result<usize, String> {
+        let b = self.bytes.get(self.pos).copied();
+        self.pos += 1;
+        b.map(usize::from)
+            .ok_or_else(|| String::from("image data is too short"))
+    }
+}
+
+/// Reads the brightness of each pixel of a PNG image.
+///
+/// Colors are converted to grayscale. The alpha channel is ignored.
+fn read_png(bytes: &[u8]) -> Result<(usize, usize, Vec<u8>), String> {
+    // By default, the decoder expands the pixels to 8-bit grayscale,
+    // grayscale with alpha, RGB or RGBA.
+    let (info, mut reader) = Decoder::new(bytes).read_info().map_err(|e| e.to_string())?;
+    let mut buf = vec![0; info.buffer_size()];
+    reader.next_frame(&mut buf).map_err(|e| e.to_string())?;
+
+    let (width, height) = (info.width as usize, info.height as usize);
+    let channels = match info.color_type {
+        ColorType::Grayscale => 1,
+        ColorType::GrayscaleAlpha => 2,
+        ColorType::RGB => 3,
+        ColorType::RGBA => 4,
+        ColorType::Indexed => return Err(String::from("unsupported PNG color type")),
+    };
+    let mut pixels = Vec::with_capacity(width * height);
+    for row in buf.chunks(info.line_size).take(height) {
+        for pixel in row.chunks(channels).take(width) {
+            let brightness = if channels >= 3 {
+                (pixel[0] as u32 * 299 + pixel[1] as u32 * 587 + pixel[2] as u32 * 114) / 1000
+            } else {
+                pixel[0] as u32
+            };
+            pixels.push(brightness as u8);
+        }
+    }
+    Ok((width, height, pixels))
+}
+
+/// Reads the brightness of each pixel, scaled to `0..=255`.
+///
+/// Returns the width, the height, and the brightness of the pixels
+/// in row-major order.
+fn read_pixels(bytes: &[u8]) -> Result<(usize, usize, Vec<u8>), String> {
+    if bytes.starts_with(PNG_SIGNATURE) {
+        return read_png(bytes);
+    }
+    let magic = bytes.get(..2).ok_or("not a PNG or Netpbm image")?;
+    let mut reader = Reader { bytes, pos: 2 };
+    let width = reader.number()?;
+    let height = reader.number()?;
+    let max = match magic {
+        b"P1" | b"P4" => 1,
+        b"P2" | b"P5" => reader.number()?,
+        _ => return Err(String::from("only PNG, PBM and PGM images are supported")),
+    };
+    if max == 0 || max > 0xffff {
+        return Err(String::from("invalid image header"));
+    }
+    // Raw images have exactly one whitespace after the header.
+    reader.pos += 1;
+
+    let mut pixels = Vec::with_capacity(width * height);
+    for _ in 0..height {
+        for x in 0..width {
+            let value = match magic {
+                // In PBM images, `1` is black.
+                b"P1" => 1 - reader.bit()?,
+                b"P4" => {
+                    if x % 8 == 0 {
+                        reader.byte()?;
+                    }
+                    1 - (bytes[reader.pos - 1] as usize >> (7 - x % 8) & 1)
+                }
+                b"P2" => reader.number()?,
+                _ => {
+                    if max > 0xff {
+                        reader.byte()? << 8 | reader.byte()?
+                    } else {
+                        reader.byte()?
+                    }
+                }
+            };
+            pixels.push((value.min(max) * 0xff / max) as u8);
+        }
+    }
+    Ok((width, height, pixels))
+}
+
+/// Reads the brightness of each pixel of an image file.
+fn read_image(path: &str) -> Result<(usize, usize, Vec<u8>), String> {
+    let bytes = fs::read(path).map_err(|e| e.to_string())?;
+    read_pixels(&bytes)
+}
+
+/// Reads an image file, and converts its pixels to known cells
+/// in generation 0.
+///
+/// The pixel at `(x, y)` corresponds to the cell at `(x, y, 0)`.
+///
+/// Pixels darker than `low` are alive, pixels lighter than `high`
+/// are dead, and pixels in between are left unknown.
+/// The brightness is scaled to `0..=255`.
+pub(crate) fn known_cells_from_image(
+    path: &str,
+    low: u8,
+    high: u8,
+) -> Result<Vec<KnownCell>, String> {
+    let (width, height, pixels) = read_image(path)?;
+    let mut known_cells = Vec::new();
+    for y in 0..height {
+        for x in 0..width {
+            let brightness = pixels[y * width + x];
+            let state = if brightness < low {
+                ALIVE
+            } else if brightness > high {
+                DEAD
+            } else {
+                continue;
+            };
+            known_cells.push(KnownCell {
+                coord: (x as isize, y as isize, 0),
+                state,
+            });
+        }
+    }
+    Ok(known_cells)
+}
+
+/// Reads an image file as a mask, and returns the cells that are
+/// dead in all generations.
+///
+/// The pixel at `(x, y)` corresponds to the cell at `(x, y)`.
+///
+/// Pixels darker than `low` are masked, i.e., the cells are dead.
+/// Other cells are left unknown. The brightness is scaled to `0..=255`.
+pub(crate) fn dead_cells_from_image(path: &str, low: u8) -> Result<Vec<(isize, isize)>, String> {
+    let (width, height, pixels) = read_image(path)?;
+    let mut dead_cells = Vec::new();
+    for y in 0..height {
+        for x in 0..width {
+            if pixels[y * width + x] < low {
+                dead_cells.push((x as isize, y as isize));
+            }
+        }
+    }
+    Ok(dead_cells)
+}
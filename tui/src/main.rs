@@ -1,54 +1,308 @@
 mod args;
+mod census;
+mod gallery;
+mod image;
 
 #[cfg(feature = "tui")]
 mod tui;
 
 use args::Args;
-use rlifesrc_lib::{Search, Status};
-use std::process::exit;
+use census::{census, write_census};
+use gallery::{write_gallery, Solution};
+use rlifesrc_lib::{Checkpoint, Growth, Search, Session, Status};
+use std::{
+    collections::HashSet,
+    io::{stdout, BufWriter, Write},
+    process::exit,
+};
+
+/// Prints some generation of the world to the standard output.
+///
+/// The world is written line by line, so that a large world
+/// is never built as a whole string.
+fn print_gen(search: &dyn Search, t: isize) {
+    let stdout = stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    if let Err(e) = search
+        .write_rle_gen(t, &mut out)
+        .and_then(|_| writeln!(out))
+        .and_then(|_| out.flush())
+    {
+        eprintln!("Unable to print the result: {}", e);
+        exit(1);
+    }
+}
+
+/// Prints the statistics of the search to the standard error.
+fn print_stats(search: &dyn Search) {
+    let stats = search.stats();
+    eprintln!(
+        "Decisions: {}  Conflicts: {}  Propagations: {}  Max depth: {}  Restarts: {}  Time: {:.2?}",
+        stats.decisions,
+        stats.conflicts,
+        stats.propagations,
+        stats.max_depth,
+        stats.restarts,
+        stats.elapsed
+    );
+}
+
+/// Number of steps between two checks for checkpoints.
+const CHECKPOINT_STEPS: u64 = 100000;
+
+/// Searches for the next result.
+///
+/// If `checkpoint` is not `None`, checkpoints are written
+/// every `CHECKPOINT_STEPS` steps if they are due.
+fn search_next(search: &mut dyn Search, checkpoint: &mut Option<Checkpoint>) -> Status {
+    let checkpoint = match checkpoint {
+        Some(checkpoint) => checkpoint,
+        None => return search.search(None),
+    };
+    loop {
+        let status = search.search(Some(CHECKPOINT_STEPS));
+        if let Err(e) = checkpoint.update(search) {
+            eprintln!("Unable to write the checkpoint: {}", e);
+            exit(1);
+        }
+        if status != Status::Searching {
+            return status;
+        }
+    }
+}
 
 /// Runs the search without TUI.
 ///
 /// If `all` is true, it will print all possible results
 /// instead of only the first one.
-fn run_search(mut search: Box<dyn Search>, all: bool) {
+///
+/// If `gallery` is not `None`, an HTML gallery of all results
+/// will be written to this file.
+///
+/// If `unique` is true, results that are the same as an earlier result
+/// up to phase, translation, rotation and reflection are skipped.
+///
+/// If `checkpoint` is not `None`, checkpoints of the search
+/// will be written periodically.
+///
+/// If `stats` is true, the statistics of the search will be printed
+/// to the standard error at the end.
+fn run_search(
+    mut search: Box<dyn Search>,
+    all: bool,
+    gallery: Option<String>,
+    unique: bool,
+    mut checkpoint: Option<Checkpoint>,
+    stats: bool,
+) {
     if all {
         let mut found = false;
+        let mut solutions = Vec::new();
+        let mut seen = HashSet::new();
         loop {
-            match search.search(None) {
+            match search_next(search.as_mut(), &mut checkpoint) {
                 Status::Found => {
+                    if unique && !seen.insert(search.canonical_form()) {
+                        continue;
+                    }
                     found = true;
-                    println!("{}", search.rle_gen(0))
+                    print_gen(search.as_ref(), 0);
+                    if gallery.is_some() {
+                        solutions.push(Solution::new(search.as_ref()));
+                    }
                 }
                 Status::None => break,
+                Status::TimedOut => {
+                    eprintln!("Time is up.");
+                    break;
+                }
                 _ => (),
             }
         }
+        if stats {
+            print_stats(search.as_ref());
+        }
+        if let Some(path) = gallery {
+            if let Err(e) = write_gallery(&path, search.config(), &solutions) {
+                eprintln!("Unable to write the gallery: {}", e);
+                exit(1);
+            }
+        }
         if !found {
             eprintln!("Not found.");
             exit(1);
         }
-    } else if let Status::Found = search.search(None) {
-        println!("{}", search.rle_gen(0));
     } else {
-        eprintln!("Not found.");
+        let status = search_next(search.as_mut(), &mut checkpoint);
+        if stats {
+            print_stats(search.as_ref());
+        }
+        match status {
+            Status::Found => print_gen(search.as_ref(), 0),
+            Status::TimedOut => {
+                eprintln!("Time is up. The deepest partial result:");
+                print_gen(search.as_ref(), 0);
+                exit(1);
+            }
+            _ => {
+                eprintln!("Not found.");
+                exit(1);
+            }
+        }
+    }
+}
+
+/// Runs a census of all small objects, and prints the report.
+fn run_census(search: Box<dyn Search>) {
+    let config = search.config().clone();
+    let entries = census(&config).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        exit(1);
+    });
+    let stdout = stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    if let Err(e) = write_census(&mut out, &config, &entries).and_then(|_| out.flush()) {
+        eprintln!("Unable to print the census: {}", e);
+        exit(1);
+    }
+}
+
+/// Deduces the cells forced by the configuration, and prints them.
+///
+/// Exits with an error if the configuration is contradictory.
+fn run_presolve(search: Box<dyn Search>) {
+    let presolve = search.config().presolve().unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        exit(1);
+    });
+    println!(
+        "Cells: {}  Known: {}  Forced: {}  Unknown: {}",
+        presolve.total,
+        presolve.known,
+        presolve.forced.len(),
+        presolve.unknown
+    );
+    for known_cell in presolve.forced.iter() {
+        let (x, y, t) = known_cell.coord;
+        println!("{},{},{}: {}", x, y, t, known_cell.state.0);
+    }
+    if !presolve.consistent {
+        eprintln!("The configuration is contradictory.");
+        exit(1);
+    }
+}
+
+/// Searches in each of the rules, and prints whether each rule
+/// admits a result.
+fn run_sweep(search: Box<dyn Search>, rules: &[String]) {
+    let config = search.config().clone();
+    let mut session = Session::new();
+    let mut found = false;
+    for rule in rules {
+        let config = config.clone().set_rule_string(rule);
+        let mut search = session.world(&config).unwrap_or_else(|e| {
+            eprintln!("{}: {}", rule, e);
+            exit(1);
+        });
+        if let Status::Found = search.search(None) {
+            found = true;
+            println!("{}: found", rule);
+        } else {
+            println!("{}: not found", rule);
+        }
+    }
+    if !found {
         exit(1);
     }
 }
 
+/// Searches in larger and larger worlds until a result is found,
+/// and prints the result.
+fn run_grow(search: Box<dyn Search>, growth: Growth, stats: bool) {
+    let config = search.config().clone();
+    let mut session = Session::new();
+    let (search, status) = session.grow(&config, growth, None).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        exit(1);
+    });
+    if stats {
+        print_stats(search.as_ref());
+    }
+    let config = search.config();
+    match status {
+        Status::Found => {
+            eprintln!("Found in a {} x {} world.", config.width, config.height);
+            print_gen(search.as_ref(), 0);
+        }
+        Status::TimedOut => {
+            eprintln!(
+                "Time is up in a {} x {} world.",
+                config.width, config.height
+            );
+            exit(1);
+        }
+        _ => {
+            eprintln!(
+                "Not found up to a {} x {} world.",
+                config.width, config.height
+            );
+            exit(1);
+        }
+    }
+}
+
 #[cfg(feature = "tui")]
 fn main() {
     let args = Args::parse().unwrap_or_else(|e| e.exit());
     let search = args.search;
-    if args.no_tui {
-        run_search(search, args.all);
+    if args.census {
+        run_census(search);
+    } else if args.presolve_only {
+        run_presolve(search);
+    } else if let Some(rules) = args.sweep {
+        run_sweep(search, &rules);
+    } else if let Some(growth) = args.grow {
+        run_grow(search, growth, args.stats);
+    } else if args.no_tui {
+        run_search(
+            search,
+            args.all,
+            args.gallery,
+            args.unique,
+            args.checkpoint,
+            args.stats,
+        );
     } else {
-        tui::tui(search, args.reset).unwrap();
+        tui::tui(
+            search,
+            args.reset,
+            args.checkpoint,
+            args.stats,
+            args.save_decisions,
+        )
+        .unwrap();
     }
 }
 
 #[cfg(not(feature = "tui"))]
 fn main() {
     let args = Args::parse().unwrap_or_else(|e| e.exit());
-    run_search(args.search, args.all);
+    if args.census {
+        run_census(args.search);
+    } else if args.presolve_only {
+        run_presolve(args.search);
+    } else if let Some(rules) = args.sweep {
+        run_sweep(args.search, &rules);
+    } else if let Some(growth) = args.grow {
+        run_grow(args.search, growth, args.stats);
+    } else {
+        run_search(
+            args.search,
+            args.all,
+            args.gallery,
+            args.unique,
+            args.checkpoint,
+            args.stats,
+        );
+    }
 }
@@ -9,9 +9,10 @@ use crossterm::{
     ExecutableCommand, QueueableCommand, Result as CrosstermResult,
 };
 use futures::{select, FutureExt, TryStreamExt};
-use rlifesrc_lib::{Search, State, Status, ALIVE, DEAD};
+use rlifesrc_lib::{write_decisions, Checkpoint, Search, State, Status, ALIVE, DEAD};
 use std::{
-    io::{stdout, Write},
+    fs::File,
+    io::{self, stdout, BufWriter, Write},
     time::{Duration, Instant},
 };
 
@@ -43,10 +44,17 @@ struct App<'a, W: Write> {
     output: &'a mut W,
     term_size: (u16, u16),
     world_size: (isize, isize),
+    checkpoint: Option<Checkpoint>,
+    checkpoint_error: Option<io::Error>,
 }
 
 impl<'a, W: Write> App<'a, W> {
-    fn new(search: Box<dyn Search>, reset: bool, output: &'a mut W) -> Self {
+    fn new(
+        search: Box<dyn Search>,
+        reset: bool,
+        checkpoint: Option<Checkpoint>,
+        output: &'a mut W,
+    ) -> Self {
         let period = search.config().period;
         let world_size = (search.config().width, search.config().height);
         App {
@@ -60,6 +68,8 @@ impl<'a, W: Write> App<'a, W> {
             output,
             term_size: (80, 24),
             world_size,
+            checkpoint,
+            checkpoint_error: None,
         }
     }
 
@@ -92,10 +102,15 @@ impl<'a, W: Write> App<'a, W> {
             .queue(Print(format!(
                 "{:1$}",
                 format!(
-                    "Gen: {}  Cells: {}  Confl: {}{}",
+                    "Gen: {}  Cells: {}  Confl: {}  Prog: {:.2}%{}",
                     self.gen,
                     self.search.cell_count_gen(self.gen),
                     self.search.conflicts(),
+                    if self.status == Status::None {
+                        100.0
+                    } else {
+                        self.search.progress()
+                    },
                     if self.status == Status::Searching {
                         String::new()
                     } else {
@@ -154,8 +169,9 @@ impl<'a, W: Write> App<'a, W> {
         const INITIAL: &str = "Press [space] to start.";
         const FOUND: &str = "Found a result. Press [q] to quit or [space] to search for the next.";
         const NONE: &str = "No more result. Press [q] to quit.";
-        const SEARCHING: &str = "Searching... Press [space] to pause.";
-        const PAUSED: &str = "Paused. Press [space] to resume.";
+        const SEARCHING: &str = "Searching... Press [space] to pause or [s] to skip the branch.";
+        const PAUSED: &str = "Paused. Press [space] to resume or [s] to skip the branch.";
+        const TIMED_OUT: &str = "Time is up. Showing the deepest partial result.";
 
         self.output
             .queue(MoveTo(0, self.term_size.1 - 1))?
@@ -169,6 +185,7 @@ impl<'a, W: Write> App<'a, W> {
                     Status::None => NONE,
                     Status::Searching => SEARCHING,
                     Status::Paused => PAUSED,
+                    Status::TimedOut => TIMED_OUT,
                 },
                 self.term_size.0 as usize
             )))?;
@@ -198,9 +215,30 @@ impl<'a, W: Write> App<'a, W> {
         self.start_time = Some(Instant::now());
     }
 
+    /// Skips the subtree of the last decision.
+    fn skip(&mut self) {
+        if !self.search.skip_subtree() {
+            self.status = Status::None;
+            if let Some(instant) = self.start_time.take() {
+                self.timing += instant.elapsed();
+            }
+        } else if self.status == Status::Found {
+            self.status = Status::Paused;
+        }
+    }
+
     /// Searches for one step.
     async fn step(&mut self) {
-        match self.search.search(Some(VIEW_FREQ)) {
+        let status = self.search.search(Some(VIEW_FREQ));
+        if let Some(checkpoint) = self.checkpoint.as_mut() {
+            // Stops writing checkpoints after the first failure,
+            // and reports it after leaving the TUI.
+            if let Err(e) = checkpoint.update(self.search.as_ref()) {
+                self.checkpoint = None;
+                self.checkpoint_error = Some(e);
+            }
+        }
+        match status {
             Status::Searching => (),
             s => {
                 self.status = s;
@@ -252,6 +290,7 @@ impl<'a, W: Write> App<'a, W> {
             KEY_PAGEDOWN => KeyCode::PageDown,
             KEY_SPACE => KeyCode::Char(' '),
             KEY_ENTER => KeyCode::Enter,
+            KEY_S => KeyCode::Char('s'),
         };
 
         match event {
@@ -286,6 +325,12 @@ impl<'a, W: Write> App<'a, W> {
                 }
                 self.update()?;
             }
+            Some(KEY_S) => {
+                if let Status::Searching | Status::Paused | Status::Found = self.status {
+                    self.skip();
+                    self.update()?;
+                }
+            }
             Some(Event::Resize(width, height)) => {
                 self.term_size = (width, height);
                 self.world_size.0 = self.world_size.0.min(self.term_size.0 as isize - 1);
@@ -333,13 +378,44 @@ impl<'a, W: Write> App<'a, W> {
 /// Runs the search with a TUI.
 ///
 /// If `reset` is true, the time will be reset when starting a new search.
-pub(crate) fn tui(search: Box<dyn Search>, reset: bool) -> CrosstermResult<()> {
+///
+/// If `checkpoint` is not `None`, checkpoints of the search
+/// will be written periodically.
+///
+/// If `stats` is true, the statistics of the search will be printed
+/// to the standard error after leaving the TUI.
+///
+/// If `save_decisions` is not `None`, the decisions on the current
+/// search path will be written to this file after leaving the TUI.
+pub(crate) fn tui(
+    search: Box<dyn Search>,
+    reset: bool,
+    checkpoint: Option<Checkpoint>,
+    stats: bool,
+    save_decisions: Option<String>,
+) -> CrosstermResult<()> {
     let mut stdout = stdout();
     let mut reader = EventStream::new();
-    let mut app = App::new(search, reset, &mut stdout);
+    let mut app = App::new(search, reset, checkpoint, &mut stdout);
     app.init()?;
     task::block_on(app.main_loop(&mut reader))?;
     app.quit()?;
-    println!("{}", app.search.rle_gen(app.gen));
+    crate::print_gen(app.search.as_ref(), app.gen);
+    if stats {
+        crate::print_stats(app.search.as_ref());
+    }
+    if let Some(e) = &app.checkpoint_error {
+        eprintln!("Unable to write the checkpoint: {}", e);
+    }
+    if let Some(path) = save_decisions {
+        let decisions = app.search.decision_path();
+        if let Err(e) = File::create(&path).and_then(|file| {
+            let mut writer = BufWriter::new(file);
+            write_decisions(app.search.config(), &decisions, &mut writer)?;
+            writer.flush()
+        }) {
+            eprintln!("Unable to write the decisions: {}", e);
+        }
+    }
     Ok(())
 }
@@ -40,6 +40,7 @@ pub struct App {
     status: Status,
     gen: isize,
     cells: usize,
+    progress: f64,
     world: String,
     period: isize,
     worker: Box<dyn Bridge<Worker>>,
@@ -96,6 +97,7 @@ impl Component for App {
             status,
             gen: 0,
             cells: 0,
+            progress: 0.0,
             world,
             period,
             worker,
@@ -154,9 +156,10 @@ impl Component for App {
                 self.worker.send(Request::SetWorld(self.config.clone()));
             }
             Msg::DataReceived(response) => match response {
-                Response::UpdateWorld((world, cells)) => {
+                Response::UpdateWorld((world, cells, progress)) => {
                     self.world = world;
                     self.cells = cells;
+                    self.progress = progress;
                 }
                 Response::UpdateConfig(config) => {
                     self.config = config;
@@ -323,6 +326,13 @@ impl App {
                     { ": " }
                     { self.cells }
                 </li>
+                <li>
+                    <abbr title="A rough estimate of the explored part of the search tree.">
+                        { "Progress" }
+                    </abbr>
+                    { ": " }
+                    { format!("{:.2}%", self.progress) }
+                </li>
                 <li>
                     {
                         match self.status {
@@ -331,6 +341,7 @@ impl App {
                             Status::None => "No more result.",
                             Status::Searching => "Searching...",
                             Status::Paused => "Paused.",
+                            Status::TimedOut => "Time is up.",
                         }
                     }
                 </li>
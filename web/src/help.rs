@@ -109,6 +109,22 @@ The notations are stolen from Oscar Cunningham's \
 [Logic Life Search](https://github.com/OscarCunningham/logic-life-search). \
 Please see the [Life Wiki](https://conwaylife.com/wiki/Symmetry) for details.
 
+`G` means glide symmetry: after half a period, the pattern becomes its own reflection \
+across the axis, translated by half of `dx` and `dy`. It requires an even period, \
+no transformation, and an even translation along the axis.
+
+`g` means gutter symmetry: the axis is a row or column of dead cells. \
+It requires an odd height for `D2-g`, and an odd width for `D2|g`.
+
+`C3`, `C6`, `D6` and `D12` are symmetries of the hexagonal and triangular grids, \
+rotating around the center of the world. For hexagonal rules, the center must be a cell, \
+i.e., both the width and the height are odd. Only these symmetries, \
+`C2`, `D2\\`, `D2/`, `D4X`, `G\\` and `G/` are valid. \
+For triangular rules, the center must be a vertex, i.e., the height is even, \
+and the width plus the height is 1 modulo 4. Only these symmetries, \
+`C2`, `D2|`, `D2-`, `D4+`, `G|` and `G-` are valid, \
+when their axes agree with the triangles.
+
 Some symmetries require that the world is square.
 
 ### Max cell count
@@ -121,7 +137,9 @@ If this value is set to 0, it means there is no limitation.
 
 Search order.
 
-Row first or column first.
+Row first, column first, diagonal, or from the center outwards.
+
+`Diagonal` is useful for diagonal spaceships.
 
 `Automatic` means that it will start from the shorter side, i.e., \
 start from the columns if there are more columns than rows, \
@@ -133,6 +151,9 @@ How to choose a state for unknown cells.
 
 `Random` might work better for oscillators.
 
+`Biased` chooses a living state with probability 75%, \
+which might work better for dense oscillators.
+
 ### Non empty front
 
 Force the first row or column to be nonempty
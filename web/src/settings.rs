@@ -453,6 +453,16 @@ impl Settings {
                     "D4+" => Msg::SetSym(Symmetry::D4Ortho),
                     "D4X" => Msg::SetSym(Symmetry::D4Diag),
                     "D8" => Msg::SetSym(Symmetry::D8),
+                    "G|" => Msg::SetSym(Symmetry::GlideCol),
+                    "G-" => Msg::SetSym(Symmetry::GlideRow),
+                    "G\\" => Msg::SetSym(Symmetry::GlideDiag),
+                    "G/" => Msg::SetSym(Symmetry::GlideAntidiag),
+                    "D2|g" => Msg::SetSym(Symmetry::GutterCol),
+                    "D2-g" => Msg::SetSym(Symmetry::GutterRow),
+                    "C3" => Msg::SetSym(Symmetry::C3),
+                    "C6" => Msg::SetSym(Symmetry::C6),
+                    "D6" => Msg::SetSym(Symmetry::D6),
+                    "D12" => Msg::SetSym(Symmetry::D12),
                     _ => Msg::None,
                 }
             } else {
@@ -469,25 +479,63 @@ impl Settings {
                 </label>
                 <select id="set_sym" onchange=onchange>
                     <option> { "C1" } </option>
-                    <option> { "C2" } </option>
-                    <option disabled=self.config.width != self.config.height>
+                    <option disabled=!self.config.is_compatible(Symmetry::C2)>
+                        { "C2" }
+                    </option>
+                    <option disabled=!self.config.is_compatible(Symmetry::C4)>
                         { "C4" }
                     </option>
-                    <option> { "D2|" } </option>
-                    <option> { "D2-" } </option>
-                    <option disabled=self.config.width != self.config.height>
+                    <option disabled=!self.config.is_compatible(Symmetry::D2Col)>
+                        { "D2|" }
+                    </option>
+                    <option disabled=!self.config.is_compatible(Symmetry::D2Row)>
+                        { "D2-" }
+                    </option>
+                    <option disabled=!self.config.is_compatible(Symmetry::D2Diag)>
                         { "D2\\" }
                     </option>
-                    <option disabled=self.config.width != self.config.height>
+                    <option disabled=!self.config.is_compatible(Symmetry::D2Antidiag)>
                         { "D2/" }
                     </option>
-                    <option> { "D4+" } </option>
-                    <option disabled=self.config.width != self.config.height>
+                    <option disabled=!self.config.is_compatible(Symmetry::D4Ortho)>
+                        { "D4+" }
+                    </option>
+                    <option disabled=!self.config.is_compatible(Symmetry::D4Diag)>
                         { "D4X" }
                     </option>
-                    <option disabled=self.config.width != self.config.height>
+                    <option disabled=!self.config.is_compatible(Symmetry::D8)>
                         { "D8" }
                     </option>
+                    <option disabled=!self.config.is_compatible(Symmetry::GlideCol)>
+                        { "G|" }
+                    </option>
+                    <option disabled=!self.config.is_compatible(Symmetry::GlideRow)>
+                        { "G-" }
+                    </option>
+                    <option disabled=!self.config.is_compatible(Symmetry::GlideDiag)>
+                        { "G\\" }
+                    </option>
+                    <option disabled=!self.config.is_compatible(Symmetry::GlideAntidiag)>
+                        { "G/" }
+                    </option>
+                    <option disabled=!self.config.is_compatible(Symmetry::GutterCol)>
+                        { "D2|g" }
+                    </option>
+                    <option disabled=!self.config.is_compatible(Symmetry::GutterRow)>
+                        { "D2-g" }
+                    </option>
+                    <option disabled=!self.config.is_compatible(Symmetry::C3)>
+                        { "C3" }
+                    </option>
+                    <option disabled=!self.config.is_compatible(Symmetry::C6)>
+                        { "C6" }
+                    </option>
+                    <option disabled=!self.config.is_compatible(Symmetry::D6)>
+                        { "D6" }
+                    </option>
+                    <option disabled=!self.config.is_compatible(Symmetry::D12)>
+                        { "D12" }
+                    </option>
                 </select>
             </div>
         }
@@ -500,6 +548,8 @@ impl Settings {
                     "Automatic" => Msg::SetOrder(None),
                     "Column" => Msg::SetOrder(Some(SearchOrder::ColumnFirst)),
                     "Row" => Msg::SetOrder(Some(SearchOrder::RowFirst)),
+                    "Diagonal" => Msg::SetOrder(Some(SearchOrder::Diagonal)),
+                    "Center" => Msg::SetOrder(Some(SearchOrder::CenterOut)),
                     _ => Msg::None,
                 }
             } else {
@@ -520,6 +570,8 @@ impl Settings {
                     <option> { "Automatic" } </option>
                     <option value="Column"> { "Column first" } </option>
                     <option value="Row"> { "Row first" } </option>
+                    <option value="Diagonal"> { "Diagonal" } </option>
+                    <option value="Center"> { "Center outwards" } </option>
                 </select>
             </div>
         }
@@ -532,6 +584,7 @@ impl Settings {
                     "Dead" => Msg::SetChoose(NewState::ChooseDead),
                     "Alive" => Msg::SetChoose(NewState::ChooseAlive),
                     "Random" => Msg::SetChoose(NewState::Random),
+                    "Biased" => Msg::SetChoose(NewState::Biased(75)),
                     _ => Msg::None,
                 }
             } else {
@@ -550,6 +603,7 @@ impl Settings {
                     <option> { "Alive" } </option>
                     <option> { "Dead" } </option>
                     <option> { "Random" } </option>
+                    <option> { "Biased" } </option>
                 </select>
             </div>
         }
@@ -20,7 +20,7 @@ pub enum Request {
 
 #[derive(Serialize, Deserialize)]
 pub enum Response {
-    UpdateWorld((String, usize)),
+    UpdateWorld((String, usize, f64)),
     UpdateStatus(Status),
     UpdateConfig(Config),
     InvalidRule,
@@ -55,7 +55,13 @@ impl Worker {
     fn update_world(&mut self, id: HandlerId, gen: isize) {
         let world = self.search.rle_gen(gen);
         let count = self.search.cell_count_gen(gen);
-        self.link.respond(id, Response::UpdateWorld((world, count)));
+        let progress = if self.status == Status::None {
+            100.0
+        } else {
+            self.search.progress()
+        };
+        self.link
+            .respond(id, Response::UpdateWorld((world, count, progress)));
         self.update_status(id);
     }
 